@@ -22,6 +22,7 @@ pub struct Builtins<'a> {
     pub trycatch: bool,
     pub ptr_len: bool,
     pub repr_fat: bool,
+    pub repr_tagged: bool,
     pub rust_str_new_unchecked: bool,
     pub rust_str_repr: bool,
     pub rust_slice_new: bool,
@@ -217,6 +218,25 @@ pub fn write(out: &mut OutFile) {
         writeln!(out, "using Fat = ::std::array<::std::uintptr_t, 2>;");
     }
 
+    if builtin.repr_tagged {
+        include.algorithm = true;
+        include.cstddef = true;
+        include.cstdint = true;
+        include.initializer_list = true;
+        out.next_section();
+        writeln!(out, "#ifndef CXXBRIDGE1_REPR_TAGGED");
+        writeln!(out, "#define CXXBRIDGE1_REPR_TAGGED");
+        writeln!(out, "template <typename... Variants>");
+        writeln!(out, "struct Tagged final {{");
+        writeln!(out, "  ::std::uint8_t tag;");
+        writeln!(
+            out,
+            "  alignas(Variants...) unsigned char payload[::std::max({{sizeof(Variants)...}})];",
+        );
+        writeln!(out, "}};");
+        writeln!(out, "#endif // CXXBRIDGE1_REPR_TAGGED");
+    }
+
     out.end_block(Block::Namespace("repr"));
 
     out.begin_block(Block::Namespace("detail"));
@@ -113,10 +113,12 @@ pub fn cfg(api: &Api) -> &CfgExpr {
         Api::Include(include) => &include.cfg,
         Api::Struct(strct) => &strct.cfg,
         Api::Enum(enm) => &enm.cfg,
+        Api::DataEnum(enm) => &enm.cfg,
         Api::CxxType(ety) | Api::RustType(ety) => &ety.cfg,
         Api::CxxFunction(efn) | Api::RustFunction(efn) => &efn.cfg,
         Api::TypeAlias(alias) => &alias.cfg,
         Api::Impl(imp) => &imp.cfg,
+        Api::Trait(trt) => &trt.cfg,
     }
 }
 
@@ -7,7 +7,9 @@ pub fn namespace(api: &Api) -> &Namespace {
         Api::CxxType(ety) | Api::RustType(ety) => &ety.name.namespace,
         Api::TypeAlias(ety) => &ety.name.namespace,
         Api::Enum(enm) => &enm.name.namespace,
+        Api::DataEnum(enm) => &enm.name.namespace,
         Api::Struct(strct) => &strct.name.namespace,
+        Api::Trait(trt) => &trt.name.namespace,
         Api::Impl(_) | Api::Include(_) => Default::default(),
     }
 }
@@ -4,14 +4,14 @@ use crate::out::OutFile;
 use crate::{builtin, include, Opt};
 use proc_macro2::Ident;
 use syntax::atom::Atom::{self, *};
-use syntax::instantiate::{ImplKey, NamedImplKey};
+use syntax::instantiate::{ImplKey, NamedImplKey, VecElement};
 use syntax::map::UnorderedMap as Map;
 use syntax::set::UnorderedSet;
 use syntax::symbol::{self, Symbol};
 use syntax::trivial::{self, TrivialReason};
 use syntax::{
-    derive, mangle, Api, Doc, Enum, EnumRepr, ExternFn, ExternType, Lang, Pair, Signature, Struct,
-    Trait, Type, TypeAlias, Types, Var,
+    derive, mangle, Api, DataEnum, Doc, Enum, EnumRepr, ExternFn, ExternTrait, ExternType, Lang,
+    Pair, Receiver, Signature, Struct, Trait, Type, TypeAlias, Types, Var,
 };
 
 pub fn gen(apis: &[Api], types: &Types, opt: &Opt, header: bool) -> Vec<u8> {
@@ -22,6 +22,7 @@ pub fn gen(apis: &[Api], types: &Types, opt: &Opt, header: bool) -> Vec<u8> {
     out.include.extend(&opt.include);
 
     write_forward_declarations(out, apis);
+    write_rust_type_deleters(out);
     write_data_structures(out, apis);
     write_functions(out, apis);
     write_generic_instantiations(out);
@@ -34,9 +35,10 @@ pub fn gen(apis: &[Api], types: &Types, opt: &Opt, header: bool) -> Vec<u8> {
 
 fn write_forward_declarations(out: &mut OutFile, apis: &[Api]) {
     let needs_forward_declaration = |api: &&Api| match api {
-        Api::Struct(_) | Api::CxxType(_) | Api::RustType(_) => true,
+        Api::Struct(_) | Api::CxxType(_) | Api::RustType(_) | Api::Trait(_) => true,
         Api::TypeAlias(ety) => ety.lang == Lang::Rust,
         Api::Enum(enm) => !out.types.cxx.contains(&enm.name.rust),
+        Api::DataEnum(enm) => !enm.one_of,
         _ => false,
     };
 
@@ -53,9 +55,11 @@ fn write_forward_declarations(out: &mut OutFile, apis: &[Api]) {
             match api {
                 Api::Struct(strct) => write_struct_decl(out, &strct.name),
                 Api::Enum(enm) => write_enum_decl(out, enm),
+                Api::DataEnum(enm) => write_struct_decl(out, &enm.name),
                 Api::CxxType(ety) => write_struct_using(out, &ety.name),
                 Api::RustType(ety) => write_struct_decl(out, &ety.name),
                 Api::TypeAlias(ety) => write_struct_decl(out, &ety.name),
+                Api::Trait(trt) => write_struct_decl(out, &trt.name),
                 _ => unreachable!(),
             }
         }
@@ -68,6 +72,46 @@ fn write_forward_declarations(out: &mut OutFile, apis: &[Api]) {
     }
 }
 
+// Objects of a Rust type are deleted by Rust's drop, through the default deleter of
+// std::unique_ptr. A std::shared_ptr made from a std::unique_ptr and the kj::Own disposer use the
+// same deleter, so it is written for the Rust types of every UniquePtr, SharedPtr and KjOwn
+// instantiation. The specialization only needs the forward declaration, so it is written before
+// anything which could instantiate the primary template.
+fn write_rust_type_deleters(out: &mut OutFile) {
+    let mut written = UnorderedSet::new();
+    for impl_key in out.types.impls.keys() {
+        let (ImplKey::UniquePtr(key) | ImplKey::SharedPtr(key) | ImplKey::Own(key)) = *impl_key
+        else {
+            continue;
+        };
+        if !out.types.rust.contains(key.rust) || !written.insert(key.rust) {
+            continue;
+        }
+        let resolve = out.types.resolve(&key);
+        out.next_section();
+        out.set_namespace(Default::default());
+        out.include.memory = true;
+        let guard = format!("CXXBRIDGE1_DELETE_{}", resolve.name.to_symbol());
+        let qualified = resolve.name.to_fully_qualified();
+        let link_name = mangle::operator(resolve.name, "delete");
+        writeln!(out, "#ifndef {}", guard);
+        writeln!(out, "#define {}", guard);
+        out.begin_block(Block::ExternC);
+        writeln!(out, "void {}({} *ptr) noexcept;", link_name, qualified);
+        out.end_block(Block::ExternC);
+        out.begin_block(Block::Namespace("std"));
+        writeln!(out, "template <> struct default_delete<{}> {{", qualified);
+        writeln!(
+            out,
+            "  void operator()({} *ptr) const noexcept {{ ::{}(ptr); }}",
+            qualified, link_name,
+        );
+        writeln!(out, "}};");
+        out.end_block(Block::Namespace("std"));
+        writeln!(out, "#endif // {}", guard);
+    }
+}
+
 fn write_data_structures<'a>(out: &mut OutFile<'a>, apis: &'a [Api]) {
     let mut methods_for_type = Map::new();
     for api in apis {
@@ -81,13 +125,33 @@ fn write_data_structures<'a>(out: &mut OutFile<'a>, apis: &'a [Api]) {
         }
     }
 
+    // kj::OneOf aliases only name their variants, so they can go before any
+    // struct definition that holds one by value.
+    for api in apis {
+        if let Api::DataEnum(enm) = api {
+            if !enm.one_of {
+                continue;
+            }
+            out.next_section();
+            if out.types.cxx.contains(&enm.name.rust) {
+                check_one_of(out, enm);
+            } else {
+                write_one_of(out, enm);
+            }
+        }
+    }
+
     let mut structs_written = UnorderedSet::new();
+    let mut data_enums_written = UnorderedSet::new();
     let mut toposorted_structs = out.types.toposorted_structs.iter();
     for api in apis {
         match api {
             Api::Struct(strct) if !structs_written.contains(&strct.name.rust) => {
                 for next in &mut toposorted_structs {
                     if !out.types.cxx.contains(&next.name.rust) {
+                        for field in &next.fields {
+                            write_data_enums_held_by(out, &field.ty, &mut data_enums_written);
+                        }
                         out.next_section();
                         let methods = methods_for_type
                             .get(&next.name.rust)
@@ -117,16 +181,52 @@ fn write_data_structures<'a>(out: &mut OutFile<'a>, apis: &'a [Api]) {
                     .unwrap_or_default();
                 write_opaque_type(out, ety, methods);
             }
+            Api::Trait(trt) => {
+                out.next_section();
+                let methods = methods_for_type
+                    .get(&trt.impl_type.name.rust)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                write_trait(out, trt, methods);
+            }
             _ => {}
         }
     }
 
+    // Tagged structs that no shared struct holds by value. Every shared struct
+    // has been written by now, so their variants are complete.
+    for api in apis {
+        if let Api::DataEnum(enm) = api {
+            write_data_enum_after_variants(out, enm, &mut data_enums_written);
+        }
+    }
+
     if out.header {
         return;
     }
 
     out.set_namespace(Default::default());
 
+    out.next_section();
+    for api in apis {
+        if let Api::DataEnum(enm) = api {
+            if enm.one_of {
+                check_one_of_layout(out, enm);
+            } else {
+                check_data_enum_layout(out, enm);
+            }
+        }
+    }
+
+    out.next_section();
+    for api in apis {
+        if let Api::Struct(strct) = api {
+            if strct.fields.is_empty() {
+                check_empty_struct_layout(out, strct);
+            }
+        }
+    }
+
     out.next_section();
     for api in apis {
         if let Api::TypeAlias(ety) = api {
@@ -208,8 +308,10 @@ fn write_std_specializations(out: &mut OutFile, apis: &[Api]) {
 
 fn pick_includes_and_builtins(out: &mut OutFile, apis: &[Api]) {
     for api in apis {
-        if let Api::Include(include) = api {
-            out.include.insert(include);
+        match api {
+            Api::Include(include) => out.include.insert(include),
+            Api::DataEnum(enm) if enm.one_of => out.include.kj_rs = true,
+            _ => {}
         }
     }
 
@@ -237,7 +339,15 @@ fn pick_includes_and_builtins(out: &mut OutFile, apis: &[Api]) {
             | Type::KjMaybe(_)
             | Type::KjOwn(_)
             | Type::KjRc(_)
-            | Type::KjArc(_) => {
+            | Type::KjArc(_)
+            | Type::KjString(_)
+            | Type::KjStringPtr(_)
+            | Type::KjArray(_)
+            | Type::KjArrayPtr(_)
+            | Type::KjStream(_)
+            | Type::KjOwnedPromise(_)
+            | Type::KjForkedPromise(_)
+            | Type::KjFunction(_) => {
                 out.include.kj_rs = true;
             }
             Type::KjDate(_) => {
@@ -424,6 +534,37 @@ fn write_opaque_type<'a>(out: &mut OutFile<'a>, ety: &'a ExternType, methods: &[
     writeln!(out, "#endif // {}", guard);
 }
 
+fn write_trait<'a>(out: &mut OutFile<'a>, trt: &'a ExternTrait, methods: &[&ExternFn]) {
+    out.set_namespace(&trt.name.namespace);
+    let guard = format!("CXXBRIDGE1_TRAIT_{}", trt.name.to_symbol());
+    writeln!(out, "#ifndef {}", guard);
+    writeln!(out, "#define {}", guard);
+    write_doc(out, "", &trt.doc);
+
+    writeln!(out, "struct {} {{", trt.name.cxx);
+    writeln!(
+        out,
+        "  virtual ~{}() noexcept(false) = default;",
+        trt.name.cxx
+    );
+
+    for method in methods {
+        if !method.doc.is_empty() {
+            out.next_section();
+        }
+        write_doc(out, "  ", &method.doc);
+        write!(out, "  virtual ");
+        let local_name = method.name.cxx.to_string();
+        let indirect_call = false;
+        let throws = true;
+        write_rust_function_shim_decl(out, &local_name, &method.sig, indirect_call, throws);
+        writeln!(out, " = 0;");
+    }
+
+    writeln!(out, "}};");
+    writeln!(out, "#endif // {}", guard);
+}
+
 fn write_enum<'a>(out: &mut OutFile<'a>, enm: &'a Enum) {
     let repr = match &enm.repr {
         #[cfg(feature = "experimental-enum-variants-from-header")]
@@ -473,6 +614,318 @@ fn check_enum<'a>(out: &mut OutFile<'a>, enm: &'a Enum) {
     }
 }
 
+fn write_data_enums_held_by<'a>(
+    out: &mut OutFile<'a>,
+    ty: &Type,
+    written: &mut UnorderedSet<&'a Ident>,
+) {
+    let types = out.types;
+    if let Type::Ident(ident) = ty {
+        if let Some(enm) = types.data_enums.get(&ident.rust) {
+            write_data_enum_after_variants(out, enm, written);
+        }
+    }
+}
+
+fn write_data_enum_after_variants<'a>(
+    out: &mut OutFile<'a>,
+    enm: &'a DataEnum,
+    written: &mut UnorderedSet<&'a Ident>,
+) {
+    if !written.insert(&enm.name.rust) {
+        return;
+    }
+    // The variants are held by value in a union, so any tagged struct among
+    // them needs to be complete first. Shared structs among them are already
+    // written, the toposort puts them ahead of whatever holds the enum.
+    for variant in &enm.variants {
+        if let Some(ty) = &variant.ty {
+            write_data_enums_held_by(out, ty, written);
+        }
+    }
+    if !enm.one_of {
+        out.next_section();
+        write_data_enum(out, enm);
+    }
+}
+
+fn write_data_enum<'a>(out: &mut OutFile<'a>, enm: &'a DataEnum) {
+    let name = &enm.name.cxx;
+    let copy = derive::contains(&enm.derives, Trait::Clone);
+    let has_unit = enm.variants.iter().any(|variant| variant.ty.is_none());
+
+    out.set_namespace(&enm.name.namespace);
+    out.include.cassert = true;
+    out.include.cstdint = true;
+    out.include.memory = true;
+    out.include.type_traits = true;
+    out.include.utility = true;
+    let guard = format!("CXXBRIDGE1_ENUM_{}", enm.name.to_symbol());
+    writeln!(out, "#ifndef {}", guard);
+    writeln!(out, "#define {}", guard);
+    write_doc(out, "", &enm.doc);
+    writeln!(out, "struct {} final {{", name);
+
+    writeln!(out, "  enum class Tag : ::std::uint8_t {{");
+    for variant in &enm.variants {
+        writeln!(out, "    {} = {},", variant.name.cxx, variant.discriminant);
+    }
+    writeln!(out, "  }};");
+
+    if has_unit {
+        writeln!(out);
+        for variant in &enm.variants {
+            if variant.ty.is_none() {
+                writeln!(out, "  struct {} final {{}};", variant.name.cxx);
+            }
+        }
+    }
+
+    writeln!(out);
+    writeln!(out, "  Tag tag;");
+    writeln!(out, "  union {{");
+    for variant in &enm.variants {
+        if let Some(ty) = &variant.ty {
+            write_doc(out, "    ", &variant.doc);
+            write!(out, "    ");
+            write_type_space(out, ty);
+            writeln!(out, "{};", variant.name.cxx.to_snake_case());
+        }
+    }
+    writeln!(out, "  }};");
+
+    for variant in &enm.variants {
+        let member = variant.name.cxx.to_snake_case();
+        writeln!(out);
+        match &variant.ty {
+            Some(ty) => {
+                write!(out, "  static {} make_{}(", name, member);
+                write_type_space(out, ty);
+                writeln!(out, "value) noexcept {{");
+                writeln!(out, "    {} enm(Tag::{});", name, variant.name.cxx);
+                writeln!(
+                    out,
+                    "    ::std::construct_at(&enm.{}, ::std::move(value));",
+                    member,
+                );
+                writeln!(out, "    return enm;");
+                writeln!(out, "  }}");
+            }
+            None => {
+                writeln!(out, "  static {} make_{}() noexcept {{", name, member);
+                writeln!(out, "    return {}(Tag::{});", name, variant.name.cxx);
+                writeln!(out, "  }}");
+            }
+        }
+        writeln!(out, "  bool is_{}() const noexcept {{", member);
+        writeln!(out, "    return tag == Tag::{};", variant.name.cxx);
+        writeln!(out, "  }}");
+        if let Some(ty) = &variant.ty {
+            for constness in ["", "const "] {
+                write!(out, "  {}", constness);
+                write_type(out, ty);
+                write!(out, " &get_{}() ", member);
+                writeln!(out, "{}noexcept {{", constness);
+                writeln!(out, "    assert(is_{}());", member);
+                writeln!(out, "    return {};", member);
+                writeln!(out, "  }}");
+            }
+        }
+    }
+
+    for constness in ["", " const"] {
+        writeln!(out);
+        writeln!(out, "  template <typename Visitor>");
+        writeln!(
+            out,
+            "  decltype(auto) visit(Visitor &&visitor){} {{",
+            constness,
+        );
+        for (i, variant) in enm.variants.iter().enumerate() {
+            let arg = match &variant.ty {
+                Some(_) => variant.name.cxx.to_snake_case(),
+                None => format!("{}{{}}", variant.name.cxx),
+            };
+            if i + 1 < enm.variants.len() {
+                writeln!(out, "    if (tag == Tag::{}) {{", variant.name.cxx);
+                writeln!(
+                    out,
+                    "      return ::std::forward<Visitor>(visitor)({});",
+                    arg,
+                );
+                writeln!(out, "    }}");
+            } else {
+                writeln!(out, "    return ::std::forward<Visitor>(visitor)({});", arg);
+            }
+        }
+        writeln!(out, "  }}");
+    }
+
+    writeln!(out);
+    writeln!(out, "  {0}({0} &&other) noexcept : tag(other.tag) {{", name);
+    write_data_enum_switch(out, enm, |member| {
+        format!("::std::construct_at(&{0}, ::std::move(other.{0}));", member)
+    });
+    writeln!(out, "  }}");
+    if copy {
+        writeln!(out, "  {0}(const {0} &other) : tag(other.tag) {{", name);
+        write_data_enum_switch(out, enm, |member| {
+            format!("::std::construct_at(&{0}, other.{0});", member)
+        });
+        writeln!(out, "  }}");
+    } else {
+        writeln!(out, "  {0}(const {0} &) = delete;", name);
+    }
+    writeln!(out, "  {0} &operator=({0} &&other) noexcept {{", name);
+    writeln!(out, "    if (this != &other) {{");
+    writeln!(out, "      ::std::destroy_at(this);");
+    writeln!(out, "      ::std::construct_at(this, ::std::move(other));");
+    writeln!(out, "    }}");
+    writeln!(out, "    return *this;");
+    writeln!(out, "  }}");
+    if copy {
+        writeln!(out, "  {0} &operator=(const {0} &other) {{", name);
+        writeln!(out, "    if (this != &other) {{");
+        writeln!(out, "      *this = {}(other);", name);
+        writeln!(out, "    }}");
+        writeln!(out, "    return *this;");
+        writeln!(out, "  }}");
+    } else {
+        writeln!(out, "  {0} &operator=(const {0} &) = delete;", name);
+    }
+    writeln!(out, "  ~{}() noexcept {{", name);
+    write_data_enum_switch(out, enm, |member| {
+        format!("::std::destroy_at(&{});", member)
+    });
+    writeln!(out, "  }}");
+
+    writeln!(out);
+    writeln!(out, "  using IsRelocatable = ::std::true_type;");
+
+    writeln!(out);
+    writeln!(out, "private:");
+    writeln!(
+        out,
+        "  explicit {}(Tag which) noexcept : tag(which) {{}}",
+        name
+    );
+    writeln!(out, "}};");
+    writeln!(out, "#endif // {}", guard);
+}
+
+// Writes a switch over the tag that runs `stmt` on the active member, if
+// the active variant has one.
+fn write_data_enum_switch(out: &mut OutFile, enm: &DataEnum, stmt: impl Fn(&str) -> String) {
+    writeln!(out, "    switch (tag) {{");
+    for variant in &enm.variants {
+        if variant.ty.is_some() {
+            let member = variant.name.cxx.to_snake_case();
+            writeln!(out, "    case Tag::{}:", variant.name.cxx);
+            writeln!(out, "      {}", stmt(&member));
+            writeln!(out, "      break;");
+        }
+    }
+    if enm.variants.iter().any(|variant| variant.ty.is_none()) {
+        writeln!(out, "    default:");
+        writeln!(out, "      break;");
+    }
+    writeln!(out, "    }}");
+}
+
+fn check_data_enum_layout(out: &mut OutFile, enm: &DataEnum) {
+    // The Rust enum is #[repr(C, u8)], which Rust defines as a #[repr(C)]
+    // struct of a u8 tag followed by a #[repr(C)] union of the variants.
+    // Compare against that rather than trusting the union in the tagged
+    // struct to be laid out the same way.
+    out.builtin.repr_tagged = true;
+    let id = enm.name.to_fully_qualified();
+    for what in ["sizeof", "alignof"] {
+        write!(
+            out,
+            "static_assert({}({}) == {}(::rust::repr::Tagged<",
+            what, id, what
+        );
+        let payloads = enm
+            .variants
+            .iter()
+            .filter_map(|variant| variant.ty.as_ref());
+        for (i, ty) in payloads.enumerate() {
+            if i > 0 {
+                write!(out, ", ");
+            }
+            write_type(out, ty);
+        }
+        writeln!(
+            out,
+            ">), \"layout of {} does not match the #[repr(C, u8)] Rust enum {}\");",
+            id.trim_start_matches("::"),
+            enm.name.rust,
+        );
+    }
+}
+
+fn check_empty_struct_layout(out: &mut OutFile, strct: &Struct) {
+    // C++ gives an empty struct a size of one byte, which the Rust struct
+    // matches with a byte of padding. This also catches C++ definitions of
+    // an extern shared struct which add members of their own.
+    let id = strct.name.to_fully_qualified();
+    for what in ["sizeof", "alignof"] {
+        writeln!(
+            out,
+            "static_assert({}({}) == 1, \"layout of {} does not match the single byte of the empty Rust struct {}\");",
+            what,
+            id,
+            id.trim_start_matches("::"),
+            strct.name.rust,
+        );
+    }
+}
+
+fn write_one_of<'a>(out: &mut OutFile<'a>, enm: &'a DataEnum) {
+    out.set_namespace(&enm.name.namespace);
+    write_doc(out, "", &enm.doc);
+    write!(out, "using {} = ", enm.name.cxx);
+    write_one_of_type(out, enm);
+    writeln!(out, ";");
+}
+
+fn check_one_of<'a>(out: &mut OutFile<'a>, enm: &'a DataEnum) {
+    out.set_namespace(&enm.name.namespace);
+    out.include.type_traits = true;
+    write!(out, "static_assert(::std::is_same<{}, ", enm.name.cxx);
+    write_one_of_type(out, enm);
+    writeln!(
+        out,
+        ">::value, \"disagrees with the variants in #[cxx::bridge]\");",
+    );
+}
+
+fn write_one_of_type(out: &mut OutFile, enm: &DataEnum) {
+    write!(out, "::kj::OneOf<");
+    for (i, variant) in enm.variants.iter().enumerate() {
+        if i > 0 {
+            write!(out, ", ");
+        }
+        write_type(out, variant.ty.as_ref().unwrap());
+    }
+    write!(out, ">");
+}
+
+fn check_one_of_layout(out: &mut OutFile, enm: &DataEnum) {
+    // The Rust enum is #[repr(C, u32)] with variants numbered from 1, which
+    // matches kj::OneOf as long as both place and align the payload the same
+    // way. If kj::OneOf over-aligns its storage, this fails to compile rather
+    // than letting the two disagree.
+    let id = enm.name.to_fully_qualified();
+    writeln!(
+        out,
+        "static_assert(::kj_rs::repr::OneOfLayout<{}>::matches, \"layout of {} does not match the Rust enum of #[cxx::one_of] {}\");",
+        id,
+        id.trim_start_matches("::"),
+        enm.name.rust,
+    );
+}
+
 fn check_trivial_extern_type(out: &mut OutFile, alias: &TypeAlias, reasons: &[TrivialReason]) {
     // NOTE: The following static assertion is just nice-to-have and not
     // necessary for soundness. That's because triviality is always declared by
@@ -739,6 +1192,14 @@ fn write_cxx_function_shim<'a>(out: &mut OutFile<'a>, efn: &'a ExternFn) {
     out.next_section();
     out.set_namespace(&efn.name.namespace);
     out.begin_block(Block::ExternC);
+    for arg in &efn.args {
+        if let Type::KjFunction(f) = &arg.ty {
+            let r_trampoline = mangle::r_trampoline(efn, &arg.name, out.types);
+            let c_trampoline = mangle::c_trampoline(efn, &arg.name, out.types);
+            write_rust_trampoline(out, &r_trampoline, &c_trampoline, f);
+            out.next_section();
+        }
+    }
     begin_function_definition(out);
     // C++ functions always return a Result: an exception must never be allowed to
     // escape through the extern "C" boundary (that would terminate the process), so
@@ -749,15 +1210,20 @@ fn write_cxx_function_shim<'a>(out: &mut OutFile<'a>, efn: &'a ExternFn) {
     let mangled = mangle::extern_fn(efn, out.types);
     write!(out, "{}(", mangled);
     if let Some(receiver) = &efn.receiver {
-        write!(
-            out,
-            "{}",
-            out.types.resolve(&receiver.ty).name.to_fully_qualified(),
-        );
-        if !receiver.mutable {
-            write!(out, " const");
+        if let Some((rc, ..)) = &receiver.rc_tokens {
+            write_receiver_rc(out, rc, receiver);
+            write!(out, " *self");
+        } else {
+            write!(
+                out,
+                "{}",
+                out.types.resolve(&receiver.ty).name.to_fully_qualified(),
+            );
+            if !receiver.mutable {
+                write!(out, " const");
+            }
+            write!(out, " &self");
         }
-        write!(out, " &self");
     }
     for (i, arg) in efn.args.iter().enumerate() {
         if i > 0 || efn.receiver.is_some() {
@@ -808,6 +1274,49 @@ fn write_cxx_function_shim<'a>(out: &mut OutFile<'a>, efn: &'a ExternFn) {
         ),
     }
     writeln!(out, ";");
+    let callee = match &efn.receiver {
+        None => format!("{}$", efn.name.rust),
+        Some(receiver) => match &receiver.rc_tokens {
+            None => format!("(self.*{}$)", efn.name.rust),
+            Some((rc, ..)) => {
+                // Taken out of Rust's memory up front, so that it is released even if the call
+                // throws. The returned promise is attached to it in `write_cxx_shim_call`.
+                out.include.utility = true;
+                write!(out, "  ");
+                write_receiver_rc(out, rc, receiver);
+                writeln!(out, " self$(::std::move(*self));");
+                format!("((*self$).*{}$)", efn.name.rust)
+            }
+        },
+    };
+    write_cxx_shim_call(out, efn, &efn.sig, &callee);
+    for arg in &efn.args {
+        if let Type::Fn(f) = &arg.ty {
+            let var = &arg.name;
+            write_function_pointer_trampoline(out, efn, var, f);
+        }
+    }
+    if let Some(Type::KjFunction(f)) = &efn.ret {
+        let c_trampoline = mangle::c_return_trampoline(efn, out.types);
+        write_kj_function_trampoline(out, efn, &c_trampoline, f);
+    }
+    out.end_block(Block::ExternC);
+}
+
+// `::kj::Rc<T>` or `::kj::Arc<T>` for a method taking `self: KjRc<T>` or `self: KjArc<T>`.
+fn write_receiver_rc(out: &mut OutFile, rc: &Ident, receiver: &Receiver) {
+    let rc = if rc == "KjArc" { "Arc" } else { "Rc" };
+    write!(
+        out,
+        "::kj::{}<{}>",
+        rc,
+        out.types.resolve(&receiver.ty).name.to_fully_qualified(),
+    );
+}
+
+/// Write the body of a cxx shim, which calls `callee` and reports its exception to Rust
+fn write_cxx_shim_call(out: &mut OutFile, efn: &ExternFn, sig: &Signature, callee: &str) {
+    let indirect_return = indirect_return(sig);
     write!(out, "  ");
     out.builtin.trycatch = true;
     writeln!(out, "return ::rust::repr::Result::run([&] {{");
@@ -815,31 +1324,34 @@ fn write_cxx_function_shim<'a>(out: &mut OutFile<'a>, efn: &'a ExternFn) {
     if indirect_return {
         out.include.new = true;
         write!(out, "new (return$) ");
-        write_indirect_return_type(out, efn.ret.as_ref().unwrap());
+        write_indirect_return_type(out, sig.ret.as_ref().unwrap());
         write!(out, "(");
     }
-    match &efn.ret {
+    match &sig.ret {
         Some(Type::Ref(_)) => write!(out, "&"),
         Some(Type::KjDate(_)) => {
             write!(out, "::kj_rs::repr::toNanos(");
         }
+        Some(Type::KjFunction(_)) => write!(out, "::kj_rs::repr::toClosure("),
         _ => {}
     }
-    match &efn.receiver {
-        None => write!(out, "{}$(", efn.name.rust),
-        Some(_) => write!(out, "(self.*{}$)(", efn.name.rust),
-    }
-    for (i, arg) in efn.args.iter().enumerate() {
+    write!(out, "{}(", callee);
+    for (i, arg) in sig.args.iter().enumerate() {
         if i > 0 {
             write!(out, ", ");
         }
-        write_cxx_shim_arg(out, arg);
+        write_cxx_shim_arg(out, efn, arg);
     }
     write!(out, ")");
-    match &efn.ret {
+    let owned_receiver = matches!(&sig.receiver, Some(receiver) if receiver.rc_tokens.is_some());
+    if owned_receiver {
+        // The promise keeps the receiver alive until it completes.
+        write!(out, ".attach(::std::move(self$))");
+    }
+    match &sig.ret {
         Some(Type::RustBox(_)) => write!(out, ".into_raw()"),
         Some(Type::UniquePtr(_)) => write!(out, ".release()"),
-        Some(Type::KjDate(_)) => write!(out, ")"),
+        Some(Type::KjDate(_) | Type::KjFunction(_)) => write!(out, ")"),
         _ => {}
     }
     if indirect_return {
@@ -848,18 +1360,45 @@ fn write_cxx_function_shim<'a>(out: &mut OutFile<'a>, efn: &'a ExternFn) {
     writeln!(out, ";");
     writeln!(out, "  }});");
     writeln!(out, "}}");
-    for arg in &efn.args {
-        if let Type::Fn(f) = &arg.ty {
-            let var = &arg.name;
-            write_function_pointer_trampoline(out, efn, var, f);
-        }
-    }
-    out.end_block(Block::ExternC);
+}
+
+// Called by Rust to invoke the `kj::Function` behind a `Box<dyn FnMut>` it received from C++.
+fn write_kj_function_trampoline(
+    out: &mut OutFile,
+    efn: &ExternFn,
+    link_name: &Symbol,
+    f: &Signature,
+) {
+    out.next_section();
+    begin_function_definition(out);
+    out.builtin.ptr_len = true;
+    write!(out, "::rust::repr::Result {}(", link_name);
+    for arg in &f.args {
+        write_cxx_shim_sig_arg(out, arg);
+        write!(out, ", ");
+    }
+    if indirect_return(f) {
+        write_indirect_return_type_space(out, f.ret.as_ref().unwrap());
+        write!(out, "*return$, ");
+    }
+    writeln!(out, "void *extern$) noexcept {{");
+    write!(out, "  ::kj::Function<");
+    write_function_type(out, f);
+    write!(out, "> &function$ = *static_cast<::kj::Function<");
+    write_function_type(out, f);
+    writeln!(out, "> *>(extern$);");
+    write_cxx_shim_call(out, efn, f, "function$");
 }
 
 /// Write argument as part of cxx function call inside of cxx shim
-fn write_cxx_shim_arg(out: &mut OutFile, arg: &Var) {
+fn write_cxx_shim_arg(out: &mut OutFile, efn: &ExternFn, arg: &Var) {
     match &arg.ty {
+        Type::KjFunction(f) => {
+            let c_trampoline = mangle::c_trampoline(efn, &arg.name, out.types);
+            write!(out, "::kj_rs::repr::fromClosure<");
+            write_function_type(out, f);
+            write!(out, ">({}, &{})", arg.name.cxx, c_trampoline);
+        }
         Type::RustBox(_) => {
             write_type(out, &arg.ty);
             write!(out, "::from_raw({})", arg.name.cxx);
@@ -916,13 +1455,34 @@ fn write_cxx_shim_sig_arg(out: &mut OutFile, arg: &Var) {
 
 fn write_function_pointer_trampoline(out: &mut OutFile, efn: &ExternFn, var: &Pair, f: &Signature) {
     let r_trampoline = mangle::r_trampoline(efn, var, out.types);
+    let c_trampoline = mangle::c_trampoline(efn, var, out.types);
+    write_rust_trampoline(out, &r_trampoline, &c_trampoline, f);
+}
+
+// The Rust half of a trampoline calls the function pointer or closure it is passed as `extern$`,
+// and the C++ half wraps it into a C++ function taking the same arguments.
+fn write_rust_trampoline(
+    out: &mut OutFile,
+    r_trampoline: &Symbol,
+    c_trampoline: &Symbol,
+    f: &Signature,
+) {
     let indirect_call = true;
-    write_rust_function_decl_impl(out, &r_trampoline, f, indirect_call);
+    write_rust_function_decl_impl(out, r_trampoline, f, indirect_call);
 
     out.next_section();
-    let c_trampoline = mangle::c_trampoline(efn, var, out.types).to_string();
+    let c_trampoline = c_trampoline.to_string();
     let doc = Doc::new();
-    write_rust_function_shim_impl(out, &c_trampoline, f, &doc, &r_trampoline, indirect_call);
+    let ret_trampoline = None;
+    write_rust_function_shim_impl(
+        out,
+        &c_trampoline,
+        f,
+        &doc,
+        r_trampoline,
+        indirect_call,
+        ret_trampoline,
+    );
 }
 
 fn write_rust_function_decl<'a>(out: &mut OutFile<'a>, efn: &'a ExternFn) {
@@ -931,6 +1491,18 @@ fn write_rust_function_decl<'a>(out: &mut OutFile<'a>, efn: &'a ExternFn) {
     let link_name = mangle::extern_fn(efn, out.types);
     let indirect_call = false;
     write_rust_function_decl_impl(out, &link_name, efn, indirect_call);
+    for arg in &efn.args {
+        if let Type::KjFunction(f) = &arg.ty {
+            let c_trampoline = mangle::c_trampoline(efn, &arg.name, out.types);
+            write_kj_function_trampoline(out, efn, &c_trampoline, f);
+        }
+    }
+    if let Some(Type::KjFunction(f)) = &efn.ret {
+        let r_trampoline = mangle::r_return_trampoline(efn, out.types);
+        let c_trampoline = mangle::c_return_trampoline(efn, out.types);
+        out.next_section();
+        write_rust_trampoline(out, &r_trampoline, &c_trampoline, f);
+    }
     out.end_block(Block::ExternC);
 }
 
@@ -984,11 +1556,25 @@ fn write_rust_function_decl_impl(
             Type::Future(_) => {
                 write!(out, "::kj_rs::repr::RustFuture ");
             }
+            Type::KjStream(_) => {
+                write!(out, "::kj_rs::repr::RustStream ");
+            }
+            Type::KjFunction(_) => {
+                write!(out, "::kj_rs::repr::Closure ");
+            }
             ret => write_type_space(out, ret),
         }
         write!(out, "*return$");
         needs_comma = true;
     }
+    if let Some(error) = &sig.error {
+        if needs_comma {
+            write!(out, ", ");
+        }
+        write_type_space(out, error);
+        write!(out, "*err$");
+        needs_comma = true;
+    }
     if indirect_call {
         if needs_comma {
             write!(out, ", ");
@@ -1011,7 +1597,19 @@ fn write_rust_function_shim<'a>(out: &mut OutFile<'a>, efn: &'a ExternFn) {
     let doc = &efn.doc;
     let invoke = mangle::extern_fn(efn, out.types);
     let indirect_call = false;
-    write_rust_function_shim_impl(out, &local_name, efn, doc, &invoke, indirect_call);
+    let ret_trampoline = match &efn.ret {
+        Some(Type::KjFunction(_)) => Some(mangle::c_return_trampoline(efn, out.types)),
+        _ => None,
+    };
+    write_rust_function_shim_impl(
+        out,
+        &local_name,
+        efn,
+        doc,
+        &invoke,
+        indirect_call,
+        ret_trampoline.as_ref(),
+    );
 }
 
 fn write_rust_function_shim_decl(
@@ -1022,7 +1620,10 @@ fn write_rust_function_shim_decl(
     throws: bool,
 ) {
     begin_function_definition(out);
-    write_return_type(out, &sig.ret);
+    match &sig.error {
+        Some(error) => write_typed_result_type_space(out, &sig.ret, error),
+        None => write_return_type(out, &sig.ret),
+    }
     write!(out, "{}(", local_name);
     for (i, arg) in sig.args.iter().enumerate() {
         if i > 0 {
@@ -1055,6 +1656,7 @@ fn write_rust_function_shim_impl(
     doc: &Doc,
     invoke: &Symbol,
     indirect_call: bool,
+    ret_trampoline: Option<&Symbol>,
 ) {
     if out.header && sig.receiver.is_some() {
         // We've already defined this inside the struct.
@@ -1106,11 +1708,24 @@ fn write_rust_function_shim_impl(
             Type::Future(_) => {
                 write!(out, "::kj_rs::repr::RustFuture");
             }
+            Type::KjStream(_) => {
+                write!(out, "::kj_rs::repr::RustStream");
+            }
+            Type::KjFunction(_) => {
+                write!(out, "::kj_rs::repr::Closure");
+            }
             ret => write_type(out, ret),
         }
         writeln!(out, "> return$;");
         write!(out, "  ");
     }
+    if let Some(error) = &sig.error {
+        out.builtin.maybe_uninit = true;
+        write!(out, "::rust::MaybeUninit<");
+        write_type(out, error);
+        writeln!(out, "> err$;");
+        write!(out, "  ");
+    }
     out.builtin.ptr_len = true;
     write!(out, "::rust::repr::Result error$ = ");
 
@@ -1124,6 +1739,16 @@ fn write_rust_function_shim_impl(
         if needs_comma {
             write!(out, ", ");
         }
+        if let Type::KjFunction(_) = &arg.ty {
+            out.include.utility = true;
+            write!(
+                out,
+                "::kj_rs::repr::toClosure(::std::move({}))",
+                arg.name.cxx
+            );
+            needs_comma = true;
+            continue;
+        }
         if out.types.needs_indirect_abi(&arg.ty) {
             write!(out, "&");
         }
@@ -1143,6 +1768,13 @@ fn write_rust_function_shim_impl(
         write!(out, "&return$.value");
         needs_comma = true;
     }
+    if sig.error.is_some() {
+        if needs_comma {
+            write!(out, ", ");
+        }
+        write!(out, "&err$.value");
+        needs_comma = true;
+    }
     if indirect_call {
         if needs_comma {
             write!(out, ", ");
@@ -1159,22 +1791,57 @@ fn write_rust_function_shim_impl(
     }
     writeln!(out, ";");
     out.builtin.rust_error = true;
+
+    if let Some(error) = &sig.error {
+        out.include.utility = true;
+        writeln!(out, "  if (error$.typed_error()) {{");
+        write!(out, "    return ");
+        write_typed_result_type(out, &sig.ret, error);
+        writeln!(out, "::err(::std::move(err$.value));");
+        writeln!(out, "  }}");
+        writeln!(out, "  error$.check();");
+        write!(out, "  return ");
+        write_typed_result_type(out, &sig.ret, error);
+        if indirect_return {
+            write!(out, "::ok(");
+            write_rust_function_return_value(out, sig.ret.as_ref().unwrap(), ret_trampoline);
+            writeln!(out, ");");
+        } else {
+            writeln!(out, "::ok();");
+        }
+        writeln!(out, "}}");
+        return;
+    }
+
     writeln!(out, "  error$.check();");
 
     if indirect_return {
         write!(out, "  return ");
-        match sig.ret.as_ref().unwrap() {
-            Type::Ref(_) => write!(out, "*return$.value"),
-            _ => {
-                out.include.utility = true;
-                write!(out, "::std::move(return$.value)");
-            }
-        }
+        write_rust_function_return_value(out, sig.ret.as_ref().unwrap(), ret_trampoline);
         writeln!(out, ";");
     }
     writeln!(out, "}}");
 }
 
+fn write_rust_function_return_value(
+    out: &mut OutFile,
+    ret: &Type,
+    ret_trampoline: Option<&Symbol>,
+) {
+    match ret {
+        Type::Ref(_) => write!(out, "*return$.value"),
+        Type::KjFunction(f) => {
+            write!(out, "::kj_rs::repr::fromClosure<");
+            write_function_type(out, f);
+            write!(out, ">(return$.value, &{})", ret_trampoline.unwrap());
+        }
+        _ => {
+            out.include.utility = true;
+            write!(out, "::std::move(return$.value)");
+        }
+    }
+}
+
 fn write_return_type(out: &mut OutFile, ty: &Option<Type>) {
     match ty {
         None => write!(out, "void "),
@@ -1182,6 +1849,25 @@ fn write_return_type(out: &mut OutFile, ty: &Option<Type>) {
     }
 }
 
+// `Result<T, E>` with a typed error comes out of an extern "Rust" function as
+// `kj_rs::Result<T, E>` rather than a thrown exception.
+fn write_typed_result_type(out: &mut OutFile, ret: &Option<Type>, error: &Type) {
+    out.include.kj_rs = true;
+    write!(out, "::kj_rs::Result<");
+    match ret {
+        None => write!(out, "void"),
+        Some(ret) => write_type(out, ret),
+    }
+    write!(out, ", ");
+    write_type(out, error);
+    write!(out, ">");
+}
+
+fn write_typed_result_type_space(out: &mut OutFile, ret: &Option<Type>, error: &Type) {
+    write_typed_result_type(out, ret, error);
+    write!(out, " ");
+}
+
 // C++ functions always report exceptions through their return value, so any actual
 // return value has to travel through an out parameter.
 fn indirect_return(sig: &Signature) -> bool {
@@ -1202,6 +1888,8 @@ fn write_indirect_return_type(out: &mut OutFile, ty: &Type) {
             write!(out, "*");
         }
         Type::Future(_) => write!(out, "kj_rs::repr::KjPromiseNodeImpl"),
+        Type::KjStream(_) => write!(out, "::kj_rs::repr::KjStreamImpl"),
+        Type::KjFunction(_) => write!(out, "::kj_rs::repr::Closure"),
         // kj::Date travels across the boundary as nanoseconds since the unix epoch.
         Type::KjDate(_) => {
             out.include.cstdint = true;
@@ -1226,6 +1914,7 @@ fn write_extern_arg(out: &mut OutFile, arg: &Var) {
             write_type_space(out, &ty.inner);
             write!(out, "*");
         }
+        Type::KjFunction(_) => write!(out, "::kj_rs::repr::Closure "),
         _ => write_type_space(out, &arg.ty),
     }
     if out.types.needs_indirect_abi(&arg.ty) {
@@ -1321,18 +2010,13 @@ fn write_type(out: &mut OutFile, ty: &Type) {
         }
         Type::Fn(f) => {
             write!(out, "::rust::Fn<");
-            match &f.ret {
-                Some(ret) => write_type(out, ret),
-                None => write!(out, "void"),
-            }
-            write!(out, "(");
-            for (i, arg) in f.args.iter().enumerate() {
-                if i > 0 {
-                    write!(out, ", ");
-                }
-                write_type(out, &arg.ty);
-            }
-            write!(out, ")>");
+            write_function_type(out, f);
+            write!(out, ">");
+        }
+        Type::KjFunction(f) => {
+            write!(out, "::kj::Function<");
+            write_function_type(out, f);
+            write!(out, ">");
         }
         Type::Array(a) => {
             write!(out, "::std::array<");
@@ -1341,6 +2025,33 @@ fn write_type(out: &mut OutFile, ty: &Type) {
         }
         Type::Void(_) => write!(out, "void"),
         Type::KjDate(_) => write!(out, "::kj::Date"),
+        Type::KjString(_) => write!(out, "::kj::String"),
+        Type::KjStringPtr(_) => write!(out, "::kj::StringPtr"),
+        Type::KjArray(ty) => {
+            write!(out, "::kj::Array<");
+            write_type(out, &ty.inner);
+            write!(out, ">");
+        }
+        Type::KjArrayPtr(ty) => {
+            write!(out, "::kj::ArrayPtr<");
+            write_type_space(out, &ty.inner);
+            write!(out, "const>");
+        }
+        Type::KjStream(ty) => {
+            write!(out, "::kj_rs::Stream<");
+            write_type(out, &ty.inner);
+            write!(out, ">");
+        }
+        Type::KjOwnedPromise(ty) => {
+            write!(out, "::kj_rs::Promise<");
+            write_type(out, &ty.inner);
+            write!(out, ">");
+        }
+        Type::KjForkedPromise(ty) => {
+            write!(out, "::kj_rs::ForkedPromise<");
+            write_type(out, &ty.inner);
+            write!(out, ">");
+        }
         Type::Future(ty) => {
             write!(out, "kj::Promise<");
             write_type(out, &ty.output);
@@ -1370,6 +2081,22 @@ fn write_atom(out: &mut OutFile, atom: Atom) {
     }
 }
 
+// The `Ret(Args...)` of a function type.
+fn write_function_type(out: &mut OutFile, f: &Signature) {
+    match &f.ret {
+        Some(ret) => write_type(out, ret),
+        None => write!(out, "void"),
+    }
+    write!(out, "(");
+    for (i, arg) in f.args.iter().enumerate() {
+        if i > 0 {
+            write!(out, ", ");
+        }
+        write_type(out, &arg.ty);
+    }
+    write!(out, ")");
+}
+
 fn write_type_space(out: &mut OutFile, ty: &Type) {
     write_type(out, ty);
     write_space_after_type(out, ty);
@@ -1388,10 +2115,18 @@ fn write_space_after_type(out: &mut OutFile, ty: &Type) {
         | Type::Str(_)
         | Type::KjMaybe(_)
         | Type::KjDate(_)
+        | Type::KjString(_)
+        | Type::KjStringPtr(_)
+        | Type::KjArray(_)
+        | Type::KjArrayPtr(_)
+        | Type::KjStream(_)
+        | Type::KjOwnedPromise(_)
+        | Type::KjForkedPromise(_)
         | Type::CxxVector(_)
         | Type::RustVec(_)
         | Type::SliceRef(_)
         | Type::Fn(_)
+        | Type::KjFunction(_)
         | Type::Array(_) => write!(out, " "),
         Type::Ref(_) | Type::Ptr(_) => {}
         Type::Void(_) => unreachable!(),
@@ -1399,12 +2134,19 @@ fn write_space_after_type(out: &mut OutFile, ty: &Type) {
     }
 }
 
+// The target of a std::unique_ptr, std::shared_ptr or std::weak_ptr.
 #[derive(Copy, Clone)]
-enum UniquePtr<'a> {
+enum Pointee<'a> {
     Ident(&'a Ident),
     CxxVector(&'a Ident),
 }
 
+#[derive(Copy, Clone)]
+struct RustVecElement<'a> {
+    ident: &'a Ident,
+    element: VecElement,
+}
+
 trait ToTypename {
     fn to_typename(&self, types: &Types) -> String;
 }
@@ -1415,17 +2157,28 @@ impl ToTypename for Ident {
     }
 }
 
-impl ToTypename for UniquePtr<'_> {
+impl ToTypename for Pointee<'_> {
     fn to_typename(&self, types: &Types) -> String {
         match self {
-            UniquePtr::Ident(ident) => ident.to_typename(types),
-            UniquePtr::CxxVector(element) => {
+            Pointee::Ident(ident) => ident.to_typename(types),
+            Pointee::CxxVector(element) => {
                 format!("::std::vector<{}>", element.to_typename(types))
             }
         }
     }
 }
 
+impl ToTypename for RustVecElement<'_> {
+    fn to_typename(&self, types: &Types) -> String {
+        let inner = self.ident.to_typename(types);
+        match self.element {
+            VecElement::Value => inner,
+            VecElement::KjOwn => format!("::kj::Own<{}>", inner),
+            VecElement::UniquePtr => format!("::std::unique_ptr<{}>", inner),
+        }
+    }
+}
+
 trait ToMangled {
     fn to_mangled(&self, types: &Types) -> Symbol;
 }
@@ -1436,17 +2189,28 @@ impl ToMangled for Ident {
     }
 }
 
-impl ToMangled for UniquePtr<'_> {
+impl ToMangled for Pointee<'_> {
     fn to_mangled(&self, types: &Types) -> Symbol {
         match self {
-            UniquePtr::Ident(ident) => ident.to_mangled(types),
-            UniquePtr::CxxVector(element) => {
+            Pointee::Ident(ident) => ident.to_mangled(types),
+            Pointee::CxxVector(element) => {
                 symbol::join(&[&"std", &"vector", &element.to_mangled(types)])
             }
         }
     }
 }
 
+impl ToMangled for RustVecElement<'_> {
+    fn to_mangled(&self, types: &Types) -> Symbol {
+        let inner = self.ident.to_mangled(types);
+        match self.element {
+            VecElement::Value => inner,
+            VecElement::KjOwn => symbol::join(&[&"kj", &"Own", &inner]),
+            VecElement::UniquePtr => symbol::join(&[&"std", &"unique_ptr", &inner]),
+        }
+    }
+}
+
 fn write_generic_instantiations(out: &mut OutFile) {
     if out.header {
         return;
@@ -1459,7 +2223,7 @@ fn write_generic_instantiations(out: &mut OutFile) {
         out.next_section();
         match *impl_key {
             ImplKey::RustBox(ident) => write_rust_box_extern(out, ident),
-            ImplKey::RustVec(ident) => write_rust_vec_extern(out, ident),
+            ImplKey::RustVec(ident, element) => write_rust_vec_extern(out, ident, element),
             ImplKey::UniquePtr(ident) => write_unique_ptr(out, ident),
             ImplKey::Own(ident) => write_kj_own(out, ident),
             ImplKey::Maybe(ident) => write_kj_maybe(out, ident),
@@ -1477,7 +2241,7 @@ fn write_generic_instantiations(out: &mut OutFile) {
     for impl_key in out.types.impls.keys() {
         match *impl_key {
             ImplKey::RustBox(ident) => write_rust_box_impl(out, ident),
-            ImplKey::RustVec(ident) => write_rust_vec_impl(out, ident),
+            ImplKey::RustVec(ident, element) => write_rust_vec_impl(out, ident, element),
             _ => {}
         }
     }
@@ -1507,13 +2271,32 @@ fn write_rust_box_extern(out: &mut OutFile, key: NamedImplKey) {
     );
 }
 
-fn write_rust_vec_extern(out: &mut OutFile, key: NamedImplKey) {
-    let element = key.rust;
+fn write_rust_vec_extern(out: &mut OutFile, key: NamedImplKey, element: VecElement) {
+    // Elements of an extern C++ type, which must be trivially relocatable, are destroyed by their
+    // C++ destructor when the Vec is dropped or truncated from C++.
+    let cxx_element =
+        element == VecElement::Value && out.types.required_trivial.contains_key(key.rust);
+    let element = RustVecElement {
+        ident: key.rust,
+        element,
+    };
     let inner = element.to_typename(out.types);
     let instance = element.to_mangled(out.types);
 
     out.include.cstddef = true;
 
+    if cxx_element {
+        out.include.memory = true;
+        begin_function_definition(out);
+        writeln!(
+            out,
+            "void cxxbridge1$rust_vec${}$destroy({} *ptr, ::std::size_t len) noexcept {{",
+            instance, inner,
+        );
+        writeln!(out, "  ::std::destroy_n(ptr, len);");
+        writeln!(out, "}}");
+    }
+
     writeln!(
         out,
         "void cxxbridge1$rust_vec${}$new(::rust::Vec<{}> const *ptr) noexcept;",
@@ -1588,8 +2371,11 @@ fn write_rust_box_impl(out: &mut OutFile, key: NamedImplKey) {
     writeln!(out, "}}");
 }
 
-fn write_rust_vec_impl(out: &mut OutFile, key: NamedImplKey) {
-    let element = key.rust;
+fn write_rust_vec_impl(out: &mut OutFile, key: NamedImplKey, element: VecElement) {
+    let element = RustVecElement {
+        ident: key.rust,
+        element,
+    };
     let inner = element.to_typename(out.types);
     let instance = element.to_mangled(out.types);
 
@@ -1701,6 +2487,19 @@ fn write_kj_own(out: &mut OutFile, key: NamedImplKey) {
         "static_assert(!::kj::_::IsRefcounted<{}>, \"Value must not inherit from kj::Refcounted\");",
         inner
     );
+
+    // Rust creates the kj::Own of a Box with this disposer, which runs Rust's drop.
+    if out.types.rust.contains(ident) {
+        let instance = resolve.name.to_symbol();
+        begin_function_definition(out);
+        writeln!(
+            out,
+            "::kj::Disposer const *cxxbridge1$own${}$disposer() noexcept {{",
+            instance,
+        );
+        writeln!(out, "  return &::kj_rs::RustDisposer<{}>::instance;", inner);
+        writeln!(out, "}}");
+    }
 }
 
 // Writes static assertions for Maybe.
@@ -1769,12 +2568,12 @@ fn write_kj_arc(out: &mut OutFile, key: NamedImplKey) {
 }
 
 fn write_unique_ptr(out: &mut OutFile, key: NamedImplKey) {
-    let ty = UniquePtr::Ident(key.rust);
+    let ty = Pointee::Ident(key.rust);
     write_unique_ptr_common(out, ty);
 }
 
 // Shared by UniquePtr<T> and UniquePtr<CxxVector<T>>.
-fn write_unique_ptr_common(out: &mut OutFile, ty: UniquePtr) {
+fn write_unique_ptr_common(out: &mut OutFile, ty: Pointee) {
     out.include.new = true;
     out.include.utility = true;
     let inner = ty.to_typename(out.types);
@@ -1785,22 +2584,24 @@ fn write_unique_ptr_common(out: &mut OutFile, ty: UniquePtr) {
         // know at code generation time, so we generate both C++ and Rust side
         // bindings for a "new" method anyway. But the Rust code can't be called
         // for Opaque types because the 'new' method is not implemented.
-        UniquePtr::Ident(ident) => out.types.is_maybe_trivial(ident),
-        UniquePtr::CxxVector(_) => false,
+        Pointee::Ident(ident) => out.types.is_maybe_trivial(ident),
+        Pointee::CxxVector(_) => false,
     };
 
     let conditional_delete = match ty {
-        UniquePtr::Ident(ident) => {
-            !out.types.structs.contains_key(ident) && !out.types.enums.contains_key(ident)
+        Pointee::Ident(ident) => {
+            !out.types.structs.contains_key(ident)
+                && !out.types.enums.contains_key(ident)
+                && !out.types.data_enums.contains_key(ident)
         }
-        UniquePtr::CxxVector(_) => false,
+        Pointee::CxxVector(_) => false,
     };
 
     if conditional_delete {
         out.builtin.is_complete = true;
         let definition = match ty {
-            UniquePtr::Ident(ty) => &out.types.resolve(ty).name.cxx,
-            UniquePtr::CxxVector(_) => unreachable!(),
+            Pointee::Ident(ty) => &out.types.resolve(ty).name.cxx,
+            Pointee::CxxVector(_) => unreachable!(),
         };
         writeln!(
             out,
@@ -1893,10 +2694,14 @@ fn write_unique_ptr_common(out: &mut OutFile, ty: UniquePtr) {
 }
 
 fn write_shared_ptr(out: &mut OutFile, key: NamedImplKey) {
-    let ident = key.rust;
-    let resolve = out.types.resolve(ident);
-    let inner = resolve.name.to_fully_qualified();
-    let instance = resolve.name.to_symbol();
+    let ty = Pointee::Ident(key.rust);
+    write_shared_ptr_common(out, ty);
+}
+
+// Shared by SharedPtr<T> and SharedPtr<CxxVector<T>>.
+fn write_shared_ptr_common(out: &mut OutFile, ty: Pointee) {
+    let inner = ty.to_typename(out.types);
+    let instance = ty.to_mangled(out.types);
 
     out.include.new = true;
     out.include.utility = true;
@@ -1905,7 +2710,10 @@ fn write_shared_ptr(out: &mut OutFile, key: NamedImplKey) {
     // know at code generation time, so we generate both C++ and Rust side
     // bindings for a "new" method anyway. But the Rust code can't be called for
     // Opaque types because the 'new' method is not implemented.
-    let can_construct_from_value = out.types.is_maybe_trivial(ident);
+    let can_construct_from_value = match ty {
+        Pointee::Ident(ident) => out.types.is_maybe_trivial(ident),
+        Pointee::CxxVector(_) => false,
+    };
 
     writeln!(
         out,
@@ -1974,9 +2782,14 @@ fn write_shared_ptr(out: &mut OutFile, key: NamedImplKey) {
 }
 
 fn write_weak_ptr(out: &mut OutFile, key: NamedImplKey) {
-    let resolve = out.types.resolve(&key);
-    let inner = resolve.name.to_fully_qualified();
-    let instance = resolve.name.to_symbol();
+    let ty = Pointee::Ident(key.rust);
+    write_weak_ptr_common(out, ty);
+}
+
+// Shared by WeakPtr<T> and WeakPtr<CxxVector<T>>.
+fn write_weak_ptr_common(out: &mut OutFile, ty: Pointee) {
+    let inner = ty.to_typename(out.types);
+    let instance = ty.to_mangled(out.types);
 
     out.include.new = true;
     out.include.utility = true;
@@ -2101,5 +2914,9 @@ fn write_cxx_vector(out: &mut OutFile, key: NamedImplKey) {
     }
 
     out.include.memory = true;
-    write_unique_ptr_common(out, UniquePtr::CxxVector(element));
+    write_unique_ptr_common(out, Pointee::CxxVector(element));
+    out.next_section();
+    write_shared_ptr_common(out, Pointee::CxxVector(element));
+    out.next_section();
+    write_weak_ptr_common(out, Pointee::CxxVector(element));
 }
@@ -48,14 +48,20 @@ namespace repr {
 
 // corresponds to `result::Result` in Rust.
 struct Result final {
-  // Tagged pointer: nullptr=None, 0x1=Canceled, other=KjException*
+  // Tagged pointer: nullptr=None, 0x1=Canceled, 0x2=TypedError, other=KjException*
   kj::Exception *exception;
 
+  // True if a Rust function returning Result<T, E> failed with a typed error,
+  // which has been written to the caller's error slot instead of thrown.
+  inline bool typed_error() const noexcept {
+    return reinterpret_cast<uintptr_t>(exception) == 2;
+  }
+
   // always called at the end of Result lifecycle by generated code.
   inline void check() {
     auto ptr = reinterpret_cast<uintptr_t>(exception);
-    if (ptr == 0) {
-      // None
+    if (ptr == 0 || ptr == 2) {
+      // None, or TypedError which the caller has already taken out of its error slot
       return;
     } else if (ptr == 1) {
       // Canceled
@@ -108,6 +114,58 @@ struct Result final {
 
 } // namespace repr
 
+#ifndef CXXBRIDGE1_EXCEPTION_DETAIL
+#define CXXBRIDGE1_EXCEPTION_DETAIL
+// Typed view of a kj::Exception::Detail, the C++ side of Rust's
+// `cxx::ExceptionDetail` trait. Specialize it for a type with the same id and
+// byte encoding as its Rust counterpart:
+//
+//   template <> struct rust::ExceptionDetail<RetryAfter> {
+//     static constexpr kj::Exception::DetailTypeId TYPE_ID = ...;
+//     static kj::Array<kj::byte> encode(const RetryAfter &value);
+//     static kj::Maybe<RetryAfter> decode(kj::ArrayPtr<const kj::byte> data);
+//   };
+template <typename T>
+struct ExceptionDetail;
+
+// Attaches `value` to `exception`, replacing any detail with the same id.
+template <typename T>
+void setDetail(kj::Exception &exception, const T &value) {
+  exception.setDetail(ExceptionDetail<T>::TYPE_ID,
+                      ExceptionDetail<T>::encode(value));
+}
+
+// Returns the detail of type T, if `exception` carries one that decodes
+// successfully.
+template <typename T>
+kj::Maybe<T> getDetail(const kj::Exception &exception) {
+  KJ_IF_SOME(data, exception.getDetail(ExceptionDetail<T>::TYPE_ID)) {
+    return ExceptionDetail<T>::decode(data);
+  }
+  return kj::none;
+}
+
+// Backtrace of the Rust panic an exception was created from, attached when
+// backtraces are enabled through RUST_BACKTRACE or RUST_LIB_BACKTRACE.
+struct PanicBacktrace final {
+  kj::String text;
+};
+
+template <>
+struct ExceptionDetail<PanicBacktrace> {
+  // "rustbtrc", must match `cxx::PanicBacktrace` in unwind.rs.
+  static constexpr kj::Exception::DetailTypeId TYPE_ID = 0x7275737462747263;
+
+  static kj::Array<kj::byte> encode(const PanicBacktrace &value) {
+    return kj::heapArray(value.text.asBytes());
+  }
+
+  static kj::Maybe<PanicBacktrace> decode(kj::ArrayPtr<const kj::byte> data) {
+    return PanicBacktrace{kj::str(data.asChars())};
+  }
+};
+#endif // CXXBRIDGE1_EXCEPTION_DETAIL
+
 #ifndef CXXBRIDGE1_RUST_STRING
 #define CXXBRIDGE1_RUST_STRING
 // https://cxx.rs/binding/string.html
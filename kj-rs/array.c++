@@ -0,0 +1,50 @@
+#include "array.h"
+
+namespace {
+
+// `kj::ArrayDisposer::disposeImpl()` is protected. Re-declaring it public in a subclass lets us
+// form a member pointer that can be invoked on any disposer.
+class DisposerAccess: public kj::ArrayDisposer {
+ public:
+  using kj::ArrayDisposer::disposeImpl;
+};
+
+class RustVecArrayDisposer final: public kj::ArrayDisposer {
+ public:
+  using DropVec = void (*)(void* ptr, size_t len, size_t capacity);
+
+  RustVecArrayDisposer(size_t capacity, DropVec dropVec): capacity(capacity), dropVec(dropVec) {}
+
+ protected:
+  void disposeImpl(void* firstElement,
+      size_t,
+      size_t elementCount,
+      size_t,
+      void (*)(void*)) const override {
+    // Rust drops the elements along with the `Vec`, so the element destructor is not needed.
+    dropVec(firstElement, elementCount, capacity);
+    delete this;
+  }
+
+ private:
+  size_t capacity;
+  DropVec dropVec;
+};
+
+}  // namespace
+
+extern "C" {
+
+void cxxbridge$kjrs$array$dispose(const kj::ArrayDisposer* disposer,
+    void* ptr,
+    size_t elementSize,
+    size_t size,
+    void (*destroyElement)(void*)) {
+  (disposer->*(&DisposerAccess::disposeImpl))(ptr, elementSize, size, size, destroyElement);
+}
+
+const kj::ArrayDisposer* cxxbridge$kjrs$array$rust_vec_disposer(
+    size_t capacity, void (*dropVec)(void* ptr, size_t len, size_t capacity)) {
+  return new RustVecArrayDisposer(capacity, dropVec);
+}
+}
@@ -0,0 +1,24 @@
+#pragma once
+
+#include <kj/array.h>
+
+#include <cstddef>
+
+extern "C" {
+
+// Rust `kj_rs::repr::KjArray<T>` mirrors `kj::Array<T>` as data pointer, size, disposer. Rust does
+// not know `T`'s C++ type, so these functions work on the element size instead.
+
+// Releases `size` elements of `elementSize` bytes at `ptr` through `disposer`, like
+// `kj::Array<T>`'s destructor would. `destroyElement` is null for trivially destructible elements.
+void cxxbridge$kjrs$array$dispose(const kj::ArrayDisposer* disposer,
+    void* ptr,
+    size_t elementSize,
+    size_t size,
+    void (*destroyElement)(void*));
+
+// Returns a heap allocated disposer for a buffer leaked from a Rust `Vec<T>` with the given
+// capacity. Disposing the array hands the buffer back to `dropVec` and deletes the disposer.
+const kj::ArrayDisposer* cxxbridge$kjrs$array$rust_vec_disposer(
+    size_t capacity, void (*dropVec)(void* ptr, size_t len, size_t capacity));
+}
@@ -0,0 +1,303 @@
+//! Bindings to `kj::Array<T>` and `kj::ArrayPtr<const T>`.
+//!
+//! Element types must have the same layout in Rust and C++, which the bridge checks for. Arrays
+//! allocated on either side are released through their `kj::ArrayDisposer`, so a [`KjArray`] built
+//! from a `Vec<T>` can be dropped by C++ and vice versa.
+
+use static_assertions::{assert_eq_align, assert_eq_size};
+use std::ffi::c_void;
+
+assert_eq_size!(repr::KjArray<u8>, [*const (); 3]);
+assert_eq_align!(repr::KjArray<u8>, *const ());
+assert_eq_size!(repr::KjArrayPtr<'static, u8>, [*const (); 2]);
+assert_eq_align!(repr::KjArrayPtr<'static, u8>, *const ());
+
+/// Passed to `kj::ArrayDisposer` as the element destructor, mirroring what `kj::Array<T>` does for
+/// non-trivially destructible `T`.
+///
+/// Safety: `element` must point to a valid `T` that is not used afterwards.
+unsafe extern "C" fn destroy_element<T>(element: *mut c_void) {
+    unsafe { std::ptr::drop_in_place(element.cast::<T>()) }
+}
+
+/// Called by the C++ disposer of a `kj::Array` that was built from a `Vec<T>`.
+///
+/// Safety: the arguments must come from a `Vec<T>` that was leaked by [`repr::KjArray::from`].
+unsafe extern "C" fn drop_vec<T>(ptr: *mut c_void, len: usize, capacity: usize) {
+    drop(unsafe { Vec::from_raw_parts(ptr.cast::<T>(), len, capacity) });
+}
+
+/// Empty arrays may carry a null pointer, which `std::slice::from_raw_parts` does not accept.
+///
+/// Safety: `ptr` must point to `size` initialized elements living for `'a` when `size > 0`.
+unsafe fn slice<'a, T>(ptr: *const T, size: usize) -> &'a [T] {
+    if size == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(ptr, size) }
+    }
+}
+
+pub mod repr {
+    use super::{destroy_element, drop_vec, slice};
+    use std::ffi::c_void;
+    use std::fmt::{self, Debug};
+    use std::hash::{Hash, Hasher};
+    use std::marker::PhantomData;
+    use std::mem::ManuallyDrop;
+    use std::ops::{Deref, DerefMut};
+
+    /// Bindings to the kj type `kj::Array<T>`. An owned, fixed size array released by its
+    /// `kj::ArrayDisposer`.
+    #[repr(C)]
+    pub struct KjArray<T> {
+        ptr: *mut T,
+        size: usize,
+        disposer: *const c_void,
+    }
+
+    /// Bindings to the kj type `kj::ArrayPtr<const T>`. A borrowed, read-only view of contiguous
+    /// elements, the kj equivalent of `&'a [T]`.
+    #[repr(C)]
+    pub struct KjArrayPtr<'a, T> {
+        ptr: *const T,
+        size: usize,
+        _marker: PhantomData<&'a [T]>,
+    }
+
+    unsafe impl<T: Send> Send for KjArray<T> {}
+    unsafe impl<T: Sync> Sync for KjArray<T> {}
+    unsafe impl<T: Sync> Send for KjArrayPtr<'_, T> {}
+    unsafe impl<T: Sync> Sync for KjArrayPtr<'_, T> {}
+
+    impl<T> KjArray<T> {
+        /// Returns the elements as a slice.
+        #[must_use]
+        pub fn as_slice(&self) -> &[T] {
+            unsafe { slice(self.ptr, self.size) }
+        }
+
+        /// Returns the elements as a mutable slice.
+        #[must_use]
+        pub fn as_mut_slice(&mut self) -> &mut [T] {
+            if self.size == 0 {
+                &mut []
+            } else {
+                unsafe { std::slice::from_raw_parts_mut(self.ptr, self.size) }
+            }
+        }
+
+        /// Borrows this array as a `kj::ArrayPtr`.
+        #[must_use]
+        pub fn as_array_ptr(&self) -> KjArrayPtr<'_, T> {
+            KjArrayPtr::from(self.as_slice())
+        }
+    }
+
+    impl<'a, T> KjArrayPtr<'a, T> {
+        /// Returns the elements as a slice.
+        #[must_use]
+        pub fn as_slice(&self) -> &'a [T] {
+            unsafe { slice(self.ptr, self.size) }
+        }
+    }
+
+    impl<T> Default for KjArray<T> {
+        /// Returns the empty array, which like `kj::Array<T>()` owns no allocation.
+        fn default() -> Self {
+            Self {
+                ptr: std::ptr::null_mut(),
+                size: 0,
+                disposer: std::ptr::null(),
+            }
+        }
+    }
+
+    impl<T> Default for KjArrayPtr<'_, T> {
+        fn default() -> Self {
+            Self::from(&[][..])
+        }
+    }
+
+    impl<T> From<Vec<T>> for KjArray<T> {
+        /// Hands the vector's buffer over to a `kj::Array` without copying. The buffer is returned
+        /// to Rust to be freed when the array is disposed.
+        fn from(vec: Vec<T>) -> Self {
+            unsafe extern "C" {
+                #[link_name = "cxxbridge$kjrs$array$rust_vec_disposer"]
+                fn __rust_vec_disposer(
+                    capacity: usize,
+                    drop_vec: unsafe extern "C" fn(*mut c_void, usize, usize),
+                ) -> *const c_void;
+            }
+
+            if vec.is_empty() {
+                return Self::default();
+            }
+
+            let mut vec = ManuallyDrop::new(vec);
+            Self {
+                ptr: vec.as_mut_ptr(),
+                size: vec.len(),
+                disposer: unsafe { __rust_vec_disposer(vec.capacity(), drop_vec::<T>) },
+            }
+        }
+    }
+
+    impl<T: Clone> From<&[T]> for KjArray<T> {
+        fn from(elements: &[T]) -> Self {
+            Self::from(elements.to_vec())
+        }
+    }
+
+    impl<'a, T> From<&'a [T]> for KjArrayPtr<'a, T> {
+        fn from(elements: &'a [T]) -> Self {
+            Self {
+                ptr: elements.as_ptr(),
+                size: elements.len(),
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    impl<'a, T> From<&'a KjArray<T>> for KjArrayPtr<'a, T> {
+        fn from(array: &'a KjArray<T>) -> Self {
+            array.as_array_ptr()
+        }
+    }
+
+    impl<T: Clone> Clone for KjArray<T> {
+        fn clone(&self) -> Self {
+            Self::from(self.as_slice())
+        }
+    }
+
+    impl<T> Clone for KjArrayPtr<'_, T> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+
+    impl<T> Copy for KjArrayPtr<'_, T> {}
+
+    impl<T> Deref for KjArray<T> {
+        type Target = [T];
+
+        fn deref(&self) -> &Self::Target {
+            self.as_slice()
+        }
+    }
+
+    impl<T> DerefMut for KjArray<T> {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            self.as_mut_slice()
+        }
+    }
+
+    impl<T> Deref for KjArrayPtr<'_, T> {
+        type Target = [T];
+
+        fn deref(&self) -> &Self::Target {
+            self.as_slice()
+        }
+    }
+
+    impl<T> AsRef<[T]> for KjArray<T> {
+        fn as_ref(&self) -> &[T] {
+            self.as_slice()
+        }
+    }
+
+    impl<T> AsRef<[T]> for KjArrayPtr<'_, T> {
+        fn as_ref(&self) -> &[T] {
+            self.as_slice()
+        }
+    }
+
+    impl<T: Debug> Debug for KjArray<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Debug::fmt(self.as_slice(), f)
+        }
+    }
+
+    impl<T: Debug> Debug for KjArrayPtr<'_, T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Debug::fmt(self.as_slice(), f)
+        }
+    }
+
+    impl<T: PartialEq> PartialEq for KjArray<T> {
+        fn eq(&self, other: &Self) -> bool {
+            self.as_slice() == other.as_slice()
+        }
+    }
+
+    impl<T: Eq> Eq for KjArray<T> {}
+
+    impl<T: PartialEq> PartialEq for KjArrayPtr<'_, T> {
+        fn eq(&self, other: &Self) -> bool {
+            self.as_slice() == other.as_slice()
+        }
+    }
+
+    impl<T: Eq> Eq for KjArrayPtr<'_, T> {}
+
+    impl<T: PartialEq> PartialEq<[T]> for KjArray<T> {
+        fn eq(&self, other: &[T]) -> bool {
+            self.as_slice() == other
+        }
+    }
+
+    impl<T: PartialEq> PartialEq<[T]> for KjArrayPtr<'_, T> {
+        fn eq(&self, other: &[T]) -> bool {
+            self.as_slice() == other
+        }
+    }
+
+    impl<T: Hash> Hash for KjArray<T> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.as_slice().hash(state);
+        }
+    }
+
+    impl<T: Hash> Hash for KjArrayPtr<'_, T> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.as_slice().hash(state);
+        }
+    }
+
+    impl<T> Drop for KjArray<T> {
+        fn drop(&mut self) {
+            unsafe extern "C" {
+                #[link_name = "cxxbridge$kjrs$array$dispose"]
+                fn __dispose(
+                    disposer: *const c_void,
+                    ptr: *mut c_void,
+                    element_size: usize,
+                    size: usize,
+                    destroy_element: Option<unsafe extern "C" fn(*mut c_void)>,
+                );
+            }
+
+            if self.ptr.is_null() {
+                return;
+            }
+
+            let destroy_element: Option<unsafe extern "C" fn(*mut c_void)> =
+                if std::mem::needs_drop::<T>() {
+                    Some(destroy_element::<T>)
+                } else {
+                    None
+                };
+            unsafe {
+                __dispose(
+                    self.disposer,
+                    self.ptr.cast::<c_void>(),
+                    size_of::<T>(),
+                    self.size,
+                    destroy_element,
+                );
+            }
+        }
+    }
+}
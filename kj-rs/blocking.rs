@@ -0,0 +1,207 @@
+//! Running blocking or CPU-heavy closures off the KJ event loop.
+//!
+//! [`spawn_blocking`] queues a closure on a pool of Rust threads and returns a future for its
+//! result. The worker wakes the future through its [`std::task::Waker`], which for a future polled
+//! by KJ is a `KjWaker` completing a `kj::CrossThreadPromiseFulfiller` on the originating event
+//! loop, so no KJ state is ever touched from the worker.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread::JoinHandle;
+
+use cxx::KjException;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed set of threads running closures passed to [`BlockingPool::spawn`].
+///
+/// Dropping the pool waits for the queued and running closures to finish.
+pub struct BlockingPool {
+    queue: Arc<Queue>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct Queue {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: VecDeque<Job>,
+    shutdown: bool,
+}
+
+static GLOBAL: OnceLock<BlockingPool> = OnceLock::new();
+
+impl BlockingPool {
+    /// Starts a pool of `threads` threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread can't be spawned.
+    #[must_use]
+    pub fn new(threads: NonZeroUsize) -> Self {
+        let queue = Arc::new(Queue::default());
+        let threads = (0..threads.get())
+            .map(|i| {
+                let queue = queue.clone();
+                std::thread::Builder::new()
+                    .name(format!("kj-rs-blocking-{i}"))
+                    .spawn(move || queue.run())
+                    .expect("failed to spawn a blocking pool thread")
+            })
+            .collect();
+        Self { queue, threads }
+    }
+
+    /// Makes this pool the one used by [`spawn_blocking`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the pool if [`spawn_blocking`] has already started the default pool or another
+    /// pool was installed.
+    pub fn install_global(self) -> Result<(), BlockingPool> {
+        GLOBAL.set(self)
+    }
+
+    /// Runs `f` on the pool and returns a future for its result. A panic of `f` is reported as a
+    /// `kj::Exception`, like a panic of an `extern "Rust"` function. Dropping the future before `f`
+    /// has started cancels it; once started, `f` runs to completion and its result is discarded.
+    pub fn spawn<F, T>(&self, f: F) -> BlockingTask<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let shared = Arc::new(Mutex::new(TaskState::Queued(None)));
+        let job_shared = shared.clone();
+        self.queue.push(Box::new(move || run_task(&job_shared, f)));
+        BlockingTask { shared }
+    }
+}
+
+impl Drop for BlockingPool {
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().shutdown = true;
+        self.queue.ready.notify_all();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Queue {
+    fn push(&self, job: Job) {
+        self.state.lock().unwrap().jobs.push_back(job);
+        self.ready.notify_one();
+    }
+
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(job) = state.jobs.pop_front() {
+                drop(state);
+                job();
+                state = self.state.lock().unwrap();
+            } else if state.shutdown {
+                return;
+            } else {
+                state = self.ready.wait(state).unwrap();
+            }
+        }
+    }
+}
+
+/// Runs `f` on the global [`BlockingPool`] and returns a future for its result, see
+/// [`BlockingPool::spawn`].
+///
+/// Unless [`BlockingPool::install_global`] was called first, the global pool is started on first
+/// use with one thread per available CPU.
+pub fn spawn_blocking<F, T>(f: F) -> BlockingTask<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    GLOBAL
+        .get_or_init(|| {
+            let threads = std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN);
+            BlockingPool::new(threads)
+        })
+        .spawn(f)
+}
+
+enum TaskState<T> {
+    // Waiting for a worker, with the waker of the last poll.
+    Queued(Option<Waker>),
+    Running(Option<Waker>),
+    Done(Result<T, KjException>),
+    // The output was returned, or the task was dropped before it started.
+    Gone,
+}
+
+fn run_task<F, T>(shared: &Mutex<TaskState<T>>, f: F)
+where
+    F: FnOnce() -> T,
+{
+    {
+        let mut state = shared.lock().unwrap();
+        match std::mem::replace(&mut *state, TaskState::Gone) {
+            TaskState::Queued(waker) => *state = TaskState::Running(waker),
+            // Canceled.
+            _ => return,
+        }
+    }
+    let result = cxx::private::catch_unwind_exception("spawn_blocking", f);
+    let mut state = shared.lock().unwrap();
+    let TaskState::Running(waker) = &mut *state else {
+        // The task was dropped while running.
+        return;
+    };
+    let waker = waker.take();
+    *state = TaskState::Done(result);
+    drop(state);
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+/// The future returned by [`spawn_blocking`] and [`BlockingPool::spawn`].
+pub struct BlockingTask<T> {
+    shared: Arc<Mutex<TaskState<T>>>,
+}
+
+impl<T> Future for BlockingTask<T> {
+    type Output = Result<T, KjException>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.lock().unwrap();
+        match &mut *state {
+            TaskState::Queued(waker) | TaskState::Running(waker) => {
+                if !waker.as_ref().is_some_and(|w| w.will_wake(cx.waker())) {
+                    *waker = Some(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+            TaskState::Done(_) => {
+                let TaskState::Done(result) = std::mem::replace(&mut *state, TaskState::Gone)
+                else {
+                    unreachable!()
+                };
+                Poll::Ready(result)
+            }
+            TaskState::Gone => panic!("BlockingTask polled after completion"),
+        }
+    }
+}
+
+impl<T> Drop for BlockingTask<T> {
+    fn drop(&mut self) {
+        // Cancels the task if it hasn't started yet.
+        *self.shared.lock().unwrap() = TaskState::Gone;
+    }
+}
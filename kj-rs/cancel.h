@@ -0,0 +1,118 @@
+#pragma once
+
+#include <rust/cxx.h>
+
+#include <kj/common.h>
+#include <kj/exception.h>
+#include <kj/string.h>
+
+extern "C" {
+
+// These functions are implemented in cancel.rs. `token` and `out` point to the `repr` of a
+// `kj_rs::CancellationToken`, which holds a Rust `kj_rs::CancellationToken`.
+
+// Constructs a new, uncanceled token into `out`.
+void cxxbridge$kjrs$cancellation_token$new(void* out) noexcept;
+
+// Constructs a token sharing the signal of `token` into `out`.
+void cxxbridge$kjrs$cancellation_token$clone(const void* token, void* out) noexcept;
+
+// Destroys `token` in place.
+void cxxbridge$kjrs$cancellation_token$drop(void* token) noexcept;
+
+// Cancels `token`, taking ownership of `reason`, which must be allocated with new.
+void cxxbridge$kjrs$cancellation_token$cancel(const void* token, kj::Exception* reason) noexcept;
+
+bool cxxbridge$kjrs$cancellation_token$is_canceled(const void* token) noexcept;
+
+// Makes `reason` the current cancellation reason of the thread, taking ownership of it. `reason`
+// must be allocated with new.
+void cxxbridge$kjrs$cancellation_scope$enter(kj::Exception* reason) noexcept;
+
+// Restores the reason current before the matching `enter()`.
+void cxxbridge$kjrs$cancellation_scope$exit() noexcept;
+
+// The current cancellation reason of the thread, or null. Entered from C++ or from Rust.
+const kj::Exception* cxxbridge$kjrs$cancellation_scope$current() noexcept;
+}
+
+namespace kj_rs {
+
+// A cancellation signal shared with Rust futures, the C++ side of `kj_rs::CancellationToken`.
+//
+// Pass a copy to a Rust async function, as a `kj::Own<CancellationToken>`, then call `cancel()` to
+// ask its future to stop. Unlike
+// destroying the promise, this lets the future clean up asynchronously, and it typically rejects
+// with the exception passed to `cancel()`. Copies share the same signal. A token must only be used
+// on the thread that created it.
+class CancellationToken {
+ public:
+  CancellationToken() {
+    cxxbridge$kjrs$cancellation_token$new(&repr);
+  }
+  // There is deliberately no move constructor: a moved-from token would be invalid on the Rust side.
+  CancellationToken(const CancellationToken& other) {
+    cxxbridge$kjrs$cancellation_token$clone(&other.repr, &repr);
+  }
+  CancellationToken& operator=(const CancellationToken& other) {
+    if (this != &other) {
+      cxxbridge$kjrs$cancellation_token$drop(&repr);
+      cxxbridge$kjrs$cancellation_token$clone(&other.repr, &repr);
+    }
+    return *this;
+  }
+  ~CancellationToken() noexcept {
+    cxxbridge$kjrs$cancellation_token$drop(&repr);
+  }
+
+  // Cancels with `reason`, waking any Rust future waiting for cancellation. Only the first reason
+  // is kept, later calls are ignored.
+  void cancel(kj::Exception&& reason) {
+    cxxbridge$kjrs$cancellation_token$cancel(&repr, new kj::Exception(kj::mv(reason)));
+  }
+
+  // Cancels with an exception of `type` carrying `reason`. DISCONNECTED by default, like
+  // `kj::Canceler::cancel()`.
+  void cancel(
+      kj::StringPtr reason, kj::Exception::Type type = kj::Exception::Type::DISCONNECTED) {
+    cancel(kj::Exception(type, __FILE__, __LINE__, kj::str(reason)));
+  }
+
+  bool isCanceled() const {
+    return cxxbridge$kjrs$cancellation_token$is_canceled(&repr);
+  }
+
+ private:
+  void* repr;
+};
+
+// Makes `reason` the current cancellation reason of the thread until destroyed. Destroying the
+// promise of a Rust future inside the scope lets the future's `Drop` impls see why, as Rust's
+// `kj_rs::CancellationReason::current()`. Combined with `kj::Canceler`, the reason both rejects the
+// wrapped promises and reaches the futures they drop:
+//
+//   kj_rs::CancellationScope scope(kj::cp(reason));
+//   canceler.cancel(reason);
+//
+// Rust sets the same reason when it cancels a C++ promise with `KjPromiseFuture::cancel()`.
+class CancellationScope {
+ public:
+  explicit CancellationScope(kj::Exception reason) {
+    cxxbridge$kjrs$cancellation_scope$enter(new kj::Exception(kj::mv(reason)));
+  }
+  ~CancellationScope() noexcept {
+    cxxbridge$kjrs$cancellation_scope$exit();
+  }
+  KJ_DISALLOW_COPY_AND_MOVE(CancellationScope);
+
+  // The reason of the innermost scope on this thread, entered from C++ or Rust.
+  static kj::Maybe<const kj::Exception&> current() {
+    auto reason = cxxbridge$kjrs$cancellation_scope$current();
+    if (reason == nullptr) {
+      return kj::none;
+    }
+    return *reason;
+  }
+};
+
+}  // namespace kj_rs
@@ -0,0 +1,343 @@
+//! Cooperative cancellation of Rust futures driven by KJ.
+//!
+//! Destroying the `kj::Promise` of a Rust future drops the future on the spot, without a chance to
+//! clean up asynchronously or to learn why it was canceled. A [`CancellationToken`] shared between
+//! the caller and the future lets the caller request cancellation with a [`CancellationReason`]
+//! instead, and leaves it to the future to decide how to wind down.
+//!
+//! When a future or promise has to be dropped anyway, the reason can still travel with the drop:
+//! C++ destroys promises inside a `kj_rs::CancellationScope`, and Rust cancels C++ promises with
+//! [`crate::KjPromiseFuture::cancel`]. Code running during the drop, on either side, reads the
+//! reason with [`CancellationReason::current`] or `kj_rs::CancellationScope::current()`.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::c_void;
+use std::fmt::{self, Display};
+use std::future::Future;
+use std::mem::ManuallyDrop;
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+use cxx::{ExternType, IntoKjException, KjError, KjException, KjExceptionType};
+use static_assertions::{assert_eq_align, assert_eq_size};
+
+assert_eq_size!(CancellationToken, *const ());
+assert_eq_align!(CancellationToken, *const ());
+
+/// Why a future was canceled, carrying the `kj::Exception` passed to
+/// [`CancellationToken::cancel`].
+#[derive(Clone, Debug)]
+pub struct CancellationReason(KjException);
+
+impl CancellationReason {
+    /// Creates a `DISCONNECTED` reason, the same kind of exception `kj::Canceler::cancel()` builds
+    /// from a description.
+    #[must_use]
+    #[track_caller]
+    pub fn new(description: &str) -> Self {
+        Self::with_type(KjExceptionType::Disconnected, description)
+    }
+
+    /// Creates a reason with an exception of the given type, e.g. `Overloaded` when shedding load.
+    #[must_use]
+    #[track_caller]
+    pub fn with_type(exception_type: KjExceptionType, description: &str) -> Self {
+        let location = std::panic::Location::caller();
+        let exception = KjError::new(exception_type, description.to_owned())
+            .into_kj_exception(location.file(), location.line());
+        Self(exception)
+    }
+
+    /// Returns the reason of the cancellation in progress on this thread, if any. It is set while
+    /// C++ destroys promises inside a `kj_rs::CancellationScope`, so the `Drop` impls of a Rust
+    /// future dropped that way can tell why, and while [`crate::KjPromiseFuture::cancel`] drops a
+    /// C++ promise.
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        CURRENT_REASONS.with_borrow(|reasons| {
+            let reason = *reasons.last()?;
+            // Safety: the scope owning the exception is alive while it is on the stack.
+            let exception = ManuallyDrop::new(unsafe { KjException::from_raw(reason.cast()) });
+            Some(Self(KjException::clone(&exception)))
+        })
+    }
+
+    /// Returns the exception the future was canceled with.
+    #[must_use]
+    pub fn exception(&self) -> &KjException {
+        &self.0
+    }
+
+    /// Returns the exception the future was canceled with.
+    #[must_use]
+    pub fn into_exception(self) -> KjException {
+        self.0
+    }
+}
+
+impl Display for CancellationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl From<KjException> for CancellationReason {
+    fn from(exception: KjException) -> Self {
+        Self(exception)
+    }
+}
+
+impl From<CancellationReason> for KjException {
+    fn from(reason: CancellationReason) -> Self {
+        reason.0
+    }
+}
+
+/// Keeps the type, location and details of the exception, so returning the reason as a `KjError`
+/// rejects the future's promise with the exception the caller canceled it with.
+impl From<CancellationReason> for KjError {
+    fn from(reason: CancellationReason) -> Self {
+        KjError::from(reason.0)
+    }
+}
+
+thread_local! {
+    // The `kj::Exception`s of the cancellation scopes entered on this thread, innermost last.
+    // Shared with C++, which reads the innermost one in `kj_rs::CancellationScope::current()`.
+    static CURRENT_REASONS: RefCell<Vec<NonNull<c_void>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Makes a reason the current cancellation reason of the thread until dropped, the Rust side of
+/// `kj_rs::CancellationScope`.
+pub(crate) struct CancellationScope(());
+
+impl CancellationScope {
+    pub(crate) fn enter(reason: CancellationReason) -> Self {
+        // Safety: ownership of the exception moves to the stack, `exit()` takes it back.
+        let reason = unsafe { reason.0.into_raw() };
+        CURRENT_REASONS.with_borrow_mut(|reasons| reasons.push(reason.cast()));
+        Self(())
+    }
+
+    fn exit() {
+        let reason = CURRENT_REASONS
+            .with_borrow_mut(Vec::pop)
+            .expect("cancellation scopes must be exited in reverse order of entering");
+        drop(unsafe { KjException::from_raw(reason.cast()) });
+    }
+}
+
+impl Drop for CancellationScope {
+    fn drop(&mut self) {
+        Self::exit();
+    }
+}
+
+#[derive(Default)]
+struct State {
+    reason: Option<CancellationReason>,
+    wakers: BTreeMap<u64, Waker>,
+    next_waker_id: u64,
+}
+
+/// A cancellation signal shared between a caller and the futures it runs. Clones share the same
+/// signal.
+///
+/// Tokens are bound to the thread running the KJ event loop. C++ code sees them as
+/// `kj_rs::CancellationToken`, declared with `type CancellationToken = kj_rs::CancellationToken;`
+/// under `#[namespace = "kj_rs"]` in an `extern "C++"` block. The C++ type copies and destroys
+/// its token through Rust, so bridge functions take it by reference or as a
+/// `KjOwn<CancellationToken>`, e.g. from `kj::heap<kj_rs::CancellationToken>(token)`.
+#[derive(Clone, Default)]
+#[repr(transparent)]
+pub struct CancellationToken(Rc<RefCell<State>>);
+
+impl CancellationToken {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels with `reason` and wakes every future waiting in [`CancellationToken::canceled`].
+    /// Only the first reason is kept, later calls are ignored.
+    pub fn cancel(&self, reason: CancellationReason) {
+        let wakers = {
+            let mut state = self.0.borrow_mut();
+            if state.reason.is_some() {
+                return;
+            }
+            state.reason = Some(reason);
+            std::mem::take(&mut state.wakers)
+        };
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+
+    #[must_use]
+    pub fn is_canceled(&self) -> bool {
+        self.0.borrow().reason.is_some()
+    }
+
+    /// Returns the reason passed to the first [`CancellationToken::cancel`] call, if any.
+    #[must_use]
+    pub fn reason(&self) -> Option<CancellationReason> {
+        self.0.borrow().reason.clone()
+    }
+
+    /// Checks for cancellation at a convenient point of a future, usually with `?`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the cancellation reason once the token is canceled.
+    pub fn check(&self) -> Result<(), CancellationReason> {
+        self.reason().map_or(Ok(()), Err)
+    }
+
+    /// Returns a future resolving to the cancellation reason once the token is canceled.
+    #[must_use]
+    pub fn canceled(&self) -> Canceled {
+        Canceled {
+            token: self.clone(),
+            waker_id: None,
+        }
+    }
+
+    /// Runs `future` until it completes or the token is canceled, whichever happens first. On
+    /// cancellation `future` is dropped without being polled again.
+    ///
+    /// # Errors
+    ///
+    /// Returns the cancellation reason if the token is canceled before `future` completes.
+    pub async fn or_canceled<F: Future>(&self, future: F) -> Result<F::Output, CancellationReason> {
+        OrCanceled {
+            future,
+            canceled: self.canceled(),
+        }
+        .await
+    }
+}
+
+/// Future returned by [`CancellationToken::canceled`].
+pub struct Canceled {
+    token: CancellationToken,
+    waker_id: Option<u64>,
+}
+
+impl Future for Canceled {
+    type Output = CancellationReason;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut state = this.token.0.borrow_mut();
+        if let Some(reason) = &state.reason {
+            return Poll::Ready(reason.clone());
+        }
+
+        let waker_id = *this.waker_id.get_or_insert_with(|| {
+            state.next_waker_id += 1;
+            state.next_waker_id
+        });
+        match state.wakers.get_mut(&waker_id) {
+            Some(waker) => waker.clone_from(cx.waker()),
+            None => {
+                state.wakers.insert(waker_id, cx.waker().clone());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Canceled {
+    fn drop(&mut self) {
+        if let Some(waker_id) = self.waker_id {
+            self.token.0.borrow_mut().wakers.remove(&waker_id);
+        }
+    }
+}
+
+struct OrCanceled<F> {
+    future: F,
+    canceled: Canceled,
+}
+
+impl<F: Future> Future for OrCanceled<F> {
+    type Output = Result<F::Output, CancellationReason>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: self is pinned, so future is pinned. `canceled` is Unpin.
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Poll::Ready(reason) = Pin::new(&mut this.canceled).poll(cx) {
+            return Poll::Ready(Err(reason));
+        }
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        future.poll(cx).map(Ok)
+    }
+}
+
+// Safety: `kj_rs::CancellationToken` in cancel.h holds a single pointer, which it only manipulates
+// through the functions below. Its copy constructor and destructor aren't trivial, so it is opaque
+// to the bridge.
+unsafe impl ExternType for CancellationToken {
+    type Id = cxx::type_id!("::kj_rs::CancellationToken");
+    type Kind = cxx::kind::Opaque;
+}
+
+// C++ API of `kj_rs::CancellationToken`, declared in cancel.h.
+
+#[unsafe(export_name = "cxxbridge$kjrs$cancellation_token$new")]
+unsafe extern "C" fn cancellation_token_new(out: *mut CancellationToken) {
+    unsafe { out.write(CancellationToken::new()) };
+}
+
+#[unsafe(export_name = "cxxbridge$kjrs$cancellation_token$clone")]
+unsafe extern "C" fn cancellation_token_clone(
+    token: *const CancellationToken,
+    out: *mut CancellationToken,
+) {
+    unsafe { out.write((*token).clone()) };
+}
+
+#[unsafe(export_name = "cxxbridge$kjrs$cancellation_token$drop")]
+unsafe extern "C" fn cancellation_token_drop(token: *mut CancellationToken) {
+    unsafe { std::ptr::drop_in_place(token) };
+}
+
+#[unsafe(export_name = "cxxbridge$kjrs$cancellation_token$cancel")]
+unsafe extern "C" fn cancellation_token_cancel(
+    token: *const CancellationToken,
+    reason: *mut c_void,
+) {
+    let reason = NonNull::new(reason).expect("cancellation reason must not be null");
+    let reason = unsafe { KjException::from_raw(reason.cast()) };
+    unsafe { (*token).cancel(CancellationReason(reason)) };
+}
+
+#[unsafe(export_name = "cxxbridge$kjrs$cancellation_token$is_canceled")]
+unsafe extern "C" fn cancellation_token_is_canceled(token: *const CancellationToken) -> bool {
+    unsafe { (*token).is_canceled() }
+}
+
+#[unsafe(export_name = "cxxbridge$kjrs$cancellation_scope$enter")]
+unsafe extern "C" fn cancellation_scope_enter(reason: *mut c_void) {
+    let reason = NonNull::new(reason).expect("cancellation reason must not be null");
+    let reason = unsafe { KjException::from_raw(reason.cast()) };
+    std::mem::forget(CancellationScope::enter(CancellationReason(reason)));
+}
+
+#[unsafe(export_name = "cxxbridge$kjrs$cancellation_scope$exit")]
+unsafe extern "C" fn cancellation_scope_exit() {
+    CancellationScope::exit();
+}
+
+#[unsafe(export_name = "cxxbridge$kjrs$cancellation_scope$current")]
+unsafe extern "C" fn cancellation_scope_current() -> *const c_void {
+    CURRENT_REASONS.with_borrow(|reasons| {
+        reasons
+            .last()
+            .map_or(std::ptr::null(), |reason| reason.as_ptr().cast_const())
+    })
+}
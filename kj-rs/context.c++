@@ -0,0 +1,175 @@
+#include "kj-rs/context.h"
+
+namespace kj_rs {
+
+namespace {
+
+thread_local AsyncContext* currentContext = nullptr;
+
+AsyncContext* toPointer(kj::Maybe<AsyncContext&> context) {
+  KJ_IF_SOME(c, context) {
+    return &c;
+  }
+  return nullptr;
+}
+
+}  // namespace
+
+AsyncContext::Scope::Scope(kj::Maybe<AsyncContext&> context): previous(currentContext) {
+  AsyncContext* next = toPointer(context);
+  if (next == previous) {
+    return;
+  }
+
+  auto parentOf = [](AsyncContext* c) -> AsyncContext* {
+    KJ_IF_SOME(p, c->parent) {
+      return p.get();
+    }
+    return nullptr;
+  };
+  auto frameOf = [](AsyncContext* c) -> kj::Maybe<Frame&> {
+    KJ_IF_SOME(frame, c->entry.tryGet<kj::Own<Frame>>()) {
+      return *frame;
+    }
+    return kj::none;
+  };
+
+  // The frames of the closest context both the previous and the new one descend from stay
+  // entered. The previous context's frames below it are exited, and the new one's entered.
+  AsyncContext* shared = previous;
+  for (; shared != nullptr; shared = parentOf(shared)) {
+    AsyncContext* c = next;
+    while (c != nullptr && c != shared) {
+      c = parentOf(c);
+    }
+    if (c == shared) {
+      break;
+    }
+  }
+
+  for (AsyncContext* c = previous; c != shared; c = parentOf(c)) {
+    KJ_IF_SOME(frame, frameOf(c)) {
+      frame.exit();
+      exited.add(&frame);
+    }
+  }
+  for (AsyncContext* c = next; c != shared; c = parentOf(c)) {
+    KJ_IF_SOME(frame, frameOf(c)) {
+      frames.add(&frame);
+    }
+  }
+  for (size_t i = frames.size(); i > 0; --i) {
+    frames[i - 1]->enter();
+  }
+  currentContext = next;
+}
+
+AsyncContext::Scope::Scope(kj::Maybe<kj::Own<AsyncContext>>& context)
+    : Scope(context.map([](kj::Own<AsyncContext>& c) -> AsyncContext& { return *c; })) {}
+
+AsyncContext::Scope::~Scope() noexcept(false) {
+  for (Frame* frame: frames) {
+    frame->exit();
+  }
+  for (size_t i = exited.size(); i > 0; --i) {
+    exited[i - 1]->enter();
+  }
+  currentContext = previous;
+}
+
+AsyncContext::AsyncContext(
+    kj::Maybe<kj::Own<AsyncContext>> parent, uint64_t id, kj::Array<kj::byte> value)
+    : parent(kj::mv(parent)),
+      entry(Value{.id = id, .data = kj::mv(value)}) {}
+
+AsyncContext::AsyncContext(kj::Maybe<kj::Own<AsyncContext>> parent, kj::Own<Frame> frame)
+    : parent(kj::mv(parent)),
+      entry(kj::mv(frame)) {}
+
+kj::Maybe<AsyncContext&> AsyncContext::current() {
+  if (currentContext == nullptr) {
+    return kj::none;
+  }
+  return *currentContext;
+}
+
+kj::Maybe<kj::Own<AsyncContext>> AsyncContext::capture() {
+  return current().map([](AsyncContext& c) { return c.addRef(); });
+}
+
+kj::Own<AsyncContext> AsyncContext::withValue(uint64_t id, kj::Array<kj::byte> value) {
+  return kj::refcounted<AsyncContext>(capture(), id, kj::mv(value));
+}
+
+kj::Own<AsyncContext> AsyncContext::withFrame(kj::Own<Frame> frame) {
+  return kj::refcounted<AsyncContext>(capture(), kj::mv(frame));
+}
+
+kj::Maybe<kj::ArrayPtr<const kj::byte>> AsyncContext::get(uint64_t id) const {
+  for (const AsyncContext* c = this; c != nullptr;) {
+    KJ_IF_SOME(value, c->entry.tryGet<Value>()) {
+      if (value.id == id) {
+        return value.data.asPtr();
+      }
+    }
+    KJ_IF_SOME(p, c->parent) {
+      c = p.get();
+    } else {
+      c = nullptr;
+    }
+  }
+  return kj::none;
+}
+
+}  // namespace kj_rs
+
+namespace {
+
+kj::Maybe<kj_rs::AsyncContext&> fromOwn(const void* context) {
+  if (context == nullptr) {
+    return kj::none;
+  }
+  // Contexts are immutable apart from their refcount.
+  return **reinterpret_cast<kj::Own<kj_rs::AsyncContext>*>(const_cast<void*>(context));
+}
+
+}  // namespace
+
+extern "C" {
+
+bool cxxbridge$kjrs$async_context$current(void* out) noexcept {
+  KJ_IF_SOME(context, kj_rs::AsyncContext::capture()) {
+    new (out) kj::Own<kj_rs::AsyncContext>(kj::mv(context));
+    return true;
+  }
+  return false;
+}
+
+void cxxbridge$kjrs$async_context$new(
+    const void* parent, uint64_t id, const kj::byte* data, size_t len, void* out) noexcept {
+  auto value = kj::heapArray(data, len);
+  auto own = fromOwn(parent).map([](kj_rs::AsyncContext& c) { return c.addRef(); });
+  new (out) kj::Own<kj_rs::AsyncContext>(
+      kj::refcounted<kj_rs::AsyncContext>(kj::mv(own), id, kj::mv(value)));
+}
+
+bool cxxbridge$kjrs$async_context$get(
+    const void* context, uint64_t id, const kj::byte** data, size_t* len) noexcept {
+  KJ_IF_SOME(c, fromOwn(context)) {
+    KJ_IF_SOME(value, c.get(id)) {
+      *data = value.begin();
+      *len = value.size();
+      return true;
+    }
+  }
+  return false;
+}
+
+void* cxxbridge$kjrs$async_context$enter(const void* context) noexcept {
+  return new kj_rs::AsyncContext::Scope(fromOwn(context));
+}
+
+void cxxbridge$kjrs$async_context$exit(void* scope) noexcept {
+  delete reinterpret_cast<kj_rs::AsyncContext::Scope*>(scope);
+}
+}
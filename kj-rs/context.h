@@ -0,0 +1,139 @@
+#pragma once
+
+#include <kj/array.h>
+#include <kj/one-of.h>
+#include <kj/refcount.h>
+#include <kj/vector.h>
+
+#include <cstddef>
+#include <cstdint>
+
+namespace kj_rs {
+
+// The ambient context of an async task, such as its current request or tracing span.
+//
+// A context is an immutable chain of entries, each holding either a value, keyed by a 64-bit id
+// and stored as bytes, or a `Frame` of external state. A Rust future converted to a `kj::Promise`
+// captures the context current at that point and makes it current again around each poll, so C++
+// code called from the future sees the context the future was created in. Rust reads and extends
+// the context with `kj_rs::AsyncContext` and `kj_rs::TaskLocal`.
+class AsyncContext final: public kj::Refcounted {
+ public:
+  // External state restored around each poll of a Rust future, such as workerd's
+  // `jsg::AsyncContextFrame`.
+  class Frame {
+   public:
+    virtual ~Frame() noexcept(false) = default;
+
+    // Makes the frame's state current on this thread.
+    virtual void enter() noexcept = 0;
+    // Restores the state which was current before `enter()`.
+    virtual void exit() noexcept = 0;
+  };
+
+  // Makes a context current on this thread, and restores the previous context when destroyed. The
+  // frames of the previous context which the new one doesn't share are exited, and the new
+  // context's own frames entered. The context must outlive the scope.
+  class Scope {
+   public:
+    explicit Scope(kj::Maybe<AsyncContext&> context);
+    explicit Scope(kj::Maybe<kj::Own<AsyncContext>>& context);
+    ~Scope() noexcept(false);
+    KJ_DISALLOW_COPY_AND_MOVE(Scope);
+
+   private:
+    AsyncContext* previous;
+    // Frames entered by this scope, innermost first.
+    kj::Vector<Frame*> frames;
+    // Frames of the previous context exited by this scope, innermost first.
+    kj::Vector<Frame*> exited;
+  };
+
+  AsyncContext(kj::Maybe<kj::Own<AsyncContext>> parent, uint64_t id, kj::Array<kj::byte> value);
+  AsyncContext(kj::Maybe<kj::Own<AsyncContext>> parent, kj::Own<Frame> frame);
+
+  // Returns the context current on this thread, if any.
+  static kj::Maybe<AsyncContext&> current();
+
+  // Returns a new reference to the current context, if any.
+  static kj::Maybe<kj::Own<AsyncContext>> capture();
+
+  // Returns a child of the current context holding `value` under `id`.
+  static kj::Own<AsyncContext> withValue(uint64_t id, kj::Array<kj::byte> value);
+
+  // Returns a child of the current context holding `frame`.
+  static kj::Own<AsyncContext> withFrame(kj::Own<Frame> frame);
+
+  // Returns the value stored under `id` by this context or its closest parent holding one.
+  kj::Maybe<kj::ArrayPtr<const kj::byte>> get(uint64_t id) const;
+
+  kj::Own<AsyncContext> addRef() {
+    return kj::addRef(*this);
+  }
+
+ private:
+  struct Value {
+    uint64_t id;
+    kj::Array<kj::byte> data;
+  };
+
+  kj::Maybe<kj::Own<AsyncContext>> parent;
+  kj::OneOf<Value, kj::Own<Frame>> entry;
+};
+
+// Typed view of a context value, the C++ side of Rust's `kj_rs::TaskLocal` trait. Specialize it
+// for a type with the same id and byte encoding as its Rust counterpart:
+//
+//   template <> struct kj_rs::TaskLocal<RequestId> {
+//     static constexpr uint64_t ID = ...;
+//     static kj::Array<kj::byte> encode(const RequestId& value);
+//     static kj::Maybe<RequestId> decode(kj::ArrayPtr<const kj::byte> data);
+//   };
+template <typename T>
+struct TaskLocal;
+
+// Returns the value of type T in the current context, if it holds one which decodes successfully.
+template <typename T>
+kj::Maybe<T> getTaskLocal() {
+  KJ_IF_SOME(context, AsyncContext::current()) {
+    KJ_IF_SOME(data, context.get(TaskLocal<T>::ID)) {
+      return TaskLocal<T>::decode(data);
+    }
+  }
+  return kj::none;
+}
+
+// Returns a child of the current context holding `value`, to make current with a `Scope`.
+template <typename T>
+kj::Own<AsyncContext> withTaskLocal(const T& value) {
+  return AsyncContext::withValue(TaskLocal<T>::ID, TaskLocal<T>::encode(value));
+}
+
+}  // namespace kj_rs
+
+extern "C" {
+
+// The `context` inputs and `out` outputs point to a `kj::Own<kj_rs::AsyncContext>`, which Rust's
+// `kj_rs::AsyncContext` owns and destroys with `cxxbridge$kjrs$own$drop`. A null `context` stands
+// for no context.
+
+// Constructs a reference to the current context into `out`. Returns false, leaving `out`
+// uninitialized, if there is none.
+bool cxxbridge$kjrs$async_context$current(void* out) noexcept;
+
+// Constructs a child of `parent` holding `len` bytes at `data` under `id` into `out`.
+void cxxbridge$kjrs$async_context$new(
+    const void* parent, uint64_t id, const kj::byte* data, size_t len, void* out) noexcept;
+
+// Points `data` and `len` at the value stored under `id` in `context`. Returns false if there is
+// none.
+bool cxxbridge$kjrs$async_context$get(
+    const void* context, uint64_t id, const kj::byte** data, size_t* len) noexcept;
+
+// Makes `context` current, returning the `AsyncContext::Scope` to pass to
+// `cxxbridge$kjrs$async_context$exit`.
+void* cxxbridge$kjrs$async_context$enter(const void* context) noexcept;
+
+// Destroys a scope returned by `cxxbridge$kjrs$async_context$enter`.
+void cxxbridge$kjrs$async_context$exit(void* scope) noexcept;
+}
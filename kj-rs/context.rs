@@ -0,0 +1,198 @@
+//! The Rust side of `kj-rs/context.h`.
+
+use std::ffi::c_void;
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use static_assertions::assert_not_impl_any;
+
+use crate::KjOwn;
+
+unsafe extern "C" {
+    #[link_name = "cxxbridge$kjrs$async_context$current"]
+    fn async_context_current(out: *mut c_void) -> bool;
+
+    #[link_name = "cxxbridge$kjrs$async_context$new"]
+    fn async_context_new(
+        parent: *const c_void,
+        id: u64,
+        data: *const u8,
+        len: usize,
+        out: *mut c_void,
+    );
+
+    #[link_name = "cxxbridge$kjrs$async_context$get"]
+    fn async_context_get(
+        context: *const c_void,
+        id: u64,
+        data: *mut *const u8,
+        len: *mut usize,
+    ) -> bool;
+
+    #[link_name = "cxxbridge$kjrs$async_context$enter"]
+    fn async_context_enter(context: *const c_void) -> *mut c_void;
+
+    #[link_name = "cxxbridge$kjrs$async_context$exit"]
+    fn async_context_exit(scope: *mut c_void);
+}
+
+/// A `kj_rs::AsyncContext`.
+///
+/// Neither `Send` nor `Sync`: the C++ object is a non-atomic `kj::Refcounted`, and the current
+/// context is thread-local.
+#[repr(C)]
+struct AsyncContextImpl {
+    _private: [u8; 0],
+    _not_send: PhantomData<*mut ()>,
+}
+
+/// A value stored in the [`AsyncContext`] of a task, which both Rust and C++ code running as part
+/// of the task can read.
+///
+/// Context values are identified by a 64-bit id and stored as bytes. Implementing this trait pairs
+/// a Rust type with its id and byte encoding. The C++ side gets the same view by specializing
+/// `kj_rs::TaskLocal<T>` from `kj-rs/context.h` with the same id and encoding.
+pub trait TaskLocal: Sized {
+    /// The id this value is stored under.
+    const ID: u64;
+
+    /// Encodes the value into the bytes stored in the context.
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decodes the value from the bytes stored in the context, or returns `None` if they are
+    /// malformed.
+    fn decode(data: &[u8]) -> Option<Self>;
+}
+
+/// The ambient context of an async task, `kj_rs::AsyncContext` in C++.
+///
+/// A Rust future converted to a `kj::Promise` captures the context current at that point, and
+/// makes it current again around each poll, so that it is visible to C++ code called from the
+/// future. Read values with [`task_local`], and add them with [`with_task_local`].
+pub struct AsyncContext {
+    own: KjOwn<AsyncContextImpl>,
+}
+
+assert_not_impl_any!(AsyncContext: Send, Sync);
+
+impl AsyncContext {
+    /// Returns the context current on this thread, if any.
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        let mut own = MaybeUninit::<KjOwn<AsyncContextImpl>>::uninit();
+        // Safety: `out` has the layout of a `kj::Own`, and is initialized if the call returns true.
+        unsafe { async_context_current(own.as_mut_ptr().cast()).then(|| own.assume_init()) }
+            .map(|own| Self { own })
+    }
+
+    /// Returns a child of the current context, holding `value`.
+    #[must_use]
+    pub fn with<T: TaskLocal>(value: &T) -> Self {
+        let parent = Self::current();
+        let data = value.encode();
+        let mut own = MaybeUninit::<KjOwn<AsyncContextImpl>>::uninit();
+        // Safety: `out` has the layout of a `kj::Own`, which the call initializes.
+        unsafe {
+            async_context_new(
+                as_ptr(parent.as_ref()),
+                T::ID,
+                data.as_ptr(),
+                data.len(),
+                own.as_mut_ptr().cast(),
+            );
+        }
+        Self {
+            own: unsafe { own.assume_init() },
+        }
+    }
+
+    /// Returns the value of type `T` stored in this context or its parents, if there is one which
+    /// decodes successfully.
+    #[must_use]
+    pub fn get<T: TaskLocal>(&self) -> Option<T> {
+        let mut data = std::ptr::null();
+        let mut len = 0;
+        // Safety: the value outlives the borrow of `self`, as contexts are immutable.
+        unsafe {
+            async_context_get(as_ptr(Some(self)), T::ID, &raw mut data, &raw mut len)
+                .then(|| std::slice::from_raw_parts(data, len))
+        }
+        .and_then(T::decode)
+    }
+
+    /// Calls `f` with this context current.
+    pub fn run<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _scope = Scope::enter(Some(self));
+        f()
+    }
+
+    /// Returns a future which polls `future` with this context current.
+    pub fn scope<F: Future>(self, future: F) -> InContext<F> {
+        InContext {
+            future,
+            context: self,
+        }
+    }
+}
+
+impl fmt::Debug for AsyncContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncContext").finish_non_exhaustive()
+    }
+}
+
+fn as_ptr(context: Option<&AsyncContext>) -> *const c_void {
+    context.map_or(std::ptr::null(), |context| {
+        std::ptr::from_ref(&context.own).cast()
+    })
+}
+
+/// A `kj_rs::AsyncContext::Scope`, which restores the previous context when dropped.
+struct Scope(*mut c_void);
+
+impl Scope {
+    fn enter(context: Option<&AsyncContext>) -> Self {
+        Scope(unsafe { async_context_enter(as_ptr(context)) })
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        unsafe { async_context_exit(self.0) };
+    }
+}
+
+/// A future polled with an [`AsyncContext`] current, created by [`AsyncContext::scope`] and
+/// [`with_task_local`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct InContext<F> {
+    future: F,
+    context: AsyncContext,
+}
+
+impl<F: Future> Future for InContext<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: `future` is structurally pinned, and never moved out of `InContext`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let _scope = Scope::enter(Some(&this.context));
+        unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx)
+    }
+}
+
+/// Returns the value of type `T` in the current context, if there is one which decodes
+/// successfully.
+#[must_use]
+pub fn task_local<T: TaskLocal>() -> Option<T> {
+    AsyncContext::current()?.get()
+}
+
+/// Returns a future which polls `future` in a child of the current context holding `value`.
+pub fn with_task_local<T: TaskLocal, F: Future>(value: &T, future: F) -> InContext<F> {
+    AsyncContext::with(value).scope(future)
+}
@@ -0,0 +1,153 @@
+#include "kj-rs/event-loop.h"
+
+#include <kj/debug.h>
+
+namespace kj_rs {
+
+class RustEventLoop::Backend {
+ public:
+  virtual ~Backend() noexcept(false) = default;
+  virtual kj::WaitScope& getWaitScope() = 0;
+  virtual kj::Maybe<kj::Timer&> getTimer() {
+    return kj::none;
+  }
+  virtual const kj::Clock& getClock() {
+    return kj::systemPreciseCalendarClock();
+  }
+  virtual kj::Maybe<kj::TimerImpl&> getMockTimer() {
+    return kj::none;
+  }
+};
+
+class RustEventLoop::PlainBackend final: public Backend {
+ public:
+  kj::WaitScope& getWaitScope() override {
+    return waitScope;
+  }
+
+ private:
+  kj::EventLoop loop;
+  kj::WaitScope waitScope{loop};
+};
+
+class RustEventLoop::IoBackend final: public Backend {
+ public:
+  kj::WaitScope& getWaitScope() override {
+    return io.waitScope;
+  }
+  kj::Maybe<kj::Timer&> getTimer() override {
+    return io.provider->getTimer();
+  }
+
+ private:
+  kj::AsyncIoContext io = kj::setupAsyncIo();
+};
+
+class RustEventLoop::MockBackend final: public Backend, private kj::Clock {
+ public:
+  kj::WaitScope& getWaitScope() override {
+    return waitScope;
+  }
+  kj::Maybe<kj::Timer&> getTimer() override {
+    return timer;
+  }
+  const kj::Clock& getClock() override {
+    return *this;
+  }
+  kj::Maybe<kj::TimerImpl&> getMockTimer() override {
+    return timer;
+  }
+
+ private:
+  kj::Date now() const override {
+    return kj::UNIX_EPOCH + (timer.now() - kj::origin<kj::TimePoint>());
+  }
+
+  kj::EventLoop loop;
+  kj::WaitScope waitScope{loop};
+  kj::TimerImpl timer{kj::origin<kj::TimePoint>()};
+};
+
+RustEventLoop::RustEventLoop(): RustEventLoop(kj::heap<PlainBackend>()) {}
+
+RustEventLoop::RustEventLoop(AsyncIo): RustEventLoop(kj::heap<IoBackend>()) {}
+
+RustEventLoop::RustEventLoop(MockTimer): RustEventLoop(kj::heap<MockBackend>()) {}
+
+RustEventLoop::RustEventLoop(kj::Own<Backend> backendParam): backend(kj::mv(backendParam)) {
+  KJ_IF_SOME(timer, backend->getTimer()) {
+    timerScope = kj::heap<TimerScope>(timer, backend->getClock());
+  }
+}
+
+RustEventLoop::~RustEventLoop() noexcept(false) {}
+
+void RustEventLoop::blockOn(kj::Promise<void> promise) {
+  auto paf = kj::newPromiseAndFulfiller<void>();
+  failed = kj::mv(paf.fulfiller);
+  KJ_DEFER(failed = kj::none);
+  promise.exclusiveJoin(kj::mv(paf.promise)).wait(backend->getWaitScope());
+}
+
+void RustEventLoop::spawn(kj::Promise<void> promise) {
+  tasks.add(kj::mv(promise));
+}
+
+void RustEventLoop::advanceMockTime(kj::Duration delay) {
+  auto& timer = KJ_REQUIRE_NONNULL(backend->getMockTimer(), "event loop has no mock timer");
+  timer.advanceTo(saturatingDeadline(timer.now(), delay));
+}
+
+void RustEventLoop::taskFailed(kj::Exception&& exception) {
+  // Rust stores the panic of a spawned task for `block_on()` to resume, so waking the current
+  // `blockOn()` is enough. A failure outside of `blockOn()` can't happen: tasks only run while the
+  // loop is waited on.
+  KJ_IF_SOME(fulfiller, failed) {
+    fulfiller->reject(kj::mv(exception));
+  }
+}
+
+}  // namespace kj_rs
+
+extern "C" {
+
+::rust::repr::Result cxxbridge$kjrs$event_loop$new(
+    std::uint8_t backend, kj_rs::RustEventLoop** out) noexcept {
+  return ::rust::repr::Result::run([&]() {
+    switch (backend) {
+      case 0:
+        *out = new kj_rs::RustEventLoop();
+        return;
+      case 1:
+        *out = new kj_rs::RustEventLoop(kj_rs::RustEventLoop::AsyncIo{});
+        return;
+      case 2:
+        *out = new kj_rs::RustEventLoop(kj_rs::RustEventLoop::MockTimer{});
+        return;
+    }
+    KJ_FAIL_REQUIRE("unknown event loop backend", backend);
+  });
+}
+
+void cxxbridge$kjrs$event_loop$drop(kj_rs::RustEventLoop* loop) noexcept {
+  delete loop;
+}
+
+::rust::repr::Result cxxbridge$kjrs$event_loop$block_on(
+    kj_rs::RustEventLoop* loop, kj_rs::repr::RustFuture* future) noexcept {
+  // Converting to a promise takes ownership of the future, so do it before anything can throw.
+  kj::Promise<void> promise = *future;
+  return ::rust::repr::Result::run([&]() { loop->blockOn(kj::mv(promise)); });
+}
+
+void cxxbridge$kjrs$event_loop$spawn(
+    kj_rs::RustEventLoop* loop, kj_rs::repr::RustFuture* future) noexcept {
+  loop->spawn(*future);
+}
+
+::rust::repr::Result cxxbridge$kjrs$event_loop$advance_mock_time(
+    kj_rs::RustEventLoop* loop, std::int64_t nanoseconds) noexcept {
+  return ::rust::repr::Result::run(
+      [&]() { loop->advanceMockTime(nanoseconds * kj::NANOSECONDS); });
+}
+}
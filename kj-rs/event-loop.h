@@ -0,0 +1,82 @@
+#pragma once
+
+#include "kj-rs/future.h"
+#include "kj-rs/timer.h"
+
+#include <rust/cxx.h>
+
+#include <kj/async-io.h>
+#include <kj/async.h>
+#include <kj/timer.h>
+
+#include <cstdint>
+
+namespace kj_rs {
+
+// An event loop and `kj::WaitScope` owned by Rust's `kj_rs::EventLoop`, which runs Rust futures on
+// the current thread without a C++ caller waiting on their promises.
+class RustEventLoop final: private kj::TaskSet::ErrorHandler {
+ public:
+  // Runs on a plain `kj::EventLoop`, without a timer or async IO.
+  RustEventLoop();
+  // Runs on a `kj::setupAsyncIo()` event loop, whose timer follows the system clock.
+  struct AsyncIo {};
+  explicit RustEventLoop(AsyncIo);
+  // Runs on a plain `kj::EventLoop` whose timer and calendar clock start at the Unix epoch, and
+  // only advance through `advanceMockTime()`.
+  struct MockTimer {};
+  explicit RustEventLoop(MockTimer);
+  ~RustEventLoop() noexcept(false);
+  KJ_DISALLOW_COPY_AND_MOVE(RustEventLoop);
+
+  // Runs the loop until `promise` completes. Fails early with the error of a spawned task.
+  void blockOn(kj::Promise<void> promise);
+
+  // Adds `promise` to the background tasks, which run during `blockOn()`.
+  void spawn(kj::Promise<void> promise);
+
+  // Moves the mock timer forward by `delay`. Timers which expire are fulfilled, and their
+  // continuations run during the next `blockOn()`. Requires a `MockTimer` loop.
+  void advanceMockTime(kj::Duration delay);
+
+ private:
+  class Backend;
+  class PlainBackend;
+  class IoBackend;
+  class MockBackend;
+
+  explicit RustEventLoop(kj::Own<Backend> backend);
+
+  void taskFailed(kj::Exception&& exception) override;
+
+  kj::Own<Backend> backend;
+  // Set if the backend has a timer.
+  kj::Maybe<kj::Own<TimerScope>> timerScope;
+  kj::TaskSet tasks{*this};
+
+  // Rejects the current `blockOn()`, if any.
+  kj::Maybe<kj::Own<kj::PromiseFulfiller<void>>> failed;
+};
+
+}  // namespace kj_rs
+
+extern "C" {
+
+// Creates a `RustEventLoop` for the current thread into `out`: a plain loop for backend 0, an
+// async IO loop for 1 and a mock timer loop for 2. Fails if the thread already has a KJ event loop.
+::rust::repr::Result cxxbridge$kjrs$event_loop$new(
+    std::uint8_t backend, kj_rs::RustEventLoop** out) noexcept;
+
+void cxxbridge$kjrs$event_loop$drop(kj_rs::RustEventLoop* loop) noexcept;
+
+// Takes ownership of `future` and runs the loop until it completes.
+::rust::repr::Result cxxbridge$kjrs$event_loop$block_on(
+    kj_rs::RustEventLoop* loop, kj_rs::repr::RustFuture* future) noexcept;
+
+// Takes ownership of `future` and adds it to the loop's background tasks.
+void cxxbridge$kjrs$event_loop$spawn(
+    kj_rs::RustEventLoop* loop, kj_rs::repr::RustFuture* future) noexcept;
+
+::rust::repr::Result cxxbridge$kjrs$event_loop$advance_mock_time(
+    kj_rs::RustEventLoop* loop, std::int64_t nanoseconds) noexcept;
+}
@@ -0,0 +1,266 @@
+//! Driving Rust futures on a KJ event loop without a C++ `main()`.
+//!
+//! Futures returned to C++ only make progress while C++ waits on their `kj::Promise`. An
+//! [`EventLoop`] creates the `kj::EventLoop` and `kj::WaitScope` for the current thread from Rust,
+//! so Rust code, such as integration tests, can run futures which await KJ promises directly.
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::ffi::c_void;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use cxx::{IntoKjException, KjError, KjException, KjExceptionType};
+
+type PanicPayload = Box<dyn Any + Send>;
+
+unsafe extern "C" {
+    #[link_name = "cxxbridge$kjrs$event_loop$new"]
+    fn event_loop_new(backend: Backend, out: *mut *mut c_void) -> cxx::private::Result;
+
+    #[link_name = "cxxbridge$kjrs$event_loop$drop"]
+    fn event_loop_drop(event_loop: *mut c_void);
+
+    #[link_name = "cxxbridge$kjrs$event_loop$block_on"]
+    fn event_loop_block_on(event_loop: *mut c_void, future: *mut c_void) -> cxx::private::Result;
+
+    #[link_name = "cxxbridge$kjrs$event_loop$spawn"]
+    fn event_loop_spawn(event_loop: *mut c_void, future: *mut c_void);
+
+    #[link_name = "cxxbridge$kjrs$event_loop$advance_mock_time"]
+    fn event_loop_advance_mock_time(
+        event_loop: *mut c_void,
+        nanoseconds: i64,
+    ) -> cxx::private::Result;
+}
+
+std::thread_local! {
+    // The `EventLoop` created by Rust on this thread, if any, for `block_on()` and
+    // `spawn_local()`.
+    static CURRENT: Cell<Option<NonNull<Inner>>> = const { Cell::new(None) };
+}
+
+// Matches the backends of `cxxbridge$kjrs$event_loop$new`.
+#[repr(u8)]
+enum Backend {
+    Plain = 0,
+    AsyncIo = 1,
+    MockTimer = 2,
+}
+
+struct Inner {
+    event_loop: NonNull<c_void>,
+    // Panic of a future run by the loop, resumed by the `block_on()` call it interrupted.
+    panic: Rc<RefCell<Option<PanicPayload>>>,
+}
+
+/// A `kj::EventLoop` and `kj::WaitScope` on the current thread.
+///
+/// Futures run by the loop may await KJ promises and anything else woken through a
+/// [`std::task::Waker`]. Only one KJ event loop can exist per thread, so creating an `EventLoop`
+/// on a thread which already runs one, for example from C++, panics.
+pub struct EventLoop {
+    inner: Box<Inner>,
+    _not_send: PhantomData<*mut ()>,
+}
+
+impl EventLoop {
+    /// Creates a plain event loop for the current thread, without a timer or async IO. Use
+    /// [`EventLoop::with_io`] or [`EventLoop::with_mock_timer`] for [`crate::sleep`] and friends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread already has a KJ event loop.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::create(Backend::Plain)
+    }
+
+    /// Creates an event loop for the current thread on `kj::setupAsyncIo()`, whose timer follows
+    /// the system clock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread already has a KJ event loop.
+    #[must_use]
+    pub fn with_io() -> Self {
+        Self::create(Backend::AsyncIo)
+    }
+
+    /// Creates an event loop for the current thread whose timer, used by [`crate::sleep`] and
+    /// friends, is under the caller's control: it starts at the Unix epoch and only moves through
+    /// [`EventLoop::advance_mock_time`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread already has a KJ event loop.
+    #[must_use]
+    pub fn with_mock_timer() -> Self {
+        Self::create(Backend::MockTimer)
+    }
+
+    fn create(backend: Backend) -> Self {
+        let mut event_loop = std::ptr::null_mut();
+        if let Err(exception) =
+            unsafe { event_loop_new(backend, &raw mut event_loop) }.into_result()
+        {
+            panic!("failed to create kj::EventLoop: {exception}");
+        }
+        let inner = Box::new(Inner {
+            event_loop: NonNull::new(event_loop).expect("kj::EventLoop must not be null"),
+            panic: Rc::default(),
+        });
+        CURRENT.set(Some(NonNull::from(&*inner)));
+        Self {
+            inner,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Runs the event loop until `future` completes, and returns its output. Tasks started with
+    /// [`EventLoop::spawn_local`] run in the meantime.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic of `future` or of a spawned task, and panics if the loop runs out of
+    /// work before `future` completes. Must not be called from within a future run by the loop.
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.inner.block_on(future)
+    }
+
+    /// Starts running `future` in the background. It makes progress during later
+    /// [`EventLoop::block_on`] calls, and is dropped unfinished with the loop.
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    pub fn spawn_local(&self, future: impl Future<Output = ()> + 'static) {
+        self.inner.spawn_local(future);
+    }
+
+    /// Moves the mock timer forward by `delay`. Sleeps which expire complete during the next
+    /// [`EventLoop::block_on`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the loop wasn't created with [`EventLoop::with_mock_timer`].
+    pub fn advance_mock_time(&self, delay: Duration) {
+        // `kj::Duration` is signed, so longer delays saturate to its maximum.
+        let nanoseconds = i64::try_from(delay.as_nanos()).unwrap_or(i64::MAX);
+        let result =
+            unsafe { event_loop_advance_mock_time(self.inner.event_loop.as_ptr(), nanoseconds) };
+        if let Err(exception) = result.into_result() {
+            panic!("{exception}");
+        }
+    }
+}
+
+impl Inner {
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let mut output = None;
+        let mut wrapper = crate::repr::future(Box::pin(async {
+            match CatchUnwind(future).await {
+                Ok(value) => {
+                    output = Some(value);
+                    Ok(())
+                }
+                Err(payload) => Err(store_panic(&self.panic, payload)),
+            }
+        }));
+        // The loop takes ownership of the wrapper.
+        let result =
+            unsafe { event_loop_block_on(self.event_loop.as_ptr(), (&raw mut wrapper).cast()) };
+        if let Some(payload) = self.panic.take() {
+            panic::resume_unwind(payload);
+        }
+        if let Err(exception) = result.into_result() {
+            panic!("{exception}");
+        }
+        output.expect("future completed without output")
+    }
+
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    fn spawn_local(&self, future: impl Future<Output = ()> + 'static) {
+        let panic = self.panic.clone();
+        let mut wrapper = crate::repr::future(Box::pin(async move {
+            CatchUnwind(future)
+                .await
+                .map_err(|payload| store_panic(&panic, payload))
+        }));
+        // The loop takes ownership of the wrapper.
+        unsafe { event_loop_spawn(self.event_loop.as_ptr(), (&raw mut wrapper).cast()) };
+    }
+}
+
+impl Default for EventLoop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EventLoop {
+    fn drop(&mut self) {
+        CURRENT.set(None);
+        unsafe { event_loop_drop(self.inner.event_loop.as_ptr()) };
+    }
+}
+
+/// Runs `future` to completion on the current thread's [`EventLoop`], creating one for the
+/// duration of the call if the thread has none.
+///
+/// # Panics
+///
+/// See [`EventLoop::block_on`].
+#[cfg_attr(feature = "instrumentation", track_caller)]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    match CURRENT.get() {
+        // Safety: `CURRENT` is cleared before the `EventLoop` owning `Inner` is dropped, and
+        // `EventLoop` is not `Send`, so `Inner` stays alive and on this thread until then.
+        Some(inner) => unsafe { inner.as_ref() }.block_on(future),
+        None => EventLoop::new().block_on(future),
+    }
+}
+
+/// Starts running `future` in the background on the current thread's [`EventLoop`].
+///
+/// # Panics
+///
+/// Panics if the thread has no [`EventLoop`] created from Rust.
+#[cfg_attr(feature = "instrumentation", track_caller)]
+pub fn spawn_local(future: impl Future<Output = ()> + 'static) {
+    let inner = CURRENT
+        .get()
+        .expect("spawn_local() requires a kj_rs::EventLoop on the current thread");
+    // Safety: see `block_on()`.
+    unsafe { inner.as_ref() }.spawn_local(future);
+}
+
+/// Keeps the first panic for `block_on()` to resume, and rejects the future's promise so the
+/// loop stops waiting for it.
+fn store_panic(slot: &RefCell<Option<PanicPayload>>, payload: PanicPayload) -> KjException {
+    slot.borrow_mut().get_or_insert(payload);
+    KjError::new(KjExceptionType::Failed, "future panicked".to_owned())
+        .into_kj_exception(file!(), line!())
+}
+
+/// Catches panics of the wrapped future, which must not unwind into the KJ event loop.
+struct CatchUnwind<F>(F);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = std::thread::Result<F::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: self is pinned, so the inner future is pinned.
+        let future = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
+        match panic::catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(value)) => Poll::Ready(Ok(value)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
@@ -4,6 +4,19 @@
 
 namespace kj_rs {
 
+namespace {
+
+// `kj::getCurrentThreadExecutor()`, or none instead of throwing if the thread has no event loop.
+kj::Maybe<const kj::Executor&> tryGetCurrentThreadExecutor() noexcept {
+  const kj::Executor* executor = nullptr;
+  if (kj::runCatchingExceptions([&]() { executor = &kj::getCurrentThreadExecutor(); }) != kj::none) {
+    return kj::none;
+  }
+  return *executor;
+}
+
+}  // namespace
+
 bool isCurrent(const kj::Executor& executor) {
   return &executor == &kj::getCurrentThreadExecutor();
 }
@@ -13,3 +26,22 @@ void requireCurrent(const kj::Executor& executor, kj::LiteralStringConst message
 }
 
 }  // namespace kj_rs
+
+extern "C" {
+
+bool cxxbridge$kjrs$executor$current(void* out) noexcept {
+  KJ_IF_SOME(executor, kj_rs::tryGetCurrentThreadExecutor()) {
+    new (out) kj::Own<const kj::Executor>(executor.addRef());
+    return true;
+  }
+  return false;
+}
+
+bool cxxbridge$kjrs$executor$is_current(const void* executor) noexcept {
+  auto& own = *reinterpret_cast<const kj::Own<const kj::Executor>*>(executor);
+  KJ_IF_SOME(current, kj_rs::tryGetCurrentThreadExecutor()) {
+    return own.get() == &current;
+  }
+  return false;
+}
+}
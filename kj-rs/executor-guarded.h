@@ -54,3 +54,17 @@ class ExecutorGuarded {
 };
 
 }  // namespace kj_rs
+
+extern "C" {
+
+// The `executor` inputs point to Rust `kj_rs::ExecutorGuarded<T>` owners of a
+// `kj::Own<const kj::Executor>`, which is destroyed with `cxxbridge$kjrs$own$drop`.
+
+// Constructs a `kj::Own<const kj::Executor>` for the current thread's event loop into `out`.
+// Returns false, leaving `out` uninitialized, if the thread has no event loop.
+bool cxxbridge$kjrs$executor$current(void* out) noexcept;
+
+// Returns whether the event loop of the input `kj::Own<const kj::Executor>` is active on the
+// current thread.
+bool cxxbridge$kjrs$executor$is_current(const void* executor) noexcept;
+}
@@ -0,0 +1,140 @@
+//! The Rust side of `kj-rs/executor-guarded.h`.
+
+use std::ffi::c_void;
+use std::fmt;
+use std::mem::{ManuallyDrop, MaybeUninit};
+
+use crate::KjOwn;
+
+unsafe extern "C" {
+    #[link_name = "cxxbridge$kjrs$executor$current"]
+    fn executor_current(out: *mut c_void) -> bool;
+
+    #[link_name = "cxxbridge$kjrs$executor$is_current"]
+    fn executor_is_current(executor: *const c_void) -> bool;
+}
+
+/// A `const kj::Executor`, which is safe to use from any thread.
+#[repr(C)]
+struct Executor {
+    _private: [u8; 0],
+}
+
+unsafe impl Send for Executor {}
+unsafe impl Sync for Executor {}
+
+/// Returns the executor of the current thread's event loop, if any.
+fn current_executor() -> Option<KjOwn<Executor>> {
+    let mut executor = MaybeUninit::<KjOwn<Executor>>::uninit();
+    // Safety: `out` has the layout of a `kj::Own`, and is initialized if the call returns true.
+    unsafe { executor_current(executor.as_mut_ptr().cast()).then(|| executor.assume_init()) }
+}
+
+/// A value which may only be accessed on the thread running the KJ event loop that was active when
+/// it was created, like `kj_rs::ExecutorGuarded<T>` in C++.
+///
+/// The guard checks the event loop on every access, so it is `Send` and `Sync` even when `T` is
+/// not, and can hold event-loop-affine state such as [`crate::KjRc`] inside values shared with
+/// other threads.
+pub struct ExecutorGuarded<T> {
+    executor: KjOwn<Executor>,
+    value: ManuallyDrop<T>,
+}
+
+// Safety: `value` is only accessed, and dropped, on the thread running `executor`'s event loop.
+// An event loop runs on one thread at a time, so all accesses are effectively single-threaded.
+unsafe impl<T> Send for ExecutorGuarded<T> {}
+unsafe impl<T> Sync for ExecutorGuarded<T> {}
+
+impl<T> ExecutorGuarded<T> {
+    /// Guards `value` by the current thread's event loop.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current thread has no KJ event loop.
+    pub fn new(value: T) -> Self {
+        let executor = current_executor().expect("ExecutorGuarded requires a KJ event loop");
+        Self {
+            executor,
+            value: ManuallyDrop::new(value),
+        }
+    }
+
+    /// Returns true if the current thread runs the event loop the value was created on.
+    #[must_use]
+    pub fn is_current(&self) -> bool {
+        unsafe { executor_is_current(std::ptr::from_ref(&self.executor).cast()) }
+    }
+
+    /// Returns the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current thread does not run the event loop the value was created on.
+    #[must_use]
+    pub fn get(&self) -> &T {
+        self.try_get().expect("access on wrong event loop")
+    }
+
+    /// Returns the value, or `None` if the current thread does not run the event loop the value
+    /// was created on.
+    #[must_use]
+    pub fn try_get(&self) -> Option<&T> {
+        self.is_current().then(|| &*self.value)
+    }
+
+    /// Returns the value mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current thread does not run the event loop the value was created on.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.try_get_mut().expect("access on wrong event loop")
+    }
+
+    /// Returns the value mutably, or `None` if the current thread does not run the event loop the
+    /// value was created on.
+    pub fn try_get_mut(&mut self) -> Option<&mut T> {
+        if self.is_current() {
+            Some(&mut self.value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value, consuming the guard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current thread does not run the event loop the value was created on.
+    pub fn into_inner(self) -> T {
+        assert!(self.is_current(), "access on wrong event loop");
+        let mut this = ManuallyDrop::new(self);
+        // Safety: `this` is never used again, and its executor is dropped in place.
+        unsafe {
+            std::ptr::drop_in_place(&raw mut this.executor);
+            ManuallyDrop::take(&mut this.value)
+        }
+    }
+}
+
+impl<T> Drop for ExecutorGuarded<T> {
+    fn drop(&mut self) {
+        if self.is_current() {
+            // Safety: `value` is not used after this.
+            unsafe { ManuallyDrop::drop(&mut self.value) };
+        } else if !std::thread::panicking() {
+            // The value can't be dropped on this thread, so it is leaked.
+            panic!("destruction on wrong event loop");
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ExecutorGuarded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_get() {
+            Some(value) => f.debug_tuple("ExecutorGuarded").field(value).finish(),
+            None => f.write_str("ExecutorGuarded(<wrong event loop>)"),
+        }
+    }
+}
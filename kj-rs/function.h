@@ -0,0 +1,66 @@
+#pragma once
+
+#include <kj/common.h>
+#include <kj/function.h>
+
+namespace kj_rs::repr {
+
+// A closure owned by the other language, matching `kj_rs::repr::Closure` in Rust.
+//
+// `closure` points to a `Box<dyn FnMut>` when it comes from Rust, or to a `kj::Function` when it
+// comes from C++. Whoever receives it must eventually call `drop(closure)` exactly once.
+struct Closure {
+  void* closure;
+  void (*drop)(void* closure) noexcept;
+};
+
+template <typename Signature>
+class RustClosure;
+
+// A Rust closure called through the trampoline generated for its signature, which takes the
+// arguments followed by the closure, and throws the Rust error or panic as a `kj::Exception`.
+template <typename Ret, typename... Args>
+class RustClosure<Ret(Args...)> {
+ public:
+  using Trampoline = Ret (*)(Args..., void*);
+
+  RustClosure(Closure closure, Trampoline trampoline): closure(closure), trampoline(trampoline) {}
+  RustClosure(RustClosure&& other): closure(other.closure), trampoline(other.trampoline) {
+    other.closure.closure = nullptr;
+  }
+  ~RustClosure() noexcept {
+    if (closure.closure != nullptr) {
+      closure.drop(closure.closure);
+    }
+  }
+
+  KJ_DISALLOW_COPY(RustClosure);
+  RustClosure& operator=(RustClosure&&) = delete;
+
+  Ret operator()(Args... args) {
+    return trampoline(kj::fwd<Args>(args)..., closure.closure);
+  }
+
+ private:
+  Closure closure;
+  Trampoline trampoline;
+};
+
+// Takes ownership of a Rust closure.
+template <typename Signature>
+kj::Function<Signature> fromClosure(
+    Closure closure, typename RustClosure<Signature>::Trampoline trampoline) {
+  return RustClosure<Signature>(closure, trampoline);
+}
+
+// Hands `function` over to Rust, which calls it through the trampoline generated for its
+// signature.
+template <typename Signature>
+Closure toClosure(kj::Function<Signature> function) {
+  return Closure{
+    .closure = new kj::Function<Signature>(kj::mv(function)),
+    .drop = [](void* closure) noexcept { delete static_cast<kj::Function<Signature>*>(closure); },
+  };
+}
+
+}  // namespace kj_rs::repr
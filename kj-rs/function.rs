@@ -0,0 +1,53 @@
+//! Bindings between boxed Rust closures and `kj::Function<T>`.
+//!
+//! A `Box<dyn FnMut(Args) -> R>` in bridge declarations is `kj::Function<R(Args)>` in C++. Either
+//! side hands its closure to the other as a [`repr::Closure`], which the receiving side calls
+//! through a trampoline generated for that signature.
+
+// These types are shared with C++ code.
+pub mod repr {
+    use std::ffi::c_void;
+
+    use static_assertions::{assert_eq_align, assert_eq_size};
+
+    assert_eq_size!(Closure, [*mut c_void; 2]);
+    assert_eq_align!(Closure, *mut c_void);
+
+    type DropCallback = unsafe extern "C" fn(closure: *mut c_void);
+
+    /// A closure owned by the other language, matching `kj_rs::repr::Closure` in C++.
+    ///
+    /// `closure` points to a `Box<dyn FnMut>` when it comes from Rust, or to a `kj::Function`
+    /// when it comes from C++. Dropping it destroys the closure.
+    #[repr(C)]
+    pub struct Closure {
+        closure: *mut c_void,
+        drop: DropCallback,
+    }
+
+    impl Closure {
+        /// Moves `f` to the heap, to be called by C++ through the trampoline for `F`.
+        pub fn new<F>(f: F) -> Self {
+            unsafe extern "C" fn drop_box<F>(closure: *mut c_void) {
+                drop(unsafe { Box::from_raw(closure.cast::<F>()) });
+            }
+
+            Self {
+                closure: Box::into_raw(Box::new(f)).cast(),
+                drop: drop_box::<F>,
+            }
+        }
+
+        /// The closure to pass to its trampoline.
+        #[must_use]
+        pub fn as_ptr(&self) -> *mut c_void {
+            self.closure
+        }
+    }
+
+    impl Drop for Closure {
+        fn drop(&mut self) {
+            unsafe { (self.drop)(self.closure) }
+        }
+    }
+}
@@ -1,12 +1,15 @@
 #pragma once
 
 #include "kj-rs/awaiter.h"
+#include "kj-rs/context.h"
 #include "kj-rs/waker.h"
 
 #include <kj/debug.h>
 
 #include <concepts>
 #include <cstdint>
+#include <type_traits>
+#include <utility>
 
 namespace kj_rs {
 
@@ -76,7 +79,12 @@ using DropCallback = void (*)(void /* RustFuture::fut */* fut);
 // same layout.
 //
 // Cancellation: Destroying the enclosing FutureAwaiter calls this struct's `drop` function pointer,
-// which drops the Rust Future and transitively cancels any KJ sub-promises it was .await'ing.
+// which drops the Rust Future and transitively cancels any KJ sub-promises it was .await'ing. If
+// that happens inside a `kj_rs::CancellationScope`, the Rust Future and those sub-promises see its
+// reason while they are dropped.
+//
+// Context: The promise captures the current `kj_rs::AsyncContext`, which is current again during
+// each poll of the Rust Future.
 struct RustFuture {
 
   template <typename T>
@@ -85,10 +93,11 @@ struct RustFuture {
       using ExceptionOrValue = ::kj::_::ExceptionOr<::kj::_::FixVoid<T>>;
       using Output = ::kj::_::FixVoid<T>;
 
-      Impl(RustFuture fut): fut(fut) {}
+      Impl(RustFuture fut): fut(fut), context(::kj_rs::AsyncContext::capture()) {}
 
       ~Impl() {
         if (fut.repr != std::array<std::uintptr_t, 2>{}) {
+          ::kj_rs::AsyncContext::Scope scope(context);
           fut.drop(&fut);
         }
       }
@@ -96,17 +105,20 @@ struct RustFuture {
         KJ_ASSERT(other.fut.repr != (std::array<std::uintptr_t, 2>{}));
         fut = other.fut;
         other.fut.repr = {};
+        context = kj::mv(other.context);
       }
 
       KJ_DISALLOW_COPY(Impl);
 
       void poll(const ::kj_rs::KjWaker& waker, ExceptionOrValue& output) noexcept {
+        ::kj_rs::AsyncContext::Scope scope(context);
         ::kj_rs::FuturePoller<Output> poller;
         poller.poll(
             [this, &waker](void* result) { return fut.poll(&fut, &waker, result); }, output);
       }
 
       RustFuture fut;
+      kj::Maybe<kj::Own<::kj_rs::AsyncContext>> context;
     };
 
     return kj::_::PromiseNode::to<kj::Promise<T>>(
@@ -119,6 +131,62 @@ struct RustFuture {
 };
 
 static_assert(sizeof(RustFuture) == 4 * sizeof(std::uintptr_t), "incorrect RustFuture layout");
+
+// ::kj_rs::promise::KjForkedPromise
+struct ForkedPromise {
+  void /* Rust future */* future;
+  void (*addBranch)(const void* future, RustFuture* ret) noexcept;
+  void (*drop)(void* future) noexcept;
+};
 }  // namespace repr
 
+// A clonable Rust future, the C++ side of `KjForkedPromise<T>` in bridge declarations. Like
+// `kj::ForkedPromise<T>`, each `addBranch()` returns a promise for the same result: Rust clones the
+// future for every branch, so `T` doesn't have to be copyable in C++.
+template <typename T>
+class ForkedPromise {
+ public:
+  ForkedPromise(ForkedPromise&& other): impl(other.impl) {
+    other.impl.future = nullptr;
+  }
+  ForkedPromise& operator=(ForkedPromise&& other) {
+    if (this != &other) {
+      dispose();
+      impl = other.impl;
+      other.impl.future = nullptr;
+    }
+    return *this;
+  }
+  KJ_DISALLOW_COPY(ForkedPromise);
+  ~ForkedPromise() noexcept(false) {
+    dispose();
+  }
+
+  kj::Promise<T> addBranch() {
+    KJ_REQUIRE(impl.future != nullptr, "addBranch() called on a moved-from ForkedPromise");
+    repr::RustFuture branch;
+    impl.addBranch(impl.future, &branch);
+    kj::Promise<T> promise = branch;
+    return promise;
+  }
+
+ private:
+  void dispose() {
+    if (impl.future != nullptr) {
+      impl.drop(std::exchange(impl.future, nullptr));
+    }
+  }
+
+  // `::kj_rs::promise::KjForkedPromise<T>` has the same layout.
+  repr::ForkedPromise impl;
+};
+
 }  // namespace kj_rs
+
+namespace rust {
+
+// `kj_rs::ForkedPromise<T>` only holds a pointer to the Rust future and a pair of callbacks.
+template <typename T>
+struct IsRelocatable<::kj_rs::ForkedPromise<T>>: std::true_type {};
+
+}  // namespace rust
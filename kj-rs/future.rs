@@ -137,10 +137,28 @@ pub(crate) mod repr {
         }
     }
 
+    /// Bound on the output of futures passed to C++. With the `instrumentation` feature the future
+    /// is boxed again behind a tracker, which needs the output to outlive the future.
+    #[cfg(feature = "instrumentation")]
+    pub trait FutureOutput<'a>: Unpin + 'a {}
+
+    #[cfg(feature = "instrumentation")]
+    impl<'a, T: Unpin + 'a> FutureOutput<'a> for T {}
+
+    /// Bound on the output of futures passed to C++.
+    #[cfg(not(feature = "instrumentation"))]
+    pub trait FutureOutput<'a>: Unpin {}
+
+    #[cfg(not(feature = "instrumentation"))]
+    impl<T: Unpin> FutureOutput<'_> for T {}
+
     #[must_use]
-    pub fn future<'a, T: Unpin>(
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    pub fn future<'a, T: FutureOutput<'a>>(
         fut: Pin<Box<dyn Future<Output = Result<T, cxx::KjException>> + 'a>>,
     ) -> RustFuture<'a, T> {
+        #[cfg(feature = "instrumentation")]
+        let fut = track(fut);
         let fut = Box::into_raw(unsafe { Pin::into_inner_unchecked(fut) });
         let poll = RustFuture::<T>::poll;
         let drop = RustFuture::<T>::drop_in_place;
@@ -148,14 +166,26 @@ pub(crate) mod repr {
     }
 
     #[must_use]
-    pub fn infallible_future<'a, T: Unpin>(
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    pub fn infallible_future<'a, T: FutureOutput<'a>>(
         fut: Pin<Box<dyn Future<Output = T> + 'a>>,
     ) -> RustInfallibleFuture<'a, T> {
+        #[cfg(feature = "instrumentation")]
+        let fut = track(fut);
         let fut = Box::into_raw(unsafe { Pin::into_inner_unchecked(fut) });
         let poll = RustInfallibleFuture::<T>::poll;
         let drop = RustInfallibleFuture::<T>::drop_in_place;
         RustInfallibleFuture { fut, poll, drop }
     }
+
+    #[cfg(feature = "instrumentation")]
+    #[track_caller]
+    fn track<'a, T: 'a>(
+        fut: Pin<Box<dyn Future<Output = T> + 'a>>,
+    ) -> Pin<Box<dyn Future<Output = T> + 'a>> {
+        use crate::instrumentation::{FutureKind, Tracked};
+        Box::pin(Tracked::new(fut, FutureKind::RustFuture))
+    }
 }
 
 // A future that converts error into `cxx::KjException`
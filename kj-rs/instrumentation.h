@@ -0,0 +1,36 @@
+#pragma once
+
+// Introspection of the futures bridged between Rust and C++, available when kj-rs is built with
+// `--//kj-rs:instrumentation`, which enables the `instrumentation` feature of the Rust crate.
+
+#ifdef KJ_RS_INSTRUMENTATION
+
+#include <kj/string.h>
+#include <kj/vector.h>
+
+#include <cstddef>
+
+extern "C" {
+
+// Implemented in instrumentation.rs. Calls `write` once with the description of the live futures.
+void cxxbridge$kjrs$instrumentation$dump(
+    void* out, void (*write)(void* out, const char* data, size_t len) noexcept) noexcept;
+}
+
+namespace kj_rs {
+
+// Describes each live `RustFuture` and `PromiseFuture` on its own line, oldest first: where it was
+// created, whether it completed, how many times it was polled and when it was last polled. This is
+// the same as `kj_rs::dump_live_futures()` in Rust.
+inline kj::String dumpLiveFutures() {
+  kj::Vector<char> dump;
+  cxxbridge$kjrs$instrumentation$dump(&dump, [](void* out, const char* data, size_t len) noexcept {
+    reinterpret_cast<kj::Vector<char>*>(out)->addAll(data, data + len);
+  });
+  dump.add('\0');
+  return kj::String(dump.releaseAsArray());
+}
+
+}  // namespace kj_rs
+
+#endif  // KJ_RS_INSTRUMENTATION
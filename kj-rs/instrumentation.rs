@@ -0,0 +1,235 @@
+//! Tracking of the futures crossing the FFI boundary, enabled by the `instrumentation` feature.
+//!
+//! Every `RustFuture` (a Rust future awaited by C++ as a `kj::Promise`) and every `PromiseFuture`
+//! (a `kj::Promise` awaited by Rust) is registered while it is alive, with the source location
+//! which created it, its number of polls and the time of its last poll. This helps finding the
+//! futures a stuck task is waiting on.
+
+use std::collections::BTreeMap;
+use std::ffi::c_void;
+use std::fmt::{self, Write};
+use std::future::Future;
+use std::panic::Location;
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+/// Which side of the FFI boundary a tracked future comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FutureKind {
+    /// A Rust future converted to a `kj::Promise` for C++.
+    RustFuture,
+    /// A `kj::Promise` converted to a Rust future.
+    PromiseFuture,
+}
+
+/// Whether a tracked future has completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FutureState {
+    Pending,
+    Ready,
+}
+
+/// A snapshot of a live tracked future.
+#[derive(Debug, Clone)]
+pub struct FutureInfo {
+    /// Unique among the futures tracked by the process, in creation order.
+    pub id: u64,
+    pub kind: FutureKind,
+    /// Where the future was created. For futures created by bridge functions, this is the bridge
+    /// declaration.
+    pub location: &'static Location<'static>,
+    pub created: Instant,
+    pub state: FutureState,
+    pub polls: u64,
+    pub last_poll: Option<Instant>,
+}
+
+impl fmt::Display for FutureInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let now = Instant::now();
+        write!(
+            f,
+            "#{} {:?} created at {} {:?} ago: {:?}, polled {} times",
+            self.id,
+            self.kind,
+            self.location,
+            now.duration_since(self.created),
+            self.state,
+            self.polls,
+        )?;
+        if let Some(last_poll) = self.last_poll {
+            write!(f, ", last {:?} ago", now.duration_since(last_poll))?;
+        }
+        Ok(())
+    }
+}
+
+struct Registry {
+    next_id: u64,
+    futures: BTreeMap<u64, FutureInfo>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    next_id: 0,
+    futures: BTreeMap::new(),
+});
+
+fn registry() -> MutexGuard<'static, Registry> {
+    // The registry is left consistent by every critical section.
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the futures which are currently alive, oldest first.
+#[must_use]
+pub fn live_futures() -> Vec<FutureInfo> {
+    registry().futures.values().cloned().collect()
+}
+
+/// Describes the futures which are currently alive, one per line, oldest first.
+#[must_use]
+pub fn dump_live_futures() -> String {
+    let mut dump = String::new();
+    for info in live_futures() {
+        let _ = writeln!(dump, "{info}");
+    }
+    dump
+}
+
+/// The registration of a tracked future, removed when dropped.
+pub(crate) struct Tracker {
+    id: u64,
+}
+
+impl Tracker {
+    #[track_caller]
+    pub(crate) fn new(kind: FutureKind) -> Self {
+        let location = Location::caller();
+        let mut registry = registry();
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.futures.insert(
+            id,
+            FutureInfo {
+                id,
+                kind,
+                location,
+                created: Instant::now(),
+                state: FutureState::Pending,
+                polls: 0,
+                last_poll: None,
+            },
+        );
+        Tracker { id }
+    }
+
+    /// Records a poll, which completed the future if `ready`.
+    pub(crate) fn record_poll(&self, ready: bool) {
+        if let Some(info) = registry().futures.get_mut(&self.id) {
+            info.polls += 1;
+            info.last_poll = Some(Instant::now());
+            if ready {
+                info.state = FutureState::Ready;
+            }
+        }
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        registry().futures.remove(&self.id);
+    }
+}
+
+/// A future recording its polls with a [`Tracker`].
+pub(crate) struct Tracked<F: ?Sized> {
+    future: Pin<Box<F>>,
+    tracker: Tracker,
+}
+
+impl<F: Future + ?Sized> Tracked<F> {
+    #[track_caller]
+    pub(crate) fn new(future: Pin<Box<F>>, kind: FutureKind) -> Self {
+        Tracked {
+            future,
+            tracker: Tracker::new(kind),
+        }
+    }
+}
+
+impl<F: Future + ?Sized> Future for Tracked<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let poll = self.future.as_mut().poll(cx);
+        self.tracker.record_poll(poll.is_ready());
+        poll
+    }
+}
+
+// C++ API, declared in instrumentation.h.
+
+type WriteCallback = unsafe extern "C" fn(out: *mut c_void, data: *const u8, len: usize);
+
+#[unsafe(export_name = "cxxbridge$kjrs$instrumentation$dump")]
+unsafe extern "C" fn instrumentation_dump(out: *mut c_void, write: WriteCallback) {
+    let dump = dump_live_futures();
+    unsafe { write(out, dump.as_ptr(), dump.len()) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FutureInfo, FutureKind, FutureState, Tracked, dump_live_futures, live_futures};
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    fn find_info(id: u64) -> Option<FutureInfo> {
+        live_futures().into_iter().find(|info| info.id == id)
+    }
+
+    #[test]
+    fn test_tracked_future() {
+        let mut ready = false;
+        let future = std::future::poll_fn(|_| {
+            if ready {
+                Poll::Ready(())
+            } else {
+                ready = true;
+                Poll::Pending
+            }
+        });
+        let line = line!() + 1;
+        let mut tracked = pin!(Tracked::new(Box::pin(future), FutureKind::RustFuture));
+        let id = tracked.tracker.id;
+
+        let info = find_info(id).unwrap();
+        assert_eq!(info.kind, FutureKind::RustFuture);
+        assert_eq!(info.location.file(), file!());
+        assert_eq!(info.location.line(), line);
+        assert_eq!(info.polls, 0);
+        assert!(info.last_poll.is_none());
+
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(tracked.as_mut().poll(&mut cx).is_pending());
+        let info = find_info(id).unwrap();
+        assert_eq!((info.state, info.polls), (FutureState::Pending, 1));
+        assert!(tracked.as_mut().poll(&mut cx).is_ready());
+        let info = find_info(id).unwrap();
+        assert_eq!((info.state, info.polls), (FutureState::Ready, 2));
+        assert!(dump_live_futures().contains(&format!("#{id} RustFuture")));
+    }
+
+    #[test]
+    fn test_dropped_future_is_removed() {
+        let tracked = Tracked::new(
+            Box::pin(std::future::pending::<()>()),
+            FutureKind::PromiseFuture,
+        );
+        let id = tracked.tracker.id;
+        assert!(find_info(id).is_some());
+        drop(tracked);
+        assert!(find_info(id).is_none());
+    }
+}
@@ -0,0 +1,96 @@
+#include "kj-rs/io.h"
+
+namespace kj_rs {
+
+namespace {
+
+// A `kj::AsyncInputStream` reading from a Rust `futures::io::AsyncRead`.
+class RustInputStream final: public kj::AsyncInputStream {
+ public:
+  RustInputStream(void* reader, kj::Maybe<uint64_t> length): reader(reader), length(length) {}
+  ~RustInputStream() noexcept(false) {
+    cxxbridge$kjrs$io$reader_drop(reader);
+  }
+  KJ_DISALLOW_COPY_AND_MOVE(RustInputStream);
+
+  kj::Promise<size_t> tryRead(void* buffer, size_t minBytes, size_t maxBytes) override {
+    repr::RustFuture future;
+    cxxbridge$kjrs$io$reader_read(
+        reader, reinterpret_cast<kj::byte*>(buffer), minBytes, maxBytes, &future);
+    kj::Promise<size_t> promise = future;
+    if (length == kj::none) {
+      return promise;
+    }
+    return promise.then([this](size_t n) {
+      KJ_IF_SOME(l, length) {
+        l -= kj::min(l, n);
+      }
+      return n;
+    });
+  }
+
+  kj::Maybe<uint64_t> tryGetLength() override {
+    return length;
+  }
+
+ private:
+  void* reader;
+  // Bytes left to read, if known.
+  kj::Maybe<uint64_t> length;
+};
+
+}  // namespace
+
+}  // namespace kj_rs
+
+extern "C" {
+
+::rust::repr::Result cxxbridge$kjrs$io$try_read(kj::AsyncInputStream* stream,
+    kj::byte* buffer,
+    size_t minBytes,
+    size_t maxBytes,
+    kj_rs::repr::KjPromiseNodeImpl* out) noexcept {
+  return ::rust::repr::Result::run([&]() {
+    new (out) kj_rs::repr::KjPromiseNodeImpl(stream->tryRead(buffer, minBytes, maxBytes));
+  });
+}
+
+::rust::repr::Result cxxbridge$kjrs$io$try_get_length(
+    kj::AsyncInputStream* stream, bool* known, uint64_t* length) noexcept {
+  return ::rust::repr::Result::run([&]() {
+    KJ_IF_SOME(l, stream->tryGetLength()) {
+      *known = true;
+      *length = l;
+    } else {
+      *known = false;
+    }
+  });
+}
+
+::rust::repr::Result cxxbridge$kjrs$io$pump_to(kj::AsyncInputStream* input,
+    kj::AsyncOutputStream* output,
+    uint64_t amount,
+    kj_rs::repr::KjPromiseNodeImpl* out) noexcept {
+  return ::rust::repr::Result::run([&]() {
+    new (out) kj_rs::repr::KjPromiseNodeImpl(input->pumpTo(*output, amount));
+  });
+}
+
+::rust::repr::Result cxxbridge$kjrs$io$write(kj::AsyncOutputStream* stream,
+    const kj::byte* data,
+    size_t len,
+    kj_rs::repr::KjPromiseNodeImpl* out) noexcept {
+  return ::rust::repr::Result::run([&]() {
+    new (out) kj_rs::repr::KjPromiseNodeImpl(stream->write(kj::arrayPtr(data, len)));
+  });
+}
+
+void cxxbridge$kjrs$io$input_stream_new(
+    void* reader, bool hasLength, uint64_t length, void* out) noexcept {
+  kj::Maybe<uint64_t> maybeLength;
+  if (hasLength) {
+    maybeLength = length;
+  }
+  new (out) kj::Own<kj::AsyncInputStream>(kj::heap<kj_rs::RustInputStream>(reader, maybeLength));
+}
+}
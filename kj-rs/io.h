@@ -0,0 +1,60 @@
+#pragma once
+
+#include "kj-rs/future.h"
+#include "kj-rs/promise.h"
+
+#include <rust/cxx.h>
+
+#include <kj/async-io.h>
+
+#include <cstddef>
+#include <cstdint>
+
+// Byte streams shared with Rust: `kj_rs::KjInputStream` reads a `kj::AsyncInputStream` as a
+// `futures::io::AsyncRead`, `kj_rs::KjOutputStream` writes a `kj::AsyncOutputStream` as an
+// `AsyncWrite`, and `kj_rs::AsyncInputStream::from_reader()` exposes an `AsyncRead` as a
+// `kj::Own<kj::AsyncInputStream>`.
+
+extern "C" {
+
+// Constructs the promise of `stream->tryRead(buffer, minBytes, maxBytes)` into `out`.
+::rust::repr::Result cxxbridge$kjrs$io$try_read(kj::AsyncInputStream* stream,
+    kj::byte* buffer,
+    size_t minBytes,
+    size_t maxBytes,
+    kj_rs::repr::KjPromiseNodeImpl* out) noexcept;
+
+// Sets `known` and `length` from `stream->tryGetLength()`.
+::rust::repr::Result cxxbridge$kjrs$io$try_get_length(
+    kj::AsyncInputStream* stream, bool* known, uint64_t* length) noexcept;
+
+// Constructs the promise of `input->pumpTo(*output, amount)` into `out`.
+::rust::repr::Result cxxbridge$kjrs$io$pump_to(kj::AsyncInputStream* input,
+    kj::AsyncOutputStream* output,
+    uint64_t amount,
+    kj_rs::repr::KjPromiseNodeImpl* out) noexcept;
+
+// Constructs the promise of `stream->write()` of `len` bytes at `data` into `out`.
+::rust::repr::Result cxxbridge$kjrs$io$write(kj::AsyncOutputStream* stream,
+    const kj::byte* data,
+    size_t len,
+    kj_rs::repr::KjPromiseNodeImpl* out) noexcept;
+
+// Constructs a stream reading from the Rust reader `reader` into `out`, a
+// `kj::Own<kj::AsyncInputStream>`, taking ownership of the reader.
+void cxxbridge$kjrs$io$input_stream_new(
+    void* reader, bool hasLength, uint64_t length, void* out) noexcept;
+
+// These functions are implemented in io.rs.
+
+// Constructs the future of reading between `minBytes` and `maxBytes` bytes into `buffer` into
+// `out`. The reader and the buffer must outlive the future.
+void cxxbridge$kjrs$io$reader_read(void* reader,
+    kj::byte* buffer,
+    size_t minBytes,
+    size_t maxBytes,
+    kj_rs::repr::RustFuture* out) noexcept;
+
+// Destroys `reader`.
+void cxxbridge$kjrs$io$reader_drop(void* reader) noexcept;
+}
@@ -0,0 +1,425 @@
+//! Adapters between KJ byte streams and the `futures-io` traits.
+//!
+//! A `KjOwn<AsyncInputStream>` becomes an [`AsyncRead`] with [`KjInputStream`], and a
+//! `KjOwn<AsyncOutputStream>` an [`AsyncWrite`] with [`KjOutputStream`]. In the other direction,
+//! [`AsyncInputStream::from_reader`] exposes an `AsyncRead` to C++ as a `kj::AsyncInputStream`.
+
+use std::ffi::c_void;
+use std::future::poll_fn;
+use std::io;
+use std::marker::PhantomPinned;
+use std::mem::MaybeUninit;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use cxx::{ExternType, IntoKjException, KjError, KjException, KjExceptionType};
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+use crate::KjOwn;
+use crate::KjPromiseNodeImpl;
+use crate::promise::{CallbacksFuture, PromiseFuture, callbacks_promise_future};
+use crate::repr::RustFuture;
+
+unsafe extern "C" {
+    #[link_name = "cxxbridge$kjrs$io$try_read"]
+    fn io_try_read(
+        stream: *mut c_void,
+        buffer: *mut u8,
+        min_bytes: usize,
+        max_bytes: usize,
+        out: *mut KjPromiseNodeImpl,
+    ) -> cxx::private::Result;
+
+    #[link_name = "cxxbridge$kjrs$io$try_get_length"]
+    fn io_try_get_length(
+        stream: *mut c_void,
+        known: *mut bool,
+        length: *mut u64,
+    ) -> cxx::private::Result;
+
+    #[link_name = "cxxbridge$kjrs$io$pump_to"]
+    fn io_pump_to(
+        input: *mut c_void,
+        output: *mut c_void,
+        amount: u64,
+        out: *mut KjPromiseNodeImpl,
+    ) -> cxx::private::Result;
+
+    #[link_name = "cxxbridge$kjrs$io$write"]
+    fn io_write(
+        stream: *mut c_void,
+        data: *const u8,
+        len: usize,
+        out: *mut KjPromiseNodeImpl,
+    ) -> cxx::private::Result;
+
+    #[link_name = "cxxbridge$kjrs$io$input_stream_new"]
+    fn io_input_stream_new(reader: *mut c_void, has_length: bool, length: u64, out: *mut c_void);
+}
+
+/// A `kj::AsyncInputStream`, to own as a `KjOwn<AsyncInputStream>`. Bridges can name it with
+/// `type AsyncInputStream = kj_rs::AsyncInputStream;` in a `namespace = "kj"` block including
+/// `kj-rs/io.h`.
+#[repr(C)]
+pub struct AsyncInputStream {
+    _private: [u8; 0],
+    _pinned: PhantomPinned,
+}
+
+unsafe impl ExternType for AsyncInputStream {
+    type Id = cxx::type_id!("kj::AsyncInputStream");
+    type Kind = cxx::kind::Opaque;
+}
+
+/// A `kj::AsyncOutputStream`, to own as a `KjOwn<AsyncOutputStream>`, like [`AsyncInputStream`].
+#[repr(C)]
+pub struct AsyncOutputStream {
+    _private: [u8; 0],
+    _pinned: PhantomPinned,
+}
+
+unsafe impl ExternType for AsyncOutputStream {
+    type Id = cxx::type_id!("kj::AsyncOutputStream");
+    type Kind = cxx::kind::Opaque;
+}
+
+fn input_ptr(stream: &KjOwn<AsyncInputStream>) -> *mut c_void {
+    stream.as_ptr().cast_mut().cast()
+}
+
+fn output_ptr(stream: &KjOwn<AsyncOutputStream>) -> *mut c_void {
+    stream.as_ptr().cast_mut().cast()
+}
+
+/// Starts the promise which `start` constructs into its argument.
+fn start_promise<T>(
+    start: impl FnOnce(*mut KjPromiseNodeImpl) -> cxx::private::Result,
+) -> io::Result<Pin<Box<PromiseFuture<CallbacksFuture<T>>>>> {
+    let mut node = MaybeUninit::<KjPromiseNodeImpl>::uninit();
+    start(node.as_mut_ptr()).into_result().map_err(io_error)?;
+    Ok(Box::pin(callbacks_promise_future(unsafe {
+        node.assume_init()
+    })))
+}
+
+/// Converts the exception of a KJ stream to an `io::Error` holding it.
+fn io_error(exception: KjException) -> io::Error {
+    let kind = match exception.r#type() {
+        KjExceptionType::Disconnected => io::ErrorKind::BrokenPipe,
+        KjExceptionType::Unimplemented => io::ErrorKind::Unsupported,
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(kind, exception)
+}
+
+/// Converts an `io::Error` to the exception thrown to C++, unwrapping the one it holds, if any.
+fn kj_exception(error: io::Error) -> KjException {
+    let kind = error.kind();
+    let Some(inner) = error.into_inner() else {
+        return io_kj_error(kind, kind.to_string());
+    };
+    match inner.downcast::<KjException>() {
+        Ok(exception) => *exception,
+        Err(inner) => io_kj_error(kind, inner.to_string()),
+    }
+}
+
+fn io_kj_error(kind: io::ErrorKind, description: String) -> KjException {
+    let exception_type = match kind {
+        io::ErrorKind::BrokenPipe
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::UnexpectedEof => KjExceptionType::Disconnected,
+        io::ErrorKind::Unsupported => KjExceptionType::Unimplemented,
+        _ => KjExceptionType::Failed,
+    };
+    KjError::new(exception_type, description).into_kj_exception(file!(), line!())
+}
+
+/// The size of the buffer [`KjInputStream`] reads into.
+const BUFFER_SIZE: usize = 8192;
+
+/// A `kj::AsyncInputStream` read as an [`AsyncRead`] and [`AsyncBufRead`].
+///
+/// KJ reads complete asynchronously into a buffer which must outlive them, so the stream reads
+/// into a buffer of its own, which is then copied out. Use [`KjInputStream::pump_to`] to copy to a
+/// [`KjOutputStream`] without going through Rust.
+pub struct KjInputStream {
+    // Declared first: the outstanding read refers to `buffer` and `stream`.
+    pending: Option<Pin<Box<PromiseFuture<CallbacksFuture<usize>>>>>,
+    buffer: Box<[u8]>,
+    // The unread bytes of `buffer`.
+    start: usize,
+    end: usize,
+    stream: KjOwn<AsyncInputStream>,
+}
+
+impl From<KjOwn<AsyncInputStream>> for KjInputStream {
+    fn from(stream: KjOwn<AsyncInputStream>) -> Self {
+        Self {
+            pending: None,
+            buffer: Box::default(),
+            start: 0,
+            end: 0,
+            stream,
+        }
+    }
+}
+
+/// Returns the KJ stream, or a stream reading from the [`KjInputStream`] if it holds buffered
+/// data.
+impl From<KjInputStream> for KjOwn<AsyncInputStream> {
+    fn from(stream: KjInputStream) -> Self {
+        if stream.start == stream.end && stream.pending.is_none() {
+            stream.stream
+        } else {
+            AsyncInputStream::from_reader(stream)
+        }
+    }
+}
+
+impl KjInputStream {
+    /// Returns the number of bytes left in the stream, if known, with `tryGetLength()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the exception thrown by `tryGetLength()`.
+    pub fn try_get_length(&self) -> io::Result<Option<u64>> {
+        let mut known = false;
+        let mut length = 0;
+        unsafe { io_try_get_length(input_ptr(&self.stream), &raw mut known, &raw mut length) }
+            .into_result()
+            .map_err(io_error)?;
+        Ok(known.then(|| length + (self.end - self.start) as u64))
+    }
+
+    /// Copies up to `amount` bytes to `output` and returns the number of bytes copied, which is
+    /// less than `amount` only if the stream ended. Bytes already read into this stream's buffer
+    /// are written first, and the rest is copied with `pumpTo()`, which lets KJ use the fastest
+    /// path available between the two streams.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of either stream.
+    pub async fn pump_to(&mut self, output: &mut KjOutputStream, amount: u64) -> io::Result<u64> {
+        poll_fn(|cx| self.poll_pending(cx)).await?;
+        let mut pumped = 0;
+        while self.start < self.end && pumped < amount {
+            let buffered = &self.buffer[self.start..self.end];
+            let len = buffered
+                .len()
+                .min(usize::try_from(amount - pumped).unwrap_or(usize::MAX));
+            let written =
+                poll_fn(|cx| Pin::new(&mut *output).poll_write(cx, &buffered[..len])).await?;
+            self.start += written;
+            pumped += written as u64;
+        }
+        poll_fn(|cx| Pin::new(&mut *output).poll_flush(cx)).await?;
+        if pumped == amount {
+            return Ok(pumped);
+        }
+        let stream = output.stream()?;
+        let pump = start_promise::<u64>(|out| unsafe {
+            io_pump_to(input_ptr(&self.stream), stream, amount - pumped, out)
+        })?;
+        Ok(pumped + pump.await.map_err(io_error)?)
+    }
+
+    /// Completes the outstanding read, if any, into the buffer.
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(pending) = &mut self.pending {
+            let result = ready!(pending.as_mut().poll(cx));
+            self.pending = None;
+            self.start = 0;
+            self.end = result.map_err(io_error)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncBufRead for KjInputStream {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        if this.start == this.end && this.pending.is_none() {
+            if this.buffer.is_empty() {
+                this.buffer = vec![0; BUFFER_SIZE].into_boxed_slice();
+            }
+            let buffer = this.buffer.as_mut_ptr();
+            let len = this.buffer.len();
+            this.pending = Some(start_promise(|out| unsafe {
+                io_try_read(input_ptr(&this.stream), buffer, 1, len, out)
+            })?);
+        }
+        ready!(this.poll_pending(cx))?;
+        Poll::Ready(Ok(&this.buffer[this.start..this.end]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        this.start = (this.start + amt).min(this.end);
+    }
+}
+
+impl AsyncRead for KjInputStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let available = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+}
+
+/// A `kj::AsyncOutputStream` written as an [`AsyncWrite`].
+///
+/// Each write is copied to a buffer which KJ writes from in the background, so a write completes
+/// right away unless the previous one is still in progress. Errors are reported by the next write
+/// or flush. Closing flushes, then destroys the KJ stream, which is how KJ streams end.
+pub struct KjOutputStream {
+    // Declared first: the outstanding write refers to `buffer` and `stream`.
+    pending: Option<Pin<Box<PromiseFuture<CallbacksFuture<()>>>>>,
+    buffer: Vec<u8>,
+    // None once closed.
+    stream: Option<KjOwn<AsyncOutputStream>>,
+}
+
+impl From<KjOwn<AsyncOutputStream>> for KjOutputStream {
+    fn from(stream: KjOwn<AsyncOutputStream>) -> Self {
+        Self {
+            pending: None,
+            buffer: Vec::new(),
+            stream: Some(stream),
+        }
+    }
+}
+
+impl KjOutputStream {
+    fn stream(&self) -> io::Result<*mut c_void> {
+        self.stream
+            .as_ref()
+            .map(output_ptr)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "stream is closed"))
+    }
+
+    /// Completes the outstanding write, if any.
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(pending) = &mut self.pending {
+            let result = ready!(pending.as_mut().poll(cx));
+            self.pending = None;
+            result.map_err(io_error)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for KjOutputStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        let stream = this.stream()?;
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        this.buffer.clear();
+        this.buffer.extend_from_slice(buf);
+        let (data, len) = (this.buffer.as_ptr(), this.buffer.len());
+        this.pending = Some(start_promise(|out| unsafe {
+            io_write(stream, data, len, out)
+        })?);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_pending(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        this.stream = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+// The Rust side of `RustInputStream` in io.c++, which owns a `Box<Reader>`.
+
+type Reader = Box<dyn AsyncRead + Unpin>;
+
+impl AsyncInputStream {
+    /// Exposes `reader` to C++ as a `kj::AsyncInputStream`, whose `tryGetLength()` is unknown.
+    #[must_use]
+    pub fn from_reader<R: AsyncRead + Unpin + 'static>(reader: R) -> KjOwn<Self> {
+        Self::new(reader, None)
+    }
+
+    /// Exposes `reader` to C++ as a `kj::AsyncInputStream`, whose `tryGetLength()` is `length`
+    /// minus the bytes read so far.
+    #[must_use]
+    pub fn from_reader_with_length<R: AsyncRead + Unpin + 'static>(
+        reader: R,
+        length: u64,
+    ) -> KjOwn<Self> {
+        Self::new(reader, Some(length))
+    }
+
+    fn new<R: AsyncRead + Unpin + 'static>(reader: R, length: Option<u64>) -> KjOwn<Self> {
+        let reader: Box<Reader> = Box::new(Box::new(reader));
+        let mut own = MaybeUninit::<KjOwn<Self>>::uninit();
+        // Safety: `out` has the layout of a `kj::Own`, which the call initializes. The C++ stream
+        // takes ownership of the reader.
+        unsafe {
+            io_input_stream_new(
+                Box::into_raw(reader).cast(),
+                length.is_some(),
+                length.unwrap_or(0),
+                own.as_mut_ptr().cast(),
+            );
+            own.assume_init()
+        }
+    }
+}
+
+/// Constructs into `out` the future of `tryRead(buffer, min_bytes, max_bytes)`. KJ guarantees that
+/// the reader and the buffer outlive it.
+#[unsafe(export_name = "cxxbridge$kjrs$io$reader_read")]
+unsafe extern "C" fn reader_read(
+    reader: *mut c_void,
+    buffer: *mut u8,
+    min_bytes: usize,
+    max_bytes: usize,
+    out: *mut RustFuture<'static, usize>,
+) {
+    let reader = reader.cast::<Reader>();
+    let future = async move {
+        let reader = unsafe { &mut *reader };
+        let buffer = unsafe { std::slice::from_raw_parts_mut(buffer, max_bytes) };
+        let mut read = 0;
+        while read < min_bytes {
+            let n = poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, &mut buffer[read..]))
+                .await
+                .map_err(kj_exception)?;
+            if n == 0 {
+                break;
+            }
+            read += n;
+        }
+        Ok(read)
+    };
+    unsafe { out.write(crate::repr::future(Box::pin(future))) };
+}
+
+#[unsafe(export_name = "cxxbridge$kjrs$io$reader_drop")]
+unsafe extern "C" fn reader_drop(reader: *mut c_void) {
+    drop(unsafe { Box::from_raw(reader.cast::<Reader>()) });
+}
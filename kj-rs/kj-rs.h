@@ -2,7 +2,25 @@
 
 // KJ-C++ conversion utilities
 #include "kj-rs/convert.h"
+// kj::Own of Rust types
+#include "kj-rs/own.h"
 // Rust futures support
 #include "kj-rs/future.h"
 // KJ promises support
 #include "kj-rs/promise.h"
+// Boxed Rust closures as kj::Function
+#include "kj-rs/function.h"
+// Async streams support
+#include "kj-rs/stream.h"
+// Byte streams shared with futures-io
+#include "kj-rs/io.h"
+// Cooperative cancellation of Rust futures
+#include "kj-rs/cancel.h"
+// Async context propagation to Rust futures
+#include "kj-rs/context.h"
+// Tracking of live bridged futures, with the `instrumentation` build flag
+#include "kj-rs/instrumentation.h"
+// kj::OneOf layout checks
+#include "kj-rs/one-of.h"
+// Rust Result<T, E> with a typed error
+#include "kj-rs/result.h"
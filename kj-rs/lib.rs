@@ -2,33 +2,69 @@ use awaiter::OptionWaker;
 use awaiter::WakerRef;
 
 pub use crate::ffi::KjWaker;
+pub use array::repr::{KjArray, KjArrayPtr};
 pub use awaiter::PromiseAwaiter;
+pub use blocking::{BlockingPool, BlockingTask, spawn_blocking};
+pub use cancel::{Canceled, CancellationReason, CancellationToken};
+pub use context::{AsyncContext, InContext, TaskLocal, task_local, with_task_local};
 pub use date::KjDate;
+pub use event_loop::{EventLoop, block_on, spawn_local};
+pub use executor_guarded::ExecutorGuarded;
 pub use future::FuturePollStatus;
 pub use future::map_err;
+pub use futures_core::Stream;
+pub use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+#[cfg(feature = "instrumentation")]
+pub use instrumentation::{FutureInfo, FutureKind, FutureState, dump_live_futures, live_futures};
+pub use io::{AsyncInputStream, AsyncOutputStream, KjInputStream, KjOutputStream};
 pub use maybe::repr::KjMaybe;
 pub use own::repr::KjOwn;
+pub use promise::ForkedFuture;
+pub use promise::KjForkedPromise;
+pub use promise::KjOwnedPromise;
 pub use promise::KjPromise;
+pub use promise::KjPromiseFuture;
 pub use promise::KjPromiseNodeImpl;
 pub use promise::OwnPromiseNode;
 pub use promise::PromiseFuture;
 pub use promise::new_callbacks_promise_future;
 pub use refcount::repr::{KjArc, KjRc};
+pub use stream::map_stream_err;
+pub use stream::repr::{KjStream, KjStreamImpl};
+pub use string::repr::{KjString, KjStringPtr};
+pub use timer::{Elapsed, Sleep, Timeout, sleep, sleep_until, timeout};
 
+mod array;
 mod awaiter;
+mod blocking;
+mod cancel;
+mod context;
 mod date;
+mod event_loop;
+mod executor_guarded;
+mod function;
 mod future;
+#[cfg(feature = "instrumentation")]
+mod instrumentation;
+mod io;
 pub mod maybe;
 mod own;
 mod promise;
 pub mod refcount;
+mod stream;
+mod string;
+mod timer;
 mod waker;
 
 pub mod repr {
+    pub use crate::array::repr::*;
+    pub use crate::function::repr::*;
     pub use crate::future::repr::*;
     pub use crate::maybe::repr::*;
     pub use crate::own::repr::*;
     pub use crate::refcount::repr::*;
+    pub use crate::stream::repr::*;
+    pub use crate::string::repr::*;
 }
 
 pub type Result<T> = std::io::Result<T>;
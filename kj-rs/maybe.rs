@@ -271,6 +271,33 @@ unsafe impl<T> MaybeItem for crate::KjArc<T> {
     }
 }
 
+// `kj_rs::Promise<T>` doesn't define `kj::MaybeTraits` niche members either.
+unsafe impl<T> MaybeItem for crate::KjOwnedPromise<T> {
+    type Discriminant = bool;
+
+    fn is_some(value: &KjMaybe<Self>) -> bool {
+        value.is_set
+    }
+
+    fn is_none(value: &KjMaybe<Self>) -> bool {
+        !value.is_set
+    }
+
+    const NONE: KjMaybe<Self> = {
+        KjMaybe {
+            is_set: false,
+            some: MaybeUninit::uninit(),
+        }
+    };
+
+    fn some(value: Self) -> KjMaybe<Self> {
+        KjMaybe {
+            is_set: true,
+            some: MaybeUninit::new(value),
+        }
+    }
+}
+
 pub(crate) mod repr {
     use super::MaybeItem;
     use static_assertions::assert_eq_size;
@@ -304,6 +331,8 @@ pub(crate) mod repr {
     // `String` (three-pointer struct, no niche) uses the same layout: `bool` discriminant plus
     // padding, followed by three pointers.
     assert_eq_size!(KjMaybe<String>, [usize; 4]);
+    // Likewise `kj_rs::Promise<T>`, which is two pointers.
+    assert_eq_size!(KjMaybe<crate::KjOwnedPromise<isize>>, [usize; 3]);
 
     impl<T: MaybeItem> KjMaybe<T> {
         /// # Safety
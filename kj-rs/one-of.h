@@ -0,0 +1,40 @@
+#pragma once
+
+#include <kj/one-of.h>
+
+#include <algorithm>
+#include <cstddef>
+#include <cstdint>
+
+namespace kj_rs {
+namespace repr {
+
+// Layout of the Rust enum generated for a `#[cxx::one_of]` declaration. The enum is
+// `#[repr(C, u32)]`, so Rust lays it out as a `uint32_t` tag followed by a union of the variants,
+// and numbers the variants from 1 like kj::OneOf does.
+//
+// kj::OneOf<Variants...> has the same shape, but sizes and aligns its storage on its own. The
+// generated code checks that the two agree with `OneOfLayout<T>::matches`.
+template <typename T>
+struct OneOfLayout;
+
+template <typename... Variants>
+struct OneOfLayout<kj::OneOf<Variants...>> {
+  static constexpr std::size_t roundUp(std::size_t size, std::size_t align) {
+    return (size + align - 1) / align * align;
+  }
+
+  static constexpr std::size_t payloadAlign = std::max({alignof(Variants)...});
+  static constexpr std::size_t payloadSize =
+      roundUp(std::max({sizeof(Variants)...}), payloadAlign);
+
+  static constexpr std::size_t align = std::max(alignof(std::uint32_t), payloadAlign);
+  static constexpr std::size_t size =
+      roundUp(roundUp(sizeof(std::uint32_t), payloadAlign) + payloadSize, align);
+
+  static constexpr bool matches =
+      sizeof(kj::OneOf<Variants...>) == size && alignof(kj::OneOf<Variants...>) == align;
+};
+
+}  // namespace repr
+}  // namespace kj_rs
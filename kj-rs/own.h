@@ -2,6 +2,29 @@
 
 #include <kj/memory.h>
 
+#include <memory>
+
 extern "C" {
 void cxxbridge$kjrs$own$drop(void* own);
 }
+
+namespace kj_rs {
+
+// Disposes of an object of a Rust type with its std::default_delete, which the bridge specializes
+// to run Rust's drop. `kj::Own<T>(box.into_raw(), RustDisposer<T>::instance)` takes ownership of a
+// `rust::Box<T>`.
+template <typename T>
+class RustDisposer final: public kj::Disposer {
+ public:
+  static const RustDisposer instance;
+
+ protected:
+  void disposeImpl(void* pointer) const override {
+    std::default_delete<T>()(static_cast<T*>(pointer));
+  }
+};
+
+template <typename T>
+const RustDisposer<T> RustDisposer<T>::instance{};
+
+}  // namespace kj_rs
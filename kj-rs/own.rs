@@ -43,6 +43,7 @@ pub mod repr {
     use std::ffi::c_void;
     use std::fmt::{self, Debug, Display};
     use std::hash::{Hash, Hasher};
+    use std::marker::PhantomData;
     use std::ops::Deref;
     use std::ops::DerefMut;
     use std::pin::Pin;
@@ -99,6 +100,28 @@ pub mod repr {
         }
     }
 
+    /// A Rust type which can be owned by a `kj::Own`, implemented by the bridge for every Rust type
+    /// used in a [`KjOwn`].
+    ///
+    /// # Safety
+    ///
+    /// The disposer must drop objects allocated with a [`Box`].
+    pub unsafe trait OwnTarget {
+        #[doc(hidden)]
+        fn __disposer() -> *const c_void;
+    }
+
+    impl<T: OwnTarget> From<Box<T>> for KjOwn<T> {
+        /// Moves the object of the [`Box`] into a `kj::Own` without copying it. The `kj::Own`
+        /// drops it like the [`Box`] would have.
+        fn from(value: Box<T>) -> Self {
+            KjOwn {
+                disposer: T::__disposer(),
+                ptr: NonNullExceptMaybe(Box::into_raw(value), PhantomData),
+            }
+        }
+    }
+
     impl<T> AsRef<T> for KjOwn<T> {
         /// Returns a reference to the object owned by this [`Own`] if any,
         /// otherwise None.
@@ -1,5 +1,7 @@
 #include "kj-rs/promise.h"
 
+#include "kj-rs/future.h"
+
 #include <kj/debug.h>
 
 namespace kj_rs {
@@ -11,8 +13,64 @@ namespace kj_rs {
 static_assert(sizeof(OwnPromiseNode) == sizeof(uint64_t) * 1, "OwnPromiseNode size changed");
 static_assert(alignof(OwnPromiseNode) == alignof(uint64_t) * 1, "OwnPromiseNode alignment changed");
 
+// `kj_rs::Promise<T>` must match the layout of `KjOwnedPromise<T>` in promise.rs.
+static_assert(sizeof(Promise<int>) == sizeof(repr::KjPromiseNodeImpl), "Promise size changed");
+static_assert(alignof(Promise<int>) == alignof(repr::KjPromiseNodeImpl), "Promise alignment changed");
+
+// `kj_rs::ForkedPromise<T>` must match the layout of `KjForkedPromise<T>` in promise.rs.
+static_assert(sizeof(ForkedPromise<int>) == sizeof(repr::ForkedPromise), "ForkedPromise size changed");
+static_assert(
+    alignof(ForkedPromise<int>) == alignof(repr::ForkedPromise), "ForkedPromise alignment changed");
+
 void own_promise_node_drop_in_place(OwnPromiseNode* node) {
   kj::dtor(*node);
 }
 
+namespace _ {
+
+// The `kj::ForkedPromise` behind Rust's `ForkedFuture<T>`. The forked future stores its result on
+// the Rust side, where each branch clones it, so the promise itself carries no value.
+class ForkHub {
+ public:
+  explicit ForkHub(kj::Promise<void> promise): forked(promise.fork()) {}
+  KJ_DISALLOW_COPY_AND_MOVE(ForkHub);
+
+  static void addBranch(void* hub, repr::KjPromiseNodeImpl* ret) noexcept {
+    new (ret) repr::KjPromiseNodeImpl(reinterpret_cast<ForkHub*>(hub)->forked.addBranch());
+  }
+
+  static void drop(void* hub) noexcept {
+    delete reinterpret_cast<ForkHub*>(hub);
+  }
+
+ private:
+  kj::ForkedPromise<void> forked;
+};
+
+// ::kj_rs::promise::ForkHubImpl
+struct ForkHubImpl {
+  void /* ForkHub */* hub;
+  void (*addBranch)(void* hub, repr::KjPromiseNodeImpl* ret) noexcept;
+  void (*drop)(void* hub) noexcept;
+};
+
+}  // namespace _
+
 }  // namespace kj_rs
+
+extern "C" {
+
+// Forks the Rust future `future`, taking ownership of it, into `ret`.
+::rust::repr::Result cxxbridge$kjrs$promise$fork(
+    kj_rs::repr::RustFuture* future, kj_rs::_::ForkHubImpl* ret) noexcept {
+  // Converting to a promise takes ownership of the future, so do it before anything can throw.
+  kj::Promise<void> promise = *future;
+  return ::rust::repr::Result::run([&]() {
+    *ret = kj_rs::_::ForkHubImpl{
+      .hub = new kj_rs::_::ForkHub(kj::mv(promise)),
+      .addBranch = kj_rs::_::ForkHub::addBranch,
+      .drop = kj_rs::_::ForkHub::drop,
+    };
+  });
+}
+}
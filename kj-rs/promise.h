@@ -5,6 +5,9 @@
 #include <kj/async.h>
 #include <kj/debug.h>
 
+#include <type_traits>
+#include <utility>
+
 // These types are shared with rust
 namespace kj_rs {
 
@@ -66,6 +69,43 @@ inline KjPromiseNodeImpl::KjPromiseNodeImpl(kj::Promise<T>&& p)
 
 }  // namespace repr
 
+// An owned `kj::Promise<T>` which Rust can store and await later, the C++ side of
+// `KjOwnedPromise<T>` in bridge declarations. Converts to and from `kj::Promise<T>`.
+template <typename T>
+class Promise {
+ public:
+  Promise(kj::Promise<T>&& promise): impl(kj::mv(promise)) {}
+  Promise(Promise&& other): impl(other.impl) {
+    other.impl.node = nullptr;
+  }
+  Promise& operator=(Promise&& other) {
+    if (this != &other) {
+      dispose();
+      impl = other.impl;
+      other.impl.node = nullptr;
+    }
+    return *this;
+  }
+  KJ_DISALLOW_COPY(Promise);
+  ~Promise() noexcept(false) {
+    dispose();
+  }
+
+  operator kj::Promise<T>() && {
+    return kj::_::PromiseNode::to<kj::Promise<T>>(
+        OwnPromiseNode(std::exchange(impl.node, nullptr)));
+  }
+
+ private:
+  void dispose() {
+    // Disposing a node may throw, so it is released first.
+    OwnPromiseNode(std::exchange(impl.node, nullptr));
+  }
+
+  // `::kj_rs::promise::KjOwnedPromise<T>` has the same layout.
+  repr::KjPromiseNodeImpl impl;
+};
+
 }  // namespace kj_rs
 
 namespace rust {
@@ -74,4 +114,8 @@ namespace rust {
 template <>
 struct IsRelocatable<::kj_rs::OwnPromiseNode>: std::true_type {};
 
+// As does `kj_rs::Promise<T>`, which only holds an `OwnPromiseNode` and a pair of callbacks.
+template <typename T>
+struct IsRelocatable<::kj_rs::Promise<T>>: std::true_type {};
+
 }  // namespace rust
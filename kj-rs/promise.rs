@@ -2,13 +2,16 @@ use cxx::ExternType;
 use cxx::core::mem::MaybeUninit;
 
 use crate::PromiseAwaiter;
+use crate::cancel::{CancellationReason, CancellationScope};
+use crate::repr::FutureOutput;
 
-use std::marker::PhantomData;
-
+use std::cell::OnceCell;
 use std::ffi::c_void;
-use std::future::Future;
+use std::future::{Future, IntoFuture};
+use std::marker::PhantomData;
 use std::mem::ManuallyDrop;
 use std::pin::Pin;
+use std::rc::Rc;
 use std::task::Context;
 use std::task::Poll;
 
@@ -64,13 +67,20 @@ pub trait KjPromise: Sized {
 
 pub struct PromiseFuture<P: KjPromise> {
     awaiter: PromiseAwaiter<P::Data>,
+    #[cfg(feature = "instrumentation")]
+    tracker: crate::instrumentation::Tracker,
     _marker: PhantomData<P>,
 }
 
 impl<P: KjPromise> PromiseFuture<P> {
+    #[cfg_attr(feature = "instrumentation", track_caller)]
     pub fn new(promise: P, data: P::Data) -> Self {
         PromiseFuture {
             awaiter: PromiseAwaiter::new(promise.into_own_promise_node(), data),
+            #[cfg(feature = "instrumentation")]
+            tracker: crate::instrumentation::Tracker::new(
+                crate::instrumentation::FutureKind::PromiseFuture,
+            ),
             _marker: PhantomData,
         }
     }
@@ -81,8 +91,12 @@ impl<P: KjPromise> Future for PromiseFuture<P> {
     fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
         // Safety: `awaiter` is structurally pinned within `PromiseFuture` -- it is never moved
         // after pinning, and `PromiseFuture` has no `Drop` impl that could move it.
-        let mut awaiter = unsafe { self.map_unchecked_mut(|s| &mut s.awaiter) };
-        if awaiter.as_mut().poll(cx) {
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut awaiter = unsafe { Pin::new_unchecked(&mut this.awaiter) };
+        let ready = awaiter.as_mut().poll(cx);
+        #[cfg(feature = "instrumentation")]
+        this.tracker.record_poll(ready);
+        if ready {
             let node = awaiter.as_mut().get_awaiter().take_own_promise_node();
             // Safety: `node` was created by `P::into_own_promise_node()` in `PromiseFuture::new()`,
             // and the promise is resolved (poll returned true).
@@ -103,10 +117,105 @@ pub struct KjPromiseNodeImpl {
     pub unwrap: UnwrapCallback,
 }
 
+/// An owned `kj::Promise<T>` which can be stored and awaited later, `kj_rs::Promise<T>` in C++.
+///
+/// Unlike the future returned by a C++ `async fn`, this type can appear anywhere a value can in
+/// bridge declarations: in shared structs, as an argument of Rust functions, or inside `KjMaybe`.
+/// Awaiting it consumes the promise.
+#[repr(transparent)]
+pub struct KjOwnedPromise<T> {
+    inner: KjPromiseNodeImpl,
+    _marker: PhantomData<T>,
+}
+
+impl<T> IntoFuture for KjOwnedPromise<T> {
+    type Output = CxxResult<T>;
+    type IntoFuture = PromiseFuture<CallbacksFuture<T>>;
+
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    fn into_future(self) -> Self::IntoFuture {
+        let this = ManuallyDrop::new(self);
+        // Safety: `this` is never dropped, so the node is only owned by the returned future.
+        callbacks_promise_future(unsafe { std::ptr::read(&raw const this.inner) })
+    }
+}
+
+impl<T> Drop for KjOwnedPromise<T> {
+    fn drop(&mut self) {
+        // The node is null when C++ moved the promise out.
+        drop(OwnPromiseNode(self.inner.node));
+    }
+}
+
+impl<T> std::fmt::Debug for KjOwnedPromise<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KjOwnedPromise").finish_non_exhaustive()
+    }
+}
+
+/// Extension methods for the futures of C++ promises, such as those returned by C++ `async fn`s.
+pub trait KjPromiseFuture<T>: Future<Output = CxxResult<T>> + Sized {
+    /// Forks the future with `kj::ForkedPromise`, so that several futures can await its result.
+    /// The future runs once, and each clone of the returned [`ForkedFuture`] completes with its
+    /// own clone of the result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if C++ throws while setting up the fork, e.g. on allocation failure.
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    fn fork(self) -> ForkedFuture<T>
+    where
+        Self: 'static,
+        T: Clone + 'static,
+    {
+        let result = Rc::new(OnceCell::new());
+        let slot = result.clone();
+        let mut future = crate::repr::future(Box::pin(async move {
+            // Errors reach the branches through `result`, so the forked promise always succeeds.
+            let _ = slot.set(self.await);
+            Ok(())
+        }));
+        let mut hub = MaybeUninit::<ForkHubImpl>::uninit();
+        // The fork takes ownership of the future.
+        let fork = unsafe { promise_fork((&raw mut future).cast(), hub.as_mut_ptr()) };
+        if let Err(exception) = fork.into_result() {
+            panic!("failed to fork promise: {exception}");
+        }
+        let hub = Rc::new(ForkHub {
+            hub: unsafe { hub.assume_init() },
+            result,
+        });
+        ForkedFuture {
+            branch: hub.add_branch(),
+            hub,
+        }
+    }
+
+    /// Drops the promise because of `reason`. Unlike a plain drop, C++ code destroyed along with
+    /// the promise sees the reason as `kj_rs::CancellationScope::current()`, and so do nested Rust
+    /// futures as [`CancellationReason::current`].
+    fn cancel(self, reason: CancellationReason) {
+        let _scope = CancellationScope::enter(reason);
+        drop(self);
+    }
+}
+
+impl<T, F: Future<Output = CxxResult<T>>> KjPromiseFuture<T> for F {}
+
 #[allow(clippy::needless_pass_by_value)]
+#[cfg_attr(feature = "instrumentation", track_caller)]
 pub fn new_callbacks_promise_future<T>(
     r#impl: KjPromiseNodeImpl,
 ) -> impl Future<Output = CxxResult<T>> {
+    callbacks_promise_future(r#impl)
+}
+
+// Same as `new_callbacks_promise_future()`, but with a nameable type so it can be stored.
+#[allow(clippy::needless_pass_by_value)]
+#[cfg_attr(feature = "instrumentation", track_caller)]
+pub(crate) fn callbacks_promise_future<T>(
+    r#impl: KjPromiseNodeImpl,
+) -> PromiseFuture<CallbacksFuture<T>> {
     PromiseFuture::new(
         CallbacksFuture {
             node: r#impl.node,
@@ -146,3 +255,135 @@ impl<T> KjPromise for CallbacksFuture<T> {
 }
 
 unsafe impl<T: Send> Send for CallbacksFuture<T> {}
+
+unsafe extern "C" {
+    #[link_name = "cxxbridge$kjrs$promise$fork"]
+    fn promise_fork(future: *mut c_void, ret: *mut ForkHubImpl) -> cxx::private::Result;
+}
+
+/// The C++ side of a forked promise, see `kj_rs::_::ForkHub` in promise.c++.
+#[repr(C)]
+pub struct ForkHubImpl {
+    hub: *mut c_void,
+    add_branch: unsafe extern "C" fn(hub: *mut c_void, ret: *mut KjPromiseNodeImpl),
+    drop: unsafe extern "C" fn(hub: *mut c_void),
+}
+
+struct ForkHub<T> {
+    hub: ForkHubImpl,
+    // Set once the forked future completes, before any branch resolves.
+    result: Rc<OnceCell<CxxResult<T>>>,
+}
+
+impl<T> ForkHub<T> {
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    fn add_branch(&self) -> PromiseFuture<CallbacksFuture<()>> {
+        let mut node = MaybeUninit::<KjPromiseNodeImpl>::uninit();
+        unsafe { (self.hub.add_branch)(self.hub.hub, node.as_mut_ptr()) };
+        callbacks_promise_future(unsafe { node.assume_init() })
+    }
+}
+
+impl<T> Drop for ForkHub<T> {
+    fn drop(&mut self) {
+        unsafe { (self.hub.drop)(self.hub.hub) };
+    }
+}
+
+/// A branch of a forked future, created by [`KjPromiseFuture::fork`]. Cloning it adds a branch.
+pub struct ForkedFuture<T> {
+    // Declared before `hub`, which the branch refers to.
+    branch: PromiseFuture<CallbacksFuture<()>>,
+    hub: Rc<ForkHub<T>>,
+}
+
+impl<T> Clone for ForkedFuture<T> {
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    fn clone(&self) -> Self {
+        ForkedFuture {
+            branch: self.hub.add_branch(),
+            hub: self.hub.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Future for ForkedFuture<T> {
+    type Output = CxxResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // Safety: `branch` is structurally pinned, and never moved out of `ForkedFuture`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let branch = unsafe { Pin::new_unchecked(&mut this.branch) };
+        branch.poll(cx).map(|ready| {
+            ready?;
+            let result = this.hub.result.get();
+            let result = result.expect("forked future resolved without a result");
+            result.clone()
+        })
+    }
+}
+
+type AddBranchCallback = unsafe extern "C" fn(future: *const c_void, ret: *mut c_void);
+
+/// A clonable Rust future handed to C++ as a `kj_rs::ForkedPromise<T>`, which works like
+/// `kj::ForkedPromise<T>`: each `addBranch()` returns a `kj::Promise<T>` for a new clone of the
+/// future. `T` only needs to be `Clone` in Rust, not copyable in C++.
+#[repr(C)]
+pub struct KjForkedPromise<T> {
+    future: *mut c_void,
+    add_branch: AddBranchCallback,
+    drop: unsafe extern "C" fn(future: *mut c_void),
+    _marker: PhantomData<T>,
+}
+
+impl<T> KjForkedPromise<T> {
+    /// Wraps a clonable future, such as a [`ForkedFuture`] or a shared future.
+    pub fn new<F>(future: F) -> Self
+    where
+        F: Future<Output = CxxResult<T>> + Clone + 'static,
+        T: FutureOutput<'static>,
+    {
+        KjForkedPromise {
+            future: Box::into_raw(Box::new(future)).cast(),
+            add_branch: forked_promise_add_branch::<F, T>,
+            drop: forked_promise_drop::<F>,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone + FutureOutput<'static> + 'static> From<ForkedFuture<T>> for KjForkedPromise<T> {
+    fn from(future: ForkedFuture<T>) -> Self {
+        Self::new(future)
+    }
+}
+
+impl<T> Drop for KjForkedPromise<T> {
+    fn drop(&mut self) {
+        // The future is null when C++ moved the promise out.
+        if !self.future.is_null() {
+            unsafe { (self.drop)(self.future) };
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for KjForkedPromise<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KjForkedPromise").finish_non_exhaustive()
+    }
+}
+
+unsafe extern "C" fn forked_promise_add_branch<F, T>(future: *const c_void, ret: *mut c_void)
+where
+    F: Future<Output = CxxResult<T>> + Clone + 'static,
+    T: FutureOutput<'static>,
+{
+    let future = unsafe { &*future.cast::<F>() };
+    let branch = crate::repr::future(Box::pin(future.clone()));
+    let ret = ret.cast::<crate::repr::RustFuture<'static, T>>();
+    unsafe { ret.write(branch) };
+}
+
+unsafe extern "C" fn forked_promise_drop<F>(future: *mut c_void) {
+    drop(unsafe { Box::from_raw(future.cast::<F>()) });
+}
@@ -0,0 +1,127 @@
+#pragma once
+
+#include <kj/debug.h>
+#include <kj/one-of.h>
+
+#include <utility>
+
+namespace kj_rs {
+
+// The C++ side of a Rust `Result<T, E>` returned by an `extern "Rust"` function whose error type
+// `E` is a shared struct or enum.
+//
+// Such errors are part of the function's signature, so instead of being thrown as a
+// `kj::Exception` they are returned as a value for the caller to inspect. Panics and errors of
+// functions returning `Result<T>` are still thrown as usual.
+template <typename T, typename E>
+class Result {
+ public:
+  static Result ok(T&& value) {
+    return Result(Ok{kj::mv(value)});
+  }
+  static Result err(E&& error) {
+    return Result(Err{kj::mv(error)});
+  }
+
+  bool isOk() const {
+    return inner.template is<Ok>();
+  }
+  bool isErr() const {
+    return inner.template is<Err>();
+  }
+
+  // The success value. Requires `isOk()`.
+  T& get() & {
+    KJ_REQUIRE(isOk(), "called get() on an error Result");
+    return inner.template get<Ok>().value;
+  }
+  const T& get() const& {
+    KJ_REQUIRE(isOk(), "called get() on an error Result");
+    return inner.template get<Ok>().value;
+  }
+  T get() && {
+    KJ_REQUIRE(isOk(), "called get() on an error Result");
+    return kj::mv(inner.template get<Ok>().value);
+  }
+
+  // The error value. Requires `isErr()`.
+  E& getError() & {
+    KJ_REQUIRE(isErr(), "called getError() on a successful Result");
+    return inner.template get<Err>().value;
+  }
+  const E& getError() const& {
+    KJ_REQUIRE(isErr(), "called getError() on a successful Result");
+    return inner.template get<Err>().value;
+  }
+  E getError() && {
+    KJ_REQUIRE(isErr(), "called getError() on a successful Result");
+    return kj::mv(inner.template get<Err>().value);
+  }
+
+  kj::Maybe<T&> tryGet() {
+    KJ_IF_SOME(ok, inner.template tryGet<Ok>()) {
+      return ok.value;
+    }
+    return kj::none;
+  }
+  kj::Maybe<E&> tryGetError() {
+    KJ_IF_SOME(err, inner.template tryGet<Err>()) {
+      return err.value;
+    }
+    return kj::none;
+  }
+
+ private:
+  // Wrappers keep the two alternatives apart even when `T` and `E` are the same type.
+  struct Ok {
+    T value;
+  };
+  struct Err {
+    E value;
+  };
+
+  template <typename V>
+  explicit Result(V&& v): inner(kj::fwd<V>(v)) {}
+
+  kj::OneOf<Ok, Err> inner;
+};
+
+template <typename E>
+class Result<void, E> {
+ public:
+  static Result ok() {
+    return Result(kj::none);
+  }
+  static Result err(E&& error) {
+    return Result(kj::mv(error));
+  }
+
+  bool isOk() const {
+    return error == kj::none;
+  }
+  bool isErr() const {
+    return error != kj::none;
+  }
+
+  // The error value. Requires `isErr()`.
+  E& getError() & {
+    return KJ_REQUIRE_NONNULL(error, "called getError() on a successful Result");
+  }
+  const E& getError() const& {
+    return KJ_REQUIRE_NONNULL(error, "called getError() on a successful Result");
+  }
+  E getError() && {
+    return kj::mv(KJ_REQUIRE_NONNULL(error, "called getError() on a successful Result"));
+  }
+
+  kj::Maybe<E&> tryGetError() {
+    return error;
+  }
+
+ private:
+  explicit Result(kj::Maybe<E> error): error(kj::mv(error)) {}
+
+  kj::Maybe<E> error;
+};
+
+}  // namespace kj_rs
@@ -0,0 +1,275 @@
+#pragma once
+
+#include "kj-rs/future.h"
+#include "kj-rs/promise.h"
+
+#include <kj/async.h>
+#include <kj/debug.h>
+
+#include <concepts>
+#include <cstdint>
+
+namespace kj_rs {
+
+// Returned from `RustStream::poll`, indicating the state of its output parameter.
+enum class StreamPollStatus : uint8_t {
+  // Nothing was written to the output parameter.
+  Pending,
+  // An item was written to the output parameter.
+  Item,
+  // The stream is exhausted, nothing was written to the output parameter.
+  End,
+  // An error was written to the output parameter.
+  Error,
+};
+
+// A class with space for a `T` or a `kj::Exception*`, whichever is larger.
+template <typename T>
+class StreamPoller {
+ public:
+  StreamPoller() {}
+  ~StreamPoller() noexcept(false) {}
+
+  // Call `pollFunc()` with a pointer to space to which a `T` (next item) or a `kj::Exception*`
+  // (error) may be written, then propagate the item, the end of the stream or the error to
+  // `output` depending on the return value of `pollFunc()`.
+  template <typename F>
+  void poll(F&& pollFunc, kj::_::ExceptionOr<kj::Maybe<T>>& output) {
+    switch (pollFunc(&item)) {
+      case ::kj_rs::StreamPollStatus::Pending:
+        return;
+      case ::kj_rs::StreamPollStatus::Item: {
+        output.value = kj::Maybe<T>(kj::mv(item));
+        kj::dtor(item);
+        return;
+      }
+      case ::kj_rs::StreamPollStatus::End: {
+        output.value = kj::Maybe<T>(kj::none);
+        return;
+      }
+      case ::kj_rs::StreamPollStatus::Error: {
+        output.addException(kj::mv(*error));
+        delete error;
+        return;
+      }
+    }
+
+    KJ_UNREACHABLE;
+  }
+
+ private:
+  union {
+    T item;
+    kj::Exception* error;
+  };
+};
+
+// A pull-based asynchronous sequence of `T`, the C++ side of `KjStream<T>` in bridge declarations.
+//
+// Items are requested one at a time with `next()`. The stream must outlive the promise returned
+// by `next()`, and only one `next()` call may be outstanding at a time.
+template <typename T>
+class Stream {
+ public:
+  class Impl {
+   public:
+    virtual ~Impl() noexcept(false) = default;
+
+    // Resolves to the next item, or to `kj::none` once the stream is exhausted.
+    virtual kj::Promise<kj::Maybe<T>> next() = 0;
+  };
+
+  // Accepts any object with a `kj::Promise<kj::Maybe<T>> next()` method, such as a generator
+  // wrapper. It does not need to derive from `Impl`.
+  template <typename S>
+  Stream(kj::Own<S> source) {
+    if constexpr (std::derived_from<S, Impl>) {
+      impl = kj::mv(source);
+    } else {
+      impl = kj::heap<OwnImpl<S>>(kj::mv(source));
+    }
+  }
+
+  Stream(Stream&&) = default;
+  Stream& operator=(Stream&&) = default;
+  KJ_DISALLOW_COPY(Stream);
+
+  // Resolves to the next item, or to `kj::none` once the stream is exhausted.
+  kj::Promise<kj::Maybe<T>> next() {
+    return impl->next();
+  }
+
+ private:
+  template <typename S>
+  class OwnImpl final: public Impl {
+   public:
+    OwnImpl(kj::Own<S> source): source(kj::mv(source)) {}
+
+    kj::Promise<kj::Maybe<T>> next() override {
+      return source->next();
+    }
+
+   private:
+    kj::Own<S> source;
+  };
+
+  kj::Own<Impl> impl;
+};
+
+namespace _ {
+
+// The value of the promise handed to Rust by `KjStreamImpl`. Unlike `kj::Maybe<T>`, its layout
+// is the same for every `T`, so Rust can read it as `StreamNext<T>`.
+template <typename T>
+struct StreamNext {
+  StreamNext(kj::Maybe<T>&& maybe) {
+    KJ_IF_SOME(v, maybe) {
+      hasValue = true;
+      kj::ctor(value, kj::mv(v));
+    }
+  }
+  StreamNext(StreamNext&& other): hasValue(other.hasValue) {
+    if (hasValue) {
+      kj::ctor(value, kj::mv(other.value));
+    }
+  }
+  ~StreamNext() noexcept(false) {
+    if (hasValue) {
+      kj::dtor(value);
+    }
+  }
+  KJ_DISALLOW_COPY(StreamNext);
+
+  bool hasValue = false;
+  union {
+    T value;
+  };
+};
+
+}  // namespace _
+
+// These types are shared with Rust code.
+namespace repr {
+
+#pragma GCC diagnostic push
+#pragma GCC diagnostic ignored "-Wreturn-type-c-linkage"
+
+// ::kj_rs::repr::PollNextCallback
+using StreamPollCallback = kj_rs::StreamPollStatus (*)(
+    void /* RustStream::stream */* stream, const void* waker, void /* T */* ret);
+
+// ::kj_rs::repr::NextCallback
+using StreamNextCallback = Result (*)(void /* Stream<T> */* stream, KjPromiseNodeImpl* ret);
+
+// ::kj_rs::repr::DropCallback
+using StreamDropCallback = void (*)(void* stream);
+
+#pragma GCC diagnostic pop
+
+// ::kj_rs::repr::RustStream
+//
+// Cancellation: dropping a promise returned by `next()` leaves the Rust stream in place, so the
+// following `next()` call continues polling it. Destroying the `Stream<T>` drops the Rust stream.
+//
+// Context: like `RustFuture`, the stream is polled in the `kj_rs::AsyncContext` current when it
+// was converted.
+struct RustStream {
+  template <typename T>
+  operator Stream<T>() {
+    class Impl final: public Stream<T>::Impl {
+     public:
+      Impl(RustStream stream): stream(stream), context(::kj_rs::AsyncContext::capture()) {}
+      ~Impl() noexcept(false) {
+        ::kj_rs::AsyncContext::Scope scope(context);
+        stream.drop(&stream);
+      }
+      KJ_DISALLOW_COPY_AND_MOVE(Impl);
+
+      kj::Promise<kj::Maybe<T>> next() override {
+        if (done) {
+          // Rust streams may misbehave if polled again after they have ended.
+          return kj::Maybe<T>(kj::none);
+        }
+        return kj::_::PromiseNode::to<kj::Promise<kj::Maybe<T>>>(
+            kj::_::allocPromise<FutureAwaiter<Next>>(Next(*this)));
+      }
+
+     private:
+      struct Next {
+        using Output = kj::Maybe<T>;
+        using ExceptionOrValue = kj::_::ExceptionOr<Output>;
+
+        Next(Impl& impl): impl(impl) {}
+
+        void poll(const ::kj_rs::KjWaker& waker, ExceptionOrValue& output) noexcept {
+          ::kj_rs::AsyncContext::Scope scope(impl.context);
+          ::kj_rs::StreamPoller<T> poller;
+          poller.poll([this, &waker](void* result) {
+            return impl.stream.poll(&impl.stream, &waker, result);
+          }, output);
+          KJ_IF_SOME(value, output.value) {
+            impl.done = value == kj::none;
+          }
+        }
+
+        Impl& impl;
+      };
+
+      RustStream stream;
+      kj::Maybe<kj::Own<::kj_rs::AsyncContext>> context;
+      bool done = false;
+    };
+
+    return Stream<T>(kj::heap<Impl>(*this));
+  }
+
+  ::std::array<std::uintptr_t, 2> repr;
+  StreamPollCallback poll;
+  DropCallback drop;
+};
+
+static_assert(sizeof(RustStream) == 4 * sizeof(std::uintptr_t), "incorrect RustStream layout");
+
+// ::kj_rs::repr::KjStreamImpl
+struct KjStreamImpl {
+  template <typename T>
+  inline KjStreamImpl(Stream<T>&& stream);
+
+  void* stream;
+  StreamNextCallback next;
+  StreamDropCallback drop;
+};
+
+static_assert(sizeof(KjStreamImpl) == 3 * sizeof(void*), "incorrect KjStreamImpl layout");
+
+}  // namespace repr
+
+namespace _ {
+
+template <typename T>
+repr::Result streamNextCallback(void* stream, repr::KjPromiseNodeImpl* ret) noexcept {
+  return repr::Result::run([stream, ret]() {
+    auto promise = reinterpret_cast<Stream<T>*>(stream)->next().then(
+        [](kj::Maybe<T> maybe) { return StreamNext<T>(kj::mv(maybe)); });
+    new (ret) repr::KjPromiseNodeImpl(kj::mv(promise));
+  });
+}
+
+template <typename T>
+void streamDropCallback(void* stream) noexcept {
+  delete reinterpret_cast<Stream<T>*>(stream);
+}
+
+}  // namespace _
+
+namespace repr {
+
+template <typename T>
+inline KjStreamImpl::KjStreamImpl(Stream<T>&& stream)
+    : stream(new Stream<T>(kj::mv(stream))),
+      next(::kj_rs::_::streamNextCallback<T>),
+      drop(::kj_rs::_::streamDropCallback<T>) {}
+
+}  // namespace repr
+
+}  // namespace kj_rs
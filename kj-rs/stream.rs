@@ -0,0 +1,263 @@
+//! Bindings between Rust `Stream`s and `kj_rs::Stream<T>`, a pull-based asynchronous sequence whose
+//! `next()` returns a `kj::Promise<kj::Maybe<T>>`.
+//!
+//! A Rust function returning `KjStream<T>` hands C++ a stream it polls one item at a time, while a
+//! C++ function returning `KjStream<T>` gives Rust a [`KjStream`] that implements `Stream`.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use cxx::IntoKjException;
+use futures_core::Stream;
+
+// NOTE: StreamPollStatus must be kept in sync with the C++ enum of the same name in stream.h
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct StreamPollStatus {
+    pub repr: u8,
+}
+
+#[allow(non_upper_case_globals)]
+impl StreamPollStatus {
+    pub const Pending: Self = Self { repr: 0 };
+    pub const Item: Self = Self { repr: 1 };
+    pub const End: Self = Self { repr: 2 };
+    pub const Error: Self = Self { repr: 3 };
+}
+
+// These types are shared with C++ code.
+pub mod repr {
+    use std::ffi::c_void;
+    use std::future::Future;
+    use std::mem::MaybeUninit;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+
+    use futures_core::Stream;
+    use static_assertions::{assert_eq_align, assert_eq_size};
+
+    use super::StreamPollStatus;
+    use crate::KjPromiseNodeImpl;
+    use crate::KjWaker;
+    use crate::promise::{CallbacksFuture, PromiseFuture, callbacks_promise_future};
+
+    assert_eq_size!(RustStream<()>, [*mut c_void; 4]);
+    assert_eq_size!(KjStreamImpl, [*mut c_void; 3]);
+    assert_eq_align!(KjStreamImpl, *mut c_void);
+
+    type PollNextCallback = unsafe extern "C" fn(
+        stream: *mut c_void,
+        waker: *const c_void,
+        ret: *mut c_void,
+    ) -> StreamPollStatus;
+
+    type DropCallback = unsafe extern "C" fn(stream: *mut c_void);
+
+    type StreamPtr<'a, T> = *mut (dyn Stream<Item = Result<T, cxx::KjException>> + 'a);
+
+    /// Represents a `dyn Stream<Item = Result<T, cxx::KjException>>`.
+    #[repr(C)]
+    pub struct RustStream<'a, T> {
+        pub stream: StreamPtr<'a, T>,
+        pub poll_next: PollNextCallback,
+        pub drop: DropCallback,
+    }
+
+    impl<T> RustStream<'_, T> {
+        unsafe extern "C" fn poll_next(
+            stream: *mut c_void,
+            waker: *const c_void,
+            ret: *mut c_void,
+        ) -> StreamPollStatus {
+            let stream = unsafe { *(stream.cast::<StreamPtr<T>>()) };
+            let stream = unsafe { Pin::new_unchecked(&mut *stream) };
+            let waker = unsafe { &*waker.cast::<KjWaker>() };
+            let waker = Waker::from(waker);
+            let mut context = Context::from_waker(&waker);
+            match stream.poll_next(&mut context) {
+                Poll::Ready(Some(Ok(value))) => {
+                    unsafe { std::ptr::write(ret.cast::<T>(), value) };
+                    StreamPollStatus::Item
+                }
+                Poll::Ready(Some(Err(error))) => {
+                    unsafe {
+                        std::ptr::write(
+                            ret.cast::<*mut c_void>(),
+                            error.into_raw().as_ptr().cast(),
+                        );
+                    };
+                    StreamPollStatus::Error
+                }
+                Poll::Ready(None) => StreamPollStatus::End,
+                Poll::Pending => StreamPollStatus::Pending,
+            }
+        }
+
+        unsafe extern "C" fn drop_in_place(stream: *mut c_void) {
+            let stream = unsafe { *(stream.cast::<StreamPtr<T>>()) };
+            let stream = unsafe { Box::from_raw(stream) };
+            let stream = unsafe { Pin::new_unchecked(stream) };
+            drop(stream);
+        }
+    }
+
+    #[must_use]
+    pub fn stream<'a, T>(
+        stream: Pin<Box<dyn Stream<Item = Result<T, cxx::KjException>> + 'a>>,
+    ) -> RustStream<'a, T> {
+        let stream = Box::into_raw(unsafe { Pin::into_inner_unchecked(stream) });
+        let poll_next = RustStream::<T>::poll_next;
+        let drop = RustStream::<T>::drop_in_place;
+        RustStream {
+            stream,
+            poll_next,
+            drop,
+        }
+    }
+
+    type NextCallback =
+        unsafe extern "C" fn(stream: *mut c_void, ret: *mut c_void) -> cxx::private::Result;
+
+    /// Type-erased `kj_rs::Stream<T>`, returned by C++ functions.
+    #[repr(C)]
+    pub struct KjStreamImpl {
+        pub stream: *mut c_void,
+        pub next: NextCallback,
+        pub drop: DropCallback,
+    }
+
+    /// Mirrors `kj_rs::_::StreamNext<T>`, the value of the promise returned by `next()`.
+    #[repr(C)]
+    struct StreamNext<T> {
+        has_value: bool,
+        value: MaybeUninit<T>,
+    }
+
+    impl<T> StreamNext<T> {
+        fn into_option(self) -> Option<T> {
+            if self.has_value {
+                Some(unsafe { self.value.assume_init() })
+            } else {
+                None
+            }
+        }
+    }
+
+    type NextFuture<T> = Pin<Box<PromiseFuture<CallbacksFuture<StreamNext<T>>>>>;
+
+    /// Bindings to `kj_rs::Stream<T>`. Yields the stream's items until `next()` resolves to
+    /// `kj::none`.
+    pub struct KjStream<T> {
+        stream: *mut c_void,
+        next: NextCallback,
+        drop: DropCallback,
+        // The promise returned by the outstanding `next()` call, if any.
+        pending: Option<NextFuture<T>>,
+        done: bool,
+    }
+
+    impl<T> From<KjStreamImpl> for KjStream<T> {
+        fn from(stream: KjStreamImpl) -> Self {
+            Self {
+                stream: stream.stream,
+                next: stream.next,
+                drop: stream.drop,
+                pending: None,
+                done: false,
+            }
+        }
+    }
+
+    impl<T> Stream for KjStream<T> {
+        type Item = Result<T, cxx::KjException>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            if this.pending.is_none() {
+                let mut node = MaybeUninit::<KjPromiseNodeImpl>::uninit();
+                let result = unsafe { (this.next)(this.stream, node.as_mut_ptr().cast()) };
+                if let Err(error) = result.into_result() {
+                    return Poll::Ready(Some(Err(error)));
+                }
+                let node = unsafe { node.assume_init() };
+                this.pending = Some(Box::pin(callbacks_promise_future(node)));
+            }
+
+            let pending = this
+                .pending
+                .as_mut()
+                .expect("next() promise was just created");
+            let Poll::Ready(result) = pending.as_mut().poll(cx) else {
+                return Poll::Pending;
+            };
+            this.pending = None;
+            match result.map(StreamNext::into_option) {
+                Ok(Some(value)) => Poll::Ready(Some(Ok(value))),
+                Ok(None) => {
+                    this.done = true;
+                    Poll::Ready(None)
+                }
+                Err(error) => Poll::Ready(Some(Err(error))),
+            }
+        }
+    }
+
+    impl<T> Drop for KjStream<T> {
+        fn drop(&mut self) {
+            // The outstanding promise may refer to the stream, so it must go first.
+            self.pending = None;
+            unsafe { (self.drop)(self.stream) };
+        }
+    }
+}
+
+// A stream that converts item errors into `cxx::KjException`
+struct MapStreamErr<S> {
+    stream: S,
+    file: &'static str,
+    line: u32,
+}
+
+impl<S, T, E> Stream for MapStreamErr<S>
+where
+    S: Stream<Item = Result<T, E>>,
+    E: IntoKjException,
+{
+    type Item = Result<T, cxx::KjException>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let file = self.file;
+        let line = self.line;
+
+        // Safety: self is pinned, so stream is pinned.
+        let inner: Pin<&mut S> = unsafe {
+            let this = self.get_unchecked_mut();
+            Pin::new_unchecked(&mut this.stream)
+        };
+        match inner.poll_next(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Ready(Some(Ok(value))) => Poll::Ready(Some(Ok(value))),
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(
+                ::cxx::IntoKjException::into_kj_exception(e, file, line),
+            ))),
+        }
+    }
+}
+
+/// Convert a `Stream` using any `IntoKjException` error into `cxx::KjException` one.
+pub fn map_stream_err<S, T, E>(
+    stream: S,
+    file: &'static str,
+    line: u32,
+) -> impl Stream<Item = Result<T, cxx::KjException>>
+where
+    S: Stream<Item = Result<T, E>>,
+    E: IntoKjException,
+{
+    MapStreamErr { stream, file, line }
+}
@@ -0,0 +1,14 @@
+#include "string.h"
+
+#include <new>
+
+extern "C" {
+
+void cxxbridge$kjrs$string$new(const char* ptr, size_t len, void* out) {
+  ::new (out) kj::String(kj::heapString(ptr, len));
+}
+
+void cxxbridge$kjrs$string$drop(void* string) {
+  reinterpret_cast<kj::String*>(string)->~String();
+}
+}
@@ -0,0 +1,18 @@
+#pragma once
+
+#include <kj/string.h>
+
+#include <cstddef>
+
+extern "C" {
+
+// The `string` inputs point to Rust `kj_rs::repr::KjString` values, which mirror `kj::String` as
+// its underlying `kj::Array<char>`: data pointer, size including the NUL terminator, disposer.
+
+// Constructs a `kj::String` holding a copy of `len` bytes at `ptr` into `out`. `out` points to
+// uninitialized storage with the same layout as `kj::String`.
+void cxxbridge$kjrs$string$new(const char* ptr, size_t len, void* out);
+
+// Destroys the input `kj::String` in place, releasing its array through its disposer.
+void cxxbridge$kjrs$string$drop(void* string);
+}
@@ -0,0 +1,251 @@
+//! Bindings to `kj::String` and `kj::StringPtr`.
+//!
+//! Both types are byte strings: kj does not enforce UTF-8, so the contents are exposed as `[u8]`
+//! and [`KjString::to_str`] / [`KjStringPtr::to_str`] validate on demand.
+
+use static_assertions::{assert_eq_align, assert_eq_size};
+
+assert_eq_size!(repr::KjString, [*const (); 3]);
+assert_eq_align!(repr::KjString, *const ());
+assert_eq_size!(repr::KjStringPtr<'static>, [*const (); 2]);
+assert_eq_align!(repr::KjStringPtr<'static>, *const ());
+
+/// Both `kj::String` and `kj::StringPtr` store the NUL terminator as part of their array, an empty
+/// `kj::String` additionally has no array at all.
+///
+/// Safety: `ptr` must point to at least `size` readable bytes living for `'a` when `size > 0`.
+unsafe fn bytes_without_nul<'a>(ptr: *const u8, size: usize) -> &'a [u8] {
+    if size <= 1 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(ptr, size - 1) }
+    }
+}
+
+pub mod repr {
+    use super::bytes_without_nul;
+    use std::ffi::c_void;
+    use std::fmt::{self, Debug};
+    use std::hash::{Hash, Hasher};
+    use std::marker::PhantomData;
+    use std::ops::Deref;
+    use std::str::Utf8Error;
+
+    /// Bindings to the kj type `kj::String`. An owned, heap allocated, NUL-terminated byte string,
+    /// freed by C++.
+    #[repr(C)]
+    pub struct KjString {
+        ptr: *const u8,
+        size: usize,
+        disposer: *const c_void,
+    }
+
+    /// Bindings to the kj type `kj::StringPtr`. A borrowed, NUL-terminated byte string.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct KjStringPtr<'a> {
+        ptr: *const u8,
+        size: usize,
+        _marker: PhantomData<&'a [u8]>,
+    }
+
+    unsafe impl Send for KjString {}
+    unsafe impl Sync for KjString {}
+    unsafe impl Send for KjStringPtr<'_> {}
+    unsafe impl Sync for KjStringPtr<'_> {}
+
+    impl KjString {
+        /// Returns the contents without the NUL terminator.
+        #[must_use]
+        pub fn as_bytes(&self) -> &[u8] {
+            unsafe { bytes_without_nul(self.ptr, self.size) }
+        }
+
+        /// Returns the contents as a `&str` if they are valid UTF-8.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`Utf8Error`] if the contents are not valid UTF-8.
+        pub fn to_str(&self) -> Result<&str, Utf8Error> {
+            std::str::from_utf8(self.as_bytes())
+        }
+
+        /// Borrows this string as a `kj::StringPtr`.
+        #[must_use]
+        pub fn as_string_ptr(&self) -> KjStringPtr<'_> {
+            KjStringPtr::from(self)
+        }
+    }
+
+    impl<'a> KjStringPtr<'a> {
+        /// Returns the contents without the NUL terminator.
+        #[must_use]
+        pub fn as_bytes(&self) -> &'a [u8] {
+            unsafe { bytes_without_nul(self.ptr, self.size) }
+        }
+
+        /// Returns the contents as a `&str` if they are valid UTF-8.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`Utf8Error`] if the contents are not valid UTF-8.
+        pub fn to_str(&self) -> Result<&'a str, Utf8Error> {
+            std::str::from_utf8(self.as_bytes())
+        }
+    }
+
+    impl Default for KjString {
+        /// Returns the empty string, which like `kj::String()` owns no allocation.
+        fn default() -> Self {
+            Self {
+                ptr: std::ptr::null(),
+                size: 0,
+                disposer: std::ptr::null(),
+            }
+        }
+    }
+
+    impl From<&[u8]> for KjString {
+        /// Copies `bytes` into a new `kj::String`, allocated by C++.
+        fn from(bytes: &[u8]) -> Self {
+            unsafe extern "C" {
+                #[link_name = "cxxbridge$kjrs$string$new"]
+                fn __new(ptr: *const u8, len: usize, out: *mut c_void);
+            }
+
+            let mut ret = std::mem::MaybeUninit::<Self>::uninit();
+            unsafe {
+                __new(
+                    bytes.as_ptr(),
+                    bytes.len(),
+                    ret.as_mut_ptr().cast::<c_void>(),
+                );
+                ret.assume_init()
+            }
+        }
+    }
+
+    impl From<&str> for KjString {
+        fn from(s: &str) -> Self {
+            Self::from(s.as_bytes())
+        }
+    }
+
+    impl<'a> From<&'a KjString> for KjStringPtr<'a> {
+        fn from(s: &'a KjString) -> Self {
+            if s.size == 0 {
+                // `kj::String()` has no array, but a `kj::StringPtr` always points to a terminator.
+                Self {
+                    ptr: c"".as_ptr().cast(),
+                    size: 1,
+                    _marker: PhantomData,
+                }
+            } else {
+                Self {
+                    ptr: s.ptr,
+                    size: s.size,
+                    _marker: PhantomData,
+                }
+            }
+        }
+    }
+
+    impl Clone for KjString {
+        fn clone(&self) -> Self {
+            Self::from(self.as_bytes())
+        }
+    }
+
+    impl Deref for KjString {
+        type Target = [u8];
+
+        fn deref(&self) -> &Self::Target {
+            self.as_bytes()
+        }
+    }
+
+    impl Deref for KjStringPtr<'_> {
+        type Target = [u8];
+
+        fn deref(&self) -> &Self::Target {
+            self.as_bytes()
+        }
+    }
+
+    impl AsRef<[u8]> for KjString {
+        fn as_ref(&self) -> &[u8] {
+            self.as_bytes()
+        }
+    }
+
+    impl AsRef<[u8]> for KjStringPtr<'_> {
+        fn as_ref(&self) -> &[u8] {
+            self.as_bytes()
+        }
+    }
+
+    impl Debug for KjString {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Debug::fmt(&String::from_utf8_lossy(self.as_bytes()), f)
+        }
+    }
+
+    impl Debug for KjStringPtr<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Debug::fmt(&String::from_utf8_lossy(self.as_bytes()), f)
+        }
+    }
+
+    impl PartialEq for KjString {
+        fn eq(&self, other: &Self) -> bool {
+            self.as_bytes() == other.as_bytes()
+        }
+    }
+
+    impl Eq for KjString {}
+
+    impl PartialEq for KjStringPtr<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            self.as_bytes() == other.as_bytes()
+        }
+    }
+
+    impl Eq for KjStringPtr<'_> {}
+
+    impl PartialEq<str> for KjString {
+        fn eq(&self, other: &str) -> bool {
+            self.as_bytes() == other.as_bytes()
+        }
+    }
+
+    impl PartialEq<str> for KjStringPtr<'_> {
+        fn eq(&self, other: &str) -> bool {
+            self.as_bytes() == other.as_bytes()
+        }
+    }
+
+    impl Hash for KjString {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.as_bytes().hash(state);
+        }
+    }
+
+    impl Hash for KjStringPtr<'_> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.as_bytes().hash(state);
+        }
+    }
+
+    impl Drop for KjString {
+        fn drop(&mut self) {
+            unsafe extern "C" {
+                #[link_name = "cxxbridge$kjrs$string$drop"]
+                fn __drop(this: *mut c_void);
+            }
+
+            unsafe {
+                __drop(std::ptr::from_mut(self).cast::<c_void>());
+            }
+        }
+    }
+}
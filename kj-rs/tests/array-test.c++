@@ -0,0 +1,38 @@
+#include "test-array.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("C++ receives kj::Array from Rust") {
+  auto a = r_return_kj_array();
+  KJ_EXPECT(a.asPtr() == kj::ArrayPtr<const int32_t>({1, 2, 3}));
+
+  auto strings = r_return_kj_string_array();
+  KJ_EXPECT(strings.size() == 2);
+  KJ_EXPECT(strings[0] == "a");
+  KJ_EXPECT(strings[1] == "b");
+}
+
+KJ_TEST("C++ sends kj::ArrayPtr to Rust") {
+  int32_t elements[] = {1, 2, 3, 4};
+  KJ_EXPECT(r_sum_kj_array_ptr(kj::arrayPtr(elements)) == 10);
+  KJ_EXPECT(r_sum_kj_array_ptr(nullptr) == 0);
+}
+
+KJ_TEST("kj::Array round-trips through Rust") {
+  auto original = kj::arr<int32_t>(7, 8, 9);
+  const int32_t* data = original.begin();
+  auto result = r_roundtrip_kj_array(kj::mv(original));
+  KJ_EXPECT(result.asPtr() == kj::ArrayPtr<const int32_t>({7, 8, 9}));
+  // Ownership moves through Rust without copying.
+  KJ_EXPECT(result.begin() == data);
+
+  // Rust-allocated arrays go back to Rust when disposed by C++.
+  auto fromRust = r_roundtrip_kj_array(r_return_kj_array());
+  KJ_EXPECT(fromRust.size() == 3);
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
@@ -0,0 +1,43 @@
+#include "test-async-methods.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("C++ can await async methods of a Rust type") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto counter = new_rust_counter(1);
+  // The caller keeps `counter` alive until the promises complete.
+  auto promise = counter->add_later(2);
+  KJ_EXPECT(!promise.poll(waitScope));
+  KJ_EXPECT(promise.wait(waitScope) == 3);
+  KJ_EXPECT(counter->add_later(4).wait(waitScope) == 7);
+}
+
+KJ_TEST("Async methods may borrow a 'static Rust receiver") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  const RustCounter& counter = leak_rust_counter(42);
+  KJ_EXPECT(counter.get_later().wait(waitScope) == 42);
+}
+
+KJ_TEST("Rust can await async methods of a C++ type") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  KJ_EXPECT(increment_and_add_cxx_counter(10).wait(waitScope) == 11);
+}
+
+KJ_TEST("Promises of methods taking a KjRc or KjArc receiver keep it alive") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  KJ_EXPECT(add_to_refcounted_cxx_counters(10).wait(waitScope) == 23);
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
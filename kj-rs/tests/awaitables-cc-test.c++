@@ -291,5 +291,30 @@ KJ_TEST("Cancellation: Rust dropping never-polled KJ promise future") {
   KJ_EXPECT(get_cancellation_counter() == 1);
 }
 
+KJ_TEST("Rust futures returning a shared struct can be forked") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj::ForkedPromise<Shared> forked = new_ready_future_shared_type().fork();
+  auto first = forked.addBranch();
+  auto second = forked.addBranch();
+  KJ_EXPECT(first.wait(waitScope).i == 42);
+  KJ_EXPECT(second.wait(waitScope).i == 42);
+}
+
+KJ_TEST("Pending Rust futures can be forked") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj::ForkedPromise<int32_t> forked = new_fulfillable_future_i32().fork();
+  auto first = forked.addBranch();
+  auto second = forked.addBranch();
+  KJ_EXPECT(!first.poll(waitScope));
+  fulfill_stored_promise();
+  KJ_EXPECT(first.wait(waitScope) == 42);
+  KJ_EXPECT(second.wait(waitScope) == 42);
+  KJ_EXPECT(forked.addBranch().wait(waitScope) == 42);
+}
+
 }  // namespace
 }  // namespace kj_rs_demo
\ No newline at end of file
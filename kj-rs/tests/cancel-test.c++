@@ -0,0 +1,124 @@
+#include "test-cancel.h"
+
+#include <kj/async.h>
+#include <kj/test.h>
+
+#include <string>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("C++ can cancel a Rust future with a reason") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto counter = get_cleanup_counter();
+  kj_rs::CancellationToken token;
+  auto promise = cleanup_on_cancellation(kj::heap<kj_rs::CancellationToken>(token));
+  KJ_EXPECT(!promise.poll(waitScope));
+
+  token.cancel(KJ_EXCEPTION(DISCONNECTED, "client went away"));
+  KJ_EXPECT(token.isCanceled());
+
+  // The future cleans up asynchronously, then rejects with the reason.
+  auto exception = KJ_ASSERT_NONNULL(kj::runCatchingExceptions([&]() { promise.wait(waitScope); }));
+  KJ_EXPECT(exception.getType() == kj::Exception::Type::DISCONNECTED);
+  KJ_EXPECT(exception.getDescription() == "client went away");
+  KJ_EXPECT(get_cleanup_counter() == counter + 1);
+}
+
+KJ_TEST("Rust futures observe cancellation that happened before they started") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj_rs::CancellationToken token;
+  token.cancel("canceled early"_kj);
+  // Only the first reason is kept.
+  token.cancel("canceled again"_kj);
+
+  KJ_EXPECT_THROW_MESSAGE("canceled early",
+      pending_or_canceled(kj::heap<kj_rs::CancellationToken>(token)).wait(waitScope));
+}
+
+KJ_TEST("Canceling drops the guarded Rust future") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj_rs::CancellationToken token;
+  auto promise = pending_or_canceled(kj::heap<kj_rs::CancellationToken>(token));
+  KJ_EXPECT(!promise.poll(waitScope));
+
+  token.cancel("stop waiting"_kj);
+  KJ_EXPECT_THROW_MESSAGE("stop waiting", promise.wait(waitScope));
+}
+
+KJ_TEST("Destroying the promise still drops the Rust future without cancellation") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto counter = get_cleanup_counter();
+  kj_rs::CancellationToken token;
+  {
+    auto promise = cleanup_on_cancellation(kj::heap<kj_rs::CancellationToken>(token));
+    KJ_EXPECT(!promise.poll(waitScope));
+  }
+  // The dropped future no longer observes the token.
+  token.cancel("too late"_kj);
+  KJ_EXPECT(get_cleanup_counter() == counter);
+}
+
+KJ_TEST("Rust futures see the reason of the CancellationScope they are dropped in") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto promise = pending_recording_reason();
+  KJ_EXPECT(!promise.poll(waitScope));
+  {
+    kj_rs::CancellationScope scope(KJ_EXCEPTION(OVERLOADED, "shedding load"));
+    KJ_EXPECT(KJ_ASSERT_NONNULL(kj_rs::CancellationScope::current()).getDescription() ==
+        "shedding load");
+    promise = nullptr;
+  }
+  KJ_EXPECT(std::string(take_recorded_reason()) == "shedding load");
+  KJ_EXPECT(kj_rs::CancellationScope::current() == kj::none);
+
+  // A plain destruction has no reason.
+  promise = pending_recording_reason();
+  KJ_EXPECT(!promise.poll(waitScope));
+  promise = nullptr;
+  KJ_EXPECT(std::string(take_recorded_reason()).empty());
+}
+
+KJ_TEST("A kj::Canceler reason rejects the promise and reaches the dropped Rust future") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj::Canceler canceler;
+  auto promise = canceler.wrap(pending_recording_reason());
+  KJ_EXPECT(!promise.poll(waitScope));
+
+  auto reason = KJ_EXCEPTION(DISCONNECTED, "client went away");
+  {
+    kj_rs::CancellationScope scope(kj::cp(reason));
+    canceler.cancel(reason);
+  }
+  KJ_EXPECT(std::string(take_recorded_reason()) == "client went away");
+  KJ_EXPECT_THROW_MESSAGE("client went away", promise.wait(waitScope));
+}
+
+KJ_TEST("CancellationToken::cancel() with a description uses the requested type") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj_rs::CancellationToken token;
+  token.cancel("too busy"_kj, kj::Exception::Type::OVERLOADED);
+
+  auto exception = KJ_ASSERT_NONNULL(kj::runCatchingExceptions([&]() {
+    pending_or_canceled(kj::heap<kj_rs::CancellationToken>(token)).wait(waitScope);
+  }));
+  KJ_EXPECT(exception.getType() == kj::Exception::Type::OVERLOADED);
+  KJ_EXPECT(exception.getDescription() == "too busy");
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
@@ -0,0 +1,90 @@
+#include "test-context.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("Rust futures are polled in the context they were created in") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj::Promise<uint64_t> promise = nullptr;
+  {
+    auto context = kj_rs::withTaskLocal(RequestId{42});
+    kj_rs::AsyncContext::Scope scope(*context);
+    promise = rust_request_id_later();
+  }
+  KJ_EXPECT(cxx_request_id() == 0);
+  KJ_EXPECT(promise.wait(waitScope) == 42);
+}
+
+KJ_TEST("Rust futures created without a context see none") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  KJ_EXPECT(rust_request_id_later().wait(waitScope) == 0);
+}
+
+KJ_TEST("Context frames are entered around each poll of a Rust future") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj::Promise<uint32_t> promise = nullptr;
+  {
+    auto context = kj_rs::AsyncContext::withFrame(kj::heap<TestFrame>(7));
+    kj_rs::AsyncContext::Scope scope(*context);
+    KJ_EXPECT(cxx_frame_value() == 7);
+    promise = rust_frame_value_later();
+  }
+  KJ_EXPECT(cxx_frame_value() == 0);
+  KJ_EXPECT(promise.wait(waitScope) == 7);
+  KJ_EXPECT(cxx_frame_value() == 0);
+}
+
+KJ_TEST("Nested contexts see the values of their parents") {
+  auto outer = kj_rs::withTaskLocal(RequestId{1});
+  kj_rs::AsyncContext::Scope outerScope(*outer);
+  auto inner = kj_rs::AsyncContext::withFrame(kj::heap<TestFrame>(2));
+  kj_rs::AsyncContext::Scope innerScope(*inner);
+
+  KJ_EXPECT(cxx_request_id() == 1);
+  KJ_EXPECT(cxx_frame_value() == 2);
+}
+
+KJ_TEST("Entering no context exits the frames of the current one until the scope ends") {
+  auto context = kj_rs::AsyncContext::withFrame(kj::heap<TestFrame>(3));
+  kj_rs::AsyncContext::Scope scope(*context);
+  KJ_EXPECT(cxx_frame_value() == 3);
+  {
+    kj_rs::AsyncContext::Scope none(kj::none);
+    KJ_EXPECT(cxx_frame_value() == 0);
+    KJ_EXPECT(kj_rs::AsyncContext::current() == kj::none);
+  }
+  KJ_EXPECT(cxx_frame_value() == 3);
+}
+
+KJ_TEST("Entering a sibling context only swaps the frames below their shared parent") {
+  auto parent = kj_rs::AsyncContext::withFrame(kj::heap<TestFrame>(1));
+  kj::Own<kj_rs::AsyncContext> first;
+  kj::Own<kj_rs::AsyncContext> second;
+  {
+    kj_rs::AsyncContext::Scope parentScope(*parent);
+    first = kj_rs::AsyncContext::withFrame(kj::heap<TestFrame>(2));
+    second = kj_rs::withTaskLocal(RequestId{4});
+  }
+
+  kj_rs::AsyncContext::Scope firstScope(*first);
+  KJ_EXPECT(cxx_frame_value() == 2);
+  {
+    kj_rs::AsyncContext::Scope secondScope(*second);
+    // `first`'s frame is exited, `parent`'s stays entered.
+    KJ_EXPECT(cxx_frame_value() == 1);
+    KJ_EXPECT(cxx_request_id() == 4);
+  }
+  KJ_EXPECT(cxx_frame_value() == 2);
+  KJ_EXPECT(cxx_request_id() == 0);
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
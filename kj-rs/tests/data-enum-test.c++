@@ -0,0 +1,57 @@
+#include "test-data-enum.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("enum with data returned from Rust") {
+  auto start = rust_make_event(0);
+  KJ_EXPECT(start.is_start());
+  KJ_EXPECT(!start.is_move());
+
+  auto move = rust_make_event(1);
+  KJ_EXPECT(move.tag == Event::Tag::Move);
+  KJ_EXPECT(move.get_move().x == 5);
+  KJ_EXPECT(move.get_move().y == -5);
+
+  auto key = rust_make_event(2);
+  KJ_EXPECT(key.get_key_press() == 42);
+  KJ_EXPECT(c_describe_event(key) == "key 42");
+
+  auto text = rust_make_event(3);
+  KJ_EXPECT(c_describe_event(text) == "text from Rust");
+
+  KJ_EXPECT(rust_make_event(4).is_stop());
+}
+
+KJ_TEST("enum with data passed to Rust") {
+  KJ_EXPECT(rust_event_x(c_make_move(-3, 8)) == -3);
+  KJ_EXPECT(rust_event_x(Event::make_start()) == -1);
+}
+
+KJ_TEST("enum with data copies and moves its payload") {
+  auto text = Event::make_text(rust::String("copied"));
+  Event copy = text;
+  KJ_EXPECT(copy.get_text() == "copied");
+
+  Event moved = kj::mv(text);
+  KJ_EXPECT(moved.get_text() == "copied");
+
+  copy = Event::make_key_press(1);
+  KJ_EXPECT(copy.is_key_press());
+}
+
+KJ_TEST("enum with data holding kj::String round-trips through Rust") {
+  auto message = rust_make_message("from Rust");
+  KJ_EXPECT(message.tag == Message::Tag::Body);
+  KJ_EXPECT(message.get_body() == "from Rust");
+  KJ_EXPECT(c_message_length(kj::mv(message)) == 9);
+
+  auto empty = rust_make_message("");
+  KJ_EXPECT(empty.is_empty());
+  KJ_EXPECT(static_cast<uint8_t>(empty.tag) == 7);
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
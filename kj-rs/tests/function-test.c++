@@ -0,0 +1,38 @@
+#include "test-function.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("kj::Function passed to Rust") {
+  KJ_EXPECT(rust_call_with([](uint32_t x) { return x + 1; }, 41) == 42);
+}
+
+KJ_TEST("exception thrown by kj::Function passed through Rust") {
+  rust_call_fallible([]() {});
+  KJ_EXPECT_THROW_MESSAGE(
+      "thrown from C++", rust_call_fallible([]() { KJ_FAIL_REQUIRE("thrown from C++"); }));
+}
+
+KJ_TEST("Rust closure returned to C++") {
+  auto counter = rust_make_counter();
+  KJ_EXPECT(counter() == 1);
+  KJ_EXPECT(counter() == 2);
+
+  auto other = rust_make_counter();
+  KJ_EXPECT(other() == 1);
+  KJ_EXPECT(counter() == 3);
+}
+
+KJ_TEST("Rust closure errors and panics are thrown") {
+  auto parse = rust_make_parser();
+  KJ_EXPECT(parse("123") == 123);
+  KJ_EXPECT_THROW_MESSAGE("invalid digit", parse("12x"));
+  KJ_EXPECT_THROW_MESSAGE("empty input", parse(""));
+  // The closure is still usable after a panic.
+  KJ_EXPECT(parse("7") == 7);
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
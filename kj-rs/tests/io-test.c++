@@ -0,0 +1,91 @@
+#include "test-io.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+rust::Slice<const uint8_t> bytes(kj::StringPtr text) {
+  return rust::Slice<const uint8_t>(text.asBytes().begin(), text.size());
+}
+
+KJ_TEST("Rust reads a KJ input stream as AsyncRead") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto text = read_all(new_bytes_input_stream(bytes("hello world"))).wait(waitScope);
+  KJ_EXPECT(text == "hello world");
+}
+
+KJ_TEST("KJ input stream errors reach Rust") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  KJ_EXPECT_THROW_MESSAGE(
+      "test stream disconnected", read_all(new_disconnected_input_stream()).wait(waitScope));
+}
+
+KJ_TEST("Rust writes to a KJ output stream as AsyncWrite") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj::Vector<kj::byte> out;
+  write_all(newVectorOutputStream(out), "hello world").wait(waitScope);
+  KJ_EXPECT(out.asPtr() == "hello world"_kjb);
+}
+
+KJ_TEST("Rust pumps a KJ input stream to a KJ output stream") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj::Vector<kj::byte> out;
+  auto pumped =
+      pump_all(new_bytes_input_stream(bytes("hello world")), newVectorOutputStream(out))
+          .wait(waitScope);
+  KJ_EXPECT(pumped == 11);
+  KJ_EXPECT(out.asPtr() == "hello world"_kjb);
+}
+
+KJ_TEST("C++ reads a Rust AsyncRead as a KJ input stream") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto stream = new_rust_input_stream(bytes("hello world"));
+  KJ_EXPECT(stream->tryGetLength() == kj::none);
+  KJ_EXPECT(stream->readAllText().wait(waitScope) == "hello world");
+}
+
+KJ_TEST("Rust input streams can have a known length") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto stream = new_sized_rust_input_stream(bytes("hello world"));
+  KJ_EXPECT(stream->tryGetLength() == uint64_t(11));
+
+  kj::byte buffer[5];
+  KJ_EXPECT(stream->tryRead(buffer, 5, 5).wait(waitScope) == 5);
+  KJ_EXPECT(kj::arrayPtr(buffer) == "hello"_kjb);
+  KJ_EXPECT(stream->tryGetLength() == uint64_t(6));
+
+  kj::Vector<kj::byte> out;
+  auto output = newVectorOutputStream(out);
+  KJ_EXPECT(stream->pumpTo(*output).wait(waitScope) == 6);
+  KJ_EXPECT(out.asPtr() == " world"_kjb);
+}
+
+KJ_TEST("Rust read errors are thrown to C++") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto stream = new_failing_rust_input_stream();
+  KJ_EXPECT_THROW(DISCONNECTED, stream->readAllText().wait(waitScope));
+}
+
+KJ_TEST("Unbuffered KJ streams round-trip through Rust unwrapped") {
+  auto stream = new_bytes_input_stream(bytes("abc"));
+  auto* ptr = stream.get();
+  KJ_EXPECT(round_trip_input_stream(kj::mv(stream)).get() == ptr);
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
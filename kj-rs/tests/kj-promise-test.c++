@@ -0,0 +1,109 @@
+#include "test-kj-promise.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("Rust can await kj::Promises passed as arguments") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto sum = add_kj_promises(kj::Promise<int32_t>(1), new_kj_promise_i32(2));
+  KJ_EXPECT(sum.wait(waitScope) == 3);
+}
+
+KJ_TEST("Rust can store a kj::Promise and await it later") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto paf = kj::newPromiseAndFulfiller<int32_t>();
+  store_kj_promise(kj::mv(paf.promise));
+
+  auto promise = await_stored_kj_promise();
+  KJ_EXPECT(!promise.poll(waitScope));
+  paf.fulfiller->fulfill(42);
+  KJ_EXPECT(promise.wait(waitScope) == 42);
+}
+
+KJ_TEST("Rust can await a kj::Promise inside a kj::Maybe") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  KJ_EXPECT(await_maybe_kj_promise(new_maybe_kj_promise_i32(true, 42)).wait(waitScope) == 42);
+  KJ_EXPECT(await_maybe_kj_promise(kj::none).wait(waitScope) == -1);
+}
+
+KJ_TEST("Rust propagates exceptions from a passed kj::Promise") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto sum = add_kj_promises(
+      kj::Promise<int32_t>(KJ_EXCEPTION(FAILED, "test error")), kj::Promise<int32_t>(1));
+  KJ_EXPECT_THROW_MESSAGE("test error", sum.wait(waitScope));
+}
+
+KJ_TEST("kj_rs::Promise converts back to kj::Promise") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj::Promise<int32_t> promise = new_kj_promise_i32(42);
+  KJ_EXPECT(promise.wait(waitScope) == 42);
+}
+
+KJ_TEST("Every branch of a kj_rs::ForkedPromise gets the result of the Rust future") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  kj_rs::ForkedPromise<int32_t> forked = new_forked_kj_promise_i32(42);
+  auto first = forked.addBranch();
+  auto second = forked.addBranch();
+  auto third = forked.addBranch();
+  KJ_EXPECT(second.wait(waitScope) == 42);
+  KJ_EXPECT(first.wait(waitScope) == 42);
+  KJ_EXPECT(third.wait(waitScope) == 42);
+  KJ_EXPECT(forked.addBranch().wait(waitScope) == 42);
+}
+
+KJ_TEST("kj_rs::ForkedPromise branches get their own copy of a non-copyable result") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto forked = new_forked_kj_promise_string("shared");
+  kj::String first = forked.addBranch().wait(waitScope);
+  kj::String second = forked.addBranch().wait(waitScope);
+  KJ_EXPECT(first == "shared");
+  KJ_EXPECT(second == "shared");
+  KJ_EXPECT(first.begin() != second.begin());
+}
+
+KJ_TEST("A kj::Promise forked in Rust resolves all of its branches in C++") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto paf = kj::newPromiseAndFulfiller<int32_t>();
+  auto forked = fork_kj_promise(kj::mv(paf.promise));
+  auto first = forked.addBranch();
+  auto second = forked.addBranch();
+  KJ_EXPECT(!first.poll(waitScope));
+  KJ_EXPECT(!second.poll(waitScope));
+
+  paf.fulfiller->fulfill(7);
+  KJ_EXPECT(first.wait(waitScope) == 7);
+  KJ_EXPECT(second.wait(waitScope) == 7);
+  KJ_EXPECT(forked.addBranch().wait(waitScope) == 7);
+}
+
+KJ_TEST("A kj::Promise forked in Rust rejects all of its branches in C++") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto forked = fork_kj_promise(kj::Promise<int32_t>(KJ_EXCEPTION(FAILED, "test error")));
+  auto first = forked.addBranch();
+  auto second = forked.addBranch();
+  KJ_EXPECT_THROW_MESSAGE("test error", first.wait(waitScope));
+  KJ_EXPECT_THROW_MESSAGE("test error", second.wait(waitScope));
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
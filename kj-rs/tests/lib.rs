@@ -6,11 +6,29 @@
 #![allow(clippy::should_panic_without_expect)]
 #![allow(clippy::missing_panics_doc)]
 
+mod test_array;
+mod test_async_methods;
+mod test_blocking;
+mod test_cancel;
+mod test_context;
+mod test_data_enum;
 mod test_date;
+mod test_event_loop;
+mod test_executor_guarded;
+mod test_fork;
+mod test_function;
 mod test_futures;
+mod test_io;
+mod test_kj_promise;
 mod test_maybe;
+mod test_one_of;
 mod test_own;
 mod test_refcount;
+mod test_stream;
+mod test_string;
+mod test_timer;
+mod test_trait;
+mod test_typed_error;
 
 use test_futures::{
     new_drop_cancellable_promise_without_polling, new_error_handling_future_void_infallible,
@@ -27,6 +45,8 @@ use test_maybe::{
     take_maybe_shared_ret,
 };
 
+use test_own::RustValue;
+
 use test_refcount::{modify_own_ret_arc, modify_own_ret_rc, take_maybe_rc_ret};
 
 use kj_rs::KjOwn;
@@ -101,6 +121,20 @@ mod ffi {
         fn null_exception_test_driver_2() -> String;
         #[allow(dead_code)]
         fn rust_take_own_driver();
+
+        #[allow(dead_code)]
+        fn cxx_kj_own_vec() -> Vec<KjOwn<OpaqueCxxClass>>;
+        #[allow(dead_code)]
+        fn take_kj_own_vec(owns: Vec<KjOwn<OpaqueCxxClass>>) -> u64;
+        #[allow(dead_code)]
+        fn kj_own_vec_disposed() -> u64;
+
+        #[allow(dead_code)]
+        fn take_rust_value_own(own: KjOwn<RustValue>) -> u64;
+        #[allow(dead_code)]
+        fn rust_value_box_to_own(value: Box<RustValue>) -> KjOwn<RustValue>;
+        #[allow(dead_code)]
+        fn rust_value_to_shared(value: UniquePtr<RustValue>) -> SharedPtr<RustValue>;
     }
 
     unsafe extern "C++" {
@@ -120,6 +154,9 @@ mod ffi {
         #[allow(dead_code)]
         fn give_rc_back(rc: KjRc<OpaqueRefcountedClass>);
 
+        #[allow(dead_code)]
+        async fn new_ready_promise_rc() -> KjRc<OpaqueRefcountedClass>;
+
         #[allow(dead_code)]
         fn return_maybe_rc_some() -> KjMaybe<KjRc<OpaqueRefcountedClass>>;
         #[allow(dead_code)]
@@ -148,6 +185,9 @@ mod ffi {
         #[allow(dead_code)]
         fn give_arc_back(arc: KjArc<OpaqueAtomicRefcountedClass>);
 
+        #[allow(dead_code)]
+        async fn new_ready_promise_arc() -> KjArc<OpaqueAtomicRefcountedClass>;
+
         #[allow(dead_code)]
         fn return_maybe_arc_some() -> KjMaybe<KjArc<OpaqueAtomicRefcountedClass>>;
         #[allow(dead_code)]
@@ -167,6 +207,12 @@ mod ffi {
         ) -> KjMaybe<KjRc<OpaqueRefcountedClass>>;
     }
 
+    // A Rust type owned by `kj::Own`, `std::unique_ptr` and `std::shared_ptr`
+    extern "Rust" {
+        type RustValue;
+        fn value(&self) -> u64;
+    }
+
     // Helper function to test moving `Own` to C++
     extern "Rust" {
         fn modify_own_return(cpp_own: KjOwn<OpaqueCxxClass>) -> KjOwn<OpaqueCxxClass>;
@@ -344,6 +390,7 @@ mod ffi {
 
         async fn new_promise_i32_awaiting_future_void() -> Result<()>;
         async fn new_ready_future_i32(value: i32) -> Result<i32>;
+        async fn new_fulfillable_future_i32() -> i32;
         async fn new_pass_through_feature_shared() -> Shared;
 
         async unsafe fn work_before_poll<'a>(target: &'a mut u64) -> Result<()>;
@@ -415,6 +462,13 @@ async fn new_ready_future_shared_type() -> ffi::Shared {
     ffi::Shared { i: 42 }
 }
 
+/// # Panics
+/// - if c++ side throws exception
+pub async fn new_fulfillable_future_i32() -> i32 {
+    ffi::new_fulfillable_promise_void().await.unwrap();
+    42
+}
+
 fn work_before_poll(target: &mut u64) -> impl Future<Output = Result<()>> {
     *target = 42;
 
@@ -430,7 +484,7 @@ mod tests {
     #[allow(clippy::let_underscore_future)]
     #[test]
     fn compilation() {
-        // these promises can't be driven by rust side, just check that everything compiles.
+        // just check that everything compiles, see test_event_loop for driving promises from rust.
         let _ = ffi::new_ready_promise_void();
         let _ = ffi::new_ready_promise_i32(42);
         let _ = ffi::new_ready_promise_shared_type();
@@ -0,0 +1,38 @@
+#include "test-one-of.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("kj::OneOf returned from Rust") {
+  auto circle = rust_make_shape(true);
+  KJ_EXPECT(KJ_ASSERT_NONNULL(circle.tryGet<double>()) == 2.0);
+
+  auto rectangle = rust_make_shape(false);
+  auto& size = KJ_ASSERT_NONNULL(rectangle.tryGet<Size>());
+  KJ_EXPECT(size.width == 3);
+  KJ_EXPECT(size.height == 4);
+  KJ_EXPECT(c_shape_area(rectangle) == 12.0);
+}
+
+KJ_TEST("kj::OneOf passed to Rust") {
+  KJ_EXPECT(rust_shape_area(Size{.width = 2, .height = 3}) == 6.0);
+  KJ_EXPECT(rust_shape_area(c_make_circle(1.0)) == c_shape_area(c_make_circle(1.0)));
+
+  Body text = kj::str("from C++");
+  KJ_EXPECT(kj::str(rust_body_text(text)) == "from C++");
+  KJ_EXPECT(kj::str(rust_body_text(Body(uint64_t(7)))) == "<7 bytes>");
+}
+
+KJ_TEST("kj::OneOf holding kj::String round-trips through Rust") {
+  auto body = rust_make_body("from Rust");
+  KJ_EXPECT(KJ_ASSERT_NONNULL(body.tryGet<kj::String>()) == "from Rust");
+  KJ_EXPECT(c_body_length(kj::mv(body)) == 9);
+
+  auto empty = rust_make_body("");
+  KJ_EXPECT(empty.is<uint64_t>());
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
@@ -0,0 +1,90 @@
+#include "test-stream.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+template <typename T>
+kj::Vector<T> collect(kj_rs::Stream<T>& stream, kj::WaitScope& waitScope) {
+  kj::Vector<T> items;
+  for (;;) {
+    auto next = stream.next().wait(waitScope);
+    KJ_IF_SOME(item, next) {
+      items.add(kj::mv(item));
+    } else {
+      return items;
+    }
+  }
+}
+
+KJ_TEST("C++ can pull items from a Rust stream") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto stream = new_range_stream(3);
+  KJ_EXPECT(collect(stream, waitScope).asPtr() == kj::ArrayPtr<const int32_t>({1, 2, 3}));
+  // The end of the stream is sticky.
+  KJ_EXPECT(stream.next().wait(waitScope) == kj::none);
+
+  auto empty = new_range_stream(0);
+  KJ_EXPECT(collect(empty, waitScope).size() == 0);
+}
+
+KJ_TEST("Rust streams can suspend between items") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto stream = new_yielding_stream(3);
+  KJ_EXPECT(collect(stream, waitScope).asPtr() == kj::ArrayPtr<const int32_t>({1, 2, 3}));
+}
+
+KJ_TEST("C++ KJ coroutines can co_await Rust stream items") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto strings = []() -> kj::Promise<kj::String> {
+    auto stream = new_kj_string_rust_stream();
+    kj::Vector<kj::String> items;
+    for (;;) {
+      auto next = co_await stream.next();
+      KJ_IF_SOME(item, next) {
+        items.add(kj::mv(item));
+      } else {
+        co_return kj::strArray(items, ",");
+      }
+    }
+  }().wait(waitScope);
+  KJ_EXPECT(strings == "foo,bar");
+}
+
+KJ_TEST("Rust stream errors reject the pending next() promise") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto stream = new_failing_stream();
+  KJ_EXPECT(KJ_ASSERT_NONNULL(stream.next().wait(waitScope)) == 1);
+  KJ_EXPECT_THROW_MESSAGE("failing stream error", stream.next().wait(waitScope));
+  // An error does not end the stream.
+  KJ_EXPECT(KJ_ASSERT_NONNULL(stream.next().wait(waitScope)) == 3);
+}
+
+KJ_TEST("Rust can consume C++ streams") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  KJ_EXPECT(sum_counting_stream(4).wait(waitScope) == 10);
+  KJ_EXPECT(sum_counting_stream(0).wait(waitScope) == 0);
+  KJ_EXPECT(join_kj_string_stream().wait(waitScope) == "a,b,c");
+  drop_counting_stream_early().wait(waitScope);
+}
+
+KJ_TEST("C++ stream errors surface as Rust item errors") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  KJ_EXPECT_THROW_MESSAGE("errored stream", take_from_errored_stream().wait(waitScope));
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
@@ -0,0 +1,33 @@
+#include "test-string.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("C++ receives kj::String from Rust") {
+  kj::String s = r_return_kj_string();
+  KJ_EXPECT(s == "hello from rust");
+  KJ_EXPECT(s.cStr()[s.size()] == '\0');
+}
+
+KJ_TEST("C++ sends kj::String and kj::StringPtr to Rust") {
+  r_take_kj_string(kj::str("hello from c++"));
+  KJ_EXPECT(r_take_kj_string_ptr("four"_kj) == 4);
+  KJ_EXPECT(r_take_kj_string_ptr(""_kj) == 0);
+}
+
+KJ_TEST("kj::String round-trips through Rust") {
+  auto original = kj::str("round trip");
+  const char* data = original.begin();
+  auto result = r_roundtrip_kj_string(kj::mv(original));
+  KJ_EXPECT(result == "round trip");
+  // Ownership moves through Rust without copying.
+  KJ_EXPECT(result.begin() == data);
+
+  auto empty = r_roundtrip_kj_string(kj::String());
+  KJ_EXPECT(empty == "");
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
@@ -0,0 +1,45 @@
+#include "test-array.h"
+
+#include "kj/debug.h"
+
+namespace kj_rs_demo {
+
+kj::Array<int32_t> c_return_kj_array() {
+  return kj::arr<int32_t>(1, 2, 3);
+}
+
+kj::Array<int32_t> c_return_empty_kj_array() {
+  return nullptr;
+}
+
+kj::Array<kj::String> c_return_kj_string_array() {
+  return kj::arr(kj::str("foo"), kj::str("bar"), kj::str("baz"));
+}
+
+kj::Array<Point> c_return_point_array() {
+  return kj::arr(Point{1, 2}, Point{3, 4});
+}
+
+kj::ArrayPtr<const uint8_t> c_return_kj_array_ptr() {
+  return "bytes"_kj.asBytes();
+}
+
+void c_take_kj_array(kj::Array<int32_t> a) {
+  KJ_ASSERT(a.asPtr() == kj::ArrayPtr<const int32_t>({4, 5, 6}));
+}
+
+void c_take_kj_string_array(kj::Array<kj::String> a) {
+  KJ_ASSERT(a.size() == 2);
+  KJ_ASSERT(a[0] == "hello", a[0]);
+  KJ_ASSERT(a[1] == "world", a[1]);
+}
+
+int64_t c_sum_kj_array_ptr(kj::ArrayPtr<const int32_t> a) {
+  int64_t sum = 0;
+  for (auto i: a) {
+    sum += i;
+  }
+  return sum;
+}
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,25 @@
+#pragma once
+
+#include "kj-rs-demo/test_array.rs.h"
+
+#include <kj/array.h>
+#include <kj/common.h>
+#include <kj/string.h>
+
+#include <cstdint>
+
+namespace kj_rs_demo {
+
+// Functions to test C++ -> Rust array passing
+kj::Array<int32_t> c_return_kj_array();
+kj::Array<int32_t> c_return_empty_kj_array();
+kj::Array<kj::String> c_return_kj_string_array();
+kj::Array<Point> c_return_point_array();
+kj::ArrayPtr<const uint8_t> c_return_kj_array_ptr();
+
+// Functions to test Rust -> C++ array passing
+void c_take_kj_array(kj::Array<int32_t> a);
+void c_take_kj_string_array(kj::Array<kj::String> a);
+int64_t c_sum_kj_array_ptr(kj::ArrayPtr<const int32_t> a);
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,37 @@
+#include "test-async-methods.h"
+
+namespace kj_rs_demo {
+
+kj::Promise<int32_t> CxxCounter::add_later(int32_t value) const {
+  co_await kj::yield();
+  co_return count + value;
+}
+
+kj::Promise<void> CxxCounter::increment_later() {
+  co_await kj::yield();
+  ++count;
+}
+
+kj::Own<CxxCounter> new_cxx_counter() {
+  return kj::heap<CxxCounter>();
+}
+
+kj::Promise<int32_t> RefcountedCxxCounter::add_later(int32_t value) const {
+  co_await kj::yield();
+  co_return count + value;
+}
+
+kj::Promise<int32_t> AtomicCxxCounter::add_later(int32_t value) const {
+  co_await kj::yield();
+  co_return count + value;
+}
+
+kj::Rc<RefcountedCxxCounter> new_refcounted_cxx_counter(int32_t count) {
+  return kj::rc<RefcountedCxxCounter>(count);
+}
+
+kj::Arc<AtomicCxxCounter> new_atomic_cxx_counter(int32_t count) {
+  return kj::arc<AtomicCxxCounter>(count);
+}
+
+}  // namespace kj_rs_demo
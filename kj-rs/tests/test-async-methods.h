@@ -0,0 +1,51 @@
+#pragma once
+
+#include <kj/async.h>
+#include <kj/memory.h>
+#include <kj/refcount.h>
+
+#include <cstdint>
+
+namespace kj_rs_demo {
+
+// Opaque C++ type with async methods, to test C++ -> Rust async method calls.
+class CxxCounter {
+ public:
+  // Resolves to the count plus `value` on a later turn of the event loop.
+  kj::Promise<int32_t> add_later(int32_t value) const;
+  // Increments the count on a later turn of the event loop.
+  kj::Promise<void> increment_later();
+
+ private:
+  int32_t count = 0;
+};
+
+kj::Own<CxxCounter> new_cxx_counter();
+
+// Refcounted C++ types, whose async methods Rust calls through a `KjRc` / `KjArc` receiver.
+class RefcountedCxxCounter: public kj::Refcounted {
+ public:
+  RefcountedCxxCounter(int32_t count): count(count) {}
+  // Resolves to the count plus `value` on a later turn of the event loop.
+  kj::Promise<int32_t> add_later(int32_t value) const;
+
+ private:
+  int32_t count;
+};
+
+class AtomicCxxCounter: public kj::AtomicRefcounted {
+ public:
+  AtomicCxxCounter(int32_t count): count(count) {}
+  // Resolves to the count plus `value` on a later turn of the event loop.
+  kj::Promise<int32_t> add_later(int32_t value) const;
+
+ private:
+  int32_t count;
+};
+
+kj::Rc<RefcountedCxxCounter> new_refcounted_cxx_counter(int32_t count);
+kj::Arc<AtomicCxxCounter> new_atomic_cxx_counter(int32_t count);
+
+}  // namespace kj_rs_demo
+
+#include "kj-rs-demo/test_async_methods.rs.h"
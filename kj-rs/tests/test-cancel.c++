@@ -0,0 +1,39 @@
+#include "test-cancel.h"
+
+#include <kj/debug.h>
+
+namespace kj_rs_demo {
+
+namespace {
+kj::Maybe<kj::Exception> recordedReason;
+}
+
+void c_cancel_token(const kj_rs::CancellationToken& token, rust::Str description) {
+  // Tokens share their signal between copies, so canceling a copy cancels the original.
+  kj_rs::CancellationToken copy = token;
+  copy.cancel(KJ_EXCEPTION(OVERLOADED, kj::str(description)));
+}
+
+bool c_is_token_canceled(const kj_rs::CancellationToken& token) {
+  return token.isCanceled();
+}
+
+kj::Promise<void> c_new_reason_recording_promise() {
+  return kj::Promise<void>(kj::NEVER_DONE).attach(kj::defer([]() {
+    KJ_IF_SOME(reason, kj_rs::CancellationScope::current()) {
+      recordedReason = kj::cp(reason);
+    } else {
+      recordedReason = kj::none;
+    }
+  }));
+}
+
+void c_take_recorded_reason() {
+  KJ_IF_SOME(reason, recordedReason) {
+    auto exception = kj::mv(reason);
+    recordedReason = kj::none;
+    kj::throwFatalException(kj::mv(exception));
+  }
+}
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,20 @@
+#pragma once
+
+#include "kj-rs-demo/test_cancel.rs.h"
+
+#include <kj-rs/cancel.h>
+
+#include <kj/async.h>
+
+namespace kj_rs_demo {
+
+// Cancels `token` with an OVERLOADED exception carrying `description`.
+void c_cancel_token(const kj_rs::CancellationToken& token, rust::Str description);
+bool c_is_token_canceled(const kj_rs::CancellationToken& token);
+
+// A promise which never resolves, and records `kj_rs::CancellationScope::current()` when destroyed.
+kj::Promise<void> c_new_reason_recording_promise();
+// Throws the reason recorded by the last destroyed recording promise, if it had one.
+void c_take_recorded_reason();
+
+}  // namespace kj_rs_demo
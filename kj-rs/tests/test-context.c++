@@ -0,0 +1,57 @@
+#include "test-context.h"
+
+kj::Array<kj::byte> kj_rs::TaskLocal<kj_rs_demo::RequestId>::encode(
+    const kj_rs_demo::RequestId& id) {
+  // Little-endian, like `u64::to_le_bytes`.
+  auto data = kj::heapArray<kj::byte>(sizeof(id.value));
+  for (size_t i = 0; i < data.size(); ++i) {
+    data[i] = static_cast<kj::byte>(id.value >> (8 * i));
+  }
+  return data;
+}
+
+kj::Maybe<kj_rs_demo::RequestId> kj_rs::TaskLocal<kj_rs_demo::RequestId>::decode(
+    kj::ArrayPtr<const kj::byte> data) {
+  if (data.size() != sizeof(uint64_t)) {
+    return kj::none;
+  }
+  uint64_t value = 0;
+  for (size_t i = 0; i < data.size(); ++i) {
+    value |= static_cast<uint64_t>(data[i]) << (8 * i);
+  }
+  return kj_rs_demo::RequestId{value};
+}
+
+namespace kj_rs_demo {
+
+namespace {
+thread_local uint32_t frameValue = 0;
+}  // namespace
+
+void TestFrame::enter() noexcept {
+  previous = frameValue;
+  frameValue = value;
+}
+
+void TestFrame::exit() noexcept {
+  frameValue = previous;
+}
+
+uint64_t cxx_request_id() {
+  KJ_IF_SOME(id, kj_rs::getTaskLocal<RequestId>()) {
+    return id.value;
+  }
+  return 0;
+}
+
+uint32_t cxx_frame_value() {
+  return frameValue;
+}
+
+kj::Promise<uint64_t> cxx_with_request_id(uint64_t id) {
+  auto context = kj_rs::withTaskLocal(RequestId{id});
+  kj_rs::AsyncContext::Scope scope(*context);
+  return rust_request_id_later();
+}
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,50 @@
+#pragma once
+
+#include "kj-rs-demo/test_context.rs.h"
+#include "kj-rs/context.h"
+
+#include <kj/async.h>
+
+#include <cstdint>
+
+namespace kj_rs_demo {
+
+// The id of the request a task works for, `test_context::RequestId` in Rust.
+struct RequestId {
+  uint64_t value;
+};
+
+}  // namespace kj_rs_demo
+
+template <>
+struct kj_rs::TaskLocal<kj_rs_demo::RequestId> {
+  // "requstid", must match `RequestId` in test_context.rs.
+  static constexpr uint64_t ID = 0x7265717573746964;
+
+  static kj::Array<kj::byte> encode(const kj_rs_demo::RequestId& id);
+  static kj::Maybe<kj_rs_demo::RequestId> decode(kj::ArrayPtr<const kj::byte> data);
+};
+
+namespace kj_rs_demo {
+
+// A context frame which makes `value` the result of `cxx_frame_value()` while entered.
+class TestFrame final: public kj_rs::AsyncContext::Frame {
+ public:
+  explicit TestFrame(uint32_t value): value(value) {}
+
+  void enter() noexcept override;
+  void exit() noexcept override;
+
+ private:
+  uint32_t value;
+  uint32_t previous = 0;
+};
+
+// Returns the request id of the current context, or 0 if there is none.
+uint64_t cxx_request_id();
+// Returns the value of the innermost entered `TestFrame`, or 0 if there is none.
+uint32_t cxx_frame_value();
+// Calls `rust_request_id_later()` with the request id `id` in the context.
+kj::Promise<uint64_t> cxx_with_request_id(uint64_t id);
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,49 @@
+#include "test-data-enum.h"
+
+namespace kj_rs_demo {
+
+namespace {
+
+struct Describe {
+  kj::String operator()(Event::Start) {
+    return kj::str("start");
+  }
+  kj::String operator()(const Position& point) {
+    return kj::str("move ", point.x, " ", point.y);
+  }
+  kj::String operator()(uint32_t key) {
+    return kj::str("key ", key);
+  }
+  kj::String operator()(const rust::String& text) {
+    return kj::str("text ", text);
+  }
+  kj::String operator()(Event::Stop) {
+    return kj::str("stop");
+  }
+};
+
+}  // namespace
+
+kj::String c_describe_event(const Event& event) {
+  return event.visit(Describe{});
+}
+
+Event c_make_move(int32_t x, int32_t y) {
+  return Event::make_move(Position{.x = x, .y = y});
+}
+
+size_t c_message_length(Message message) {
+  if (message.is_empty()) {
+    return 0;
+  }
+  return message.get_body().size();
+}
+
+Log c_make_log(rust::Str text) {
+  return Log{
+    .count = 1,
+    .last = Event::make_text(rust::String(text)),
+  };
+}
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,12 @@
+#pragma once
+
+#include "kj-rs-demo/test_data_enum.rs.h"
+
+namespace kj_rs_demo {
+
+kj::String c_describe_event(const Event& event);
+Event c_make_move(int32_t x, int32_t y);
+size_t c_message_length(Message message);
+Log c_make_log(rust::Str text);
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,26 @@
+#include "test-function.h"
+
+#include <kj/debug.h>
+
+namespace kj_rs_demo {
+
+uint32_t c_call_twice(kj::Function<uint32_t(uint32_t)> f) {
+  return f(1) + f(2);
+}
+
+uint64_t c_call_with_text(kj::Function<uint64_t(rust::Str)> f) {
+  return f("kj::Function");
+}
+
+kj::Function<uint32_t(uint32_t)> c_make_adder(uint32_t x) {
+  return [x](uint32_t y) { return x + y; };
+}
+
+kj::Function<uint32_t(uint32_t)> c_make_checked_divider(uint32_t x) {
+  return [x](uint32_t y) {
+    KJ_REQUIRE(y != 0, "division by zero");
+    return x / y;
+  };
+}
+
+}  // namespace kj_rs_demo
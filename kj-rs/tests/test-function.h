@@ -0,0 +1,12 @@
+#pragma once
+
+#include "kj-rs-demo/test_function.rs.h"
+
+namespace kj_rs_demo {
+
+uint32_t c_call_twice(kj::Function<uint32_t(uint32_t)> f);
+uint64_t c_call_with_text(kj::Function<uint64_t(rust::Str)> f);
+kj::Function<uint32_t(uint32_t)> c_make_adder(uint32_t x);
+kj::Function<uint32_t(uint32_t)> c_make_checked_divider(uint32_t x);
+
+}  // namespace kj_rs_demo
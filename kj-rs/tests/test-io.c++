@@ -0,0 +1,74 @@
+#include "test-io.h"
+
+#include <kj/debug.h>
+
+namespace kj_rs_demo {
+
+namespace {
+
+class BytesInputStream final: public kj::AsyncInputStream {
+ public:
+  explicit BytesInputStream(kj::Array<kj::byte> data): data(kj::mv(data)) {}
+
+  kj::Promise<size_t> tryRead(void* buffer, size_t minBytes, size_t maxBytes) override {
+    size_t n = kj::min(maxBytes, data.size() - pos);
+    memcpy(buffer, data.begin() + pos, n);
+    pos += n;
+    return n;
+  }
+
+  kj::Maybe<uint64_t> tryGetLength() override {
+    return data.size() - pos;
+  }
+
+ private:
+  kj::Array<kj::byte> data;
+  size_t pos = 0;
+};
+
+class DisconnectedInputStream final: public kj::AsyncInputStream {
+ public:
+  kj::Promise<size_t> tryRead(void* buffer, size_t minBytes, size_t maxBytes) override {
+    return KJ_EXCEPTION(DISCONNECTED, "test stream disconnected");
+  }
+};
+
+class VectorOutputStream final: public kj::AsyncOutputStream {
+ public:
+  explicit VectorOutputStream(kj::Vector<kj::byte>& out): out(out) {}
+
+  kj::Promise<void> write(kj::ArrayPtr<const kj::byte> buffer) override {
+    out.addAll(buffer);
+    return kj::READY_NOW;
+  }
+
+  kj::Promise<void> write(kj::ArrayPtr<const kj::ArrayPtr<const kj::byte>> pieces) override {
+    for (auto piece: pieces) {
+      out.addAll(piece);
+    }
+    return kj::READY_NOW;
+  }
+
+  kj::Promise<void> whenWriteDisconnected() override {
+    return kj::NEVER_DONE;
+  }
+
+ private:
+  kj::Vector<kj::byte>& out;
+};
+
+}  // namespace
+
+kj::Own<kj::AsyncInputStream> new_bytes_input_stream(rust::Slice<const uint8_t> data) {
+  return kj::heap<BytesInputStream>(kj::heapArray<kj::byte>(data.data(), data.size()));
+}
+
+kj::Own<kj::AsyncInputStream> new_disconnected_input_stream() {
+  return kj::heap<DisconnectedInputStream>();
+}
+
+kj::Own<kj::AsyncOutputStream> newVectorOutputStream(kj::Vector<kj::byte>& out) {
+  return kj::heap<VectorOutputStream>(out);
+}
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,21 @@
+#pragma once
+
+#include "kj-rs-demo/test_io.rs.h"
+#include "kj-rs/io.h"
+
+#include <rust/cxx.h>
+
+#include <kj/async-io.h>
+#include <kj/vector.h>
+
+#include <cstdint>
+
+namespace kj_rs_demo {
+
+kj::Own<kj::AsyncInputStream> new_bytes_input_stream(rust::Slice<const uint8_t> data);
+kj::Own<kj::AsyncInputStream> new_disconnected_input_stream();
+
+// A stream appending everything written to it to `out`.
+kj::Own<kj::AsyncOutputStream> newVectorOutputStream(kj::Vector<kj::byte>& out);
+
+}  // namespace kj_rs_demo
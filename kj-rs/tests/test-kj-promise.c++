@@ -0,0 +1,30 @@
+#include "test-kj-promise.h"
+
+#include <kj/debug.h>
+
+namespace kj_rs_demo {
+
+kj_rs::Promise<int32_t> new_kj_promise_i32(int32_t value) {
+  // Resolve asynchronously so that Rust has to wait on the promise.
+  return kj::yield().then([value]() { return value; });
+}
+
+kj_rs::Promise<void> new_errored_kj_promise_void() {
+  return kj::Promise<void>(KJ_EXCEPTION(FAILED, "test error"));
+}
+
+PromiseHolder new_promise_holder(uint32_t id, int32_t value) {
+  return PromiseHolder{
+    .id = id,
+    .promise = new_kj_promise_i32(value),
+  };
+}
+
+kj::Maybe<kj_rs::Promise<int32_t>> new_maybe_kj_promise_i32(bool some, int32_t value) {
+  if (some) {
+    return new_kj_promise_i32(value);
+  }
+  return kj::none;
+}
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,17 @@
+#pragma once
+
+#include "kj-rs-demo/test_kj_promise.rs.h"
+
+#include <kj/async.h>
+
+#include <cstdint>
+
+namespace kj_rs_demo {
+
+// Functions to test passing `kj_rs::Promise<T>` values to Rust
+kj_rs::Promise<int32_t> new_kj_promise_i32(int32_t value);
+kj_rs::Promise<void> new_errored_kj_promise_void();
+PromiseHolder new_promise_holder(uint32_t id, int32_t value);
+kj::Maybe<kj_rs::Promise<int32_t>> new_maybe_kj_promise_i32(bool some, int32_t value);
+
+}  // namespace kj_rs_demo
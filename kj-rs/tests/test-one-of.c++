@@ -0,0 +1,48 @@
+#include "test-one-of.h"
+
+#include <kj/debug.h>
+
+#include <numbers>
+
+namespace kj_rs_demo {
+
+double c_shape_area(const Shape& shape) {
+  KJ_SWITCH_ONEOF(shape) {
+    KJ_CASE_ONEOF(size, Size) {
+      return size.width * size.height;
+    }
+    KJ_CASE_ONEOF(radius, double) {
+      return std::numbers::pi * radius * radius;
+    }
+  }
+  KJ_UNREACHABLE;
+}
+
+Shape c_make_circle(double radius) {
+  return radius;
+}
+
+Body c_make_text_body(rust::Str text) {
+  return kj::str(text);
+}
+
+uint64_t c_body_length(Body body) {
+  KJ_SWITCH_ONEOF(body) {
+    KJ_CASE_ONEOF(text, kj::String) {
+      return text.size();
+    }
+    KJ_CASE_ONEOF(length, uint64_t) {
+      return length;
+    }
+  }
+  KJ_UNREACHABLE;
+}
+
+Labeled c_make_labeled(rust::Str label, uint32_t width, uint32_t height) {
+  return Labeled{
+    .label = kj::str(label),
+    .shape = Size{.width = width, .height = height},
+  };
+}
+
+}  // namespace kj_rs_demo
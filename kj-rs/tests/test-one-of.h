@@ -0,0 +1,13 @@
+#pragma once
+
+#include "kj-rs-demo/test_one_of.rs.h"
+
+namespace kj_rs_demo {
+
+double c_shape_area(const Shape& shape);
+Shape c_make_circle(double radius);
+Body c_make_text_body(rust::Str text);
+uint64_t c_body_length(Body body);
+Labeled c_make_labeled(rust::Str label, uint32_t width, uint32_t height);
+
+}  // namespace kj_rs_demo
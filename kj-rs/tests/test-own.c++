@@ -1,5 +1,6 @@
 #include "test-own.h"
 
+#include "kj-rs-demo/lib.rs.h"
 #include "kj-rs/convert.h"
 #include "kj-rs/kj-rs.h"
 #include "kj/string.h"
@@ -55,6 +56,52 @@ kj::Own<int64_t> own_integer_attached() {
   return own.attach(kj::mv(attach));
 }
 
+namespace {
+
+// Counts the objects created by `cxx_kj_own_vec` which have been disposed.
+uint64_t ownVecDisposed = 0;
+
+kj::Own<OpaqueCxxClass> newCountedOwn(uint64_t data) {
+  return kj::heap<OpaqueCxxClass>(data).attach(kj::defer([]() { ++ownVecDisposed; }));
+}
+
+}  // namespace
+
+rust::Vec<kj::Own<OpaqueCxxClass>> cxx_kj_own_vec() {
+  ownVecDisposed = 0;
+  rust::Vec<kj::Own<OpaqueCxxClass>> owns;
+  owns.push_back(newCountedOwn(1));
+  owns.push_back(newCountedOwn(2));
+  owns.emplace_back(newCountedOwn(3));
+  return owns;
+}
+
+uint64_t take_kj_own_vec(rust::Vec<kj::Own<OpaqueCxxClass>> owns) {
+  uint64_t sum = 0;
+  for (auto& own: owns) {
+    sum += own->getData();
+  }
+  owns.truncate(1);
+  KJ_ASSERT(owns.size() == 1);
+  return sum;
+}
+
+uint64_t kj_own_vec_disposed() {
+  return ownVecDisposed;
+}
+
+uint64_t take_rust_value_own(kj::Own<RustValue> own) {
+  return own->value();
+}
+
+kj::Own<RustValue> rust_value_box_to_own(rust::Box<RustValue> value) {
+  return kj::Own<RustValue>(value.into_raw(), RustDisposer<RustValue>::instance);
+}
+
+std::shared_ptr<RustValue> rust_value_to_shared(std::unique_ptr<RustValue> value) {
+  return std::shared_ptr<RustValue>(kj::mv(value));
+}
+
 rust::string null_exception_test_driver_1() {
   try {
     auto _ = modify_own_return(null_kj_own());
@@ -7,6 +7,7 @@
 #include <kj/debug.h>
 
 #include <cstdint>
+#include <memory>
 
 namespace kj_rs_demo {
 
@@ -25,6 +26,9 @@ class OpaqueCxxClass {
   uint64_t data;
 };
 
+// Defined in Rust
+struct RustValue;
+
 // Forward declaration for Rust function, including the lib.rs.h caused problems
 kj::Own<OpaqueCxxClass> modify_own_return(kj::Own<OpaqueCxxClass> cpp_own);
 // Rust function that takes in a cpp_own. Should cause C++ exception if the own is NULL
@@ -48,5 +52,11 @@ kj::Own<OpaqueCxxClass> cxx_try_return_own();
 kj::Own<OpaqueCxxClass> cxx_fail_return_own();
 kj::Own<int64_t> own_integer();
 kj::Own<int64_t> own_integer_attached();
+rust::Vec<kj::Own<OpaqueCxxClass>> cxx_kj_own_vec();
+uint64_t take_kj_own_vec(rust::Vec<kj::Own<OpaqueCxxClass>> owns);
+uint64_t kj_own_vec_disposed();
+uint64_t take_rust_value_own(kj::Own<RustValue> own);
+kj::Own<RustValue> rust_value_box_to_own(rust::Box<RustValue> value);
+std::shared_ptr<RustValue> rust_value_to_shared(std::unique_ptr<RustValue> value);
 
 }  // namespace kj_rs_demo
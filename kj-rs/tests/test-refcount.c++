@@ -22,6 +22,14 @@ void give_rc_back(kj::Rc<OpaqueRefcountedClass> rc) {
   KJ_ASSERT(ret_rc->getData() == 467);
 }
 
+kj::Promise<kj::Rc<OpaqueRefcountedClass>> new_ready_promise_rc() {
+  co_return kj::rc<OpaqueRefcountedClass>(17);
+}
+
+kj::Promise<kj::Arc<OpaqueAtomicRefcountedClass>> new_ready_promise_arc() {
+  co_return kj::arc<OpaqueAtomicRefcountedClass>(18);
+}
+
 kj::Maybe<kj::Rc<OpaqueRefcountedClass>> return_maybe_rc_some() {
   return kj::rc<OpaqueRefcountedClass>(111);
 }
@@ -1,5 +1,6 @@
 #pragma once
 
+#include "kj/async.h"
 #include "kj/refcount.h"
 
 #include <cstdint>
@@ -42,6 +43,10 @@ kj::Arc<OpaqueAtomicRefcountedClass> get_arc();
 void give_arc_back(kj::Arc<OpaqueAtomicRefcountedClass> arc);
 void give_rc_back(kj::Rc<OpaqueRefcountedClass> rc);
 
+// Ready promises of a `kj::Rc` / `kj::Arc`, which Rust forks to get a reference per branch.
+kj::Promise<kj::Rc<OpaqueRefcountedClass>> new_ready_promise_rc();
+kj::Promise<kj::Arc<OpaqueAtomicRefcountedClass>> new_ready_promise_arc();
+
 // Helpers to test `kj::Maybe<kj::Rc<T>>` / `kj::Maybe<kj::Arc<T>>` over FFI.
 kj::Maybe<kj::Rc<OpaqueRefcountedClass>> return_maybe_rc_some();
 kj::Maybe<kj::Rc<OpaqueRefcountedClass>> return_maybe_rc_none();
@@ -0,0 +1,70 @@
+#include "test-stream.h"
+
+#include <kj/debug.h>
+
+namespace kj_rs_demo {
+
+namespace {
+
+// Not derived from `kj_rs::Stream<T>::Impl`, to exercise the duck-typed constructor.
+class CountingStream {
+ public:
+  CountingStream(int32_t count): count(count) {}
+
+  kj::Promise<kj::Maybe<int32_t>> next() {
+    // Resolve asynchronously so that Rust has to wait on the promise.
+    co_await kj::yield();
+    if (current == count) {
+      co_return kj::none;
+    }
+    co_return ++current;
+  }
+
+ private:
+  int32_t current = 0;
+  int32_t count;
+};
+
+class ErroredStream final: public kj_rs::Stream<int32_t>::Impl {
+ public:
+  kj::Promise<kj::Maybe<int32_t>> next() override {
+    if (first) {
+      first = false;
+      return kj::Maybe<int32_t>(1);
+    }
+    return KJ_EXCEPTION(FAILED, "errored stream");
+  }
+
+ private:
+  bool first = true;
+};
+
+class KjStringStream final: public kj_rs::Stream<kj::String>::Impl {
+ public:
+  kj::Promise<kj::Maybe<kj::String>> next() override {
+    if (index == kj::size(items)) {
+      return kj::Maybe<kj::String>(kj::none);
+    }
+    return kj::Maybe<kj::String>(kj::str(items[index++]));
+  }
+
+ private:
+  const char* items[3] = {"a", "b", "c"};
+  size_t index = 0;
+};
+
+}  // namespace
+
+kj_rs::Stream<int32_t> new_counting_stream(int32_t count) {
+  return kj::heap<CountingStream>(count);
+}
+
+kj_rs::Stream<int32_t> new_errored_stream() {
+  return kj::heap<ErroredStream>();
+}
+
+kj_rs::Stream<kj::String> new_kj_string_stream() {
+  return kj::heap<KjStringStream>();
+}
+
+}  // namespace kj_rs_demo
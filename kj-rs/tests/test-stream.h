@@ -0,0 +1,17 @@
+#pragma once
+
+#include "kj-rs-demo/test_stream.rs.h"
+
+#include <kj/async.h>
+#include <kj/string.h>
+
+#include <cstdint>
+
+namespace kj_rs_demo {
+
+// Functions to test C++ -> Rust stream passing
+kj_rs::Stream<int32_t> new_counting_stream(int32_t count);
+kj_rs::Stream<int32_t> new_errored_stream();
+kj_rs::Stream<kj::String> new_kj_string_stream();
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,37 @@
+#include "test-string.h"
+
+#include "kj/debug.h"
+
+namespace kj_rs_demo {
+
+kj::String c_return_kj_string() {
+  return kj::str("hello from c++");
+}
+
+kj::String c_return_empty_kj_string() {
+  return kj::String();
+}
+
+kj::String c_return_non_utf8_kj_string() {
+  return kj::heapString("\xff\xfe");
+}
+
+kj::StringPtr c_return_kj_string_ptr() {
+  return "static string"_kj;
+}
+
+void c_take_kj_string(kj::String s) {
+  KJ_ASSERT(s == "hello from rust", s);
+}
+
+size_t c_take_kj_string_ref(const kj::String& s) {
+  return s.size();
+}
+
+void c_take_kj_string_ptr(kj::StringPtr s) {
+  KJ_ASSERT(s == "hello from rust", s);
+  // kj::StringPtr guarantees NUL termination.
+  KJ_ASSERT(s.cStr()[s.size()] == '\0');
+}
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,23 @@
+#pragma once
+
+#include "kj-rs-demo/test_string.rs.h"
+
+#include <kj/common.h>
+#include <kj/string.h>
+
+#include <cstddef>
+
+namespace kj_rs_demo {
+
+// Functions to test C++ -> Rust string passing
+kj::String c_return_kj_string();
+kj::String c_return_empty_kj_string();
+kj::String c_return_non_utf8_kj_string();
+kj::StringPtr c_return_kj_string_ptr();
+
+// Functions to test Rust -> C++ string passing
+void c_take_kj_string(kj::String s);
+size_t c_take_kj_string_ref(const kj::String& s);
+void c_take_kj_string_ptr(kj::StringPtr s);
+
+}  // namespace kj_rs_demo
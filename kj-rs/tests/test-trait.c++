@@ -0,0 +1,29 @@
+#include "test-trait.h"
+
+#include <kj/debug.h>
+
+namespace kj_rs_demo {
+
+kj::Promise<uint64_t> MemoryKvStore::get(uint32_t key) const {
+  // Resolve asynchronously so that Rust has to wait on the promise.
+  co_await kj::yield();
+  KJ_IF_SOME(value, values.find(key)) {
+    co_return value;
+  }
+  KJ_FAIL_REQUIRE("key not found", key);
+}
+
+kj::Promise<void> MemoryKvStore::put(uint32_t key, uint64_t value) {
+  co_await kj::yield();
+  values.upsert(key, value);
+}
+
+size_t MemoryKvStore::size() const {
+  return values.size();
+}
+
+kj::Own<KvStore> new_memory_kv_store() {
+  return kj::heap<MemoryKvStore>();
+}
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,27 @@
+#pragma once
+
+#include "kj-rs-demo/test_trait.rs.h"
+
+#include <kj/async.h>
+#include <kj/map.h>
+#include <kj/memory.h>
+
+#include <cstdint>
+
+namespace kj_rs_demo {
+
+// C++ implementation of the Rust `KvStore` trait, to test passing C++ objects to Rust as trait
+// objects.
+class MemoryKvStore final: public KvStore {
+ public:
+  kj::Promise<uint64_t> get(uint32_t key) const override;
+  kj::Promise<void> put(uint32_t key, uint64_t value) override;
+  size_t size() const override;
+
+ private:
+  kj::HashMap<uint32_t, uint64_t> values;
+};
+
+kj::Own<KvStore> new_memory_kv_store();
+
+}  // namespace kj_rs_demo
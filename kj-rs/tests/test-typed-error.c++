@@ -0,0 +1,14 @@
+#include "test-typed-error.h"
+
+namespace kj_rs_demo {
+
+kj::String c_describe_port(rust::Str text) {
+  auto result = rust_parse_port(text);
+  KJ_IF_SOME(port, result.tryGet()) {
+    return kj::str("port ", port);
+  }
+  auto& error = result.getError();
+  return kj::str("error at ", error.position, ": ", error.reason);
+}
+
+}  // namespace kj_rs_demo
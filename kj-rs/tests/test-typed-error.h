@@ -0,0 +1,9 @@
+#pragma once
+
+#include "kj-rs-demo/test_typed_error.rs.h"
+
+namespace kj_rs_demo {
+
+kj::String c_describe_port(rust::Str text);
+
+}  // namespace kj_rs_demo
@@ -0,0 +1,118 @@
+use kj_rs::{KjArray, KjArrayPtr, KjString};
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    #[derive(Debug, PartialEq, Clone)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-array.h");
+
+        fn c_return_kj_array() -> KjArray<i32>;
+        fn c_return_empty_kj_array() -> KjArray<i32>;
+        fn c_return_kj_string_array() -> KjArray<KjString>;
+        fn c_return_point_array() -> KjArray<Point>;
+        fn c_return_kj_array_ptr() -> KjArrayPtr<'static, u8>;
+        fn c_take_kj_array(a: KjArray<i32>);
+        fn c_take_kj_string_array(a: KjArray<KjString>);
+        fn c_sum_kj_array_ptr(a: KjArrayPtr<i32>) -> i64;
+    }
+
+    extern "Rust" {
+        // Rust functions that C++ can call for KjArray testing
+        fn r_return_kj_array() -> KjArray<i32>;
+        fn r_return_kj_string_array() -> KjArray<KjString>;
+        fn r_sum_kj_array_ptr(a: KjArrayPtr<i32>) -> i64;
+        fn r_roundtrip_kj_array(a: KjArray<i32>) -> KjArray<i32>;
+    }
+}
+
+pub fn r_return_kj_array() -> KjArray<i32> {
+    KjArray::from(vec![1, 2, 3])
+}
+
+pub fn r_return_kj_string_array() -> KjArray<KjString> {
+    KjArray::from(vec![KjString::from("a"), KjString::from("b")])
+}
+
+pub fn r_sum_kj_array_ptr(a: KjArrayPtr<i32>) -> i64 {
+    a.iter().copied().map(i64::from).sum()
+}
+
+pub fn r_roundtrip_kj_array(a: KjArray<i32>) -> KjArray<i32> {
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ffi::Point;
+    use super::*;
+
+    #[test]
+    fn test_kj_array_from_vec() {
+        let vec = vec![1, 2, 3];
+        let data = vec.as_ptr();
+        let mut a = KjArray::from(vec);
+        // The vector's buffer is handed over without copying.
+        assert_eq!(a.as_ptr(), data);
+        assert_eq!(&a[..], &[1, 2, 3]);
+
+        a[0] = 10;
+        assert_eq!(&a[..], &[10, 2, 3]);
+        assert_eq!(a.clone(), a);
+    }
+
+    #[test]
+    fn test_kj_array_default() {
+        let a = KjArray::<i32>::default();
+        assert!(a.is_empty());
+        assert!(a.as_array_ptr().is_empty());
+        assert!(KjArray::<i32>::from(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_kj_array_ptr_from_slice() {
+        let elements = [1, 2, 3];
+        let a = KjArrayPtr::from(&elements[..]);
+        assert_eq!(a.as_ptr(), elements.as_ptr());
+        assert_eq!(format!("{a:?}"), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn test_kj_array_from_cpp() {
+        let a = ffi::c_return_kj_array();
+        assert_eq!(&a[..], &[1, 2, 3]);
+
+        let empty = ffi::c_return_empty_kj_array();
+        assert!(empty.is_empty());
+
+        let strings = ffi::c_return_kj_string_array();
+        let strings: Vec<&str> = strings.iter().map(|s| s.to_str().unwrap()).collect();
+        assert_eq!(strings, ["foo", "bar", "baz"]);
+
+        let points = ffi::c_return_point_array();
+        assert_eq!(&points[..], &[Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+    }
+
+    #[test]
+    fn test_kj_array_ptr_from_cpp() {
+        let a = ffi::c_return_kj_array_ptr();
+        assert_eq!(&a[..], b"bytes");
+    }
+
+    #[test]
+    fn test_kj_array_to_cpp() {
+        ffi::c_take_kj_array(KjArray::from(vec![4, 5, 6]));
+        ffi::c_take_kj_string_array(KjArray::from(vec![
+            KjString::from("hello"),
+            KjString::from("world"),
+        ]));
+
+        let a = KjArray::from(vec![1, 2, 3]);
+        assert_eq!(ffi::c_sum_kj_array_ptr(a.as_array_ptr()), 6);
+        assert_eq!(ffi::c_sum_kj_array_ptr(KjArrayPtr::from(&[10, 20][..])), 30);
+    }
+}
@@ -0,0 +1,105 @@
+#![allow(clippy::unnecessary_box_returns)]
+
+use crate::Error;
+use crate::Result;
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-async-methods.h");
+
+        type CxxCounter;
+
+        fn new_cxx_counter() -> KjOwn<CxxCounter>;
+        async fn add_later(self: &CxxCounter, value: i32) -> Result<i32>;
+        async fn increment_later(self: Pin<&mut CxxCounter>) -> Result<()>;
+
+        type RefcountedCxxCounter;
+        type AtomicCxxCounter;
+
+        fn new_refcounted_cxx_counter(count: i32) -> KjRc<RefcountedCxxCounter>;
+        fn new_atomic_cxx_counter(count: i32) -> KjArc<AtomicCxxCounter>;
+        async fn add_later(self: KjRc<RefcountedCxxCounter>, value: i32) -> Result<i32>;
+        async fn add_later(self: KjArc<AtomicCxxCounter>, value: i32) -> Result<i32>;
+    }
+
+    extern "Rust" {
+        type RustCounter;
+
+        fn new_rust_counter(count: i32) -> Box<RustCounter>;
+        fn leak_rust_counter(count: i32) -> &'static RustCounter;
+
+        async fn get_later(self: &'static RustCounter) -> Result<i32>;
+        async unsafe fn add_later<'a>(self: &'a RustCounter, value: i32) -> Result<i32>;
+
+        async fn increment_and_add_cxx_counter(value: i32) -> Result<i32>;
+        async fn add_to_refcounted_cxx_counters(value: i32) -> Result<i32>;
+    }
+}
+
+pub struct RustCounter {
+    count: Cell<i32>,
+}
+
+/// Returns `Poll::Pending` once, so that the caller has to wait on the future.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+impl RustCounter {
+    pub async fn get_later(&'static self) -> Result<i32> {
+        YieldOnce(false).await;
+        Ok(self.count.get())
+    }
+
+    pub async fn add_later(&self, value: i32) -> Result<i32> {
+        YieldOnce(false).await;
+        self.count.set(self.count.get() + value);
+        Ok(self.count.get())
+    }
+}
+
+pub fn new_rust_counter(count: i32) -> Box<RustCounter> {
+    Box::new(RustCounter {
+        count: Cell::new(count),
+    })
+}
+
+pub fn leak_rust_counter(count: i32) -> &'static RustCounter {
+    Box::leak(new_rust_counter(count))
+}
+
+pub async fn increment_and_add_cxx_counter(value: i32) -> Result<i32> {
+    let mut counter = ffi::new_cxx_counter();
+    // Both futures borrow `counter`, which has to outlive them.
+    counter
+        .pin_mut()
+        .increment_later()
+        .await
+        .map_err(Error::other)?;
+    counter.add_later(value).await.map_err(Error::other)
+}
+
+pub async fn add_to_refcounted_cxx_counters(value: i32) -> Result<i32> {
+    // The futures own a reference to their counter, so nothing has to outlive them.
+    let rc = ffi::RefcountedCxxCounter::add_later(ffi::new_refcounted_cxx_counter(1), value);
+    let arc = ffi::AtomicCxxCounter::add_later(ffi::new_atomic_cxx_counter(2), value);
+    Ok(rc.await.map_err(Error::other)? + arc.await.map_err(Error::other)?)
+}
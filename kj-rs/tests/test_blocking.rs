@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+
+    use kj_rs::BlockingPool;
+
+    #[test]
+    fn test_spawn_blocking() {
+        let thread = std::thread::current().id();
+        let value = kj_rs::block_on(async {
+            let worker = kj_rs::spawn_blocking(|| std::thread::current().id())
+                .await
+                .unwrap();
+            assert_ne!(worker, thread);
+            kj_rs::spawn_blocking(|| 6 * 7).await.unwrap()
+        });
+        assert_eq!(value, 42);
+    }
+
+    #[test]
+    fn test_spawn_blocking_panic() {
+        let err = kj_rs::block_on(kj_rs::spawn_blocking(|| -> i32 { panic!("boom") }))
+            .expect_err("should fail");
+        assert!(err.what().contains("panic in spawn_blocking: boom"));
+        assert_eq!(err.file().to_str().unwrap(), file!());
+    }
+
+    #[test]
+    fn test_spawn_blocking_canceled_before_start() {
+        let pool = BlockingPool::new(NonZeroUsize::MIN);
+        let (release, wait) = mpsc::channel::<()>();
+        let first = pool.spawn(move || wait.recv().unwrap());
+
+        // The only thread is busy, so the second closure is still queued when it is dropped.
+        let ran = Arc::new(AtomicBool::new(false));
+        let second_ran = ran.clone();
+        drop(pool.spawn(move || second_ran.store(true, Ordering::SeqCst)));
+
+        release.send(()).unwrap();
+        kj_rs::block_on(first).unwrap();
+        // Waits for the queue to drain.
+        drop(pool);
+        assert!(!ran.load(Ordering::SeqCst));
+    }
+}
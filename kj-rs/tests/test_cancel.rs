@@ -0,0 +1,155 @@
+use kj_rs::{CancellationReason, CancellationToken, KjOwn};
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-cancel.h");
+
+        #[namespace = "kj_rs"]
+        type CancellationToken = kj_rs::CancellationToken;
+
+        fn c_cancel_token(token: &CancellationToken, description: &str);
+        fn c_is_token_canceled(token: &CancellationToken) -> bool;
+
+        async fn c_new_reason_recording_promise();
+        fn c_take_recorded_reason() -> Result<()>;
+    }
+
+    extern "Rust" {
+        async fn cleanup_on_cancellation(token: KjOwn<CancellationToken>) -> Result<()>;
+        async fn pending_or_canceled(token: KjOwn<CancellationToken>) -> Result<()>;
+        fn get_cleanup_counter() -> u32;
+
+        async fn pending_recording_reason() -> Result<()>;
+        fn take_recorded_reason() -> String;
+    }
+}
+
+thread_local! {
+    static CLEANUP_COUNTER: Cell<u32> = const { Cell::new(0) };
+    static RECORDED_REASON: RefCell<Option<CancellationReason>> = const { RefCell::new(None) };
+}
+
+pub fn get_cleanup_counter() -> u32 {
+    CLEANUP_COUNTER.get()
+}
+
+/// Returns `Poll::Pending` once, standing in for asynchronous cleanup work.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+pub async fn cleanup_on_cancellation(token: KjOwn<CancellationToken>) -> Result<(), cxx::KjError> {
+    let reason = token.canceled().await;
+    YieldOnce(false).await;
+    CLEANUP_COUNTER.set(CLEANUP_COUNTER.get() + 1);
+    Err(reason.into())
+}
+
+pub async fn pending_or_canceled(token: KjOwn<CancellationToken>) -> Result<(), cxx::KjError> {
+    token
+        .or_canceled(std::future::pending::<()>())
+        .await
+        .map_err(cxx::KjError::from)
+}
+
+/// Records the current cancellation reason when dropped.
+struct RecordReasonOnDrop;
+
+impl Drop for RecordReasonOnDrop {
+    fn drop(&mut self) {
+        RECORDED_REASON.set(CancellationReason::current());
+    }
+}
+
+pub async fn pending_recording_reason() -> Result<(), cxx::KjError> {
+    let _record = RecordReasonOnDrop;
+    std::future::pending::<()>().await;
+    Ok(())
+}
+
+/// Returns the description of the reason the last `pending_recording_reason()` future was dropped
+/// with, or an empty string if it had none.
+pub fn take_recorded_reason() -> String {
+    RECORDED_REASON
+        .take()
+        .map_or_else(String::new, |reason| reason.exception().what().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cxx::KjExceptionType;
+    use kj_rs::{EventLoop, KjPromiseFuture};
+
+    fn assert_reason(reason: &CancellationReason, description: &str) {
+        assert_eq!(reason.exception().what(), description);
+    }
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        assert!(!token.is_canceled());
+        assert!(token.reason().is_none());
+        assert!(token.check().is_ok());
+
+        let clone = token.clone();
+        clone.cancel(CancellationReason::new("first"));
+        token.cancel(CancellationReason::new("second"));
+
+        assert!(token.is_canceled());
+        assert_reason(&token.reason().unwrap(), "first");
+        assert_reason(&token.check().unwrap_err(), "first");
+        assert_eq!(
+            token.reason().unwrap().exception().r#type(),
+            KjExceptionType::Disconnected
+        );
+    }
+
+    #[test]
+    fn test_cancellation_token_from_cpp() {
+        let token = CancellationToken::new();
+        assert!(!ffi::c_is_token_canceled(&token));
+
+        ffi::c_cancel_token(&token, "canceled by C++");
+        assert!(token.is_canceled());
+        assert!(ffi::c_is_token_canceled(&token));
+
+        let reason = token.reason().unwrap();
+        assert_reason(&reason, "canceled by C++");
+        assert_eq!(reason.exception().r#type(), KjExceptionType::Overloaded);
+    }
+    #[test]
+    fn test_cancel_cpp_promise_with_reason() {
+        let _event_loop = EventLoop::new();
+        ffi::c_new_reason_recording_promise().cancel(CancellationReason::with_type(
+            KjExceptionType::Overloaded,
+            "shedding load",
+        ));
+        let reason = ffi::c_take_recorded_reason().expect_err("C++ should see the reason");
+        assert_eq!(reason.r#type(), KjExceptionType::Overloaded);
+        assert_eq!(reason.what(), "shedding load");
+        assert!(CancellationReason::current().is_none());
+
+        // A plain drop has no reason.
+        drop(ffi::c_new_reason_recording_promise());
+        ffi::c_take_recorded_reason().unwrap();
+    }
+}
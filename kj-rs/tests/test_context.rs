@@ -0,0 +1,123 @@
+use crate::Result;
+use kj_rs::{TaskLocal, task_local};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-context.h");
+
+        fn cxx_request_id() -> u64;
+        fn cxx_frame_value() -> u32;
+        async fn cxx_with_request_id(id: u64) -> Result<u64>;
+    }
+
+    extern "Rust" {
+        async fn rust_request_id_later() -> Result<u64>;
+        async fn rust_frame_value_later() -> Result<u32>;
+    }
+}
+
+/// The id of the request a task works for, `kj_rs_demo::RequestId` in C++.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RequestId(pub u64);
+
+impl TaskLocal for RequestId {
+    // "requstid", must match test-context.h.
+    const ID: u64 = 0x7265_7175_7374_6964;
+
+    fn encode(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        Some(Self(u64::from_le_bytes(data.try_into().ok()?)))
+    }
+}
+
+/// Returns `Poll::Pending` once, so that the caller has to wait on the future.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Returns the request id of the current context on a later poll, or 0 if there is none.
+pub async fn rust_request_id_later() -> Result<u64> {
+    YieldOnce(false).await;
+    let id = task_local::<RequestId>().map_or(0, |id| id.0);
+    assert_eq!(id, ffi::cxx_request_id());
+    Ok(id)
+}
+
+/// Returns the value of the C++ test frame on a later poll.
+pub async fn rust_frame_value_later() -> Result<u32> {
+    YieldOnce(false).await;
+    Ok(ffi::cxx_frame_value())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RequestId, YieldOnce, ffi};
+    use kj_rs::{AsyncContext, EventLoop, task_local, with_task_local};
+
+    #[test]
+    fn test_no_task_local() {
+        let event_loop = EventLoop::new();
+        assert_eq!(task_local::<RequestId>(), None);
+        assert_eq!(ffi::cxx_request_id(), 0);
+        let id = event_loop.block_on(super::rust_request_id_later()).unwrap();
+        assert_eq!(id, 0);
+    }
+
+    #[test]
+    fn test_with_task_local() {
+        let event_loop = EventLoop::new();
+        let id = event_loop.block_on(with_task_local(&RequestId(7), async {
+            YieldOnce(false).await;
+            assert_eq!(ffi::cxx_request_id(), 7);
+            task_local::<RequestId>()
+        }));
+        assert_eq!(id, Some(RequestId(7)));
+        assert_eq!(task_local::<RequestId>(), None);
+    }
+
+    #[test]
+    fn test_nested_task_locals() {
+        let event_loop = EventLoop::new();
+        event_loop.block_on(with_task_local(&RequestId(1), async {
+            let inner = with_task_local(&RequestId(2), super::rust_request_id_later()).await;
+            assert_eq!(inner.unwrap(), 2);
+            assert_eq!(task_local::<RequestId>(), Some(RequestId(1)));
+        }));
+    }
+
+    #[test]
+    fn test_context_propagates_to_rust_futures() {
+        // C++ calls a Rust async fn inside a scope it leaves before the future is polled again.
+        let event_loop = EventLoop::new();
+        let id = event_loop.block_on(ffi::cxx_with_request_id(42)).unwrap();
+        assert_eq!(id, 42);
+    }
+
+    #[test]
+    fn test_run_in_context() {
+        let context = AsyncContext::with(&RequestId(3));
+        assert_eq!(context.get::<RequestId>(), Some(RequestId(3)));
+        assert_eq!(context.run(ffi::cxx_request_id), 3);
+        assert_eq!(ffi::cxx_request_id(), 0);
+    }
+}
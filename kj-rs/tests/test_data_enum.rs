@@ -0,0 +1,110 @@
+use kj_rs::KjString;
+
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Position {
+        x: i32,
+        y: i32,
+    }
+
+    /// Generated in C++ as a tagged struct with `is_*()`, `get_*()` and
+    /// `visit()`.
+    #[derive(Clone, Debug, PartialEq)]
+    enum Event {
+        Start,
+        Move(Position),
+        KeyPress(u32),
+        Text(String),
+        Stop,
+    }
+
+    #[repr(u8)]
+    enum Message {
+        Empty = 7,
+        Body(KjString),
+    }
+
+    struct Log {
+        count: u32,
+        last: Event,
+    }
+
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-data-enum.h");
+
+        fn c_describe_event(event: &Event) -> KjString;
+        fn c_make_move(x: i32, y: i32) -> Event;
+        fn c_message_length(message: Message) -> usize;
+        fn c_make_log(text: &str) -> Log;
+    }
+
+    extern "Rust" {
+        fn rust_make_event(kind: u8) -> Event;
+        fn rust_event_x(event: &Event) -> i32;
+        fn rust_make_message(text: &str) -> Message;
+    }
+}
+
+use ffi::{Event, Message, Position};
+
+pub fn rust_make_event(kind: u8) -> Event {
+    match kind {
+        0 => Event::Start,
+        1 => Event::Move(Position { x: 5, y: -5 }),
+        2 => Event::KeyPress(42),
+        3 => Event::Text("from Rust".to_owned()),
+        _ => Event::Stop,
+    }
+}
+
+pub fn rust_event_x(event: &Event) -> i32 {
+    match event {
+        Event::Move(point) => point.x,
+        _ => -1,
+    }
+}
+
+pub fn rust_make_message(text: &str) -> Message {
+    if text.is_empty() {
+        Message::Empty
+    } else {
+        Message::Body(KjString::from(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ffi::{self, Event, Message, Position};
+
+    #[test]
+    fn test_event_from_cxx() {
+        let event = ffi::c_make_move(3, 4);
+        assert_eq!(event, Event::Move(Position { x: 3, y: 4 }));
+        assert_eq!(super::rust_event_x(&event), 3);
+    }
+
+    #[test]
+    fn test_event_to_cxx() {
+        let describe = |event: &Event| ffi::c_describe_event(event).to_str().unwrap().to_owned();
+        assert_eq!(describe(&Event::Start), "start");
+        assert_eq!(describe(&Event::Move(Position { x: 1, y: 2 })), "move 1 2");
+        assert_eq!(describe(&Event::KeyPress(13)), "key 13");
+        assert_eq!(describe(&Event::Text("hi".to_owned())), "text hi");
+        assert_eq!(describe(&Event::Stop), "stop");
+    }
+
+    #[test]
+    fn test_message_drops_payload() {
+        assert_eq!(ffi::c_message_length(Message::Empty), 0);
+        assert_eq!(ffi::c_message_length(super::rust_make_message("four")), 4);
+    }
+
+    #[test]
+    fn test_data_enum_struct_field() {
+        let log = ffi::c_make_log("typed");
+        assert_eq!(log.count, 1);
+        assert_eq!(log.last, Event::Text("typed".to_owned()));
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::future;
+    use std::rc::Rc;
+    use std::task::Poll;
+
+    use kj_rs::EventLoop;
+
+    use crate::ffi;
+
+    // Returns `Pending` once, waking itself, so that other tasks get a chance to run.
+    async fn yield_now() {
+        let mut yielded = false;
+        future::poll_fn(|cx| {
+            if yielded {
+                return Poll::Ready(());
+            }
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await;
+    }
+
+    #[test]
+    fn test_block_on_kj_promise() {
+        let event_loop = EventLoop::new();
+        let value = event_loop.block_on(async { ffi::new_ready_promise_i32(42).await.unwrap() });
+        assert_eq!(value, 42);
+
+        event_loop
+            .block_on(ffi::new_coroutine_promise_void())
+            .unwrap();
+        let err = event_loop
+            .block_on(ffi::new_errored_promise_void())
+            .expect_err("should throw");
+        assert!(err.what().contains("test error"));
+    }
+
+    #[test]
+    fn test_block_on_without_event_loop() {
+        let value = kj_rs::block_on(async { ffi::new_ready_promise_i32(7).await.unwrap() + 1 });
+        assert_eq!(value, 8);
+    }
+
+    #[test]
+    fn test_spawn_local() {
+        let event_loop = EventLoop::new();
+        let done = Rc::new(Cell::new(false));
+        let task_done = done.clone();
+        kj_rs::spawn_local(async move {
+            ffi::new_coroutine_promise_void().await.unwrap();
+            task_done.set(true);
+        });
+        assert!(!done.get());
+
+        event_loop.block_on(async {
+            while !done.get() {
+                yield_now().await;
+            }
+        });
+        assert!(done.get());
+    }
+
+    #[test]
+    #[should_panic(expected = "spawned task failed")]
+    fn test_spawned_task_panic() {
+        let event_loop = EventLoop::new();
+        event_loop.spawn_local(async {
+            yield_now().await;
+            panic!("spawned task failed");
+        });
+        event_loop
+            .block_on(ffi::new_pending_promise_void())
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "nested")]
+    fn test_block_on_panic() {
+        kj_rs::block_on(async {
+            ffi::new_ready_promise_void().await.unwrap();
+            panic!("nested");
+        });
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use kj_rs::{EventLoop, ExecutorGuarded};
+
+    #[test]
+    fn test_executor_guarded() {
+        let _event_loop = EventLoop::new();
+        let mut guarded = ExecutorGuarded::new(Rc::new(42));
+        assert!(guarded.is_current());
+        assert_eq!(**guarded.get(), 42);
+        *guarded.get_mut() = Rc::new(43);
+
+        // Other threads can hold, but not access, the value.
+        let debug = std::thread::scope(|s| {
+            s.spawn(|| {
+                assert!(!guarded.is_current());
+                assert!(guarded.try_get().is_none());
+                format!("{guarded:?}")
+            })
+            .join()
+            .unwrap()
+        });
+        assert_eq!(debug, "ExecutorGuarded(<wrong event loop>)");
+        assert_eq!(*guarded.into_inner(), 43);
+    }
+
+    #[test]
+    fn test_executor_guarded_other_event_loop() {
+        let _event_loop = EventLoop::new();
+        let guarded = ExecutorGuarded::new(Rc::new(1));
+        let guarded = std::thread::spawn(move || {
+            let _event_loop = EventLoop::new();
+            assert!(guarded.try_get().is_none());
+            guarded
+        })
+        .join()
+        .unwrap();
+        assert_eq!(**guarded.get(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "requires a KJ event loop")]
+    fn test_executor_guarded_without_event_loop() {
+        let _ = ExecutorGuarded::new(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "access on wrong event loop")]
+    fn test_executor_guarded_wrong_thread() {
+        let _event_loop = EventLoop::new();
+        let guarded = ExecutorGuarded::new(1);
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let _ = guarded.get();
+            })
+            .join()
+            .map_err(std::panic::resume_unwind)
+            .unwrap();
+        });
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use kj_rs::{EventLoop, KjPromiseFuture, KjString};
+
+    use crate::ffi;
+
+    #[test]
+    fn test_fork_ready_promise() {
+        let event_loop = EventLoop::new();
+        let forked = ffi::new_ready_promise_i32(42).fork();
+        let other = forked.clone();
+        event_loop.block_on(async move {
+            assert_eq!(forked.await.unwrap(), 42);
+            // A branch added after the promise resolved completes as well.
+            assert_eq!(other.clone().await.unwrap(), 42);
+            assert_eq!(other.await.unwrap(), 42);
+        });
+    }
+
+    #[test]
+    fn test_fork_pending_promise() {
+        let event_loop = EventLoop::new();
+        let forked = ffi::new_fulfillable_promise_void().fork();
+        let branches: Vec<_> = (0..3).map(|_| forked.clone()).collect();
+        event_loop.block_on(async move {
+            ffi::fulfill_stored_promise();
+            for branch in branches {
+                branch.await.unwrap();
+            }
+            forked.await.unwrap();
+        });
+    }
+
+    #[test]
+    fn test_fork_errored_promise() {
+        let event_loop = EventLoop::new();
+        let forked = ffi::new_errored_promise_void().fork();
+        let other = forked.clone();
+        event_loop.block_on(async move {
+            let first = forked.await.expect_err("should throw");
+            let second = other.await.expect_err("should throw");
+            assert!(first.what().contains("test error"));
+            assert_eq!(first.what(), second.what());
+        });
+    }
+
+    #[test]
+    fn test_fork_rc_promise() {
+        let event_loop = EventLoop::new();
+        let forked = ffi::new_ready_promise_rc().fork();
+        let other = forked.clone();
+        event_loop.block_on(async move {
+            let first = forked.await.unwrap();
+            let second = other.await.unwrap();
+            // Each branch gets its own reference to the same object.
+            assert_eq!(first.get(), second.get());
+            assert!(first.is_shared());
+            assert_eq!(first.get_data(), 17);
+        });
+    }
+
+    #[test]
+    fn test_fork_arc_promise() {
+        let event_loop = EventLoop::new();
+        let forked = ffi::new_ready_promise_arc().fork();
+        let other = forked.clone();
+        event_loop.block_on(async move {
+            let first = forked.await.unwrap();
+            let second = other.await.unwrap();
+            assert_eq!(first.get(), second.get());
+            assert!(first.is_shared());
+            assert_eq!(second.get_data(), 18);
+        });
+    }
+
+    #[test]
+    fn test_fork_rust_future() {
+        let event_loop = EventLoop::new();
+        let forked = async { Ok(KjString::from("forked")) }.fork();
+        let other = forked.clone();
+        event_loop.block_on(async move {
+            // `kj::String` can't be copied in C++, but the result is cloned in Rust.
+            assert_eq!(forked.await.unwrap(), *"forked");
+            assert_eq!(other.await.unwrap(), *"forked");
+        });
+    }
+}
@@ -0,0 +1,102 @@
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-function.h");
+
+        fn c_call_twice(f: Box<dyn FnMut(u32) -> u32>) -> u32;
+        fn c_call_with_text(f: Box<dyn FnMut(&str) -> Result<u64>>) -> Result<u64>;
+        fn c_make_adder(x: u32) -> Box<dyn FnMut(u32) -> u32>;
+        fn c_make_checked_divider(x: u32) -> Box<dyn FnMut(u32) -> Result<u32>>;
+    }
+
+    extern "Rust" {
+        fn rust_call_with(f: Box<dyn FnMut(u32) -> u32>, x: u32) -> u32;
+        fn rust_call_fallible(f: Box<dyn FnMut() -> Result<()>>) -> Result<()>;
+        fn rust_make_counter() -> Box<dyn FnMut() -> u32>;
+        fn rust_make_parser() -> Box<dyn FnMut(&str) -> Result<u32>>;
+    }
+}
+
+use cxx::{IntoKjException, KjError, KjException, KjExceptionType};
+
+type Parser = Box<dyn FnMut(&str) -> Result<u32, KjException>>;
+
+pub fn rust_call_with(mut f: Box<dyn FnMut(u32) -> u32>, x: u32) -> u32 {
+    f(x)
+}
+
+pub fn rust_call_fallible(
+    mut f: Box<dyn FnMut() -> Result<(), KjException>>,
+) -> Result<(), KjException> {
+    f()
+}
+
+pub fn rust_make_counter() -> Box<dyn FnMut() -> u32> {
+    let mut count = 0;
+    Box::new(move || {
+        count += 1;
+        count
+    })
+}
+
+pub fn rust_make_parser() -> Parser {
+    Box::new(|text| {
+        assert!(!text.is_empty(), "empty input");
+        text.parse().map_err(|err: std::num::ParseIntError| {
+            KjError::new(KjExceptionType::Failed, err.to_string())
+                .into_kj_exception(file!(), line!())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cxx::{IntoKjException, KjError, KjExceptionType};
+
+    use super::ffi;
+
+    #[test]
+    fn test_closure_to_cxx() {
+        let mut calls = Vec::new();
+        let sum = ffi::c_call_twice(Box::new(move |x| {
+            calls.push(x);
+            x * 10 + u32::try_from(calls.len()).unwrap()
+        }));
+        assert_eq!(sum, 11 + 22);
+    }
+
+    #[test]
+    fn test_closure_error_to_cxx() {
+        let len = ffi::c_call_with_text(Box::new(|text| Ok(text.len() as u64))).unwrap();
+        assert_eq!(len, "kj::Function".len() as u64);
+
+        let err = ffi::c_call_with_text(Box::new(|_| {
+            Err(
+                KjError::new(KjExceptionType::Overloaded, "too busy".to_owned())
+                    .into_kj_exception(file!(), line!()),
+            )
+        }))
+        .unwrap_err();
+        assert_eq!(err.r#type(), KjExceptionType::Overloaded);
+        assert!(err.what().contains("too busy"));
+
+        let err = ffi::c_call_with_text(Box::new(|_| panic!("closure panicked"))).unwrap_err();
+        assert!(err.what().contains("closure panicked"));
+    }
+
+    #[test]
+    fn test_closure_from_cxx() {
+        let mut add = ffi::c_make_adder(2);
+        assert_eq!(add(3), 5);
+        assert_eq!(add(40), 42);
+    }
+
+    #[test]
+    fn test_closure_from_cxx_throws() {
+        let mut divide = ffi::c_make_checked_divider(42);
+        assert_eq!(divide(6).unwrap(), 7);
+        let err = divide(0).unwrap_err();
+        assert!(err.what().contains("division by zero"));
+    }
+}
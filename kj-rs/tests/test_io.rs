@@ -0,0 +1,224 @@
+use crate::Result;
+use kj_rs::{
+    AsyncInputStream, AsyncOutputStream, AsyncRead, AsyncWrite, KjInputStream, KjOutputStream,
+    KjOwn,
+};
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    #[namespace = "kj"]
+    unsafe extern "C++" {
+        include!("kj-rs/io.h");
+
+        type AsyncInputStream = kj_rs::AsyncInputStream;
+        type AsyncOutputStream = kj_rs::AsyncOutputStream;
+    }
+
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-io.h");
+
+        // A stream of `data`, whose length is known.
+        fn new_bytes_input_stream(data: &[u8]) -> KjOwn<AsyncInputStream>;
+        // A stream whose reads fail with a DISCONNECTED exception.
+        fn new_disconnected_input_stream() -> KjOwn<AsyncInputStream>;
+    }
+
+    extern "Rust" {
+        async fn read_all(stream: KjOwn<AsyncInputStream>) -> Result<String>;
+        async fn write_all(stream: KjOwn<AsyncOutputStream>, data: String) -> Result<()>;
+        async fn pump_all(
+            input: KjOwn<AsyncInputStream>,
+            output: KjOwn<AsyncOutputStream>,
+        ) -> Result<u64>;
+
+        fn new_rust_input_stream(data: &[u8]) -> KjOwn<AsyncInputStream>;
+        fn new_sized_rust_input_stream(data: &[u8]) -> KjOwn<AsyncInputStream>;
+        fn new_failing_rust_input_stream() -> KjOwn<AsyncInputStream>;
+        fn round_trip_input_stream(stream: KjOwn<AsyncInputStream>) -> KjOwn<AsyncInputStream>;
+    }
+}
+
+/// Yields its data three bytes at a time, returning `Poll::Pending` before each chunk.
+struct ChunkedReader {
+    data: Vec<u8>,
+    pos: usize,
+    yielded: bool,
+}
+
+impl ChunkedReader {
+    fn new(data: &[u8]) -> Self {
+        Self {
+            data: data.to_vec(),
+            pos: 0,
+            yielded: false,
+        }
+    }
+}
+
+impl AsyncRead for ChunkedReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if !this.yielded {
+            this.yielded = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        this.yielded = false;
+        let len = buf.len().min(3).min(this.data.len() - this.pos);
+        buf[..len].copy_from_slice(&this.data[this.pos..this.pos + len]);
+        this.pos += len;
+        Poll::Ready(Ok(len))
+    }
+}
+
+/// Fails every read as if the connection was reset.
+struct FailingReader;
+
+impl AsyncRead for FailingReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Err(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "connection reset by peer",
+        )))
+    }
+}
+
+async fn read(stream: &mut KjInputStream, buf: &mut [u8]) -> Result<usize> {
+    poll_fn(|cx| Pin::new(&mut *stream).poll_read(cx, buf)).await
+}
+
+/// Reads the stream to the end through `AsyncRead`, five bytes at a time.
+pub async fn read_all(stream: KjOwn<AsyncInputStream>) -> Result<String> {
+    let mut stream = KjInputStream::from(stream);
+    let mut data = Vec::new();
+    let mut buf = [0; 5];
+    loop {
+        let n = read(&mut stream, &mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+    String::from_utf8(data).map_err(io::Error::other)
+}
+
+/// Writes `data` through `AsyncWrite`, four bytes at a time, then closes the stream.
+pub async fn write_all(stream: KjOwn<AsyncOutputStream>, data: String) -> Result<()> {
+    let mut stream = KjOutputStream::from(stream);
+    for chunk in data.as_bytes().chunks(4) {
+        let written = poll_fn(|cx| Pin::new(&mut stream).poll_write(cx, chunk)).await?;
+        assert_eq!(written, chunk.len());
+    }
+    poll_fn(|cx| Pin::new(&mut stream).poll_close(cx)).await
+}
+
+/// Reads one byte through `AsyncRead`, then pumps the rest, including the bytes buffered by the
+/// read.
+pub async fn pump_all(
+    input: KjOwn<AsyncInputStream>,
+    output: KjOwn<AsyncOutputStream>,
+) -> Result<u64> {
+    let mut input = KjInputStream::from(input);
+    let mut output = KjOutputStream::from(output);
+    let mut first = [0; 1];
+    let n = read(&mut input, &mut first).await?;
+    let written = poll_fn(|cx| Pin::new(&mut output).poll_write(cx, &first[..n])).await?;
+    let pumped = input.pump_to(&mut output, u64::MAX).await?;
+    Ok(written as u64 + pumped)
+}
+
+pub fn new_rust_input_stream(data: &[u8]) -> KjOwn<AsyncInputStream> {
+    AsyncInputStream::from_reader(ChunkedReader::new(data))
+}
+
+pub fn new_sized_rust_input_stream(data: &[u8]) -> KjOwn<AsyncInputStream> {
+    AsyncInputStream::from_reader_with_length(ChunkedReader::new(data), data.len() as u64)
+}
+
+pub fn new_failing_rust_input_stream() -> KjOwn<AsyncInputStream> {
+    AsyncInputStream::from_reader(FailingReader)
+}
+
+/// Wraps the stream in a `KjInputStream` and converts it back.
+pub fn round_trip_input_stream(stream: KjOwn<AsyncInputStream>) -> KjOwn<AsyncInputStream> {
+    KjInputStream::from(stream).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ffi, read};
+    use cxx::{KjException, KjExceptionType};
+    use kj_rs::{AsyncInputStream, EventLoop, KjInputStream, KjOwn};
+    use std::io;
+
+    #[test]
+    fn test_read_kj_stream() {
+        let event_loop = EventLoop::new();
+        let mut stream = KjInputStream::from(ffi::new_bytes_input_stream(b"hello world"));
+        assert_eq!(stream.try_get_length().unwrap(), Some(11));
+
+        let mut buf = [0; 5];
+        let n = event_loop.block_on(read(&mut stream, &mut buf)).unwrap();
+        assert_eq!(&buf[..n], b"hello");
+        // The rest of the data is buffered, and still counts.
+        assert_eq!(stream.try_get_length().unwrap(), Some(6));
+    }
+
+    #[test]
+    fn test_read_kj_stream_error() {
+        let event_loop = EventLoop::new();
+        let mut stream = KjInputStream::from(ffi::new_disconnected_input_stream());
+        let error = event_loop
+            .block_on(read(&mut stream, &mut [0; 5]))
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+        let exception = error
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<KjException>()
+            .unwrap();
+        assert_eq!(exception.r#type(), KjExceptionType::Disconnected);
+    }
+
+    #[test]
+    fn test_read_rust_reader_through_kj() {
+        let event_loop = EventLoop::new();
+        let stream = super::new_sized_rust_input_stream(b"hello world");
+        let mut stream = KjInputStream::from(stream);
+        assert_eq!(stream.try_get_length().unwrap(), Some(11));
+        let text = event_loop.block_on(async {
+            let mut data = Vec::new();
+            let mut buf = [0; 16];
+            loop {
+                let n = read(&mut stream, &mut buf).await.unwrap();
+                if n == 0 {
+                    break data;
+                }
+                data.extend_from_slice(&buf[..n]);
+            }
+        });
+        assert_eq!(text, b"hello world");
+        assert_eq!(stream.try_get_length().unwrap(), Some(0));
+    }
+
+    #[test]
+    fn test_unbuffered_stream_is_unwrapped() {
+        let stream = ffi::new_bytes_input_stream(b"abc");
+        let ptr = stream.as_ptr();
+        let stream: KjOwn<AsyncInputStream> = KjInputStream::from(stream).into();
+        assert_eq!(stream.as_ptr(), ptr);
+    }
+}
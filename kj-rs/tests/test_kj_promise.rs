@@ -0,0 +1,142 @@
+use crate::Error;
+use crate::Result;
+use kj_rs::{KjForkedPromise, KjMaybe, KjOwnedPromise, KjPromiseFuture, KjString};
+use std::cell::RefCell;
+use std::future::IntoFuture;
+
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    struct PromiseHolder {
+        id: u32,
+        promise: KjOwnedPromise<i32>,
+    }
+
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-kj-promise.h");
+
+        fn new_kj_promise_i32(value: i32) -> KjOwnedPromise<i32>;
+        fn new_errored_kj_promise_void() -> KjOwnedPromise<()>;
+        fn new_promise_holder(id: u32, value: i32) -> PromiseHolder;
+        fn new_maybe_kj_promise_i32(some: bool, value: i32) -> KjMaybe<KjOwnedPromise<i32>>;
+    }
+
+    extern "Rust" {
+        async fn add_kj_promises(a: KjOwnedPromise<i32>, b: KjOwnedPromise<i32>) -> Result<i32>;
+        fn store_kj_promise(promise: KjOwnedPromise<i32>);
+        async fn await_stored_kj_promise() -> Result<i32>;
+        async fn await_maybe_kj_promise(maybe: KjMaybe<KjOwnedPromise<i32>>) -> Result<i32>;
+
+        fn new_forked_kj_promise_i32(value: i32) -> KjForkedPromise<i32>;
+        fn new_forked_kj_promise_string(value: &str) -> KjForkedPromise<KjString>;
+        fn fork_kj_promise(promise: KjOwnedPromise<i32>) -> KjForkedPromise<i32>;
+    }
+}
+
+thread_local! {
+    static STORED: RefCell<Option<KjOwnedPromise<i32>>> = const { RefCell::new(None) };
+}
+
+pub async fn add_kj_promises(a: KjOwnedPromise<i32>, b: KjOwnedPromise<i32>) -> Result<i32> {
+    let a = a.await.map_err(Error::other)?;
+    let b = b.await.map_err(Error::other)?;
+    Ok(a + b)
+}
+
+pub fn store_kj_promise(promise: KjOwnedPromise<i32>) {
+    STORED.with_borrow_mut(|stored| *stored = Some(promise));
+}
+
+pub async fn await_stored_kj_promise() -> Result<i32> {
+    let promise = STORED
+        .with_borrow_mut(Option::take)
+        .ok_or_else(|| Error::other("no stored promise"))?;
+    promise.await.map_err(Error::other)
+}
+
+/// Resolves to -1 when `maybe` is none.
+pub async fn await_maybe_kj_promise(maybe: KjMaybe<KjOwnedPromise<i32>>) -> Result<i32> {
+    match Option::<KjOwnedPromise<i32>>::from(maybe) {
+        Some(promise) => promise.await.map_err(Error::other),
+        None => Ok(-1),
+    }
+}
+
+pub fn new_forked_kj_promise_i32(value: i32) -> KjForkedPromise<i32> {
+    KjForkedPromise::new(std::future::ready(Ok(value)))
+}
+
+/// `kj::String` can't be copied in C++, but each branch gets its own clone of the Rust value.
+pub fn new_forked_kj_promise_string(value: &str) -> KjForkedPromise<KjString> {
+    KjForkedPromise::new(std::future::ready(Ok(KjString::from(value))))
+}
+
+/// Forks a C++ promise in Rust, and hands the branches back to C++.
+pub fn fork_kj_promise(promise: KjOwnedPromise<i32>) -> KjForkedPromise<i32> {
+    promise.into_future().fork().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ffi;
+    use kj_rs::{EventLoop, KjOwnedPromise, KjPromiseFuture};
+    use std::future::IntoFuture;
+
+    #[test]
+    fn test_await_kj_promise() {
+        let event_loop = EventLoop::new();
+        let promise = ffi::new_kj_promise_i32(42);
+        assert_eq!(event_loop.block_on(promise.into_future()).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_await_errored_kj_promise() {
+        let event_loop = EventLoop::new();
+        let err = event_loop
+            .block_on(ffi::new_errored_kj_promise_void().into_future())
+            .expect_err("should throw");
+        assert!(err.what().contains("test error"));
+    }
+
+    #[test]
+    fn test_drop_kj_promise_without_awaiting() {
+        let _event_loop = EventLoop::new();
+        drop(ffi::new_kj_promise_i32(1));
+    }
+
+    #[test]
+    fn test_kj_promise_in_struct() {
+        let event_loop = EventLoop::new();
+        let holder = ffi::new_promise_holder(7, 42);
+        assert_eq!(holder.id, 7);
+        assert_eq!(
+            event_loop.block_on(holder.promise.into_future()).unwrap(),
+            42
+        );
+    }
+
+    #[test]
+    fn test_kj_promise_in_maybe() {
+        let event_loop = EventLoop::new();
+        let some = ffi::new_maybe_kj_promise_i32(true, 42);
+        assert!(some.is_some());
+        let promise: Option<KjOwnedPromise<i32>> = some.into();
+        assert_eq!(
+            event_loop.block_on(promise.unwrap().into_future()).unwrap(),
+            42
+        );
+
+        assert!(ffi::new_maybe_kj_promise_i32(false, 42).is_none());
+    }
+
+    #[test]
+    fn test_fork_kj_promise() {
+        let event_loop = EventLoop::new();
+        let forked = ffi::new_kj_promise_i32(42).into_future().fork();
+        let other = forked.clone();
+        event_loop.block_on(async move {
+            assert_eq!(forked.await.unwrap(), 42);
+            assert_eq!(other.await.unwrap(), 42);
+        });
+    }
+}
@@ -0,0 +1,129 @@
+use kj_rs::KjString;
+
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    #[derive(Debug, PartialEq)]
+    struct Size {
+        width: u32,
+        height: u32,
+    }
+
+    /// Corresponds to `kj::OneOf<Size, double>`.
+    #[cxx::one_of]
+    #[derive(Debug, PartialEq)]
+    enum Shape {
+        Rectangle(Size),
+        Circle(f64),
+    }
+
+    #[cxx::one_of]
+    enum Body {
+        Text(KjString),
+        Length(u64),
+    }
+
+    struct Labeled {
+        label: KjString,
+        shape: Shape,
+    }
+
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-one-of.h");
+
+        fn c_shape_area(shape: &Shape) -> f64;
+        fn c_make_circle(radius: f64) -> Shape;
+        fn c_make_text_body(text: &str) -> Body;
+        fn c_body_length(body: Body) -> u64;
+        fn c_make_labeled(label: &str, width: u32, height: u32) -> Labeled;
+    }
+
+    extern "Rust" {
+        fn rust_make_shape(circle: bool) -> Shape;
+        fn rust_shape_area(shape: Shape) -> f64;
+        fn rust_body_text(body: &Body) -> String;
+        fn rust_make_body(text: &str) -> Body;
+    }
+}
+
+use ffi::{Body, Shape, Size};
+
+pub fn rust_make_shape(circle: bool) -> Shape {
+    if circle {
+        Shape::Circle(2.0)
+    } else {
+        Size {
+            width: 3,
+            height: 4,
+        }
+        .into()
+    }
+}
+
+pub fn rust_shape_area(shape: Shape) -> f64 {
+    match shape {
+        Shape::Rectangle(size) => f64::from(size.width * size.height),
+        Shape::Circle(radius) => std::f64::consts::PI * radius * radius,
+    }
+}
+
+pub fn rust_body_text(body: &Body) -> String {
+    match body {
+        Body::Text(text) => String::from_utf8_lossy(text.as_bytes()).into_owned(),
+        Body::Length(length) => format!("<{length} bytes>"),
+    }
+}
+
+pub fn rust_make_body(text: &str) -> Body {
+    if text.is_empty() {
+        Body::Length(0)
+    } else {
+        KjString::from(text).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ffi::{self, Body, Shape, Size};
+
+    #[test]
+    fn test_shape_from_cxx() {
+        let shape = ffi::c_make_circle(1.5);
+        assert_eq!(shape, Shape::Circle(1.5));
+
+        let shape = Shape::Rectangle(Size {
+            width: 2,
+            height: 5,
+        });
+        assert!((ffi::c_shape_area(&shape) - 10.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_body_drops_variant() {
+        let body = ffi::c_make_text_body("hello");
+        match &body {
+            Body::Text(text) => assert_eq!(text.to_str().unwrap(), "hello"),
+            Body::Length(_) => panic!("expected text"),
+        }
+        // Dropping the enum drops the kj::String through C++.
+        drop(body);
+
+        let length = ffi::c_body_length(Body::Length(42));
+        assert_eq!(length, 42);
+        let length = ffi::c_body_length(super::rust_make_body("four"));
+        assert_eq!(length, 4);
+    }
+
+    #[test]
+    fn test_one_of_struct_field() {
+        let labeled = ffi::c_make_labeled("box", 1, 2);
+        assert_eq!(labeled.label.to_str().unwrap(), "box");
+        assert_eq!(
+            labeled.shape,
+            Shape::Rectangle(Size {
+                width: 1,
+                height: 2
+            })
+        );
+    }
+}
@@ -1,5 +1,26 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use crate::ffi;
 
+/// A Rust value counting its drops, to check that C++ owners drop it with Rust's drop.
+pub struct RustValue {
+    value: u64,
+    drops: Arc<AtomicUsize>,
+}
+
+impl RustValue {
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+impl Drop for RustValue {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
 // Send and Sync are explicity opt-in when using cxx.
 // # Safety
 // The type `OqaqueCxxClass` contains no raw pointers or interior mutability.
@@ -12,9 +33,22 @@ unsafe impl Sync for ffi::OpaqueCxxClass {}
 #[cfg(test)]
 pub mod tests {
     use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
+    use cxx::UniquePtr;
+    use kj_rs::KjOwn;
+
+    use super::RustValue;
     use crate::ffi;
 
+    fn new_rust_value(value: u64, drops: &Arc<AtomicUsize>) -> RustValue {
+        RustValue {
+            value,
+            drops: drops.clone(),
+        }
+    }
+
     #[test]
     fn kj_own() {
         let mut own = ffi::cxx_kj_own();
@@ -72,6 +106,61 @@ pub mod tests {
         ffi::modify_own_return_test();
     }
 
+    #[test]
+    fn test_own_vec() {
+        let mut owns = ffi::cxx_kj_own_vec();
+        let data: Vec<u64> = owns.iter().map(|own| own.get_data()).collect();
+        assert_eq!(data, [1, 2, 3]);
+
+        // Elements dropped by Rust are disposed by C++.
+        owns.pop();
+        assert_eq!(ffi::kj_own_vec_disposed(), 1);
+
+        owns.push(ffi::cxx_kj_own());
+        assert_eq!(ffi::take_kj_own_vec(owns), 45);
+        assert_eq!(ffi::kj_own_vec_disposed(), 3);
+    }
+
+    #[test]
+    fn test_own_rust_type() {
+        let drops = Arc::new(AtomicUsize::new(0));
+
+        let own = KjOwn::from(Box::new(new_rust_value(1, &drops)));
+        assert_eq!(own.value(), 1);
+        drop(own);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+
+        // Disposed by C++.
+        let own = KjOwn::from(Box::new(new_rust_value(2, &drops)));
+        assert_eq!(ffi::take_rust_value_own(own), 2);
+        assert_eq!(drops.load(Ordering::SeqCst), 2);
+
+        // Created by C++ from a `rust::Box`.
+        let own = ffi::rust_value_box_to_own(Box::new(new_rust_value(3, &drops)));
+        assert_eq!(own.value(), 3);
+        drop(own);
+        assert_eq!(drops.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_unique_ptr_and_shared_ptr_rust_type() {
+        let drops = Arc::new(AtomicUsize::new(0));
+
+        let unique = UniquePtr::from(Box::new(new_rust_value(1, &drops)));
+        assert_eq!(unique.value(), 1);
+        drop(unique);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+
+        let shared =
+            ffi::rust_value_to_shared(UniquePtr::from(Box::new(new_rust_value(2, &drops))));
+        let clone = shared.clone();
+        assert_eq!(clone.value(), 2);
+        drop(shared);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+        drop(clone);
+        assert_eq!(drops.load(Ordering::SeqCst), 2);
+    }
+
     #[test]
     fn test_primitive() {
         let own = ffi::own_integer();
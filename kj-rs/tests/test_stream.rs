@@ -0,0 +1,131 @@
+use crate::Error;
+use crate::Result;
+use kj_rs::{KjStream, Stream};
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-stream.h");
+
+        fn new_counting_stream(count: i32) -> KjStream<i32>;
+        fn new_errored_stream() -> KjStream<i32>;
+        fn new_kj_string_stream() -> KjStream<KjString>;
+    }
+
+    extern "Rust" {
+        fn new_range_stream(count: i32) -> KjStream<i32>;
+        fn new_yielding_stream(count: i32) -> KjStream<i32>;
+        fn new_failing_stream() -> KjStream<i32>;
+        fn new_kj_string_rust_stream() -> KjStream<KjString>;
+
+        async fn sum_counting_stream(count: i32) -> Result<i64>;
+        async fn take_from_errored_stream() -> Result<()>;
+        async fn join_kj_string_stream() -> Result<String>;
+        async fn drop_counting_stream_early() -> Result<()>;
+    }
+}
+
+/// Yields the items of an iterator, like `futures::stream::iter`.
+struct IterStream<I>(I);
+
+impl<I: Iterator + Unpin> Stream for IterStream<I> {
+    type Item = I::Item;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().0.next())
+    }
+}
+
+/// Returns `Poll::Pending` before every item, waking itself right away.
+struct YieldingStream {
+    next: i32,
+    count: i32,
+    yielded: bool,
+}
+
+impl Stream for YieldingStream {
+    type Item = Result<i32>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.next == this.count {
+            return Poll::Ready(None);
+        }
+        if !this.yielded {
+            this.yielded = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        this.yielded = false;
+        this.next += 1;
+        Poll::Ready(Some(Ok(this.next)))
+    }
+}
+
+pub fn new_range_stream(count: i32) -> impl Stream<Item = Result<i32>> {
+    IterStream((1..=count).map(Ok))
+}
+
+pub fn new_yielding_stream(count: i32) -> impl Stream<Item = Result<i32>> {
+    YieldingStream {
+        next: 0,
+        count,
+        yielded: false,
+    }
+}
+
+pub fn new_failing_stream() -> impl Stream<Item = Result<i32>> {
+    IterStream([Ok(1), Err(Error::other("failing stream error")), Ok(3)].into_iter())
+}
+
+pub fn new_kj_string_rust_stream() -> impl Stream<Item = Result<kj_rs::KjString>> {
+    IterStream(
+        ["foo", "bar"]
+            .into_iter()
+            .map(|s| Ok(kj_rs::KjString::from(s))),
+    )
+}
+
+async fn next<T>(stream: &mut KjStream<T>) -> Option<std::result::Result<T, cxx::KjException>> {
+    poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+pub async fn sum_counting_stream(count: i32) -> Result<i64> {
+    let mut stream = ffi::new_counting_stream(count);
+    let mut sum = 0;
+    while let Some(item) = next(&mut stream).await {
+        sum += i64::from(item.map_err(Error::other)?);
+    }
+    // The end of the stream is sticky.
+    assert!(next(&mut stream).await.is_none());
+    Ok(sum)
+}
+
+pub async fn take_from_errored_stream() -> Result<()> {
+    let mut stream = ffi::new_errored_stream();
+    assert_eq!(next(&mut stream).await.unwrap().unwrap(), 1);
+    next(&mut stream).await.unwrap().map_err(Error::other)?;
+    unreachable!("the second item is an error");
+}
+
+pub async fn join_kj_string_stream() -> Result<String> {
+    let mut stream = ffi::new_kj_string_stream();
+    let mut items = Vec::new();
+    while let Some(item) = next(&mut stream).await {
+        let item = item.map_err(Error::other)?;
+        items.push(item.to_str().map_err(Error::other)?.to_owned());
+    }
+    Ok(items.join(","))
+}
+
+pub async fn drop_counting_stream_early() -> Result<()> {
+    let mut stream = ffi::new_counting_stream(10);
+    assert_eq!(next(&mut stream).await.unwrap().unwrap(), 1);
+    // Dropping a stream with items left must not leak or crash.
+    drop(stream);
+    Ok(())
+}
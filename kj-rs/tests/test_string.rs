@@ -0,0 +1,110 @@
+use kj_rs::{KjString, KjStringPtr};
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-string.h");
+
+        fn c_return_kj_string() -> KjString;
+        fn c_return_empty_kj_string() -> KjString;
+        fn c_return_non_utf8_kj_string() -> KjString;
+        fn c_return_kj_string_ptr() -> KjStringPtr<'static>;
+        fn c_take_kj_string(s: KjString);
+        fn c_take_kj_string_ref(s: &KjString) -> usize;
+        fn c_take_kj_string_ptr(s: KjStringPtr);
+    }
+
+    extern "Rust" {
+        // Rust functions that C++ can call for KjString testing
+        fn r_return_kj_string() -> KjString;
+        fn r_take_kj_string(s: KjString);
+        fn r_take_kj_string_ptr(s: KjStringPtr) -> usize;
+        fn r_roundtrip_kj_string(s: KjString) -> KjString;
+    }
+}
+
+pub fn r_return_kj_string() -> KjString {
+    KjString::from("hello from rust")
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn r_take_kj_string(s: KjString) {
+    assert_eq!(s.to_str().unwrap(), "hello from c++");
+}
+
+pub fn r_take_kj_string_ptr(s: KjStringPtr) -> usize {
+    s.len()
+}
+
+pub fn r_roundtrip_kj_string(s: KjString) -> KjString {
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kj_string_from_str() {
+        let s = KjString::from("hello");
+        assert_eq!(s.as_bytes(), b"hello");
+        assert_eq!(s.to_str().unwrap(), "hello");
+        assert_eq!(&s, "hello");
+        assert_eq!(s.len(), 5);
+    }
+
+    #[test]
+    fn test_kj_string_default() {
+        let s = KjString::default();
+        assert!(s.is_empty());
+        assert_eq!(s.to_str().unwrap(), "");
+        assert_eq!(s.as_string_ptr().to_str().unwrap(), "");
+    }
+
+    #[test]
+    fn test_kj_string_clone() {
+        let s = KjString::from("cloned");
+        let clone = s.clone();
+        assert_eq!(s, clone);
+        assert_ne!(s.as_ptr(), clone.as_ptr());
+    }
+
+    #[test]
+    fn test_kj_string_debug() {
+        let s = KjString::from("debug");
+        assert_eq!(format!("{s:?}"), "\"debug\"");
+    }
+
+    #[test]
+    fn test_kj_string_from_cpp() {
+        let s = ffi::c_return_kj_string();
+        assert_eq!(s.to_str().unwrap(), "hello from c++");
+
+        let empty = ffi::c_return_empty_kj_string();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_kj_string_non_utf8() {
+        let s = ffi::c_return_non_utf8_kj_string();
+        assert_eq!(s.as_bytes(), b"\xff\xfe");
+        assert!(s.to_str().is_err());
+    }
+
+    #[test]
+    fn test_kj_string_ptr_from_cpp() {
+        let s = ffi::c_return_kj_string_ptr();
+        assert_eq!(s.to_str().unwrap(), "static string");
+        assert_eq!(&s, "static string");
+    }
+
+    #[test]
+    fn test_kj_string_to_cpp() {
+        ffi::c_take_kj_string(KjString::from("hello from rust"));
+
+        let s = KjString::from("borrowed");
+        assert_eq!(ffi::c_take_kj_string_ref(&s), 8);
+        ffi::c_take_kj_string_ptr(KjString::from("hello from rust").as_string_ptr());
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use kj_rs::{EventLoop, KjDate};
+
+    use crate::ffi;
+
+    #[test]
+    fn test_sleep() {
+        let event_loop = EventLoop::with_io();
+        let start = Instant::now();
+        event_loop.block_on(kj_rs::sleep(Duration::from_millis(10)));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+
+        // A date in the past completes right away.
+        event_loop.block_on(kj_rs::sleep_until(KjDate::unix_epoch()));
+    }
+
+    #[test]
+    fn test_mock_sleep() {
+        let event_loop = EventLoop::with_mock_timer();
+        let sleep = kj_rs::sleep(Duration::from_secs(10));
+        event_loop.advance_mock_time(Duration::from_secs(10));
+        event_loop.block_on(sleep);
+
+        // The mock calendar clock starts at the Unix epoch.
+        let date = KjDate::from(15_000_000_000);
+        let sleep = kj_rs::sleep_until(date);
+        event_loop.advance_mock_time(Duration::from_secs(5));
+        event_loop.block_on(sleep);
+    }
+
+    #[test]
+    fn test_mock_sleep_duration_max() {
+        let event_loop = EventLoop::with_mock_timer();
+        // `Duration::MAX` saturates rather than wrapping around to a deadline in the past.
+        let sleep = kj_rs::timeout(kj_rs::sleep(Duration::MAX), Duration::from_secs(1));
+        event_loop.advance_mock_time(Duration::from_secs(1));
+        event_loop.block_on(sleep).expect_err("should time out");
+
+        let sleep = kj_rs::sleep(Duration::MAX);
+        event_loop.advance_mock_time(Duration::MAX);
+        event_loop.block_on(sleep);
+
+        // The mock timer stays at the latest time point rather than overflowing.
+        event_loop.advance_mock_time(Duration::MAX);
+        event_loop.block_on(kj_rs::sleep(Duration::MAX));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let event_loop = EventLoop::with_mock_timer();
+        let value = event_loop.block_on(kj_rs::timeout(
+            ffi::new_ready_promise_i32(42),
+            Duration::from_secs(1),
+        ));
+        assert_eq!(value.unwrap().unwrap(), 42);
+
+        let pending = kj_rs::timeout(ffi::new_pending_promise_void(), Duration::from_secs(1));
+        event_loop.advance_mock_time(Duration::from_secs(1));
+        let elapsed = event_loop.block_on(pending).expect_err("should time out");
+        assert_eq!(elapsed.to_string(), "operation timed out");
+    }
+
+    #[test]
+    #[should_panic(expected = "no kj_rs::TimerScope on this thread")]
+    fn test_sleep_without_timer() {
+        let _event_loop = EventLoop::new();
+        let _sleep = kj_rs::sleep(Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::Error;
+use crate::Result;
+use kj_rs::KjOwn;
+
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    /// A key-value store implemented in C++.
+    trait KvStore {
+        /// Fails if `key` is not in the store.
+        async fn get(&self, key: u32) -> Result<u64>;
+        async fn put(self: Pin<&mut Self>, key: u32, value: u64) -> Result<()>;
+        fn size(&self) -> usize;
+    }
+
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-trait.h");
+
+        fn new_memory_kv_store() -> KjOwn<CxxKvStore>;
+    }
+
+    extern "Rust" {
+        async fn copy_kv_value(store: KjOwn<CxxKvStore>, from: u32, to: u32) -> Result<u64>;
+    }
+}
+
+pub use ffi::KvStore;
+
+/// Copies the value of `from` to `to` through the Rust trait, and returns it.
+pub async fn copy_kv_value(store: KjOwn<ffi::CxxKvStore>, from: u32, to: u32) -> Result<u64> {
+    let mut store = store;
+    let value = KvStore::get(&store, from).await.map_err(Error::other)?;
+    KvStore::put(std::pin::Pin::new(&mut store), to, value)
+        .await
+        .map_err(Error::other)?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KvStore;
+    use super::ffi;
+    use kj_rs::EventLoop;
+    use std::pin::Pin;
+
+    #[test]
+    fn test_trait_object() {
+        let event_loop = EventLoop::new();
+        let mut store: Pin<Box<dyn KvStore>> = ffi::new_memory_kv_store().into();
+        assert_eq!(store.size(), 0);
+        event_loop.block_on(async {
+            store.as_mut().put(1, 42).await.unwrap();
+            assert_eq!(store.get(1).await.unwrap(), 42);
+        });
+        assert_eq!(store.size(), 1);
+    }
+
+    #[test]
+    fn test_trait_error() {
+        let event_loop = EventLoop::new();
+        let store: Box<dyn KvStore> = ffi::new_memory_kv_store().into();
+        let err = event_loop.block_on(store.get(1)).expect_err("should throw");
+        assert!(err.what().contains("key not found"));
+    }
+
+    #[test]
+    fn test_trait_generic() {
+        async fn put_all(mut store: Pin<&mut impl KvStore>, values: &[u64]) {
+            for (key, &value) in (0..).zip(values) {
+                store.as_mut().put(key, value).await.unwrap();
+            }
+        }
+
+        let event_loop = EventLoop::new();
+        let mut store = ffi::new_memory_kv_store();
+        event_loop.block_on(put_all(store.pin_mut(), &[1, 2, 3]));
+        assert_eq!(KvStore::size(&*store), 3);
+    }
+
+    #[test]
+    fn test_rust_calls_through_trait() {
+        let event_loop = EventLoop::new();
+        let mut store = ffi::new_memory_kv_store();
+        event_loop.block_on(async {
+            store.pin_mut().put(1, 42).await.unwrap();
+        });
+        let value = event_loop.block_on(super::copy_kv_value(store, 1, 2));
+        assert_eq!(value.unwrap(), 42);
+    }
+}
@@ -0,0 +1,74 @@
+#[allow(dead_code)]
+#[cxx::bridge(namespace = "kj_rs_demo")]
+mod ffi {
+    #[derive(Debug, PartialEq)]
+    struct ParseError {
+        position: u32,
+        reason: String,
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum NameError {
+        Empty,
+        TooLong,
+    }
+
+    unsafe extern "C++" {
+        include!("kj-rs-demo/test-typed-error.h");
+
+        fn c_describe_port(text: &str) -> KjString;
+    }
+
+    extern "Rust" {
+        /// Returned to C++ as `kj_rs::Result<uint16_t, ParseError>`.
+        fn rust_parse_port(text: &str) -> Result<u16, ParseError>;
+        fn rust_check_name(name: &str) -> Result<(), NameError>;
+        fn rust_greet(name: &str) -> Result<String, NameError>;
+        fn rust_panic_typed() -> Result<u32, ParseError>;
+    }
+}
+
+use ffi::{NameError, ParseError};
+
+pub fn rust_parse_port(text: &str) -> Result<u16, ParseError> {
+    if let Some(position) = text.find(|c: char| !c.is_ascii_digit()) {
+        return Err(ParseError {
+            position: position as u32,
+            reason: "not a digit".to_owned(),
+        });
+    }
+    text.parse().map_err(|_| ParseError {
+        position: 0,
+        reason: "out of range".to_owned(),
+    })
+}
+
+pub fn rust_check_name(name: &str) -> Result<(), NameError> {
+    match name.len() {
+        0 => Err(NameError::Empty),
+        1..=8 => Ok(()),
+        _ => Err(NameError::TooLong),
+    }
+}
+
+pub fn rust_greet(name: &str) -> Result<String, NameError> {
+    rust_check_name(name)?;
+    Ok(format!("hello {name}"))
+}
+
+pub fn rust_panic_typed() -> Result<u32, ParseError> {
+    panic!("not a typed error");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ffi;
+
+    #[test]
+    fn test_typed_error_to_cxx() {
+        let describe = |text: &str| ffi::c_describe_port(text).to_str().unwrap().to_owned();
+        assert_eq!(describe("8080"), "port 8080");
+        assert_eq!(describe("80a"), "error at 2: not a digit");
+        assert_eq!(describe("99999"), "error at 0: out of range");
+    }
+}
@@ -0,0 +1,27 @@
+#include "test-trait.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("Rust calls a C++ implementation of a trait") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  auto store = kj::heap<MemoryKvStore>();
+  store->put(1, 42).wait(waitScope);
+
+  KJ_EXPECT(copy_kv_value(kj::mv(store), 1, 2).wait(waitScope) == 42);
+}
+
+KJ_TEST("Rust propagates exceptions from a trait method") {
+  kj::EventLoop loop;
+  kj::WaitScope waitScope(loop);
+
+  KJ_EXPECT_THROW_MESSAGE(
+      "key not found", copy_kv_value(new_memory_kv_store(), 1, 2).wait(waitScope));
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
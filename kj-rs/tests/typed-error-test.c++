@@ -0,0 +1,37 @@
+#include "test-typed-error.h"
+
+#include <kj/test.h>
+
+namespace kj_rs_demo {
+namespace {
+
+KJ_TEST("typed error returned from Rust") {
+  auto ok = rust_parse_port("443");
+  KJ_EXPECT(ok.isOk());
+  KJ_EXPECT(ok.get() == 443);
+
+  auto err = rust_parse_port("4x3");
+  KJ_EXPECT(err.isErr());
+  KJ_EXPECT(err.tryGet() == kj::none);
+  ParseError error = kj::mv(err).getError();
+  KJ_EXPECT(error.position == 1);
+  KJ_EXPECT(error.reason == "not a digit");
+}
+
+KJ_TEST("typed error with a void value") {
+  KJ_EXPECT(rust_check_name("kenton").isOk());
+  KJ_EXPECT(rust_check_name("").getError() == NameError::Empty);
+  KJ_EXPECT(rust_check_name("much too long").getError() == NameError::TooLong);
+}
+
+KJ_TEST("typed error with a Rust string value") {
+  KJ_EXPECT(rust_greet("kj").get() == "hello kj");
+  KJ_EXPECT(rust_greet("").getError() == NameError::Empty);
+}
+
+KJ_TEST("panic in a function with a typed error is still thrown") {
+  KJ_EXPECT_THROW_MESSAGE("not a typed error", rust_panic_typed());
+}
+
+}  // namespace
+}  // namespace kj_rs_demo
@@ -0,0 +1,64 @@
+#include "kj-rs/timer.h"
+
+#include "kj-rs/date.h"
+
+#include <kj/debug.h>
+
+namespace kj_rs {
+
+namespace {
+thread_local TimerScope* currentScope = nullptr;
+}
+
+TimerScope::TimerScope(kj::Timer& timer, const kj::Clock& clock)
+    : timer(timer),
+      clock(clock),
+      previous(currentScope) {
+  currentScope = this;
+}
+
+TimerScope::~TimerScope() noexcept(false) {
+  KJ_REQUIRE(currentScope == this, "TimerScopes must be destroyed in reverse order of creation");
+  currentScope = previous;
+}
+
+TimerScope& TimerScope::current() {
+  KJ_REQUIRE(currentScope != nullptr, "no kj_rs::TimerScope on this thread");
+  return *currentScope;
+}
+
+kj::Promise<void> TimerScope::afterDelay(kj::Duration delay) {
+  return timer.atTime(saturatingDeadline(timer.now(), delay));
+}
+
+kj::Promise<void> TimerScope::atDate(kj::Date date) {
+  auto now = clock.now();
+  return afterDelay(date > now ? date - now : 0 * kj::NANOSECONDS);
+}
+
+kj::TimePoint saturatingDeadline(kj::TimePoint now, kj::Duration delay) {
+  auto latest =
+      kj::origin<kj::TimePoint>() + std::numeric_limits<std::int64_t>::max() * kj::NANOSECONDS;
+  return delay > latest - now ? latest : now + delay;
+}
+
+}  // namespace kj_rs
+
+extern "C" {
+
+::rust::repr::Result cxxbridge$kjrs$timer$after_delay(
+    std::int64_t nanoseconds, kj_rs::repr::KjPromiseNodeImpl* out) noexcept {
+  return ::rust::repr::Result::run([&]() {
+    new (out) kj_rs::repr::KjPromiseNodeImpl(
+        kj_rs::TimerScope::current().afterDelay(nanoseconds * kj::NANOSECONDS));
+  });
+}
+
+::rust::repr::Result cxxbridge$kjrs$timer$at_date(
+    std::int64_t nanoseconds, kj_rs::repr::KjPromiseNodeImpl* out) noexcept {
+  return ::rust::repr::Result::run([&]() {
+    new (out) kj_rs::repr::KjPromiseNodeImpl(
+        kj_rs::TimerScope::current().atDate(kj_rs::repr::fromNanos(nanoseconds)));
+  });
+}
+}
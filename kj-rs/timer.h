@@ -0,0 +1,52 @@
+#pragma once
+
+#include "kj-rs/promise.h"
+
+#include <rust/cxx.h>
+
+#include <kj/timer.h>
+
+#include <cstdint>
+#include <limits>
+
+namespace kj_rs {
+
+// Makes `timer` the timer of Rust's `kj_rs::sleep()`, `sleep_until()` and `timeout()` on the
+// current thread, until destroyed. `clock` is the calendar clock `sleep_until()` measures dates
+// against. Scopes nest: destroying one restores the timer of the enclosing scope.
+class TimerScope {
+ public:
+  explicit TimerScope(
+      kj::Timer& timer, const kj::Clock& clock = kj::systemPreciseCalendarClock());
+  ~TimerScope() noexcept(false);
+  KJ_DISALLOW_COPY_AND_MOVE(TimerScope);
+
+  // The innermost scope on the current thread. Throws if there is none.
+  static TimerScope& current();
+
+  kj::Promise<void> afterDelay(kj::Duration delay);
+  kj::Promise<void> atDate(kj::Date date);
+
+ private:
+  kj::Timer& timer;
+  const kj::Clock& clock;
+  TimerScope* previous;
+};
+
+// `now + delay`, saturated to the latest time point `kj::TimePoint` can hold instead of overflowing
+// into the past.
+kj::TimePoint saturatingDeadline(kj::TimePoint now, kj::Duration delay);
+
+}  // namespace kj_rs
+
+extern "C" {
+
+// Constructs the promise of `TimerScope::current().afterDelay()` into `out`.
+::rust::repr::Result cxxbridge$kjrs$timer$after_delay(
+    std::int64_t nanoseconds, kj_rs::repr::KjPromiseNodeImpl* out) noexcept;
+
+// Constructs the promise of `TimerScope::current().atDate()` into `out`. The date is in
+// nanoseconds since the Unix epoch, like `KjDate`.
+::rust::repr::Result cxxbridge$kjrs$timer$at_date(
+    std::int64_t nanoseconds, kj_rs::repr::KjPromiseNodeImpl* out) noexcept;
+}
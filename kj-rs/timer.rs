@@ -0,0 +1,133 @@
+//! Sleeps and timeouts for futures running on a KJ event loop, backed by `kj::Timer`.
+//!
+//! The timer is the one of the innermost `kj_rs::TimerScope` on the current thread: the timer of
+//! an [`crate::EventLoop`] created from Rust with [`crate::EventLoop::with_io`], or one installed
+//! by the C++ code running the event loop. An event loop created with
+//! [`crate::EventLoop::with_mock_timer`] lets tests control time.
+
+use std::fmt::{self, Display};
+use std::future::Future;
+use std::mem::MaybeUninit;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use cxx::{KjError, KjExceptionType};
+
+use crate::KjDate;
+use crate::KjPromiseNodeImpl;
+use crate::promise::{CallbacksFuture, PromiseFuture, callbacks_promise_future};
+
+unsafe extern "C" {
+    #[link_name = "cxxbridge$kjrs$timer$after_delay"]
+    fn timer_after_delay(nanoseconds: i64, out: *mut KjPromiseNodeImpl) -> cxx::private::Result;
+
+    #[link_name = "cxxbridge$kjrs$timer$at_date"]
+    fn timer_at_date(nanoseconds: i64, out: *mut KjPromiseNodeImpl) -> cxx::private::Result;
+}
+
+/// The future returned by [`sleep`] and [`sleep_until`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Sleep(PromiseFuture<CallbacksFuture<()>>);
+
+impl Sleep {
+    #[cfg_attr(feature = "instrumentation", track_caller)]
+    fn new(start: impl FnOnce(*mut KjPromiseNodeImpl) -> cxx::private::Result) -> Self {
+        let mut node = MaybeUninit::<KjPromiseNodeImpl>::uninit();
+        if let Err(exception) = start(node.as_mut_ptr()).into_result() {
+            panic!("{exception}");
+        }
+        Sleep(callbacks_promise_future(unsafe { node.assume_init() }))
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        // Safety: the inner future is structurally pinned.
+        let inner = unsafe { self.map_unchecked_mut(|s| &mut s.0) };
+        match inner.poll(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(()),
+            Poll::Ready(Err(exception)) => panic!("{exception}"),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Completes after `duration` has passed on the current thread's timer.
+///
+/// # Panics
+///
+/// Panics if the current thread has no timer.
+#[cfg_attr(feature = "instrumentation", track_caller)]
+pub fn sleep(duration: Duration) -> Sleep {
+    // `kj::Duration` is signed, so longer durations saturate to its maximum.
+    let nanoseconds = i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX);
+    Sleep::new(|out| unsafe { timer_after_delay(nanoseconds, out) })
+}
+
+/// Completes once `date` is reached, right away if it is in the past.
+///
+/// # Panics
+///
+/// Panics if the current thread has no timer.
+#[cfg_attr(feature = "instrumentation", track_caller)]
+pub fn sleep_until(date: KjDate) -> Sleep {
+    Sleep::new(|out| unsafe { timer_at_date(date.nanoseconds(), out) })
+}
+
+/// Runs `future`, giving up with [`Elapsed`] if it hasn't completed after `duration`. The future is
+/// dropped when the time is up.
+///
+/// # Panics
+///
+/// Panics if the current thread has no timer.
+#[cfg_attr(feature = "instrumentation", track_caller)]
+pub fn timeout<F: Future>(future: F, duration: Duration) -> Timeout<F> {
+    Timeout {
+        future,
+        sleep: sleep(duration),
+    }
+}
+
+/// The future returned by [`timeout`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Timeout<F> {
+    future: F,
+    sleep: Sleep,
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // Safety: both fields are structurally pinned, and never moved out of `Timeout`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        if let Poll::Ready(output) = future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        let sleep = unsafe { Pin::new_unchecked(&mut this.sleep) };
+        sleep.poll(cx).map(|()| Err(Elapsed(())))
+    }
+}
+
+/// The error of a [`timeout`] whose future didn't complete in time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Elapsed(());
+
+impl Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("operation timed out")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+impl From<Elapsed> for KjError {
+    /// An `OVERLOADED` error, like the exception of `kj::Timer::timeoutAfter()`.
+    fn from(elapsed: Elapsed) -> Self {
+        KjError::new(KjExceptionType::Overloaded, elapsed.to_string())
+    }
+}
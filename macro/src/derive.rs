@@ -1,6 +1,6 @@
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, quote_spanned, ToTokens};
-use syntax::{derive, Enum, Struct};
+use syntax::{derive, DataEnum, Enum, Struct};
 
 pub(crate) use syntax::derive::*;
 
@@ -18,10 +18,19 @@ pub(crate) fn expand_struct(
             Trait::Clone => expanded.extend(struct_clone(strct, span)),
             Trait::Debug => expanded.extend(struct_debug(strct, span)),
             Trait::Default => expanded.extend(struct_default(strct, span)),
+            // The padding byte of an empty struct is uninitialized, so these
+            // must not look at it like the derived impls would.
+            Trait::Eq if strct.fields.is_empty() => expanded.extend(empty_struct_eq(strct, span)),
             Trait::Eq => traits.push(quote_spanned!(span=> ::cxx::core::cmp::Eq)),
             Trait::ExternType => unreachable!(),
+            Trait::Hash if strct.fields.is_empty() => {
+                expanded.extend(empty_struct_hash(strct, span));
+            }
             Trait::Hash => traits.push(quote_spanned!(span=> ::cxx::core::hash::Hash)),
             Trait::Ord => expanded.extend(struct_ord(strct, span)),
+            Trait::PartialEq if strct.fields.is_empty() => {
+                expanded.extend(empty_struct_partial_eq(strct, span));
+            }
             Trait::PartialEq => traits.push(quote_spanned!(span=> ::cxx::core::cmp::PartialEq)),
             Trait::PartialOrd => expanded.extend(struct_partial_ord(strct, span)),
             Trait::Serialize => traits.push(quote_spanned!(span=> ::serde::Serialize)),
@@ -99,6 +108,33 @@ pub(crate) fn expand_enum(enm: &Enum, actual_derives: &mut Option<TokenStream>)
     expanded
 }
 
+pub(crate) fn expand_data_enum(enm: &DataEnum, actual_derives: &mut Option<TokenStream>) {
+    let mut traits = Vec::new();
+
+    for derive in &enm.derives {
+        let span = derive.span;
+        match derive.what {
+            Trait::Copy => traits.push(quote_spanned!(span=> ::cxx::core::marker::Copy)),
+            Trait::Clone => traits.push(quote_spanned!(span=> ::cxx::core::clone::Clone)),
+            Trait::Debug => traits.push(quote_spanned!(span=> ::cxx::core::fmt::Debug)),
+            Trait::Default => unreachable!(),
+            Trait::Eq => traits.push(quote_spanned!(span=> ::cxx::core::cmp::Eq)),
+            Trait::ExternType => unreachable!(),
+            Trait::Hash => traits.push(quote_spanned!(span=> ::cxx::core::hash::Hash)),
+            Trait::Ord => traits.push(quote_spanned!(span=> ::cxx::core::cmp::Ord)),
+            Trait::PartialEq => traits.push(quote_spanned!(span=> ::cxx::core::cmp::PartialEq)),
+            Trait::PartialOrd => traits.push(quote_spanned!(span=> ::cxx::core::cmp::PartialOrd)),
+            Trait::Serialize => traits.push(quote_spanned!(span=> ::serde::Serialize)),
+            Trait::Deserialize => traits.push(quote_spanned!(span=> ::serde::Deserialize)),
+            Trait::JsgStruct => unreachable!(),
+        }
+    }
+
+    if !traits.is_empty() {
+        *actual_derives = Some(quote!(#[derive(#(#traits),*)]));
+    }
+}
+
 fn struct_copy(strct: &Struct, span: Span) -> TokenStream {
     let ident = &strct.name.rust;
     let generics = &strct.generics;
@@ -113,7 +149,9 @@ fn struct_clone(strct: &Struct, span: Span) -> TokenStream {
     let ident = &strct.name.rust;
     let generics = &strct.generics;
 
-    let body = if derive::contains(&strct.derives, Trait::Copy) {
+    let body = if strct.fields.is_empty() {
+        quote_spanned!(span=> #ident::new())
+    } else if derive::contains(&strct.derives, Trait::Copy) {
         quote!(*self)
     } else {
         let fields = strct.fields.iter().map(|field| &field.name.rust);
@@ -162,22 +200,65 @@ fn struct_default(strct: &Struct, span: Span) -> TokenStream {
     let ident = &strct.name.rust;
     let generics = &strct.generics;
     let fields = strct.fields.iter().map(|field| &field.name.rust);
+    let body = if strct.fields.is_empty() {
+        quote_spanned!(span=> #ident::new())
+    } else {
+        quote_spanned! {span=>
+            #ident {
+                #(
+                    #fields: ::cxx::core::default::Default::default(),
+                )*
+            }
+        }
+    };
 
     quote_spanned! {span=>
         #[automatically_derived]
         #[allow(clippy::derivable_impls)] // different spans than the derived impl
         impl #generics ::cxx::core::default::Default for #ident #generics {
             fn default() -> Self {
-                #ident {
-                    #(
-                        #fields: ::cxx::core::default::Default::default(),
-                    )*
-                }
+                #body
             }
         }
     }
 }
 
+fn empty_struct_partial_eq(strct: &Struct, span: Span) -> TokenStream {
+    let ident = &strct.name.rust;
+    let generics = &strct.generics;
+
+    quote_spanned! {span=>
+        #[automatically_derived]
+        impl #generics ::cxx::core::cmp::PartialEq for #ident #generics {
+            fn eq(&self, _other: &Self) -> bool {
+                true
+            }
+        }
+    }
+}
+
+fn empty_struct_eq(strct: &Struct, span: Span) -> TokenStream {
+    let ident = &strct.name.rust;
+    let generics = &strct.generics;
+
+    quote_spanned! {span=>
+        #[automatically_derived]
+        impl #generics ::cxx::core::cmp::Eq for #ident #generics {}
+    }
+}
+
+fn empty_struct_hash(strct: &Struct, span: Span) -> TokenStream {
+    let ident = &strct.name.rust;
+    let generics = &strct.generics;
+
+    quote_spanned! {span=>
+        #[automatically_derived]
+        impl #generics ::cxx::core::hash::Hash for #ident #generics {
+            fn hash<__H: ::cxx::core::hash::Hasher>(&self, _state: &mut __H) {}
+        }
+    }
+}
+
 fn struct_ord(strct: &Struct, span: Span) -> TokenStream {
     let ident = &strct.name.rust;
     let generics = &strct.generics;
@@ -11,13 +11,14 @@ use syntax::atom::Atom::*;
 use syntax::attrs::{self, OtherAttrs};
 use syntax::cfg::CfgExpr;
 use syntax::file::Module;
-use syntax::instantiate::{ImplKey, NamedImplKey};
+use syntax::instantiate::{ImplKey, NamedImplKey, VecElement};
 use syntax::qualified::QualifiedName;
 use syntax::report::Errors;
+use syntax::set::UnorderedSet;
 use syntax::symbol::Symbol;
 use syntax::{
-    self, check, mangle, Api, Doc, Enum, ExternFn, ExternType, Impl, Lifetimes, Pair, Signature,
-    Struct, Trait, Type, TypeAlias, Types,
+    self, check, mangle, Api, DataEnum, Doc, Enum, ExternFn, ExternTrait, ExternType, Impl,
+    Lifetimes, Pair, Signature, Struct, Trait, Type, TypeAlias, Types,
 };
 
 pub(crate) fn bridge(mut ffi: Module) -> Result<TokenStream> {
@@ -72,9 +73,16 @@ fn expand(ffi: Module, doc: Doc, attrs: OtherAttrs, apis: &[Api], types: &Types)
                 forbid.extend(expand_struct_forbid_drop(strct));
             }
             Api::Enum(enm) => expanded.extend(expand_enum(enm)),
+            Api::DataEnum(enm) => {
+                expanded.extend(expand_data_enum(enm));
+                forbid.extend(expand_data_enum_forbid_drop(enm));
+            }
             Api::CxxType(ety) => {
                 let ident = &ety.name.rust;
-                if !types.structs.contains_key(ident) && !types.enums.contains_key(ident) {
+                if !types.structs.contains_key(ident)
+                    && !types.enums.contains_key(ident)
+                    && !types.data_enums.contains_key(ident)
+                {
                     expanded.extend(expand_cxx_type(ety));
                     hidden.extend(expand_cxx_type_assert_pinned(ety, types));
                 }
@@ -99,6 +107,11 @@ fn expand(ffi: Module, doc: Doc, attrs: OtherAttrs, apis: &[Api], types: &Types)
                     hidden.extend(expand_type_alias_verify_rust(alias));
                 }
             },
+            Api::Trait(trt) => {
+                expanded.extend(expand_cxx_type(&trt.impl_type));
+                hidden.extend(expand_cxx_type_assert_pinned(&trt.impl_type, types));
+                expanded.extend(expand_trait(trt, apis));
+            }
         }
     }
 
@@ -107,8 +120,8 @@ fn expand(ffi: Module, doc: Doc, attrs: OtherAttrs, apis: &[Api], types: &Types)
             ImplKey::RustBox(ident) => {
                 hidden.extend(expand_rust_box(ident, types, explicit_impl));
             }
-            ImplKey::RustVec(ident) => {
-                hidden.extend(expand_rust_vec(ident, types, explicit_impl));
+            ImplKey::RustVec(ident, element) => {
+                hidden.extend(expand_rust_vec(ident, element, types, explicit_impl));
             }
             ImplKey::UniquePtr(ident) => {
                 expanded.extend(expand_unique_ptr(ident, types, explicit_impl));
@@ -127,8 +140,19 @@ fn expand(ffi: Module, doc: Doc, attrs: OtherAttrs, apis: &[Api], types: &Types)
             }
             ImplKey::KjRc(ident) => expanded.extend(expand_kj_rc(ident, types, explicit_impl)),
             ImplKey::KjArc(ident) => expanded.extend(expand_kj_arc(ident, types, explicit_impl)),
-            // We do not yet need to generate code on the rust side for [`kj_rs::Own`]
-            ImplKey::Own(_) => (),
+            ImplKey::Own(ident) => expanded.extend(expand_kj_own(ident, types, explicit_impl)),
+        }
+    }
+
+    // The C++ deleter of a Rust type, shared by its UniquePtr, SharedPtr and KjOwn.
+    let mut deleted = UnorderedSet::new();
+    for impl_key in types.impls.keys() {
+        if let ImplKey::UniquePtr(ident) | ImplKey::SharedPtr(ident) | ImplKey::Own(ident) =
+            *impl_key
+        {
+            if types.rust.contains(ident.rust) && deleted.insert(ident.rust) {
+                hidden.extend(expand_rust_type_delete(ident, types));
+            }
         }
     }
 
@@ -165,6 +189,7 @@ fn expand(ffi: Module, doc: Doc, attrs: OtherAttrs, apis: &[Api], types: &Types)
             clippy::no_effect_underscore_binding,
             clippy::ptr_as_ptr,
             clippy::ref_as_ptr,
+            clippy::type_complexity,
             clippy::upper_case_acronyms,
             clippy::use_self,
         )]
@@ -192,9 +217,33 @@ fn expand_struct(strct: &Struct) -> TokenStream {
     let span = ident.span();
     let visibility = strct.visibility;
     let struct_token = strct.struct_token;
+    // C++ gives an empty struct a size of one byte, so the Rust struct gets a
+    // private byte of padding to match, and a constructor in place of a struct
+    // literal. C++ leaves that byte uninitialized, so it is never read.
+    let mut empty = None;
+    let padding = if strct.fields.is_empty() {
+        let serde = derive::contains(&strct.derives, Trait::Serialize)
+            || derive::contains(&strct.derives, Trait::Deserialize);
+        let skip = serde
+            .then(|| quote!(#[serde(skip, default = "::cxx::core::mem::MaybeUninit::uninit")]));
+        empty = Some(quote_spanned! {span=>
+            impl #ident {
+                #[must_use]
+                #visibility const fn new() -> Self {
+                    #ident { _padding: ::cxx::core::mem::MaybeUninit::uninit() }
+                }
+            }
+
+            const _: () = ::cxx::core::assert!(::cxx::core::mem::size_of::<#ident>() == 1);
+        });
+        Some(quote_spanned!(span=> #skip _padding: ::cxx::core::mem::MaybeUninit<u8>,))
+    } else {
+        None
+    };
     let struct_def = quote_spanned! {span=>
         #visibility #struct_token #ident #generics {
             #(#fields,)*
+            #padding
         }
     };
 
@@ -213,6 +262,8 @@ fn expand_struct(strct: &Struct) -> TokenStream {
             type Kind = ::cxx::kind::Trivial;
         }
 
+        #empty
+
         #derived_traits
     }
 }
@@ -395,6 +446,83 @@ fn expand_enum(enm: &Enum) -> TokenStream {
     }
 }
 
+fn expand_data_enum(enm: &DataEnum) -> TokenStream {
+    let ident = &enm.name.rust;
+    let doc = &enm.doc;
+    let attrs = &enm.attrs;
+    let type_id = type_id(&enm.name);
+    let variants = enm.variants.iter().map(|variant| {
+        let doc = &variant.doc;
+        let attrs = &variant.attrs;
+        let variant_ident = &variant.name.rust;
+        let discriminant = &variant.discriminant;
+        match &variant.ty {
+            Some(ty) => quote!(#doc #attrs #variant_ident(#ty) = #discriminant),
+            None => quote!(#doc #attrs #variant_ident = #discriminant),
+        }
+    });
+    // kj::OneOf finds the active variant by type, so each variant type
+    // converts into the enum. Tagged structs may repeat a type.
+    let from_impls = enm.variants.iter().filter(|_| enm.one_of).map(|variant| {
+        let variant_ident = &variant.name.rust;
+        let ty = &variant.ty;
+        let span = variant_ident.span();
+        quote_spanned! {span=>
+            #[automatically_derived]
+            impl ::cxx::core::convert::From<#ty> for #ident {
+                fn from(value: #ty) -> Self {
+                    #ident::#variant_ident(value)
+                }
+            }
+        }
+    });
+    let repr = if enm.one_of {
+        quote!(#[repr(C, u32)])
+    } else {
+        quote!(#[repr(C, u8)])
+    };
+    let mut derives = None;
+    derive::expand_data_enum(enm, &mut derives);
+
+    let span = ident.span();
+    let visibility = enm.visibility;
+    let enum_token = enm.enum_token;
+    let enum_def = quote_spanned! {span=>
+        #visibility #enum_token #ident {
+            #(#variants,)*
+        }
+    };
+
+    quote! {
+        #doc
+        #derives
+        #attrs
+        #repr
+        #enum_def
+
+        #(#from_impls)*
+
+        #[automatically_derived]
+        unsafe impl ::cxx::ExternType for #ident {
+            #[allow(unused_attributes)] // incorrect lint
+            #[doc(hidden)]
+            type Id = #type_id;
+            type Kind = ::cxx::kind::Trivial;
+        }
+    }
+}
+
+fn expand_data_enum_forbid_drop(enm: &DataEnum) -> TokenStream {
+    let ident = &enm.name.rust;
+    let span = ident.span();
+    let impl_token = Token![impl](enm.visibility.span);
+
+    quote_spanned! {span=>
+        #[automatically_derived]
+        #impl_token self::Drop for super::#ident {}
+    }
+}
+
 fn expand_cxx_type(ety: &ExternType) -> TokenStream {
     let ident = &ety.name.rust;
     let doc = &ety.doc;
@@ -436,6 +564,141 @@ fn expand_cxx_type(ety: &ExternType) -> TokenStream {
     }
 }
 
+fn expand_trait(trt: &ExternTrait, apis: &[Api]) -> TokenStream {
+    let ident = &trt.name.rust;
+    let doc = &trt.doc;
+    let attrs = &trt.attrs;
+    let visibility = trt.visibility;
+    let impl_ident = &trt.impl_type.name.rust;
+
+    let mut decls = Vec::new();
+    let mut impl_fns = Vec::new();
+    let mut own_fns = Vec::new();
+    for api in apis {
+        let Api::CxxFunction(efn) = api else {
+            continue;
+        };
+        let Some(receiver) = &efn.receiver else {
+            continue;
+        };
+        if receiver.ty.rust != *impl_ident {
+            continue;
+        }
+
+        let doc = &efn.doc;
+        let attrs = &efn.attrs;
+        let unsafety = &efn.unsafety;
+        let generics = &efn.generics;
+        let local_name = &efn.name.rust;
+        let var = receiver.var;
+        let receiver_decl = if receiver.pinned {
+            quote!(#var: ::cxx::core::pin::Pin<&mut Self>)
+        } else {
+            let ampersand = receiver.ampersand;
+            let lifetime = &receiver.lifetime;
+            quote!(#ampersand #lifetime #var)
+        };
+        let args = efn.args.iter();
+        let arg_vars = efn.args.iter().map(|arg| &arg.name.rust);
+        let ret = match &efn.ret {
+            Some(Type::Future(fut)) => {
+                // Boxed so that the trait stays object safe. Like the future of the method
+                // itself, it borrows the receiver.
+                let output = &fut.output;
+                let lifetime = match &receiver.lifetime {
+                    Some(lifetime) => quote!(#lifetime),
+                    None => quote!('_),
+                };
+                quote! {
+                    -> ::cxx::core::pin::Pin<::cxx::alloc::boxed::Box<
+                        dyn ::cxx::core::future::Future<
+                            Output = ::cxx::core::result::Result<#output, ::cxx::KjException>,
+                        > + #lifetime,
+                    >>
+                }
+            }
+            ret if efn.throws => {
+                let ok = match ret {
+                    Some(ret) => quote!(#ret),
+                    None => quote!(()),
+                };
+                quote!(-> ::cxx::core::result::Result<#ok, ::cxx::KjException>)
+            }
+            ret => expand_return_type(ret),
+        };
+        let sig = quote!(#unsafety fn #local_name #generics(#receiver_decl, #(#args),*) #ret);
+
+        let mut call = quote!(#impl_ident::#local_name(this, #(#arg_vars),*));
+        if let Some(Type::Future(_)) = &efn.ret {
+            call = quote!(::cxx::alloc::boxed::Box::pin(#call));
+        }
+        if unsafety.is_some() {
+            call = quote!(unsafe { #call });
+        }
+        let own_receiver = if receiver.pinned {
+            quote!(::cxx::core::pin::Pin::into_inner(self).pin_mut())
+        } else {
+            quote!(::cxx::core::convert::AsRef::<#impl_ident>::as_ref(self))
+        };
+
+        decls.push(quote! {
+            #doc
+            #attrs
+            #sig;
+        });
+        impl_fns.push(quote! {
+            #attrs
+            #sig {
+                let this = self;
+                #call
+            }
+        });
+        own_fns.push(quote! {
+            #attrs
+            #sig {
+                let this = #own_receiver;
+                #call
+            }
+        });
+    }
+
+    quote! {
+        #doc
+        #attrs
+        #visibility trait #ident {
+            #(#decls)*
+        }
+
+        #[automatically_derived]
+        impl #ident for #impl_ident {
+            #(#impl_fns)*
+        }
+
+        #[automatically_derived]
+        impl #ident for ::kj_rs::KjOwn<#impl_ident> {
+            #(#own_fns)*
+        }
+
+        #[automatically_derived]
+        impl ::cxx::core::convert::From<::kj_rs::KjOwn<#impl_ident>>
+            for ::cxx::alloc::boxed::Box<dyn #ident>
+        {
+            fn from(own: ::kj_rs::KjOwn<#impl_ident>) -> Self {
+                ::cxx::alloc::boxed::Box::new(own)
+            }
+        }
+
+        #[automatically_derived]
+        impl ::cxx::core::convert::From<::kj_rs::KjOwn<#impl_ident>>
+            for ::cxx::core::pin::Pin<::cxx::alloc::boxed::Box<dyn #ident>>
+        {
+            fn from(own: ::kj_rs::KjOwn<#impl_ident>) -> Self {
+                ::cxx::alloc::boxed::Box::into_pin(::cxx::alloc::boxed::Box::new(own))
+            }
+        }
+    }
+}
+
 fn expand_cxx_type_assert_pinned(ety: &ExternType, types: &Types) -> TokenStream {
     let ident = &ety.name.rust;
     let infer = Token![_](ident.span());
@@ -475,12 +738,25 @@ fn expand_cxx_type_assert_pinned(ety: &ExternType, types: &Types) -> TokenStream
 }
 
 fn expand_cxx_function_decl(efn: &ExternFn, types: &Types) -> TokenStream {
-    let generics = &efn.generics;
-    let receiver = efn.receiver.iter().map(|receiver| {
+    let link_name = mangle::extern_fn(efn, types);
+    let local_name = format_ident!("__{}", efn.name.rust);
+    let indirect_call = false;
+    expand_cxx_function_decl_impl(efn, types, &link_name, &local_name, indirect_call)
+}
+
+fn expand_cxx_function_decl_impl(
+    sig: &Signature,
+    types: &Types,
+    link_name: &Symbol,
+    local_name: &Ident,
+    indirect_call: bool,
+) -> TokenStream {
+    let generics = &sig.generics;
+    let receiver = sig.receiver.iter().map(|receiver| {
         let receiver_type = ReceiverType(receiver);
         quote!(_: #receiver_type)
     });
-    let args = efn.args.iter().map(|arg| {
+    let args = sig.args.iter().map(|arg| {
         let var = &arg.name.rust;
         let colon = arg.colon_token;
         let ty = expand_extern_type(&arg.ty, types, true);
@@ -501,15 +777,18 @@ fn expand_cxx_function_decl(efn: &ExternFn, types: &Types) -> TokenStream {
     // extern "C" boundary, so the C++ shim catches it and reports it here instead.
     let ret = quote!(-> ::cxx::private::Result);
     let mut outparam = None;
-    if indirect_return(efn) {
-        let ret = expand_extern_type(efn.ret.as_ref().unwrap(), types, true);
-        outparam = Some(quote!(__return: *mut #ret));
+    if indirect_return(sig) {
+        let ret = expand_extern_type(sig.ret.as_ref().unwrap(), types, true);
+        outparam = Some(quote!(__return: *mut #ret,));
     }
-    let link_name = mangle::extern_fn(efn, types);
-    let local_name = format_ident!("__{}", efn.name.rust);
+    let pointer = if indirect_call {
+        Some(quote!(__extern: *mut ::cxx::core::ffi::c_void))
+    } else {
+        None
+    };
     quote! {
         #[link_name = #link_name]
-        fn #local_name #generics(#(#all_args,)* #outparam) #ret;
+        fn #local_name #generics(#(#all_args,)* #outparam #pointer) #ret;
     }
 }
 
@@ -519,7 +798,14 @@ fn expand_cxx_function_shim(efn: &ExternFn, types: &Types) -> TokenStream {
     let decl = expand_cxx_function_decl(efn, types);
     let receiver = efn.receiver.iter().map(|receiver| {
         let var = receiver.var;
-        if receiver.pinned {
+        if receiver.rc_tokens.is_some() {
+            // Arbitrary self types are unstable, so the method is called as
+            // `Type::method(rc, ...)` instead.
+            let this = Ident::new("this", var.span);
+            let colon = receiver.colon_token;
+            let ty = ReceiverTypeSelf(receiver);
+            quote!(#this #colon #ty)
+        } else if receiver.pinned {
             let colon = receiver.colon_token;
             let ty = ReceiverTypeSelf(receiver);
             quote!(#var #colon #ty)
@@ -538,15 +824,122 @@ fn expand_cxx_function_shim(efn: &ExternFn, types: &Types) -> TokenStream {
             None => quote!(()),
         };
         quote!(-> ::cxx::core::result::Result<#ok, ::cxx::KjException>)
+    } else if let (Some(receiver), Some(Type::Future(_))) = (&efn.receiver, &efn.ret) {
+        let ret = expand_return_type(&efn.ret);
+        match (&receiver.rc_tokens, &receiver.lifetime) {
+            // The promise holds on to its own reference to the receiver.
+            (Some(_), _) => ret,
+            // The promise may refer to `this`, so the future borrows the receiver.
+            (None, Some(lifetime)) => quote!(#ret + #lifetime),
+            (None, None) => quote!(#ret + '_),
+        }
     } else {
         expand_return_type(&efn.ret)
     };
-    let indirect_return = indirect_return(efn);
-    let receiver_var = efn
-        .receiver
+    let trampolines = efn
+        .args
         .iter()
-        .map(|receiver| receiver.var.to_token_stream());
-    let arg_vars = efn.args.iter().map(|arg| {
+        .filter_map(|arg| match &arg.ty {
+            Type::Fn(f) => Some(expand_function_pointer_trampoline(efn, &arg.name, f, types)),
+            Type::KjFunction(_) => {
+                let r_trampoline = mangle::r_trampoline(efn, &arg.name, types);
+                let label = arg.name.rust.to_string();
+                Some(expand_closure_trampoline(
+                    efn,
+                    &arg.ty,
+                    &r_trampoline,
+                    &label,
+                    types,
+                ))
+            }
+            _ => None,
+        })
+        .collect::<TokenStream>();
+    let local_name = format_ident!("__{}", efn.name.rust);
+    let span = efn.semi_token.span;
+    let pointer = None;
+    let dispatch = expand_cxx_function_dispatch(efn, efn, types, &local_name, span, pointer);
+    let visibility = efn.visibility;
+    let unsafety = &efn.sig.unsafety;
+    let fn_token = efn.sig.fn_token;
+    let ident = &efn.name.rust;
+    let generics = &efn.generics;
+    let missing_panics_doc = match &efn.ret {
+        Some(Type::KjOwn(_)) => quote!(#[allow(clippy::missing_panics_doc)]),
+        _ => quote!(),
+    };
+    let arg_list = quote_spanned!(efn.sig.paren_token.span=> (#(#all_args,)*));
+    let fn_body = quote_spanned!(span=> {
+        #UnsafeExtern extern "C" {
+            #decl
+        }
+        #trampolines
+        #dispatch
+    });
+    match &efn.receiver {
+        None => {
+            quote! {
+                #doc
+                #attrs
+                #missing_panics_doc
+                #visibility #unsafety #fn_token #ident #generics #arg_list #ret #fn_body
+            }
+        }
+        Some(receiver) => {
+            let elided_generics;
+            let receiver_ident = &receiver.ty.rust;
+            let resolve = types.resolve(&receiver.ty);
+            let receiver_generics = if receiver.ty.generics.lt_token.is_some() {
+                &receiver.ty.generics
+            } else {
+                elided_generics = Lifetimes {
+                    lt_token: resolve.generics.lt_token,
+                    lifetimes: resolve
+                        .generics
+                        .lifetimes
+                        .pairs()
+                        .map(|pair| {
+                            let lifetime = Lifetime::new("'_", pair.value().apostrophe);
+                            let punct = pair.punct().map(|&&comma| comma);
+                            punctuated::Pair::new(lifetime, punct)
+                        })
+                        .collect(),
+                    gt_token: resolve.generics.gt_token,
+                };
+                &elided_generics
+            };
+            quote_spanned! {ident.span()=>
+                impl #generics #receiver_ident #receiver_generics {
+                    #doc
+                    #attrs
+                    #missing_panics_doc
+                    #visibility #unsafety #fn_token #ident #arg_list #ret #fn_body
+                }
+            }
+        }
+    }
+}
+
+// The call of a C++ function from its Rust shim: converts the arguments, calls `local_name` and
+// converts the returned value. `efn` is the bridge function whose closures get trampolines, and
+// `pointer` the `kj::Function` to call when `sig` is the signature of a closure.
+fn expand_cxx_function_dispatch(
+    efn: &ExternFn,
+    sig: &Signature,
+    types: &Types,
+    local_name: &Ident,
+    span: Span,
+    pointer: Option<TokenStream>,
+) -> TokenStream {
+    let indirect_return = indirect_return(sig);
+    let receiver_var = sig.receiver.iter().map(|receiver| {
+        if receiver.rc_tokens.is_some() {
+            quote_spanned!(receiver.var.span=> this.as_mut_ptr())
+        } else {
+            receiver.var.to_token_stream()
+        }
+    });
+    let arg_vars = sig.args.iter().map(|arg| {
         let var = &arg.name.rust;
         let span = var.span();
         match &arg.ty {
@@ -608,24 +1001,24 @@ fn expand_cxx_function_shim(efn: &ExternFn, types: &Types) -> TokenStream {
                 true => quote_spanned!(span=> ::cxx::private::RustSlice::from_mut(#var)),
             },
             Type::KjDate(_) => quote_spanned!(span=> #var.into()),
+            Type::KjFunction(_) => quote_spanned!(span=> ::kj_rs::repr::Closure::new(#var)),
             ty if types.needs_indirect_abi(ty) => quote_spanned!(span=> #var.as_mut_ptr()),
             _ => quote!(#var),
         }
     });
-    let vars = receiver_var.chain(arg_vars);
-    let trampolines = efn
-        .args
+    let vars: Vec<_> = receiver_var.chain(arg_vars).collect();
+    let receiver_setup = sig
+        .receiver
         .iter()
-        .filter_map(|arg| {
-            if let Type::Fn(f) = &arg.ty {
-                let var = &arg.name;
-                Some(expand_function_pointer_trampoline(efn, var, f, types))
-            } else {
-                None
+        .filter(|receiver| receiver.rc_tokens.is_some())
+        .map(|receiver| {
+            let span = receiver.var.span;
+            // C++ takes ownership of the reference count, like it does of the arguments below.
+            quote_spanned! {span=>
+                let mut this = ::cxx::core::mem::MaybeUninit::new(this);
             }
-        })
-        .collect::<TokenStream>();
-    let mut setup = efn
+        });
+    let arg_setup = sig
         .args
         .iter()
         .filter(|arg| types.needs_indirect_abi(&arg.ty))
@@ -637,41 +1030,50 @@ fn expand_cxx_function_shim(efn: &ExternFn, types: &Types) -> TokenStream {
             quote_spanned! {span=>
                 let mut #var = ::cxx::core::mem::MaybeUninit::new(#var);
             }
-        })
-        .collect::<TokenStream>();
-    let local_name = format_ident!("__{}", efn.name.rust);
-    let span = efn.semi_token.span;
+        });
+    let mut setup = receiver_setup.chain(arg_setup).collect::<TokenStream>();
     let call = if indirect_return {
-        let ret = expand_extern_type(efn.ret.as_ref().unwrap(), types, true);
+        let ret = expand_extern_type(sig.ret.as_ref().unwrap(), types, true);
         setup.extend(quote_spanned! {span=>
             let mut __return = ::cxx::core::mem::MaybeUninit::<#ret>::uninit();
         });
-        setup.extend(if efn.throws {
+        setup.extend(if sig.throws {
             quote_spanned! {span=>
-                #local_name(#(#vars,)* __return.as_mut_ptr()).into_result()?;
+                #local_name(#(#vars,)* __return.as_mut_ptr(), #pointer).into_result()?;
             }
         } else {
             // An infallible signature cannot report the exception to its caller, so
             // turn it into a panic rather than letting the process abort.
             quote_spanned! {span=>
-                #local_name(#(#vars,)* __return.as_mut_ptr()).panic_on_exception();
+                #local_name(#(#vars,)* __return.as_mut_ptr(), #pointer).panic_on_exception();
             }
         });
         quote_spanned!(span=> __return.assume_init())
-    } else if efn.throws {
+    } else if sig.throws {
         quote_spanned! {span=>
-            #local_name(#(#vars),*).into_result()
+            #local_name(#(#vars,)* #pointer).into_result()
         }
     } else {
         quote_spanned! {span=>
-            #local_name(#(#vars),*).panic_on_exception()
+            #local_name(#(#vars,)* #pointer).panic_on_exception()
         }
     };
+    if let Some(ret @ Type::KjFunction(f)) = &sig.ret {
+        // Wrapped outside of the unsafe block, as the closure contains its own.
+        let c_trampoline = mangle::c_return_trampoline(efn, types);
+        let closure = quote_spanned!(span=> unsafe { #setup #call });
+        let function = expand_cxx_closure(efn, ret, f, &c_trampoline, types, closure);
+        return if sig.throws {
+            quote_spanned!(span=> ::cxx::core::result::Result::Ok(#function))
+        } else {
+            function
+        };
+    }
     let mut expr;
-    if efn.throws && efn.sig.ret.is_none() {
+    if sig.throws && sig.ret.is_none() {
         expr = call;
     } else {
-        expr = match &efn.ret {
+        expr = match &sig.ret {
             None => call,
             Some(ret) => match ret {
                 Type::Ident(ident) if ident.rust == RustString => {
@@ -746,74 +1148,16 @@ fn expand_cxx_function_shim(efn: &ExternFn, types: &Types) -> TokenStream {
                 Type::Future(_) => {
                     quote_spanned!(span=> ::kj_rs::new_callbacks_promise_future(#call))
                 }
+                Type::KjStream(_) => quote_spanned!(span=> ::kj_rs::KjStream::from(#call)),
                 Type::KjDate(_) => quote_spanned!(span=> #call.into()),
                 _ => call,
             },
         };
-        if efn.throws {
+        if sig.throws {
             expr = quote_spanned!(span=> ::cxx::core::result::Result::Ok(#expr));
         }
     }
-    let dispatch = quote_spanned!(span=> unsafe { #setup #expr });
-    let visibility = efn.visibility;
-    let unsafety = &efn.sig.unsafety;
-    let fn_token = efn.sig.fn_token;
-    let ident = &efn.name.rust;
-    let generics = &efn.generics;
-    let missing_panics_doc = match &efn.ret {
-        Some(Type::KjOwn(_)) => quote!(#[allow(clippy::missing_panics_doc)]),
-        _ => quote!(),
-    };
-    let arg_list = quote_spanned!(efn.sig.paren_token.span=> (#(#all_args,)*));
-    let fn_body = quote_spanned!(span=> {
-        #UnsafeExtern extern "C" {
-            #decl
-        }
-        #trampolines
-        #dispatch
-    });
-    match &efn.receiver {
-        None => {
-            quote! {
-                #doc
-                #attrs
-                #missing_panics_doc
-                #visibility #unsafety #fn_token #ident #generics #arg_list #ret #fn_body
-            }
-        }
-        Some(receiver) => {
-            let elided_generics;
-            let receiver_ident = &receiver.ty.rust;
-            let resolve = types.resolve(&receiver.ty);
-            let receiver_generics = if receiver.ty.generics.lt_token.is_some() {
-                &receiver.ty.generics
-            } else {
-                elided_generics = Lifetimes {
-                    lt_token: resolve.generics.lt_token,
-                    lifetimes: resolve
-                        .generics
-                        .lifetimes
-                        .pairs()
-                        .map(|pair| {
-                            let lifetime = Lifetime::new("'_", pair.value().apostrophe);
-                            let punct = pair.punct().map(|&&comma| comma);
-                            punctuated::Pair::new(lifetime, punct)
-                        })
-                        .collect(),
-                    gt_token: resolve.generics.gt_token,
-                };
-                &elided_generics
-            };
-            quote_spanned! {ident.span()=>
-                impl #generics #receiver_ident #receiver_generics {
-                    #doc
-                    #attrs
-                    #missing_panics_doc
-                    #visibility #unsafety #fn_token #ident #arg_list #ret #fn_body
-                }
-            }
-        }
-    }
+    quote_spanned!(span=> unsafe { #setup #expr })
 }
 
 fn expand_function_pointer_trampoline(
@@ -833,7 +1177,7 @@ fn expand_function_pointer_trampoline(
         &r_trampoline,
         local_name,
         prevent_unwind_label,
-        None,
+        Invoke::FnPointer,
         Some(&efn.generics),
         &efn.attrs,
         body_span,
@@ -855,6 +1199,79 @@ fn expand_function_pointer_trampoline(
     }
 }
 
+// The Rust function behind a boxed closure passed to C++, which C++ calls through its trampoline
+// with the closure as `__extern`.
+fn expand_closure_trampoline(
+    efn: &ExternFn,
+    ty: &Type,
+    r_trampoline: &Symbol,
+    label: &str,
+    types: &Types,
+) -> TokenStream {
+    let Type::KjFunction(sig) = ty else {
+        unreachable!()
+    };
+    let local_name = parse_quote!(__);
+    let prevent_unwind_label = format!("::{}::{}", efn.name.rust, label);
+    let body_span = efn.semi_token.span;
+    let shim = expand_rust_function_shim_impl(
+        sig,
+        types,
+        r_trampoline,
+        local_name,
+        prevent_unwind_label,
+        Invoke::Closure(ty),
+        Some(&efn.generics),
+        &efn.attrs,
+        body_span,
+    );
+
+    quote! {
+        const _: () = {
+            #shim
+        };
+    }
+}
+
+// A boxed Rust closure calling the `kj::Function` held by `closure`, through the C++ trampoline
+// `link_name`.
+fn expand_cxx_closure(
+    efn: &ExternFn,
+    ty: &Type,
+    sig: &Signature,
+    link_name: &Symbol,
+    types: &Types,
+    closure: TokenStream,
+) -> TokenStream {
+    let local_name = format_ident!("__");
+    let indirect_call = true;
+    let decl = expand_cxx_function_decl_impl(sig, types, link_name, &local_name, indirect_call);
+    let span = efn.semi_token.span;
+    let pointer = Some(quote_spanned!(span=> __closure.as_ptr()));
+    let dispatch = expand_cxx_function_dispatch(efn, sig, types, &local_name, span, pointer);
+    let args = sig.args.iter().map(|arg| quote!(#arg));
+    let ret = if sig.throws {
+        let ok = match &sig.ret {
+            Some(ret) => quote!(#ret),
+            None => quote!(()),
+        };
+        quote!(-> ::cxx::core::result::Result<#ok, ::cxx::KjException>)
+    } else {
+        expand_return_type(&sig.ret)
+    };
+
+    quote_spanned! {span=> {
+        let __closure: ::kj_rs::repr::Closure = #closure;
+        let __function: #ty = ::cxx::alloc::boxed::Box::new(move |#(#args),*| #ret {
+            #UnsafeExtern extern "C" {
+                #decl
+            }
+            #dispatch
+        });
+        __function
+    }}
+}
+
 fn expand_rust_type_import(ety: &ExternType) -> TokenStream {
     let ident = &ety.name.rust;
     let span = ident.span();
@@ -958,6 +1375,26 @@ fn expand_rust_type_layout(ety: &ExternType, types: &Types) -> TokenStream {
     }
 }
 
+fn expand_rust_type_delete(key: NamedImplKey, types: &Types) -> TokenStream {
+    let ident = key.rust;
+    let resolve = types.resolve(ident);
+    let link_delete = mangle::operator(resolve.name, "delete");
+    let local_delete = format_ident!("__delete_{}", ident);
+    let prevent_unwind_drop_label = format!("::{} as Drop>::drop", ident);
+    let lifetimes = to_underscore_lifetimes(resolve.generics);
+
+    quote_spanned! {ident.span()=>
+        #[doc(hidden)]
+        #[#UnsafeAttr(#ExportNameAttr = #link_delete)]
+        unsafe extern "C" fn #local_delete(this: *mut #ident #lifetimes) {
+            let __fn = concat!("<", module_path!(), #prevent_unwind_drop_label);
+            ::cxx::private::prevent_unwind(__fn, || {
+                let _ = unsafe { ::cxx::alloc::boxed::Box::from_raw(this) };
+            });
+        }
+    }
+}
+
 fn expand_forbid(impls: TokenStream) -> TokenStream {
     quote! {
         mod forbid {
@@ -980,9 +1417,9 @@ fn expand_rust_function_shim(efn: &ExternFn, types: &Types) -> TokenStream {
         None => format!("::{}", efn.name.rust),
         Some(receiver) => format!("::{}::{}", receiver.ty.rust, efn.name.rust),
     };
-    let invoke = Some(&efn.name.rust);
+    let invoke = Invoke::Function(efn);
     let body_span = efn.semi_token.span;
-    expand_rust_function_shim_impl(
+    let shim = expand_rust_function_shim_impl(
         efn,
         types,
         &link_name,
@@ -992,7 +1429,35 @@ fn expand_rust_function_shim(efn: &ExternFn, types: &Types) -> TokenStream {
         None,
         &efn.attrs,
         body_span,
-    )
+    );
+    let ret_trampoline = match &efn.ret {
+        Some(ret @ Type::KjFunction(_)) => {
+            let r_trampoline = mangle::r_return_trampoline(efn, types);
+            Some(expand_closure_trampoline(
+                efn,
+                ret,
+                &r_trampoline,
+                "return",
+                types,
+            ))
+        }
+        _ => None,
+    };
+    quote! {
+        #shim
+        #ret_trampoline
+    }
+}
+
+// What the shim exported to C++ calls into.
+#[derive(Copy, Clone)]
+enum Invoke<'a> {
+    // The extern "Rust" function itself.
+    Function(&'a ExternFn),
+    // The fn pointer passed in `__extern`.
+    FnPointer,
+    // The boxed closure of the given type which `__extern` points to.
+    Closure(&'a Type),
 }
 
 fn expand_rust_function_shim_impl(
@@ -1001,7 +1466,7 @@ fn expand_rust_function_shim_impl(
     link_name: &Symbol,
     local_name: Ident,
     prevent_unwind_label: String,
-    invoke: Option<&Ident>,
+    invoke: Invoke,
     outer_generics: Option<&Generics>,
     attrs: &OtherAttrs,
     body_span: Span,
@@ -1090,22 +1555,60 @@ fn expand_rust_function_shim_impl(
     });
     let vars: Vec<_> = receiver_var.into_iter().chain(arg_vars).collect();
 
-    let wrap_super = invoke.map(|invoke| expand_rust_function_shim_super(sig, &local_name, invoke));
+    // Wrapped before entering any unsafe block, as the closures contain their own.
+    let closures = sig
+        .args
+        .iter()
+        .filter_map(|arg| match &arg.ty {
+            Type::KjFunction(f) => {
+                // Checked: closures only appear in signatures of extern functions.
+                let Invoke::Function(efn) = invoke else {
+                    unreachable!()
+                };
+                let var = &arg.name.rust;
+                let c_trampoline = mangle::c_trampoline(efn, &arg.name, types);
+                let closure =
+                    expand_cxx_closure(efn, &arg.ty, f, &c_trampoline, types, quote!(#var));
+                Some(quote!(let #var = #closure;))
+            }
+            _ => None,
+        })
+        .collect::<TokenStream>();
+
+    let wrap_super = match invoke {
+        Invoke::Function(efn) => Some(expand_rust_function_shim_super(
+            sig,
+            &local_name,
+            &efn.name.rust,
+        )),
+        Invoke::FnPointer | Invoke::Closure(_) => None,
+    };
 
     let mut requires_closure;
     let mut call = match invoke {
-        Some(_) => {
+        Invoke::Function(_) => {
             requires_closure = false;
             quote!(#local_name)
         }
-        None => {
+        Invoke::FnPointer => {
             requires_closure = true;
             requires_unsafe = true;
             quote!(::cxx::core::mem::transmute::<*const (), #sig>(__extern))
         }
+        Invoke::Closure(ty) => {
+            requires_closure = true;
+            requires_unsafe = true;
+            quote!((*(__extern as *mut #ty)))
+        }
     };
     requires_closure |= !vars.is_empty();
     call.extend(quote! { (#(#vars),*) });
+    if let Invoke::Closure(_) = invoke {
+        if sig.throws {
+            // Keep the type, details and location of the KjException.
+            call = quote!(#call.map_err(::cxx::KjError::from));
+        }
+    }
 
     if let Some(Type::KjDate(_)) = sig.ret {
         call = quote!(#call.into());
@@ -1152,6 +1655,8 @@ fn expand_rust_function_shim_impl(
                 Some(quote_spanned!(span=> ::kj_rs::repr::infallible_future))
             }
         }
+        Type::KjStream(_) => Some(quote_spanned!(span=> ::kj_rs::repr::stream)),
+        Type::KjFunction(_) => Some(quote_spanned!(span=> ::kj_rs::repr::Closure::new)),
         _ => None,
     });
 
@@ -1169,11 +1674,9 @@ fn expand_rust_function_shim_impl(
 
     // always indirect return when there's a return value
     let out_param = sig.ret.as_ref().map(|ret| {
-        let ret = if let Type::Future(fut) = ret {
-            let span = sig.ret.span();
-            let output = &fut.output;
+        let lifetimes = || {
             let lifetimes: Vec<_> = sig.generics.lifetimes().map(|lt| quote!(#lt)).collect();
-            let lifetimes = if lifetimes.is_empty() {
+            if lifetimes.is_empty() {
                 quote!()
             } else {
                 assert_eq!(
@@ -1182,12 +1685,22 @@ fn expand_rust_function_shim_impl(
                     "workerd-cxx: expected single lifetime (todo: do we need to support multiple?)"
                 );
                 quote!(#(#lifetimes),*, )
-            };
+            }
+        };
+        let ret = if let Type::Future(fut) = ret {
+            let span = sig.ret.span();
+            let output = &fut.output;
+            let lifetimes = lifetimes();
             if fut.throws_tokens.is_some() {
                 quote_spanned!(span=> ::kj_rs::repr::RustFuture::<#lifetimes #output>)
             } else {
                 quote_spanned!(span=> ::kj_rs::repr::RustInfallibleFuture::<#lifetimes #output>)
             }
+        } else if let Type::KjStream(stream) = ret {
+            let span = stream.name.span();
+            let item = &stream.inner;
+            let lifetimes = lifetimes();
+            quote_spanned!(span=> ::kj_rs::repr::RustStream::<#lifetimes #item>)
         } else {
             expand_extern_type(ret, types, false)
         };
@@ -1197,8 +1710,16 @@ fn expand_rust_function_shim_impl(
         Some(_) => quote_spanned!(span=> __return),
         None => quote_spanned!(span=> &mut ()),
     };
+    let error_param = sig.error.as_ref().map(|error| {
+        let error = expand_extern_type(error, types, false);
+        quote_spanned!(span=> __error: *mut #error,)
+    });
     let indirect_return = sig.ret.is_some();
-    if sig.throws {
+    if sig.error.is_some() {
+        requires_closure = true;
+        requires_unsafe = true;
+        expr = quote_spanned!(span=> ::cxx::private::try_typed(#out, __error, #expr));
+    } else if sig.throws {
         requires_closure = true;
         requires_unsafe = true;
         expr = quote_spanned!(span=> ::cxx::private::r#try(#out, #expr, file!(), line!()));
@@ -1229,17 +1750,18 @@ fn expand_rust_function_shim_impl(
     let ret = quote!(-> ::cxx::private::Result);
 
     let pointer = match invoke {
-        None => Some(quote_spanned!(span=> __extern: *const ())),
-        Some(_) => None,
+        Invoke::FnPointer | Invoke::Closure(_) => Some(quote_spanned!(span=> __extern: *const ())),
+        Invoke::Function(_) => None,
     };
 
     quote_spanned! {span=>
         #attrs
         #[doc(hidden)]
         #[#UnsafeAttr(#ExportNameAttr = #link_name)]
-        unsafe extern "C" fn #local_name #generics(#(#all_args,)* #out_param #pointer) #ret {
+        unsafe extern "C" fn #local_name #generics(#(#all_args,)* #out_param #error_param #pointer) #ret {
             let __fn = ::cxx::private::concat!(::cxx::private::module_path!(), #prevent_unwind_label);
             #wrap_super
+            #closures
             #expr
         }
     }
@@ -1271,7 +1793,12 @@ fn expand_rust_function_shim_super(
             Some(ret) => quote!(#ret),
             None => quote!(()),
         };
-        quote!(-> ::cxx::core::result::Result<#ok, impl ::cxx::IntoKjException + use<>>)
+        match &sig.error {
+            Some(error) => quote!(-> ::cxx::core::result::Result<#ok, #error>),
+            None => {
+                quote!(-> ::cxx::core::result::Result<#ok, impl ::cxx::IntoKjException + use<>>)
+            }
+        }
     } else if let Some(Type::Future(fut)) = &sig.ret {
         let output = &fut.output;
         let lifetimes: Vec<_> = sig.generics.lifetimes().map(|lt| quote!(#lt)).collect();
@@ -1286,6 +1813,10 @@ fn expand_rust_function_shim_super(
         } else {
             quote!(-> std::pin::Pin<Box<dyn ::std::future::Future<Output = #output> #lifetimes>>)
         }
+    } else if let Some(Type::KjStream(stream)) = &sig.ret {
+        let item = &stream.inner;
+        let lifetimes = sig.generics.lifetimes().map(|lt| quote!(+ #lt));
+        quote!(-> std::pin::Pin<Box<dyn ::kj_rs::Stream<Item = ::std::result::Result<#item, ::cxx::KjException>> #(#lifetimes)*>>)
     } else {
         expand_return_type(&sig.ret)
     };
@@ -1308,6 +1839,8 @@ fn expand_rust_function_shim_super(
         } else {
             quote_spanned!(span=> Box::pin(#call(#(#vars,)*)))
         }
+    } else if let Some(Type::KjStream(_)) = &sig.ret {
+        quote_spanned!(span=> Box::pin(::kj_rs::map_stream_err(#call(#(#vars,)*), ::cxx::core::file!(), ::cxx::core::line!())))
     } else {
         quote_spanned!(span=> #call(#(#vars,)*))
     };
@@ -1450,10 +1983,24 @@ fn expand_rust_box(key: NamedImplKey, types: &Types, explicit_impl: Option<&Impl
     }
 }
 
-fn expand_rust_vec(key: NamedImplKey, types: &Types, explicit_impl: Option<&Impl>) -> TokenStream {
+fn expand_rust_vec(
+    key: NamedImplKey,
+    element: VecElement,
+    types: &Types,
+    explicit_impl: Option<&Impl>,
+) -> TokenStream {
     let elem = key.rust;
     let resolve = types.resolve(elem);
-    let link_prefix = format!("cxxbridge1$rust_vec${}$", resolve.name.to_symbol());
+    let (segment, local_segment) = match element {
+        VecElement::Value => ("", "vec"),
+        VecElement::KjOwn => ("kj$Own$", "own_vec"),
+        VecElement::UniquePtr => ("std$unique_ptr$", "unique_ptr_vec"),
+    };
+    let link_prefix = format!(
+        "cxxbridge1$rust_vec${}{}$",
+        segment,
+        resolve.name.to_symbol(),
+    );
     let link_new = format!("{}new", link_prefix);
     let link_drop = format!("{}drop", link_prefix);
     let link_len = format!("{}len", link_prefix);
@@ -1463,7 +2010,7 @@ fn expand_rust_vec(key: NamedImplKey, types: &Types, explicit_impl: Option<&Impl
     let link_set_len = format!("{}set_len", link_prefix);
     let link_truncate = format!("{}truncate", link_prefix);
 
-    let local_prefix = format_ident!("{}__vec_", elem);
+    let local_prefix = format_ident!("{}__{}_", elem, local_segment);
     let local_new = format_ident!("{}new", local_prefix);
     let local_drop = format_ident!("{}drop", local_prefix);
     let local_len = format_ident!("{}len", local_prefix);
@@ -1478,15 +2025,70 @@ fn expand_rust_vec(key: NamedImplKey, types: &Types, explicit_impl: Option<&Impl
     let begin_span = explicit_impl.map_or(key.begin_span, |explicit| explicit.impl_token.span);
     let end_span = explicit_impl.map_or(key.end_span, |explicit| explicit.brace_token.span.join());
     let unsafe_token = format_ident!("unsafe", span = begin_span);
-    let prevent_unwind_drop_label = format!("::{} as Drop>::drop", elem);
+
+    // Elements owned through a C++ pointer are dropped by their C++ deleter. The `ImplVec` marker,
+    // which turns conflicting instantiations into compile errors, is implemented for the named type
+    // with the element type as parameter, since the pointer type itself is foreign.
+    let (elem_ty, prevent_unwind_drop_label) = match element {
+        VecElement::Value => (
+            quote_spanned!(end_span=> #elem #ty_generics),
+            format!("::{} as Drop>::drop", elem),
+        ),
+        VecElement::KjOwn => (
+            quote_spanned!(end_span=> ::kj_rs::repr::KjOwn<#elem #ty_generics>),
+            format!("::KjOwn<{}> as Drop>::drop", elem),
+        ),
+        VecElement::UniquePtr => (
+            quote_spanned!(end_span=> ::cxx::UniquePtr<#elem #ty_generics>),
+            format!("::UniquePtr<{}> as Drop>::drop", elem),
+        ),
+    };
+    let impl_vec_element = match element {
+        VecElement::Value => None,
+        VecElement::KjOwn | VecElement::UniquePtr => Some(quote_spanned!(end_span=> <#elem_ty>)),
+    };
+
+    // Elements of an extern C++ type, which must be trivially relocatable, are destroyed by their C++
+    // destructor when the Vec is dropped or truncated from C++.
+    let (destroy, destroy_all, destroy_tail) = if element == VecElement::Value
+        && types.required_trivial.contains_key(elem)
+    {
+        let link_destroy = format!("{}destroy", link_prefix);
+        let local_destroy = format_ident!("{}destroy", local_prefix);
+        let destroy = quote_spanned! {end_span=>
+            #[doc(hidden)]
+            unsafe fn #local_destroy #impl_generics(this: *mut ::cxx::private::RustVec<#elem_ty>, len: usize) {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_destroy]
+                    fn __destroy(ptr: *mut ::cxx::core::ffi::c_void, len: usize);
+                }
+                unsafe {
+                    let vec = (*this).as_mut_vec();
+                    let old_len = vec.len();
+                    if len < old_len {
+                        vec.set_len(len);
+                        __destroy(vec.as_mut_ptr().add(len).cast(), old_len - len);
+                    }
+                }
+            }
+        };
+        (
+            Some(destroy),
+            Some(quote_spanned!(end_span=> #local_destroy(this, 0);)),
+            Some(quote_spanned!(end_span=> #local_destroy(this, len);)),
+        )
+    } else {
+        (None, None, None)
+    };
 
     quote_spanned! {end_span=>
         #[automatically_derived]
         #[doc(hidden)]
-        #unsafe_token impl #impl_generics ::cxx::private::ImplVec for #elem #ty_generics {}
+        #unsafe_token impl #impl_generics ::cxx::private::ImplVec #impl_vec_element for #elem #ty_generics {}
+        #destroy
         #[doc(hidden)]
         #[#UnsafeAttr(#ExportNameAttr = #link_new)]
-        unsafe extern "C" fn #local_new #impl_generics(this: *mut ::cxx::private::RustVec<#elem #ty_generics>) {
+        unsafe extern "C" fn #local_new #impl_generics(this: *mut ::cxx::private::RustVec<#elem_ty>) {
             // No prevent_unwind: cannot panic.
             unsafe {
                 ::cxx::core::ptr::write(this, ::cxx::private::RustVec::new());
@@ -1494,34 +2096,37 @@ fn expand_rust_vec(key: NamedImplKey, types: &Types, explicit_impl: Option<&Impl
         }
         #[doc(hidden)]
         #[#UnsafeAttr(#ExportNameAttr = #link_drop)]
-        unsafe extern "C" fn #local_drop #impl_generics(this: *mut ::cxx::private::RustVec<#elem #ty_generics>) {
+        unsafe extern "C" fn #local_drop #impl_generics(this: *mut ::cxx::private::RustVec<#elem_ty>) {
             let __fn = concat!("<", module_path!(), #prevent_unwind_drop_label);
             ::cxx::private::prevent_unwind(
                 __fn,
-                || unsafe { ::cxx::core::ptr::drop_in_place(this) },
+                || unsafe {
+                    #destroy_all
+                    ::cxx::core::ptr::drop_in_place(this);
+                },
             );
         }
         #[doc(hidden)]
         #[#UnsafeAttr(#ExportNameAttr = #link_len)]
-        unsafe extern "C" fn #local_len #impl_generics(this: *const ::cxx::private::RustVec<#elem #ty_generics>) -> usize {
+        unsafe extern "C" fn #local_len #impl_generics(this: *const ::cxx::private::RustVec<#elem_ty>) -> usize {
             // No prevent_unwind: cannot panic.
             unsafe { (*this).len() }
         }
         #[doc(hidden)]
         #[#UnsafeAttr(#ExportNameAttr = #link_capacity)]
-        unsafe extern "C" fn #local_capacity #impl_generics(this: *const ::cxx::private::RustVec<#elem #ty_generics>) -> usize {
+        unsafe extern "C" fn #local_capacity #impl_generics(this: *const ::cxx::private::RustVec<#elem_ty>) -> usize {
             // No prevent_unwind: cannot panic.
             unsafe { (*this).capacity() }
         }
         #[doc(hidden)]
         #[#UnsafeAttr(#ExportNameAttr = #link_data)]
-        unsafe extern "C" fn #local_data #impl_generics(this: *const ::cxx::private::RustVec<#elem #ty_generics>) -> *const #elem #ty_generics {
+        unsafe extern "C" fn #local_data #impl_generics(this: *const ::cxx::private::RustVec<#elem_ty>) -> *const #elem_ty {
             // No prevent_unwind: cannot panic.
             unsafe { (*this).as_ptr() }
         }
         #[doc(hidden)]
         #[#UnsafeAttr(#ExportNameAttr = #link_reserve_total)]
-        unsafe extern "C" fn #local_reserve_total #impl_generics(this: *mut ::cxx::private::RustVec<#elem #ty_generics>, new_cap: usize) {
+        unsafe extern "C" fn #local_reserve_total #impl_generics(this: *mut ::cxx::private::RustVec<#elem_ty>, new_cap: usize) {
             // No prevent_unwind: the global allocator is not allowed to panic.
             unsafe {
                 (*this).reserve_total(new_cap);
@@ -1529,7 +2134,7 @@ fn expand_rust_vec(key: NamedImplKey, types: &Types, explicit_impl: Option<&Impl
         }
         #[doc(hidden)]
         #[#UnsafeAttr(#ExportNameAttr = #link_set_len)]
-        unsafe extern "C" fn #local_set_len #impl_generics(this: *mut ::cxx::private::RustVec<#elem #ty_generics>, len: usize) {
+        unsafe extern "C" fn #local_set_len #impl_generics(this: *mut ::cxx::private::RustVec<#elem_ty>, len: usize) {
             // No prevent_unwind: cannot panic.
             unsafe {
                 (*this).set_len(len);
@@ -1537,11 +2142,14 @@ fn expand_rust_vec(key: NamedImplKey, types: &Types, explicit_impl: Option<&Impl
         }
         #[doc(hidden)]
         #[#UnsafeAttr(#ExportNameAttr = #link_truncate)]
-        unsafe extern "C" fn #local_truncate #impl_generics(this: *mut ::cxx::private::RustVec<#elem #ty_generics>, len: usize) {
+        unsafe extern "C" fn #local_truncate #impl_generics(this: *mut ::cxx::private::RustVec<#elem_ty>, len: usize) {
             let __fn = concat!("<", module_path!(), #prevent_unwind_drop_label);
             ::cxx::private::prevent_unwind(
                 __fn,
-                || unsafe { (*this).truncate(len) },
+                || unsafe {
+                    #destroy_tail
+                    (*this).truncate(len);
+                },
             );
         }
     }
@@ -1727,6 +2335,35 @@ fn expand_shared_ptr(
     }
 }
 
+fn expand_kj_own(key: NamedImplKey, types: &Types, explicit_impl: Option<&Impl>) -> TokenStream {
+    let ident = key.rust;
+    if !types.rust.contains(ident) {
+        return TokenStream::new();
+    }
+
+    let resolve = types.resolve(ident);
+    let link_disposer = format!("cxxbridge1$own${}$disposer", resolve.name.to_symbol());
+
+    let (impl_generics, ty_generics) = generics::split_for_impl(key, explicit_impl, resolve);
+
+    let begin_span = explicit_impl.map_or(key.begin_span, |explicit| explicit.impl_token.span);
+    let end_span = explicit_impl.map_or(key.end_span, |explicit| explicit.brace_token.span.join());
+    let unsafe_token = format_ident!("unsafe", span = begin_span);
+
+    quote_spanned! {end_span=>
+        #[automatically_derived]
+        #unsafe_token impl #impl_generics ::kj_rs::repr::OwnTarget for #ident #ty_generics {
+            fn __disposer() -> *const ::cxx::core::ffi::c_void {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_disposer]
+                    fn __disposer() -> *const ::cxx::core::ffi::c_void;
+                }
+                unsafe { __disposer() }
+            }
+        }
+    }
+}
+
 fn expand_kj_rc(_key: NamedImplKey, _types: &Types, _explicit_impl: Option<&Impl>) -> TokenStream {
     TokenStream::new()
 }
@@ -1873,6 +2510,23 @@ fn expand_cxx_vector(
     let link_unique_ptr_get = format!("{}get", unique_ptr_prefix);
     let link_unique_ptr_release = format!("{}release", unique_ptr_prefix);
     let link_unique_ptr_drop = format!("{}drop", unique_ptr_prefix);
+    let shared_ptr_prefix = format!(
+        "cxxbridge1$shared_ptr$std$vector${}$",
+        resolve.name.to_symbol(),
+    );
+    let link_shared_ptr_null = format!("{}null", shared_ptr_prefix);
+    let link_shared_ptr_clone = format!("{}clone", shared_ptr_prefix);
+    let link_shared_ptr_get = format!("{}get", shared_ptr_prefix);
+    let link_shared_ptr_drop = format!("{}drop", shared_ptr_prefix);
+    let weak_ptr_prefix = format!(
+        "cxxbridge1$weak_ptr$std$vector${}$",
+        resolve.name.to_symbol(),
+    );
+    let link_weak_ptr_null = format!("{}null", weak_ptr_prefix);
+    let link_weak_ptr_clone = format!("{}clone", weak_ptr_prefix);
+    let link_weak_ptr_downgrade = format!("{}downgrade", weak_ptr_prefix);
+    let link_weak_ptr_upgrade = format!("{}upgrade", weak_ptr_prefix);
+    let link_weak_ptr_drop = format!("{}drop", weak_ptr_prefix);
 
     let (impl_generics, ty_generics) = generics::split_for_impl(key, explicit_impl, resolve);
 
@@ -2000,6 +2654,85 @@ fn expand_cxx_vector(
                     __unique_ptr_drop(&raw mut repr);
                 }
             }
+            unsafe fn __shared_ptr_null(new: *mut ::cxx::core::ffi::c_void) {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_shared_ptr_null]
+                    fn __shared_ptr_null(new: *mut ::cxx::core::ffi::c_void);
+                }
+                unsafe {
+                    __shared_ptr_null(new);
+                }
+            }
+            unsafe fn __shared_ptr_clone(this: *const ::cxx::core::ffi::c_void, new: *mut ::cxx::core::ffi::c_void) {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_shared_ptr_clone]
+                    fn __shared_ptr_clone(this: *const ::cxx::core::ffi::c_void, new: *mut ::cxx::core::ffi::c_void);
+                }
+                unsafe {
+                    __shared_ptr_clone(this, new);
+                }
+            }
+            unsafe fn __shared_ptr_get(this: *const ::cxx::core::ffi::c_void) -> *const ::cxx::CxxVector<Self> {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_shared_ptr_get]
+                    fn __shared_ptr_get #impl_generics(this: *const ::cxx::core::ffi::c_void) -> *const ::cxx::CxxVector<#elem #ty_generics>;
+                }
+                unsafe { __shared_ptr_get(this) }
+            }
+            unsafe fn __shared_ptr_drop(this: *mut ::cxx::core::ffi::c_void) {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_shared_ptr_drop]
+                    fn __shared_ptr_drop(this: *mut ::cxx::core::ffi::c_void);
+                }
+                unsafe {
+                    __shared_ptr_drop(this);
+                }
+            }
+            unsafe fn __weak_ptr_null(new: *mut ::cxx::core::ffi::c_void) {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_weak_ptr_null]
+                    fn __weak_ptr_null(new: *mut ::cxx::core::ffi::c_void);
+                }
+                unsafe {
+                    __weak_ptr_null(new);
+                }
+            }
+            unsafe fn __weak_ptr_clone(this: *const ::cxx::core::ffi::c_void, new: *mut ::cxx::core::ffi::c_void) {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_weak_ptr_clone]
+                    fn __weak_ptr_clone(this: *const ::cxx::core::ffi::c_void, new: *mut ::cxx::core::ffi::c_void);
+                }
+                unsafe {
+                    __weak_ptr_clone(this, new);
+                }
+            }
+            unsafe fn __weak_ptr_downgrade(shared: *const ::cxx::core::ffi::c_void, weak: *mut ::cxx::core::ffi::c_void) {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_weak_ptr_downgrade]
+                    fn __weak_ptr_downgrade(shared: *const ::cxx::core::ffi::c_void, weak: *mut ::cxx::core::ffi::c_void);
+                }
+                unsafe {
+                    __weak_ptr_downgrade(shared, weak);
+                }
+            }
+            unsafe fn __weak_ptr_upgrade(weak: *const ::cxx::core::ffi::c_void, shared: *mut ::cxx::core::ffi::c_void) {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_weak_ptr_upgrade]
+                    fn __weak_ptr_upgrade(weak: *const ::cxx::core::ffi::c_void, shared: *mut ::cxx::core::ffi::c_void);
+                }
+                unsafe {
+                    __weak_ptr_upgrade(weak, shared);
+                }
+            }
+            unsafe fn __weak_ptr_drop(this: *mut ::cxx::core::ffi::c_void) {
+                #UnsafeExtern extern "C" {
+                    #[link_name = #link_weak_ptr_drop]
+                    fn __weak_ptr_drop(this: *mut ::cxx::core::ffi::c_void);
+                }
+                unsafe {
+                    __weak_ptr_drop(this);
+                }
+            }
         }
     }
 }
@@ -2035,7 +2768,7 @@ fn expand_extern_type(ty: &Type, types: &Types, proper: bool) -> TokenStream {
         Type::RustVec(ty) => {
             let span = ty.name.span();
             let langle = ty.langle;
-            let elem = expand_extern_type(&ty.inner, types, proper);
+            let elem = expand_rust_vec_element(&ty.inner, types, proper);
             let rangle = ty.rangle;
             quote_spanned!(span=> ::cxx::private::RustVec #langle #elem #rangle)
         }
@@ -2051,7 +2784,7 @@ fn expand_extern_type(ty: &Type, types: &Types, proper: bool) -> TokenStream {
                 Type::RustVec(ty) => {
                     let span = ty.name.span();
                     let langle = ty.langle;
-                    let inner = expand_extern_type(&ty.inner, types, proper);
+                    let inner = expand_rust_vec_element(&ty.inner, types, proper);
                     let rangle = ty.rangle;
                     quote_spanned!(span=> #ampersand #lifetime #mutability ::cxx::private::RustVec #langle #inner #rangle)
                 }
@@ -2089,13 +2822,29 @@ fn expand_extern_type(ty: &Type, types: &Types, proper: bool) -> TokenStream {
             let span = output.span();
             quote_spanned!(span=> ::kj_rs::KjPromiseNodeImpl)
         }
+        Type::KjStream(stream) => {
+            let span = stream.name.span();
+            quote_spanned!(span=> ::kj_rs::KjStreamImpl)
+        }
         Type::KjDate(span) => {
             quote_spanned!(*span=> i64)
         }
+        Type::KjFunction(f) => {
+            let span = f.fn_token.span;
+            quote_spanned!(span=> ::kj_rs::repr::Closure)
+        }
         _ => quote!(#ty),
     }
 }
 
+fn expand_rust_vec_element(elem: &Type, types: &Types, proper: bool) -> TokenStream {
+    match elem {
+        // Stored in the Vec as the smart pointer, not as the raw pointer passed by value.
+        Type::UniquePtr(_) => quote!(#elem),
+        _ => expand_extern_type(elem, types, proper),
+    }
+}
+
 // #UnsafeExtern extern "C" {...}
 // https://blog.rust-lang.org/2024/10/17/Rust-1.82.0.html#safe-items-with-unsafe-extern
 struct UnsafeExtern;
@@ -19,7 +19,16 @@ impl ToTokens for ReceiverType<'_> {
             shorthand: _,
             pin_tokens,
             mutability,
+            rc_tokens,
         } = &self.0;
+        if let Some((rc, langle, rangle)) = rc_tokens {
+            // Passed by pointer, like any other argument that C++ takes ownership of.
+            tokens.extend(quote_spanned!(rc.span()=> *mut ::kj_rs::repr::#rc));
+            langle.to_tokens(tokens);
+            ty.to_tokens(tokens);
+            rangle.to_tokens(tokens);
+            return;
+        }
         if let Some((pin, langle, _rangle)) = pin_tokens {
             tokens.extend(quote_spanned!(pin.span=> ::cxx::core::pin::Pin));
             langle.to_tokens(tokens);
@@ -47,7 +56,15 @@ impl ToTokens for ReceiverTypeSelf<'_> {
             shorthand: _,
             pin_tokens,
             mutability,
+            rc_tokens,
         } = &self.0;
+        if let Some((rc, langle, rangle)) = rc_tokens {
+            tokens.extend(quote_spanned!(rc.span()=> ::kj_rs::repr::#rc));
+            langle.to_tokens(tokens);
+            Token![Self](ty.rust.span()).to_tokens(tokens);
+            rangle.to_tokens(tokens);
+            return;
+        }
         if let Some((pin, langle, _rangle)) = pin_tokens {
             tokens.extend(quote_spanned!(pin.span=> ::cxx::core::pin::Pin));
             langle.to_tokens(tokens);
@@ -558,6 +558,11 @@ void cxxbridge1$kjException$dropInPlace(kj::Exception *err) noexcept {
   delete err;
 }
 
+// copy kj::Exception into a new allocation.
+kj::Exception *cxxbridge1$kjException$clone(kj::Exception *err) noexcept {
+  return new kj::Exception(*err);
+}
+
 // create new kj::CanceledException
 kj::CanceledException *cxxbridge1$kjCanceledException$new() noexcept {
   return new kj::CanceledException{};
@@ -686,6 +691,59 @@ static_assert(sizeof(std::string) <= kMaxExpectedWordsInString * sizeof(void *),
   void cxxbridge1$unique_ptr$std$vector$##RUST_TYPE##$drop(                    \
       std::unique_ptr<std::vector<CXX_TYPE>> *ptr) noexcept {                  \
     ptr->~unique_ptr();                                                        \
+  }                                                                            \
+  static_assert(sizeof(std::shared_ptr<std::vector<CXX_TYPE>>) ==              \
+                    2 * sizeof(void *),                                        \
+                "");                                                           \
+  static_assert(alignof(std::shared_ptr<std::vector<CXX_TYPE>>) ==             \
+                    alignof(void *),                                           \
+                "");                                                           \
+  void cxxbridge1$shared_ptr$std$vector$##RUST_TYPE##$null(                    \
+      std::shared_ptr<std::vector<CXX_TYPE>> *ptr) noexcept {                  \
+    new (ptr) std::shared_ptr<std::vector<CXX_TYPE>>();                        \
+  }                                                                            \
+  void cxxbridge1$shared_ptr$std$vector$##RUST_TYPE##$clone(                   \
+      const std::shared_ptr<std::vector<CXX_TYPE>> &self,                      \
+      std::shared_ptr<std::vector<CXX_TYPE>> *ptr) noexcept {                  \
+    new (ptr) std::shared_ptr<std::vector<CXX_TYPE>>(self);                    \
+  }                                                                            \
+  const std::vector<CXX_TYPE>                                                  \
+      *cxxbridge1$shared_ptr$std$vector$##RUST_TYPE##$get(                     \
+          const std::shared_ptr<std::vector<CXX_TYPE>> &self) noexcept {       \
+    return self.get();                                                         \
+  }                                                                            \
+  void cxxbridge1$shared_ptr$std$vector$##RUST_TYPE##$drop(                    \
+      const std::shared_ptr<std::vector<CXX_TYPE>> *self) noexcept {           \
+    self->~shared_ptr();                                                       \
+  }                                                                            \
+  static_assert(sizeof(std::weak_ptr<std::vector<CXX_TYPE>>) ==                \
+                    2 * sizeof(void *),                                        \
+                "");                                                           \
+  static_assert(alignof(std::weak_ptr<std::vector<CXX_TYPE>>) ==               \
+                    alignof(void *),                                           \
+                "");                                                           \
+  void cxxbridge1$weak_ptr$std$vector$##RUST_TYPE##$null(                      \
+      std::weak_ptr<std::vector<CXX_TYPE>> *ptr) noexcept {                    \
+    new (ptr) std::weak_ptr<std::vector<CXX_TYPE>>();                          \
+  }                                                                            \
+  void cxxbridge1$weak_ptr$std$vector$##RUST_TYPE##$clone(                     \
+      const std::weak_ptr<std::vector<CXX_TYPE>> &self,                        \
+      std::weak_ptr<std::vector<CXX_TYPE>> *ptr) noexcept {                    \
+    new (ptr) std::weak_ptr<std::vector<CXX_TYPE>>(self);                      \
+  }                                                                            \
+  void cxxbridge1$weak_ptr$std$vector$##RUST_TYPE##$downgrade(                 \
+      const std::shared_ptr<std::vector<CXX_TYPE>> &shared,                    \
+      std::weak_ptr<std::vector<CXX_TYPE>> *weak) noexcept {                   \
+    new (weak) std::weak_ptr<std::vector<CXX_TYPE>>(shared);                   \
+  }                                                                            \
+  void cxxbridge1$weak_ptr$std$vector$##RUST_TYPE##$upgrade(                   \
+      const std::weak_ptr<std::vector<CXX_TYPE>> &weak,                        \
+      std::shared_ptr<std::vector<CXX_TYPE>> *shared) noexcept {               \
+    new (shared) std::shared_ptr<std::vector<CXX_TYPE>>(weak.lock());          \
+  }                                                                            \
+  void cxxbridge1$weak_ptr$std$vector$##RUST_TYPE##$drop(                      \
+      const std::weak_ptr<std::vector<CXX_TYPE>> *self) noexcept {             \
+    self->~weak_ptr();                                                         \
   }
 
 #define STD_VECTOR_TRIVIAL_OPS(RUST_TYPE, CXX_TYPE)                            \
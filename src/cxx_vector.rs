@@ -377,6 +377,24 @@ pub unsafe trait VectorElement: Sized {
     unsafe fn __unique_ptr_release(repr: MaybeUninit<*mut c_void>) -> *mut CxxVector<Self>;
     #[doc(hidden)]
     unsafe fn __unique_ptr_drop(repr: MaybeUninit<*mut c_void>);
+    #[doc(hidden)]
+    unsafe fn __shared_ptr_null(new: *mut c_void);
+    #[doc(hidden)]
+    unsafe fn __shared_ptr_clone(this: *const c_void, new: *mut c_void);
+    #[doc(hidden)]
+    unsafe fn __shared_ptr_get(this: *const c_void) -> *const CxxVector<Self>;
+    #[doc(hidden)]
+    unsafe fn __shared_ptr_drop(this: *mut c_void);
+    #[doc(hidden)]
+    unsafe fn __weak_ptr_null(new: *mut c_void);
+    #[doc(hidden)]
+    unsafe fn __weak_ptr_clone(this: *const c_void, new: *mut c_void);
+    #[doc(hidden)]
+    unsafe fn __weak_ptr_downgrade(shared: *const c_void, weak: *mut c_void);
+    #[doc(hidden)]
+    unsafe fn __weak_ptr_upgrade(weak: *const c_void, shared: *mut c_void);
+    #[doc(hidden)]
+    unsafe fn __weak_ptr_drop(this: *mut c_void);
 }
 
 macro_rules! vector_element_by_value_methods {
@@ -469,6 +487,69 @@ macro_rules! impl_vector_element {
                 }
                 unsafe { __unique_ptr_drop(&mut repr) }
             }
+            unsafe fn __shared_ptr_null(new: *mut c_void) {
+                extern "C" {
+                    #[link_name = concat!("cxxbridge1$shared_ptr$std$vector$", $segment, "$null")]
+                    fn __shared_ptr_null(new: *mut c_void);
+                }
+                unsafe { __shared_ptr_null(new) }
+            }
+            unsafe fn __shared_ptr_clone(this: *const c_void, new: *mut c_void) {
+                extern "C" {
+                    #[link_name = concat!("cxxbridge1$shared_ptr$std$vector$", $segment, "$clone")]
+                    fn __shared_ptr_clone(this: *const c_void, new: *mut c_void);
+                }
+                unsafe { __shared_ptr_clone(this, new) }
+            }
+            unsafe fn __shared_ptr_get(this: *const c_void) -> *const CxxVector<Self> {
+                extern "C" {
+                    #[link_name = concat!("cxxbridge1$shared_ptr$std$vector$", $segment, "$get")]
+                    fn __shared_ptr_get(this: *const c_void) -> *const CxxVector<$ty>;
+                }
+                unsafe { __shared_ptr_get(this) }
+            }
+            unsafe fn __shared_ptr_drop(this: *mut c_void) {
+                extern "C" {
+                    #[link_name = concat!("cxxbridge1$shared_ptr$std$vector$", $segment, "$drop")]
+                    fn __shared_ptr_drop(this: *mut c_void);
+                }
+                unsafe { __shared_ptr_drop(this) }
+            }
+            unsafe fn __weak_ptr_null(new: *mut c_void) {
+                extern "C" {
+                    #[link_name = concat!("cxxbridge1$weak_ptr$std$vector$", $segment, "$null")]
+                    fn __weak_ptr_null(new: *mut c_void);
+                }
+                unsafe { __weak_ptr_null(new) }
+            }
+            unsafe fn __weak_ptr_clone(this: *const c_void, new: *mut c_void) {
+                extern "C" {
+                    #[link_name = concat!("cxxbridge1$weak_ptr$std$vector$", $segment, "$clone")]
+                    fn __weak_ptr_clone(this: *const c_void, new: *mut c_void);
+                }
+                unsafe { __weak_ptr_clone(this, new) }
+            }
+            unsafe fn __weak_ptr_downgrade(shared: *const c_void, weak: *mut c_void) {
+                extern "C" {
+                    #[link_name = concat!("cxxbridge1$weak_ptr$std$vector$", $segment, "$downgrade")]
+                    fn __weak_ptr_downgrade(shared: *const c_void, weak: *mut c_void);
+                }
+                unsafe { __weak_ptr_downgrade(shared, weak) }
+            }
+            unsafe fn __weak_ptr_upgrade(weak: *const c_void, shared: *mut c_void) {
+                extern "C" {
+                    #[link_name = concat!("cxxbridge1$weak_ptr$std$vector$", $segment, "$upgrade")]
+                    fn __weak_ptr_upgrade(weak: *const c_void, shared: *mut c_void);
+                }
+                unsafe { __weak_ptr_upgrade(weak, shared) }
+            }
+            unsafe fn __weak_ptr_drop(this: *mut c_void) {
+                extern "C" {
+                    #[link_name = concat!("cxxbridge1$weak_ptr$std$vector$", $segment, "$drop")]
+                    fn __weak_ptr_drop(this: *mut c_void);
+                }
+                unsafe { __weak_ptr_drop(this) }
+            }
         }
     };
 }
@@ -79,6 +79,9 @@ pub(crate) mod repr {
         #[link_name = "cxxbridge1$kjException$dropInPlace"]
         pub fn kj_exception_drop_in_place(err: *mut KjException);
 
+        #[link_name = "cxxbridge1$kjException$clone"]
+        pub fn kj_exception_clone(err: *mut KjException) -> *mut KjException;
+
         #[link_name = "cxxbridge1$kjException$getFile"]
         pub fn kj_exception_get_file(err: *mut KjException) -> *const c_char;
 
@@ -223,6 +226,11 @@ impl KjException {
         Some(result)
     }
 
+    /// Returns the detail of type `D`, if the exception carries one that decodes successfully.
+    pub fn detail<D: ExceptionDetail>(&self) -> Option<D> {
+        find_detail(self.details()?.iter())
+    }
+
     /// Consumes the exception, returning the raw pointer.
     /// # Safety
     /// The caller must ensure that the returned pointer is eventually dropped.
@@ -230,6 +238,15 @@ impl KjException {
         ManuallyDrop::new(self).err
     }
 
+    /// Takes ownership of a `kj::Exception` allocated with new, the inverse of
+    /// [`KjException::into_raw`].
+    /// # Safety
+    /// The pointer must point to a valid `kj::Exception` allocated with new, which is not owned
+    /// by anyone else.
+    pub unsafe fn from_raw(err: NonNull<repr::KjException>) -> Self {
+        Self { err }
+    }
+
     /// File name where the exception was thrown.
     pub fn file(&self) -> &CStr {
         unsafe { CStr::from_ptr(repr::kj_exception_get_file(self.err.as_ptr())) }
@@ -241,12 +258,40 @@ impl KjException {
     }
 }
 
+impl Clone for KjException {
+    fn clone(&self) -> Self {
+        let exception = unsafe { repr::kj_exception_clone(self.err.as_ptr()) };
+        Self {
+            err: NonNull::new(exception).expect("can't allocate new kj::Exception"),
+        }
+    }
+}
+
 impl Drop for KjException {
     fn drop(&mut self) {
         unsafe { repr::kj_exception_drop_in_place(self.err.as_ptr()) }
     }
 }
 
+/// A typed view of a `kj::Exception::Detail`.
+///
+/// kj identifies exception details by a 64-bit id and stores them as bytes. Implementing this
+/// trait pairs a Rust type with its id and byte encoding, so it can be attached with
+/// [`KjError::with_detail`] and read back with [`KjException::detail`]. The C++ side gets the
+/// same view by specializing `rust::ExceptionDetail<T>` from `rust/cxx.h` with the same id and
+/// encoding.
+pub trait ExceptionDetail: Sized {
+    /// The `kj::Exception::DetailTypeId` this detail is stored under.
+    const TYPE_ID: u64;
+
+    /// Encodes the detail into the bytes stored in the exception.
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decodes the detail from the bytes stored in the exception, or returns `None` if they are
+    /// malformed.
+    fn decode(data: &[u8]) -> Option<Self>;
+}
+
 /// Trait for converting a Rust error object into a `kj::Exception`.
 pub trait IntoKjException {
     /// Convert this error into a `kj::Exception` pointer.
@@ -291,6 +336,21 @@ impl KjError {
         self
     }
 
+    /// Attaches `value` as a typed detail, replacing any detail with the same type id.
+    #[must_use]
+    pub fn with_detail<D: ExceptionDetail>(mut self, value: &D) -> Self {
+        let details = self.details.get_or_insert_with(Vec::new);
+        let data = value.encode();
+        match details
+            .iter_mut()
+            .find(|(type_id, _)| *type_id == D::TYPE_ID)
+        {
+            Some(detail) => detail.1 = data,
+            None => details.push((D::TYPE_ID, data)),
+        }
+        self
+    }
+
     /// Adds source location information to this error.
     #[must_use]
     pub fn with_location(mut self, file: String, line: u32) -> Self {
@@ -323,6 +383,18 @@ impl KjError {
     pub fn details(&self) -> Option<&Vec<(u64, Vec<u8>)>> {
         self.details.as_ref()
     }
+
+    /// Returns the detail of type `D`, if one was attached and decodes successfully.
+    pub fn detail<D: ExceptionDetail>(&self) -> Option<D> {
+        find_detail(self.details.as_ref()?.iter())
+    }
+}
+
+fn find_detail<'a, D: ExceptionDetail>(
+    mut details: impl Iterator<Item = &'a (u64, Vec<u8>)>,
+) -> Option<D> {
+    let (_, data) = details.find(|(type_id, _)| *type_id == D::TYPE_ID)?;
+    D::decode(data)
 }
 
 impl IntoKjException for KjError {
@@ -378,6 +450,26 @@ mod tests {
         assert!(exception.details().is_none());
     }
 
+    #[test]
+    fn test_kj_exception_clone() {
+        let details = vec![(7u64, b"detail".to_vec())];
+        let exception = KjException::new(
+            repr::KjExceptionType::Disconnected,
+            "cloned message",
+            "clone.rs",
+            7,
+            Some(&details),
+        );
+        let clone = exception.clone();
+        drop(exception);
+
+        assert_eq!(clone.what(), "cloned message");
+        assert_eq!(clone.r#type(), repr::KjExceptionType::Disconnected);
+        assert_eq!(clone.file(), c"clone.rs");
+        assert_eq!(clone.line(), 7);
+        assert_eq!(clone.details(), Some(details));
+    }
+
     #[test]
     fn test_kj_exception_new_with_single_detail() {
         let details = vec![(123u64, b"detail data".to_vec())];
@@ -521,6 +613,47 @@ mod tests {
         assert_eq!(exc2.details(), Some(&details));
     }
 
+    struct RetryAfter {
+        millis: u32,
+    }
+
+    impl ExceptionDetail for RetryAfter {
+        const TYPE_ID: u64 = 0x5245_5452_5941_4654;
+
+        fn encode(&self) -> Vec<u8> {
+            self.millis.to_le_bytes().to_vec()
+        }
+
+        fn decode(data: &[u8]) -> Option<Self> {
+            let millis = u32::from_le_bytes(data.try_into().ok()?);
+            Some(Self { millis })
+        }
+    }
+
+    #[test]
+    fn test_kj_error_typed_detail() {
+        let kj_error = KjError::new(repr::KjExceptionType::Overloaded, "busy".to_string())
+            .with_details(vec![
+                (RetryAfter::TYPE_ID, b"bad".to_vec()),
+                (5, b"x".to_vec()),
+            ])
+            .with_detail(&RetryAfter { millis: 250 });
+        assert_eq!(kj_error.details().unwrap().len(), 2);
+        assert_eq!(kj_error.detail::<RetryAfter>().unwrap().millis, 250);
+
+        let exception = kj_error.into_kj_exception("detail.rs", 1);
+        assert_eq!(exception.detail::<RetryAfter>().unwrap().millis, 250);
+
+        let exception = KjException::new(
+            repr::KjExceptionType::Failed,
+            "malformed",
+            "detail.rs",
+            2,
+            Some(&vec![(RetryAfter::TYPE_ID, b"bad".to_vec())]),
+        );
+        assert!(exception.detail::<RetryAfter>().is_none());
+    }
+
     #[test]
     fn test_kj_error_into_kj_exception_basic() {
         // Test basic KjError conversion to kj::Exception
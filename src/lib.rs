@@ -439,11 +439,14 @@ mod weak_ptr;
 
 pub use crate::cxx_vector::CxxVector;
 pub use crate::exception::repr::KjExceptionType;
-pub use crate::exception::{CanceledException, IntoKjException, KjError, KjException};
+pub use crate::exception::{
+    CanceledException, ExceptionDetail, IntoKjException, KjError, KjException,
+};
 pub use crate::extern_type::{kind, ExternType};
 pub use crate::shared_ptr::SharedPtr;
 pub use crate::string::CxxString;
 pub use crate::unique_ptr::UniquePtr;
+pub use crate::unwind::PanicBacktrace;
 pub use crate::weak_ptr::WeakPtr;
 pub use cxxbridge_macro::bridge;
 pub use cxxbridge_macro::JsgStruct;
@@ -472,6 +475,7 @@ pub mod private {
     pub use crate::opaque::Opaque;
     pub use crate::result::r#try;
     pub use crate::result::repr::Result;
+    pub use crate::result::try_typed;
     pub use crate::rust_slice::RustSlice;
     pub use crate::rust_str::RustStr;
 
@@ -484,6 +488,7 @@ pub mod private {
     pub use crate::unique_ptr::UniquePtrTarget;
 
     pub use crate::unwind::catch_unwind;
+    pub use crate::unwind::catch_unwind_exception;
 
     pub use crate::unwind::prevent_unwind;
 
@@ -9,7 +9,8 @@ pub(crate) mod repr {
 
     #[repr(C)]
     /// Optional C++ exception. Represents results of calls to C++/rust functions across ffi
-    /// boundaries. Uses pointer tagging: nullptr=None, 0x1=Canceled, other=KjException*
+    /// boundaries. Uses pointer tagging: nullptr=None, 0x1=Canceled, 0x2=TypedError,
+    /// other=KjException*
     pub struct Result {
         pub(crate) exception: *mut KjException,
     }
@@ -23,6 +24,13 @@ pub(crate) mod repr {
             }
         }
 
+        #[allow(clippy::manual_dangling_ptr)]
+        pub(crate) fn typed_error() -> Result {
+            Self {
+                exception: 0x2 as *mut KjException,
+            }
+        }
+
         pub(crate) unsafe fn exception(exception: *mut KjException) -> Result {
             Self { exception }
         }
@@ -86,6 +94,10 @@ pub(crate) mod repr {
             } else if ptr == 1 {
                 // Canceled
                 CanceledException::panic()
+            } else if ptr == 2 {
+                // TypedError is only produced by `extern "Rust"` functions, whose results are
+                // never converted back on the Rust side.
+                unreachable!("typed error result outside of a Result<T, E> shim")
             } else {
                 // KjException
                 Err(crate::KjException {
@@ -127,3 +139,22 @@ where
         Err(err) => err.into_kj_exception(file, line).into(),
     }
 }
+
+/// Convert a Rust result with a typed error into a `repr::Result`, writing the value into ret
+/// if it is Ok and the error into error otherwise.
+pub unsafe fn try_typed<T, E>(
+    ret: *mut T,
+    error: *mut E,
+    result: core::result::Result<T, E>,
+) -> repr::Result {
+    match result {
+        Ok(ok) => {
+            unsafe { core::ptr::write(ret, ok) }
+            repr::Result::ok()
+        }
+        Err(err) => {
+            unsafe { core::ptr::write(error, err) }
+            repr::Result::typed_error()
+        }
+    }
+}
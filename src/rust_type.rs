@@ -2,7 +2,7 @@
 
 pub unsafe trait RustType {}
 pub unsafe trait ImplBox {}
-pub unsafe trait ImplVec {}
+pub unsafe trait ImplVec<Element: ?Sized = Self> {}
 
 #[doc(hidden)]
 pub fn verify_rust_type<T: RustType>() {}
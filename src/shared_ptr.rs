@@ -1,3 +1,4 @@
+use crate::cxx_vector::{CxxVector, VectorElement};
 use crate::fmt::display;
 use crate::kind::Trivial;
 use crate::string::CxxString;
@@ -314,3 +315,24 @@ impl_shared_ptr_target_for_primitive!(f32);
 impl_shared_ptr_target_for_primitive!(f64);
 
 impl_shared_ptr_target!("string", "CxxString", CxxString);
+
+unsafe impl<T> SharedPtrTarget for CxxVector<T>
+where
+    T: VectorElement,
+{
+    fn __typename(f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CxxVector<{}>", display(T::__typename))
+    }
+    unsafe fn __null(new: *mut c_void) {
+        unsafe { T::__shared_ptr_null(new) }
+    }
+    unsafe fn __clone(this: *const c_void, new: *mut c_void) {
+        unsafe { T::__shared_ptr_clone(this, new) }
+    }
+    unsafe fn __get(this: *const c_void) -> *const Self {
+        unsafe { T::__shared_ptr_get(this) }
+    }
+    unsafe fn __drop(this: *mut c_void) {
+        unsafe { T::__shared_ptr_drop(this) }
+    }
+}
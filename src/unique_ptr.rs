@@ -1,9 +1,11 @@
 use crate::cxx_vector::{CxxVector, VectorElement};
 use crate::fmt::display;
 use crate::kind::Trivial;
+use crate::rust_type::RustType;
 use crate::string::CxxString;
 use crate::ExternType;
 
+use alloc::boxed::Box;
 use alloc::string::String;
 
 use alloc::vec::Vec;
@@ -135,6 +137,17 @@ where
     }
 }
 
+impl<T> From<Box<T>> for UniquePtr<T>
+where
+    T: UniquePtrTarget + RustType,
+{
+    /// Moves the object of a Box of a Rust type into a UniquePtr, whose
+    /// deleter drops it like the Box would have.
+    fn from(value: Box<T>) -> Self {
+        unsafe { UniquePtr::from_raw(Box::into_raw(value)) }
+    }
+}
+
 unsafe impl<T> Send for UniquePtr<T> where T: Send + UniquePtrTarget {}
 unsafe impl<T> Sync for UniquePtr<T> where T: Sync + UniquePtrTarget {}
 
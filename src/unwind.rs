@@ -1,10 +1,21 @@
 #![allow(missing_docs)]
 #![allow(dead_code)]
 
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::RefCell;
 use core::mem;
-use std::panic;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::panic::{self, PanicHookInfo};
+use std::sync::Once;
 
-use crate::{result::Result, CanceledException, KjError, KjExceptionType};
+use crate::{
+    result::Result, CanceledException, ExceptionDetail, IntoKjException, KjError, KjException,
+    KjExceptionType,
+};
 
 pub fn prevent_unwind<F, R>(label: &'static str, foreign_call: F) -> R
 where
@@ -42,11 +53,100 @@ impl Drop for Guard {
     }
 }
 
+/// Backtrace of the Rust panic a `kj::Exception` was created from.
+///
+/// Attached as an exception detail when an `extern "Rust"` function panics while backtraces
+/// are enabled through `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`. C++ reads it with
+/// `rust::getDetail<rust::PanicBacktrace>(exception)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanicBacktrace(pub String);
+
+impl ExceptionDetail for PanicBacktrace {
+    // "rustbtrc", must match `rust::ExceptionDetail<rust::PanicBacktrace>` in cxx.h.
+    const TYPE_ID: u64 = 0x7275_7374_6274_7263;
+
+    fn encode(&self) -> Vec<u8> {
+        self.0.as_bytes().to_vec()
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        String::from_utf8(data.to_vec()).ok().map(PanicBacktrace)
+    }
+}
+
+/// Where the most recent panic on this thread happened, recorded by our panic hook because the
+/// payload seen by `catch_unwind` carries no location.
+struct PanicRecord {
+    // The message of the payload, to tell whether the record belongs to the payload caught by
+    // `catch_unwind`: `resume_unwind` doesn't run the hook, so the record may be from an earlier
+    // panic which was caught before reaching the bridge.
+    message: Option<String>,
+    file: String,
+    line: u32,
+    backtrace: Option<String>,
+}
+
+std::thread_local! {
+    static LAST_PANIC: RefCell<Option<PanicRecord>> = const { RefCell::new(None) };
+}
+
+/// Chains a hook in front of the current panic hook which records the location and backtrace
+/// of every panic. A hook installed later by the application replaces it, in which case
+/// exceptions fall back to the location of the ffi shim.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            record_panic(info);
+            previous(info);
+        }));
+    });
+}
+
+fn record_panic(info: &PanicHookInfo) {
+    let Some(location) = info.location() else {
+        return;
+    };
+    let backtrace = Backtrace::capture();
+    let record = PanicRecord {
+        message: payload_message(info.payload()).map(ToOwned::to_owned),
+        file: location.file().to_owned(),
+        line: location.line(),
+        backtrace: match backtrace.status() {
+            BacktraceStatus::Captured => Some(backtrace.to_string()),
+            _ => None,
+        },
+    };
+    // The thread local is gone if the thread is already exiting.
+    let _ = LAST_PANIC.try_with(|last| *last.borrow_mut() = Some(record));
+}
+
+/// Takes the record of the last panic on this thread, if it belongs to `payload`.
+fn take_panic_record(payload: &(dyn Any + Send)) -> Option<PanicRecord> {
+    let record = LAST_PANIC
+        .try_with(|last| last.borrow_mut().take())
+        .ok()
+        .flatten()?;
+    (record.message.as_deref() == payload_message(payload)).then_some(record)
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    if let Some(message) = payload.downcast_ref::<String>() {
+        Some(message)
+    } else if let Some(message) = payload.downcast_ref::<&'static str>() {
+        Some(message)
+    } else {
+        payload.downcast_ref::<KjError>().map(KjError::description)
+    }
+}
+
 /// Run the void `foreign_call`, intercepting panics and converting them to errors.
 pub fn catch_unwind<F>(label: &'static str, foreign_call: F) -> Result
 where
     F: FnOnce(),
 {
+    install_panic_hook();
     match panic::catch_unwind(panic::AssertUnwindSafe(foreign_call)) {
         Ok(()) => Result::ok(),
         Err(err) => panic_result(label, err),
@@ -58,29 +158,79 @@ pub fn try_unwind<F>(label: &'static str, foreign_call: F) -> Result
 where
     F: FnOnce() -> Result,
 {
+    install_panic_hook();
     match panic::catch_unwind(panic::AssertUnwindSafe(foreign_call)) {
         Ok(r) => r,
         Err(err) => panic_result(label, err),
     }
 }
 
-fn panic_result(label: &'static str, err: alloc::boxed::Box<dyn core::any::Any + Send>) -> Result {
-    if let Some(err) = err.downcast_ref::<alloc::string::String>() {
-        return Result::error(
+/// Run `foreign_call` outside of an ffi shim, for example on a worker thread, converting a panic
+/// to the exception a shim would throw. There is no `kj::CanceledException` to report there, so a
+/// panic with `CanceledException` becomes a `DISCONNECTED` exception.
+pub fn catch_unwind_exception<F, R>(
+    label: &'static str,
+    foreign_call: F,
+) -> core::result::Result<R, KjException>
+where
+    F: FnOnce() -> R,
+{
+    install_panic_hook();
+    panic::catch_unwind(panic::AssertUnwindSafe(foreign_call)).map_err(|err| {
+        let error = if err.is::<CanceledException>() {
+            drop(take_panic_record(&*err));
             KjError::new(
-                KjExceptionType::Failed,
-                std::format!("panic in {label}: {err}"),
-            ),
-            file!(),
-            line!(),
-        );
-    } else if err.downcast::<CanceledException>().is_ok() {
+                KjExceptionType::Disconnected,
+                std::format!("{label} canceled"),
+            )
+        } else {
+            panic_kj_error(label, err)
+        };
+        error.into_kj_exception(file!(), line!())
+    })
+}
+
+fn panic_result(label: &'static str, err: Box<dyn Any + Send>) -> Result {
+    if err.is::<CanceledException>() {
+        drop(take_panic_record(&*err));
         return Result::canceled();
     }
+    Result::error(panic_kj_error(label, err), file!(), line!())
+}
+
+/// The error for a panic other than `CanceledException`, with the location and backtrace recorded
+/// by our panic hook.
+fn panic_kj_error(label: &'static str, err: Box<dyn Any + Send>) -> KjError {
+    let record = take_panic_record(&*err);
+    let mut error = match err.downcast::<KjError>() {
+        // `std::panic::panic_any(KjError)` picks the exception to report.
+        Ok(error) => *error,
+        Err(err) => panic_error(label, &*err),
+    };
+    if let Some(record) = record {
+        if error.file().is_none() {
+            error = error.with_location(record.file, record.line);
+        }
+        if let Some(backtrace) = record.backtrace {
+            error = error.with_detail(&PanicBacktrace(backtrace));
+        }
+    }
+    error
+}
+
+fn panic_error(label: &'static str, payload: &(dyn Any + Send)) -> KjError {
+    let Some(message) = payload_message(payload) else {
+        return KjError::new(KjExceptionType::Failed, std::format!("panic in {label}"));
+    };
 
-    Result::error(
-        KjError::new(KjExceptionType::Failed, std::format!("panic in {label}")),
-        file!(),
-        line!(),
-    )
+    // Messages of `todo!()` and `unimplemented!()`, with or without an explanation.
+    let is_unimplemented = ["not yet implemented", "not implemented"]
+        .iter()
+        .any(|prefix| message == *prefix || message.starts_with(&std::format!("{prefix}: ")));
+    let exception_type = if is_unimplemented {
+        KjExceptionType::Unimplemented
+    } else {
+        KjExceptionType::Failed
+    };
+    KjError::new(exception_type, std::format!("panic in {label}: {message}"))
 }
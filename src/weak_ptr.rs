@@ -1,3 +1,5 @@
+use crate::cxx_vector::{CxxVector, VectorElement};
+use crate::fmt::display;
 use crate::shared_ptr::{SharedPtr, SharedPtrTarget};
 use crate::string::CxxString;
 use core::ffi::c_void;
@@ -177,3 +179,27 @@ impl_weak_ptr_target_for_primitive!(f32);
 impl_weak_ptr_target_for_primitive!(f64);
 
 impl_weak_ptr_target!("string", "CxxString", CxxString);
+
+unsafe impl<T> WeakPtrTarget for CxxVector<T>
+where
+    T: VectorElement,
+{
+    fn __typename(f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CxxVector<{}>", display(T::__typename))
+    }
+    unsafe fn __null(new: *mut c_void) {
+        unsafe { T::__weak_ptr_null(new) }
+    }
+    unsafe fn __clone(this: *const c_void, new: *mut c_void) {
+        unsafe { T::__weak_ptr_clone(this, new) }
+    }
+    unsafe fn __downgrade(shared: *const c_void, weak: *mut c_void) {
+        unsafe { T::__weak_ptr_downgrade(shared, weak) }
+    }
+    unsafe fn __upgrade(weak: *const c_void, shared: *mut c_void) {
+        unsafe { T::__weak_ptr_upgrade(weak, shared) }
+    }
+    unsafe fn __drop(this: *mut c_void) {
+        unsafe { T::__weak_ptr_drop(this) }
+    }
+}
@@ -36,6 +36,7 @@ pub struct Parser<'a> {
     pub cxx_name: Option<&'a mut Option<ForeignName>>,
     pub rust_name: Option<&'a mut Option<Ident>>,
     pub variants_from_header: Option<&'a mut Option<Attribute>>,
+    pub one_of: Option<&'a mut Option<Attribute>>,
     pub ignore_unrecognized: bool,
 
     // Suppress clippy needless_update lint ("struct update has no effect, all
@@ -167,6 +168,14 @@ pub fn parse(cx: &mut Errors, attrs: Vec<Attribute>, mut parser: Parser) -> Othe
         } else if attr_path.is_ident("serde") {
             passthrough_attrs.push(attr);
             continue;
+        } else if is_one_of(attr_path) {
+            if let Err(err) = attr.meta.require_path_only() {
+                cx.push(err);
+            }
+            if let Some(one_of) = &mut parser.one_of {
+                **one_of = Some(attr);
+                continue;
+            }
         } else if attr_path.segments.len() > 1 {
             let tool = &attr_path.segments.first().unwrap().ident;
             if tool == "rustfmt" {
@@ -185,6 +194,14 @@ pub fn parse(cx: &mut Errors, attrs: Vec<Attribute>, mut parser: Parser) -> Othe
     OtherAttrs(passthrough_attrs)
 }
 
+// `#[cxx::one_of]`, marking an enum to be bridged as `kj::OneOf`.
+fn is_one_of(path: &Path) -> bool {
+    path.leading_colon.is_none()
+        && path.segments.len() == 2
+        && path.segments[0].ident == "cxx"
+        && path.segments[1].ident == "one_of"
+}
+
 enum DocAttribute {
     Doc(LitStr),
     Hidden,
@@ -2,9 +2,9 @@ use crate::atom::Atom::{self, *};
 use crate::report::Errors;
 use crate::visit::{self, Visit};
 use crate::{
-    error, ident, trivial, Api, Array, Enum, ExternFn, ExternType, Future, Impl, Lang, Lifetimes,
-    NamedType, Ptr, Receiver, Ref, RustType, Signature, SliceRef, Struct, Trait, Ty1, Type,
-    TypeAlias, Types,
+    error, ident, trivial, Api, Array, DataEnum, Enum, ExternFn, ExternTrait, ExternType, Future,
+    Impl, Lang, Lifetimes, NamedType, Ptr, Receiver, Ref, RustType, Signature, SliceRef, Struct,
+    Trait, Ty1, Type, TypeAlias, Types,
 };
 use proc_macro2::{Delimiter, Group, Ident, TokenStream};
 use quote::{quote, ToTokens};
@@ -45,6 +45,8 @@ fn do_typecheck(cx: &mut Check) {
 
     for ty in cx.types {
         check_type(cx, ty);
+        check_nested_kj_stream(cx, ty);
+        check_nested_kj_function(cx, ty);
     }
 
     for api in cx.apis {
@@ -52,10 +54,12 @@ fn do_typecheck(cx: &mut Check) {
             Api::Include(_) => {}
             Api::Struct(strct) => check_api_struct(cx, strct),
             Api::Enum(enm) => check_api_enum(cx, enm),
+            Api::DataEnum(enm) => check_api_data_enum(cx, enm),
             Api::CxxType(ety) | Api::RustType(ety) => check_api_type(cx, ety),
             Api::CxxFunction(efn) | Api::RustFunction(efn) => check_api_fn(cx, efn),
             Api::TypeAlias(alias) => check_api_type_alias(cx, alias),
             Api::Impl(imp) => check_api_impl(cx, imp),
+            Api::Trait(trt) => check_api_trait(cx, trt),
         }
     }
 }
@@ -83,8 +87,18 @@ fn check_type(cx: &mut Check, ty: &Type) {
         Type::Ptr(ty) => check_type_ptr(cx, ty),
         Type::Array(array) => check_type_array(cx, array),
         Type::Fn(ty) => check_type_fn(cx, ty),
+        Type::KjFunction(ty) => check_type_kj_function(cx, ty),
         Type::SliceRef(ty) => check_type_slice_ref(cx, ty),
-        Type::Str(_) | Type::Void(_) | Type::KjDate(_) => {}
+        Type::KjArray(ty) => check_kj_array_element(cx, ty, &ty.inner, "kj::Array"),
+        Type::KjArrayPtr(ty) => check_kj_array_element(cx, ty, &ty.inner, "kj::ArrayPtr"),
+        Type::KjStream(ty) => check_type_kj_stream(cx, ty),
+        Type::KjOwnedPromise(ty) => check_type_kj_promise(cx, ty, "kj::Promise"),
+        Type::KjForkedPromise(ty) => check_type_kj_promise(cx, ty, "kj_rs::ForkedPromise"),
+        Type::Str(_)
+        | Type::Void(_)
+        | Type::KjDate(_)
+        | Type::KjString(_)
+        | Type::KjStringPtr(_) => {}
         Type::Future(ty) => check_type_future(cx, ty),
     }
 }
@@ -94,6 +108,7 @@ fn check_type_ident(cx: &mut Check, name: &NamedType) {
     if Atom::from(ident).is_none()
         && !cx.types.structs.contains_key(ident)
         && !cx.types.enums.contains_key(ident)
+        && !cx.types.data_enums.contains_key(ident)
         && !cx.types.cxx.contains(ident)
         && !cx.types.rust.contains(ident)
     {
@@ -108,6 +123,7 @@ fn check_type_box(cx: &mut Check, ptr: &Ty1) {
             && !cx.types.aliases.contains_key(&ident.rust)
             && !cx.types.structs.contains_key(&ident.rust)
             && !cx.types.enums.contains_key(&ident.rust)
+            && !cx.types.data_enums.contains_key(&ident.rust)
         {
             cx.error(ptr, error::BOX_CXX_TYPE.msg);
         }
@@ -123,12 +139,12 @@ fn check_type_box(cx: &mut Check, ptr: &Ty1) {
 fn check_type_rust_vec(cx: &mut Check, ty: &Ty1) {
     match &ty.inner {
         Type::Ident(ident) => {
-            if cx.types.cxx.contains(&ident.rust)
-                && !cx.types.aliases.contains_key(&ident.rust)
-                && !cx.types.structs.contains_key(&ident.rust)
-                && !cx.types.enums.contains_key(&ident.rust)
-            {
-                cx.error(ty, "Rust Vec containing C++ type is not supported yet");
+            // Extern C++ types aliased to a `cxx::kind::Trivial` ExternType impl are allowed.
+            if is_opaque_cxx(cx, &ident.rust) {
+                cx.error(
+                    ty,
+                    "Rust Vec containing opaque C++ type is not supported, use Vec<KjOwn<T>> or Vec<UniquePtr<T>>, or a type alias with a cxx::kind::Trivial ExternType impl",
+                );
                 return;
             }
 
@@ -142,6 +158,14 @@ fn check_type_rust_vec(cx: &mut Check, ty: &Ty1) {
             }
         }
         Type::Str(_) => return,
+        Type::KjOwn(ptr) | Type::UniquePtr(ptr) => {
+            // The pointer type itself is checked separately.
+            if let Type::Ident(ident) = &ptr.inner {
+                if Atom::from(&ident.rust).is_none() {
+                    return;
+                }
+            }
+        }
         _ => {}
     }
 
@@ -150,11 +174,6 @@ fn check_type_rust_vec(cx: &mut Check, ty: &Ty1) {
 
 fn check_type_unique_ptr(cx: &mut Check, ptr: &Ty1) {
     if let Type::Ident(ident) = &ptr.inner {
-        if cx.types.rust.contains(&ident.rust) {
-            cx.error(ptr, "unique_ptr of a Rust type is not supported yet");
-            return;
-        }
-
         match Atom::from(&ident.rust) {
             None | Some(CxxString) => return,
             _ => {}
@@ -168,14 +187,6 @@ fn check_type_unique_ptr(cx: &mut Check, ptr: &Ty1) {
 
 fn check_type_kj_own(cx: &mut Check, ptr: &Ty1) {
     if let Type::Ident(ident) = &ptr.inner {
-        if cx.types.rust.contains(&ident.rust) {
-            cx.error(
-                ptr,
-                "kj::Own of a Rust type is not supported yet, use a Box instead",
-            );
-            return;
-        }
-
         match Atom::from(&ident.rust) {
             None => return,
             Some(
@@ -227,11 +238,6 @@ fn check_type_kj_arc(cx: &mut Check, ptr: &Ty1) {
 
 fn check_type_shared_ptr(cx: &mut Check, ptr: &Ty1) {
     if let Type::Ident(ident) = &ptr.inner {
-        if cx.types.rust.contains(&ident.rust) {
-            cx.error(ptr, "shared_ptr of a Rust type is not supported yet");
-            return;
-        }
-
         match Atom::from(&ident.rust) {
             None
             | Some(
@@ -241,7 +247,6 @@ fn check_type_shared_ptr(cx: &mut Check, ptr: &Ty1) {
             Some(Char | RustString) => {}
         }
     } else if let Type::CxxVector(_) = &ptr.inner {
-        cx.error(ptr, "std::shared_ptr<std::vector> is not supported yet");
         return;
     }
 
@@ -264,7 +269,6 @@ fn check_type_weak_ptr(cx: &mut Check, ptr: &Ty1) {
             Some(Char | RustString) => {}
         }
     } else if let Type::CxxVector(_) = &ptr.inner {
-        cx.error(ptr, "std::weak_ptr<std::vector> is not supported yet");
         return;
     }
 
@@ -314,6 +318,10 @@ fn check_type_kj_maybe(cx: &mut Check, ptr: &Ty1) {
             check_type_kj_arc(cx, arc);
             return;
         }
+        Type::KjOwnedPromise(promise) => {
+            check_type_kj_promise(cx, promise, "kj::Promise");
+            return;
+        }
         Type::SliceRef(_) | Type::Str(_) => return,
         _ => (),
     }
@@ -363,7 +371,7 @@ fn check_type_ref(cx: &mut Check, ty: &Ref) {
     }
 
     match ty.inner {
-        Type::Fn(_) | Type::Void(_) => {}
+        Type::Fn(_) | Type::KjFunction(_) | Type::Void(_) => {}
         Type::Ref(_) => {
             cx.error(ty, "C++ does not allow references to references");
             return;
@@ -376,7 +384,7 @@ fn check_type_ref(cx: &mut Check, ty: &Ref) {
 
 fn check_type_ptr(cx: &mut Check, ty: &Ptr) {
     match ty.inner {
-        Type::Fn(_) | Type::Void(_) => {}
+        Type::Fn(_) | Type::KjFunction(_) | Type::Void(_) => {}
         Type::Ref(_) => {
             cx.error(ty, "C++ does not allow pointer to reference as a type");
             return;
@@ -408,6 +416,71 @@ fn check_type_slice_ref(cx: &mut Check, ty: &SliceRef) {
     }
 }
 
+fn check_kj_array_element(cx: &mut Check, ty: impl ToTokens, inner: &Type, what: &str) {
+    // Elements are laid out contiguously by whichever side allocated the array, so both languages
+    // need to agree on their size.
+    let supported = !is_unsized(cx, inner) && !matches!(inner, Type::Ref(_) | Type::Future(_));
+
+    if !supported {
+        let msg = format!("{} of {} is not supported", what, describe(cx, inner));
+        cx.error(ty, msg);
+    }
+}
+
+fn check_type_kj_stream(cx: &mut Check, ty: &Ty1) {
+    let supported = !is_unsized(cx, &ty.inner)
+        && !matches!(&ty.inner, Type::Ref(_) | Type::Str(_) | Type::SliceRef(_));
+
+    if !supported {
+        let msg = format!("KjStream of {} is not supported", describe(cx, &ty.inner));
+        cx.error(ty, msg);
+    }
+}
+
+fn check_type_kj_promise(cx: &mut Check, ty: &Ty1, what: &str) {
+    // The result is moved between a Rust value and the resolved promise, so it can't borrow.
+    let supported = matches!(&ty.inner, Type::Void(_))
+        || !is_unsized(cx, &ty.inner)
+            && !matches!(
+                &ty.inner,
+                Type::Ref(_)
+                    | Type::Str(_)
+                    | Type::SliceRef(_)
+                    | Type::KjStringPtr(_)
+                    | Type::KjArrayPtr(_)
+                    | Type::KjStream(_)
+                    | Type::Future(_)
+            );
+
+    if !supported {
+        let msg = format!("{what} of {} is not supported", describe(cx, &ty.inner));
+        cx.error(ty, msg);
+    }
+}
+
+// Streams cross the boundary as a pair of callbacks that only exist in a function's return slot, so
+// they can't be nested inside of other types.
+fn check_nested_kj_stream(cx: &mut Check, ty: &Type) {
+    struct FindKjStream<'a>(Option<&'a Ty1>);
+
+    impl<'a> Visit<'a> for FindKjStream<'a> {
+        fn visit_type(&mut self, ty: &'a Type) {
+            if let Type::KjStream(stream) = ty {
+                self.0 = Some(stream);
+            }
+        }
+    }
+
+    let mut find = FindKjStream(None);
+    visit::visit_type(&mut find, ty);
+    if let Some(stream) = find.0 {
+        cx.error(
+            stream,
+            "KjStream is only supported as a function return type",
+        );
+    }
+}
+
 fn check_type_array(cx: &mut Check, ty: &Array) {
     let supported = !is_unsized(cx, &ty.inner);
 
@@ -418,7 +491,10 @@ fn check_type_array(cx: &mut Check, ty: &Array) {
 
 fn check_type_fn(cx: &mut Check, ty: &Signature) {
     if ty.throws {
-        cx.error(ty, "function pointer returning Result is not supported yet");
+        cx.error(
+            ty,
+            "function pointer returning Result is not supported, use Box<dyn FnMut(...) -> Result<...>> instead",
+        );
     }
 
     for arg in &ty.args {
@@ -433,14 +509,69 @@ fn check_type_fn(cx: &mut Check, ty: &Signature) {
     }
 }
 
+fn check_type_kj_function(cx: &mut Check, ty: &Signature) {
+    for arg in &ty.args {
+        match &arg.ty {
+            Type::Fn(_) | Type::Ptr(_) | Type::KjStream(_) => {
+                let desc = describe(cx, &arg.ty);
+                cx.error(
+                    arg,
+                    format!("passing {} to a closure is not supported", desc),
+                );
+            }
+            arg_ty if is_unsized(cx, arg_ty) => {
+                let desc = describe(cx, arg_ty);
+                cx.error(arg, format!("passing {} by value is not supported", desc));
+            }
+            _ => {}
+        }
+    }
+
+    // The returned value is moved out of the callee, so it can't borrow from the closure.
+    if let Some(ret) = &ty.ret {
+        if has_reference(ret) || matches!(ret, Type::Fn(_) | Type::Ptr(_) | Type::KjStream(_)) {
+            let desc = describe(cx, ret);
+            cx.error(ret, format!("closure returning {} is not supported", desc));
+        } else if is_unsized(cx, ret) {
+            let desc = describe(cx, ret);
+            cx.error(ret, format!("returning {} by value is not supported", desc));
+        }
+    }
+}
+
+// Closures cross the boundary through trampolines generated for a function's own arguments and
+// return value, so they can't be nested inside of other types.
+fn check_nested_kj_function(cx: &mut Check, ty: &Type) {
+    struct FindKjFunction<'a>(Option<&'a Type>);
+
+    impl<'a> Visit<'a> for FindKjFunction<'a> {
+        fn visit_type(&mut self, ty: &'a Type) {
+            if let Type::KjFunction(_) = ty {
+                self.0 = Some(ty);
+            }
+        }
+    }
+
+    let mut find = FindKjFunction(None);
+    visit::visit_type(&mut find, ty);
+    if let Some(function) = find.0 {
+        cx.error(
+            function,
+            "Box<dyn FnMut> is only supported as a function argument or return type",
+        );
+    }
+}
+
 fn check_api_struct(cx: &mut Check, strct: &Struct) {
     let name = &strct.name;
     check_reserved_name(cx, &name.rust);
     check_lifetimes(cx, &strct.generics);
 
-    if strct.fields.is_empty() {
-        let span = span_for_struct_error(strct);
-        cx.error(span, "structs without any fields are not supported");
+    if strct.fields.is_empty() && !strct.generics.lifetimes.is_empty() {
+        cx.error(
+            &strct.generics,
+            "structs without any fields can't have lifetime parameters",
+        );
     }
 
     if cx.types.cxx.contains(&name.rust) {
@@ -463,6 +594,16 @@ fn check_api_struct(cx: &mut Check, strct: &Struct) {
                 field,
                 "function pointers in a struct field are not implemented yet",
             );
+        } else if let Type::KjStream(_) = field.ty {
+            cx.error(
+                field,
+                "KjStream is only supported as a function return type",
+            );
+        } else if let Type::KjFunction(_) = field.ty {
+            cx.error(
+                field,
+                "Box<dyn FnMut> is only supported as a function argument or return type",
+            );
         } else if is_unsized(cx, &field.ty) {
             let desc = describe(cx, &field.ty);
             let msg = format!("using {} by value is not supported", desc);
@@ -494,6 +635,214 @@ fn check_api_enum(cx: &mut Check, enm: &Enum) {
     }
 }
 
+fn check_api_data_enum(cx: &mut Check, enm: &DataEnum) {
+    check_reserved_name(cx, &enm.name.rust);
+
+    let what = if enm.one_of {
+        "#[cxx::one_of] enum"
+    } else {
+        "enum with data"
+    };
+
+    if enm.variants.is_empty() {
+        let span = span_for_data_enum_error(enm);
+        cx.error(
+            span,
+            format!("{} without any variants is not supported", what),
+        );
+    }
+
+    if cx.types.cxx.contains(&enm.name.rust) {
+        if !enm.one_of {
+            let msg = "enum with data cannot be declared in an extern block, its C++ definition is generated";
+            let span = cx
+                .types
+                .untrusted
+                .get(&enm.name.rust)
+                .map_or_else(|| span_for_data_enum_error(enm), |ety| quote!(#ety));
+            cx.error(span, msg);
+        } else if let Some(ety) = cx.types.untrusted.get(&enm.name.rust) {
+            let msg = "extern #[cxx::one_of] enum must be declared in an `unsafe extern` block";
+            cx.error(ety, msg);
+        }
+    }
+
+    for derive in &enm.derives {
+        if derive.what == Trait::Default || derive.what == Trait::ExternType {
+            let msg = format!("derive({}) on {} is not supported", derive, what);
+            cx.error(derive, msg);
+        } else if derive.what == Trait::JsgStruct {
+            let msg = "JsgStruct only applies to structs, not enums";
+            cx.error(derive, msg);
+        }
+    }
+
+    if !enm.one_of {
+        check_data_enum_member_names(cx, enm);
+    }
+
+    for (i, variant) in enm.variants.iter().enumerate() {
+        let Some(ty) = &variant.ty else {
+            continue;
+        };
+        if let Type::Ref(_)
+        | Type::Ptr(_)
+        | Type::Str(_)
+        | Type::SliceRef(_)
+        | Type::KjStringPtr(_)
+        | Type::KjArrayPtr(_)
+        | Type::Fn(_)
+        | Type::KjFunction(_)
+        | Type::KjStream(_)
+        | Type::Future(_) = ty
+        {
+            let desc = describe(cx, ty);
+            let msg = format!("{} in a variant of {} is not supported", desc, what);
+            cx.error(ty, msg);
+        } else if is_unsized(cx, ty) {
+            let desc = describe(cx, ty);
+            let msg = format!("using {} by value is not supported", desc);
+            cx.error(ty, msg);
+        }
+
+        // kj::OneOf finds the active variant by type, so each type may appear
+        // only once.
+        if enm.one_of
+            && enm.variants[..i]
+                .iter()
+                .any(|previous| previous.ty.as_ref() == Some(ty))
+        {
+            let msg = "kj::OneOf cannot hold the same type in more than one variant";
+            cx.error(ty, msg);
+        }
+    }
+}
+
+// The generated tagged struct names its accessors and union members after the
+// variants in snake_case, next to its own `Tag` enum and `tag` member.
+fn check_data_enum_member_names(cx: &mut Check, enm: &DataEnum) {
+    for (i, variant) in enm.variants.iter().enumerate() {
+        let member = variant.name.cxx.to_snake_case();
+        let msg = if member == "tag" {
+            format!(
+                "variant `{}` conflicts with the `tag` member of the C++ tagged struct",
+                variant.name.cxx,
+            )
+        } else if CXX_KEYWORDS.contains(&member.as_str()) {
+            format!(
+                "variant `{}` would be named `{}` in C++, which is a keyword; use #[cxx_name = \"...\"] to rename it",
+                variant.name.cxx, member,
+            )
+        } else if enm.variants[..i]
+            .iter()
+            .any(|previous| previous.name.cxx.to_snake_case() == member)
+        {
+            format!(
+                "variant `{}` would have the same `{}` member in C++ as an earlier variant",
+                variant.name.cxx, member,
+            )
+        } else {
+            continue;
+        };
+        cx.error(&variant.name.rust, msg);
+    }
+}
+
+const CXX_KEYWORDS: &[&str] = &[
+    "alignas",
+    "alignof",
+    "and",
+    "and_eq",
+    "asm",
+    "auto",
+    "bitand",
+    "bitor",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "char8_t",
+    "char16_t",
+    "char32_t",
+    "class",
+    "compl",
+    "concept",
+    "const",
+    "consteval",
+    "constexpr",
+    "constinit",
+    "const_cast",
+    "continue",
+    "co_await",
+    "co_return",
+    "co_yield",
+    "decltype",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "dynamic_cast",
+    "else",
+    "enum",
+    "explicit",
+    "export",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "not",
+    "not_eq",
+    "nullptr",
+    "operator",
+    "or",
+    "or_eq",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "reinterpret_cast",
+    "requires",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "static_assert",
+    "static_cast",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "thread_local",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "typeid",
+    "typename",
+    "union",
+    "unsigned",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "wchar_t",
+    "while",
+    "xor",
+    "xor_eq",
+];
+
 fn check_api_type(cx: &mut Check, ety: &ExternType) {
     check_reserved_name(cx, &ety.name.rust);
     check_lifetimes(cx, &ety.generics);
@@ -568,6 +917,13 @@ fn check_api_fn(cx: &mut Check, efn: &ExternFn) {
                 span,
                 "unsupported receiver type; C++ does not allow member functions on enums",
             );
+        } else if let Some(enm) = cx.types.data_enums.get(&receiver.ty.rust) {
+            let msg = if enm.one_of {
+                "unsupported receiver type; kj::OneOf does not allow adding member functions"
+            } else {
+                "unsupported receiver type; methods on enum with data are not supported"
+            };
+            cx.error(span, msg);
         } else if !cx.types.structs.contains_key(&receiver.ty.rust)
             && !cx.types.cxx.contains(&receiver.ty.rust)
             && !cx.types.rust.contains(&receiver.ty.rust)
@@ -581,6 +937,28 @@ fn check_api_fn(cx: &mut Check, efn: &ExternFn) {
                     receiver.ty.rust,
                 ),
             );
+        } else if let Some((rc, ..)) = &receiver.rc_tokens {
+            // Only the promise of a C++ method can hold on to the reference count; there is no
+            // kj::Rc of a Rust type to hand to a Rust method.
+            if efn.lang != Lang::Cxx || !matches!(efn.ret, Some(Type::Future(_))) {
+                cx.error(
+                    span,
+                    format!("`self: {rc}<...>` receiver is only supported on async C++ methods"),
+                );
+            }
+        } else if efn.lang == Lang::Rust
+            && receiver.lifetime.is_none()
+            && matches!(efn.ret, Some(Type::Future(_)))
+        {
+            // The returned kj::Promise borrows the receiver, and C++ would be free to destroy the
+            // receiver while the promise is still pending.
+            cx.error(
+                span,
+                format!(
+                    "async method must name the lifetime of its receiver -- use `self: &'static {ty}`, or `unsafe` with `self: &'a {ty}` to make C++ responsible for keeping it alive until the promise completes",
+                    ty = receiver.ty.rust,
+                ),
+            );
         }
     }
 
@@ -589,9 +967,11 @@ fn check_api_fn(cx: &mut Check, efn: &ExternFn) {
             if efn.lang == Lang::Rust {
                 cx.error(
                     arg,
-                    "passing a function pointer from C++ to Rust is not implemented yet",
+                    "passing a function pointer from C++ to Rust is not implemented yet, use Box<dyn FnMut(...)> instead",
                 );
             }
+        } else if let Type::KjStream(_) = arg.ty {
+            cx.error(arg, "KjStream is only supported as a function return type");
         } else if let Type::Ptr(_) = arg.ty {
             if efn.sig.unsafety.is_none() {
                 cx.error(
@@ -608,7 +988,15 @@ fn check_api_fn(cx: &mut Check, efn: &ExternFn) {
 
     if let Some(ty) = &efn.ret {
         if let Type::Fn(_) = ty {
-            cx.error(ty, "returning a function pointer is not implemented yet");
+            cx.error(
+                ty,
+                "returning a function pointer is not implemented yet, use Box<dyn FnMut(...)> instead",
+            );
+        } else if let (Type::KjStream(_), true) = (ty, efn.throws) {
+            cx.error(
+                ty,
+                "a function returning KjStream cannot be fallible; report errors through the stream items instead",
+            );
         } else if is_unsized(cx, ty) {
             let desc = describe(cx, ty);
             let msg = format!("returning {} by value is not supported", desc);
@@ -616,11 +1004,51 @@ fn check_api_fn(cx: &mut Check, efn: &ExternFn) {
         }
     }
 
+    if let Some(error) = &efn.error {
+        check_typed_error(cx, efn, error);
+    }
+
     if efn.lang == Lang::Cxx {
         check_mut_return_restriction(cx, efn);
     }
 }
 
+fn check_typed_error(cx: &mut Check, efn: &ExternFn, error: &Type) {
+    if efn.lang == Lang::Cxx {
+        cx.error(
+            error,
+            "Result with a typed error is only supported for extern \"Rust\" functions; C++ functions report errors by throwing",
+        );
+        return;
+    }
+
+    let is_shared = match error {
+        Type::Ident(ident) => {
+            cx.types.structs.contains_key(&ident.rust)
+                || cx.types.enums.contains_key(&ident.rust)
+                || cx.types.data_enums.contains_key(&ident.rust)
+        }
+        _ => false,
+    };
+    if !is_shared {
+        let desc = describe(cx, error);
+        let msg = format!(
+            "unsupported error type {}; the error of a Result must be a shared struct or enum",
+            desc,
+        );
+        cx.error(error, msg);
+    }
+
+    if let Some(ty @ (Type::Ref(_) | Type::Str(_) | Type::SliceRef(_))) = &efn.ret {
+        let desc = describe(cx, ty);
+        let msg = format!(
+            "returning {} inside a Result with a typed error is not supported",
+            desc,
+        );
+        cx.error(ty, msg);
+    }
+}
+
 fn check_api_type_alias(cx: &mut Check, alias: &TypeAlias) {
     check_lifetimes(cx, &alias.generics);
 
@@ -649,6 +1077,61 @@ fn check_api_type_alias(cx: &mut Check, alias: &TypeAlias) {
     }
 }
 
+fn check_api_trait(cx: &mut Check, trt: &ExternTrait) {
+    check_reserved_name(cx, &trt.name.rust);
+
+    for api in cx.apis {
+        let Api::CxxFunction(efn) = api else {
+            continue;
+        };
+        let Some(receiver) = &efn.receiver else {
+            continue;
+        };
+        if receiver.ty.rust != trt.impl_type.name.rust {
+            continue;
+        }
+
+        if receiver.rc_tokens.is_some() {
+            cx.error(
+                span_for_receiver_error(receiver),
+                "trait methods must borrow their receiver",
+            );
+        }
+
+        // The boxed future returned through the trait object can only borrow from `self`.
+        if efn.asyncness.is_some()
+            && receiver.lifetime.is_none()
+            && efn.args.iter().any(|arg| has_reference(&arg.ty))
+        {
+            cx.error(
+                efn,
+                "async trait method with reference arguments must name the lifetime of `self` and of its references",
+            );
+        }
+    }
+}
+
+fn has_reference(ty: &Type) -> bool {
+    struct FindReference(bool);
+
+    impl<'a> Visit<'a> for FindReference {
+        fn visit_type(&mut self, ty: &'a Type) {
+            match ty {
+                Type::Ref(_)
+                | Type::Str(_)
+                | Type::SliceRef(_)
+                | Type::KjStringPtr(_)
+                | Type::KjArrayPtr(_) => self.0 = true,
+                _ => visit::visit_type(self, ty),
+            }
+        }
+    }
+
+    let mut find = FindReference(false);
+    find.visit_type(ty);
+    find.0
+}
+
 fn check_api_impl(cx: &mut Check, imp: &Impl) {
     let ty = &imp.ty;
 
@@ -660,6 +1143,16 @@ fn check_api_impl(cx: &mut Check, imp: &Impl) {
         return;
     }
 
+    if let Type::RustVec(ty) = ty {
+        if let Type::KjOwn(ptr) | Type::UniquePtr(ptr) = &ty.inner {
+            if let Type::Ident(inner) = &ptr.inner {
+                if Atom::from(&inner.rust).is_none() {
+                    return;
+                }
+            }
+        }
+    }
+
     match ty {
         Type::RustBox(ty)
         | Type::RustVec(ty)
@@ -808,6 +1301,14 @@ fn is_unsized(cx: &mut Check, ty: &Type) -> bool {
         | Type::Ptr(_)
         | Type::Str(_)
         | Type::KjDate(_)
+        | Type::KjString(_)
+        | Type::KjStringPtr(_)
+        | Type::KjArray(_)
+        | Type::KjArrayPtr(_)
+        | Type::KjStream(_)
+        | Type::KjOwnedPromise(_)
+        | Type::KjForkedPromise(_)
+        | Type::KjFunction(_)
         | Type::SliceRef(_) => false,
         Type::Future(_) => false,
     }
@@ -817,17 +1318,18 @@ fn is_opaque_cxx(cx: &mut Check, ty: &Ident) -> bool {
     cx.types.cxx.contains(ty)
         && !cx.types.structs.contains_key(ty)
         && !cx.types.enums.contains_key(ty)
+        && !cx.types.data_enums.contains_key(ty)
         && !(cx.types.aliases.contains_key(ty) && cx.types.required_trivial.contains_key(ty))
 }
 
-fn span_for_struct_error(strct: &Struct) -> TokenStream {
-    let struct_token = strct.struct_token;
+fn span_for_enum_error(enm: &Enum) -> TokenStream {
+    let enum_token = enm.enum_token;
     let mut brace_token = Group::new(Delimiter::Brace, TokenStream::new());
-    brace_token.set_span(strct.brace_token.span.join());
-    quote!(#struct_token #brace_token)
+    brace_token.set_span(enm.brace_token.span.join());
+    quote!(#enum_token #brace_token)
 }
 
-fn span_for_enum_error(enm: &Enum) -> TokenStream {
+fn span_for_data_enum_error(enm: &DataEnum) -> TokenStream {
     let enum_token = enm.enum_token;
     let mut brace_token = Group::new(Delimiter::Brace, TokenStream::new());
     brace_token.set_span(enm.brace_token.span.join());
@@ -835,6 +1337,10 @@ fn span_for_enum_error(enm: &Enum) -> TokenStream {
 }
 
 fn span_for_receiver_error(receiver: &Receiver) -> TokenStream {
+    if let Some((rc, langle, rangle)) = &receiver.rc_tokens {
+        let ty = &receiver.ty;
+        return quote!(#rc #langle #ty #rangle);
+    }
     let ampersand = receiver.ampersand;
     let lifetime = &receiver.lifetime;
     let mutability = receiver.mutability;
@@ -861,6 +1367,12 @@ fn describe(cx: &mut Check, ty: &Type) -> String {
                 "struct".to_owned()
             } else if cx.types.enums.contains_key(&ident.rust) {
                 "enum".to_owned()
+            } else if let Some(enm) = cx.types.data_enums.get(&ident.rust) {
+                if enm.one_of {
+                    "kj::OneOf".to_owned()
+                } else {
+                    "enum with data".to_owned()
+                }
             } else if cx.types.aliases.contains_key(&ident.rust) {
                 "C++ type".to_owned()
             } else if cx.types.cxx.contains(&ident.rust) {
@@ -885,12 +1397,20 @@ fn describe(cx: &mut Check, ty: &Type) -> String {
         Type::WeakPtr(_) => "weak_ptr".to_owned(),
         Type::KjMaybe(_) => "kj::Maybe".to_owned(),
         Type::KjDate(_) => "kj::Date".to_owned(),
+        Type::KjString(_) => "kj::String".to_owned(),
+        Type::KjStringPtr(_) => "kj::StringPtr".to_owned(),
+        Type::KjArray(_) => "kj::Array".to_owned(),
+        Type::KjArrayPtr(_) => "kj::ArrayPtr".to_owned(),
+        Type::KjStream(_) => "KjStream".to_owned(),
+        Type::KjOwnedPromise(_) => "kj::Promise".to_owned(),
+        Type::KjForkedPromise(_) => "kj_rs::ForkedPromise".to_owned(),
         Type::Ref(_) => "reference".to_owned(),
         Type::Ptr(_) => "raw pointer".to_owned(),
         Type::Str(_) => "&str".to_owned(),
         Type::CxxVector(_) => "C++ vector".to_owned(),
         Type::SliceRef(_) => "slice".to_owned(),
         Type::Fn(_) => "function pointer".to_owned(),
+        Type::KjFunction(_) => "Box<dyn FnMut>".to_owned(),
         Type::Void(_) => "()".to_owned(),
         Type::Array(_) => "array".to_owned(),
         Type::Future(f) => format!("Future<Output = {}>", describe(cx, &f.output)),
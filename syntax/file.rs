@@ -4,7 +4,7 @@ use quote::quote;
 use syn::parse::{Error, Parse, ParseStream, Result};
 use syn::{
     braced, token, Abi, Attribute, ForeignItem, Ident, Item as RustItem, ItemEnum, ItemImpl,
-    ItemStruct, ItemUse, LitStr, Token, Visibility,
+    ItemStruct, ItemTrait, ItemUse, LitStr, Token, Visibility,
 };
 
 pub struct Module {
@@ -30,6 +30,7 @@ pub enum Item {
     ForeignMod(ItemForeignMod),
     Use(ItemUse),
     Impl(ItemImpl),
+    Trait(ItemTrait),
     Other(RustItem),
 }
 
@@ -127,6 +128,10 @@ impl Parse for Item {
                 item.attrs.splice(..0, attrs);
                 Ok(Item::Use(item))
             }
+            RustItem::Trait(mut item) => {
+                item.attrs.splice(..0, attrs);
+                Ok(Item::Trait(item))
+            }
             other => Ok(Item::Other(other)),
         }
     }
@@ -40,6 +40,12 @@ pub fn check_all(cx: &mut Check, apis: &[Api]) {
                     check(cx, &variant.name);
                 }
             }
+            Api::DataEnum(enm) => {
+                check(cx, &enm.name);
+                for variant in &enm.variants {
+                    check(cx, &variant.name);
+                }
+            }
             Api::CxxType(ety) | Api::RustType(ety) => {
                 check(cx, &ety.name);
             }
@@ -52,6 +58,10 @@ pub fn check_all(cx: &mut Check, apis: &[Api]) {
             Api::TypeAlias(alias) => {
                 check(cx, &alias.name);
             }
+            Api::Trait(trt) => {
+                check(cx, &trt.name);
+                check(cx, &trt.impl_type.name);
+            }
         }
     }
 }
@@ -1,6 +1,6 @@
 use crate::{
-    Array, ExternFn, Future, Include, Lifetimes, Ptr, Receiver, Ref, Signature, SliceRef, Ty1,
-    Type, Var,
+    Array, ArrayPtr, ExternFn, Future, Include, Lifetimes, Ptr, Receiver, Ref, Signature, SliceRef,
+    Ty1, Type, Var,
 };
 use std::hash::{Hash, Hasher};
 use std::mem;
@@ -59,9 +59,16 @@ impl Hash for Type {
             Type::RustVec(t) => t.hash(state),
             Type::CxxVector(t) => t.hash(state),
             Type::Fn(t) => t.hash(state),
+            Type::KjFunction(t) => t.hash(state),
             Type::SliceRef(t) => t.hash(state),
             Type::Array(t) => t.hash(state),
-            Type::Void(_) | Type::KjDate(_) => {}
+            Type::KjStringPtr(t) => t.hash(state),
+            Type::KjArray(t) => t.hash(state),
+            Type::KjArrayPtr(t) => t.hash(state),
+            Type::KjStream(t) => t.hash(state),
+            Type::KjOwnedPromise(t) => t.hash(state),
+            Type::KjForkedPromise(t) => t.hash(state),
+            Type::Void(_) | Type::KjDate(_) | Type::KjString(_) => {}
             Type::Future(t) => t.hash(state),
         }
     }
@@ -83,9 +90,17 @@ impl PartialEq for Type {
             (Type::RustVec(lhs), Type::RustVec(rhs)) => lhs == rhs,
             (Type::CxxVector(lhs), Type::CxxVector(rhs)) => lhs == rhs,
             (Type::Fn(lhs), Type::Fn(rhs)) => lhs == rhs,
+            (Type::KjFunction(lhs), Type::KjFunction(rhs)) => lhs == rhs,
             (Type::SliceRef(lhs), Type::SliceRef(rhs)) => lhs == rhs,
             (Type::Void(_), Type::Void(_)) => true,
             (Type::KjDate(_), Type::KjDate(_)) => true,
+            (Type::KjString(_), Type::KjString(_)) => true,
+            (Type::KjStringPtr(lhs), Type::KjStringPtr(rhs)) => lhs == rhs,
+            (Type::KjArray(lhs), Type::KjArray(rhs)) => lhs == rhs,
+            (Type::KjArrayPtr(lhs), Type::KjArrayPtr(rhs)) => lhs == rhs,
+            (Type::KjStream(lhs), Type::KjStream(rhs)) => lhs == rhs,
+            (Type::KjOwnedPromise(lhs), Type::KjOwnedPromise(rhs)) => lhs == rhs,
+            (Type::KjForkedPromise(lhs), Type::KjForkedPromise(rhs)) => lhs == rhs,
             (Type::Future(lhs), Type::Future(rhs)) => lhs == rhs,
             (_, _) => false,
         }
@@ -237,6 +252,43 @@ impl Hash for Ptr {
     }
 }
 
+impl Eq for ArrayPtr {}
+
+impl PartialEq for ArrayPtr {
+    fn eq(&self, other: &Self) -> bool {
+        let ArrayPtr {
+            name,
+            langle: _,
+            lifetime,
+            inner,
+            rangle: _,
+        } = self;
+        let ArrayPtr {
+            name: name2,
+            langle: _,
+            lifetime: lifetime2,
+            inner: inner2,
+            rangle: _,
+        } = other;
+        name == name2 && lifetime == lifetime2 && inner == inner2
+    }
+}
+
+impl Hash for ArrayPtr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let ArrayPtr {
+            name,
+            langle: _,
+            lifetime,
+            inner,
+            rangle: _,
+        } = self;
+        name.hash(state);
+        lifetime.hash(state);
+        inner.hash(state);
+    }
+}
+
 impl Eq for SliceRef {}
 
 impl PartialEq for SliceRef {
@@ -340,6 +392,7 @@ impl PartialEq for Signature {
             throws,
             paren_token: _,
             throws_tokens: _,
+            error,
         } = self;
         let Signature {
             asyncness: asyncness2,
@@ -352,12 +405,14 @@ impl PartialEq for Signature {
             throws: throws2,
             paren_token: _,
             throws_tokens: _,
+            error: error2,
         } = other;
         asyncness.is_some() == asyncness2.is_some()
             && unsafety.is_some() == unsafety2.is_some()
             && receiver == receiver2
             && ret == ret2
             && throws == throws2
+            && error == error2
             && args.len() == args2.len()
             && args.iter().zip(args2).all(|(arg, arg2)| {
                 let Var {
@@ -396,6 +451,7 @@ impl Hash for Signature {
             throws,
             paren_token: _,
             throws_tokens: _,
+            error,
         } = self;
         asyncness.is_some().hash(state);
         unsafety.is_some().hash(state);
@@ -414,6 +470,7 @@ impl Hash for Signature {
         }
         ret.hash(state);
         throws.hash(state);
+        error.hash(state);
     }
 }
 
@@ -432,6 +489,7 @@ impl PartialEq for Receiver {
             shorthand: _,
             pin_tokens: _,
             mutability: _,
+            rc_tokens,
         } = self;
         let Receiver {
             pinned: pinned2,
@@ -444,8 +502,13 @@ impl PartialEq for Receiver {
             shorthand: _,
             pin_tokens: _,
             mutability: _,
+            rc_tokens: rc_tokens2,
         } = other;
-        pinned == pinned2 && lifetime == lifetime2 && mutable == mutable2 && ty == ty2
+        pinned == pinned2
+            && lifetime == lifetime2
+            && mutable == mutable2
+            && ty == ty2
+            && rc_tokens.as_ref().map(|(rc, ..)| rc) == rc_tokens2.as_ref().map(|(rc, ..)| rc)
     }
 }
 
@@ -462,10 +525,12 @@ impl Hash for Receiver {
             shorthand: _,
             pin_tokens: _,
             mutability: _,
+            rc_tokens,
         } = self;
         pinned.hash(state);
         lifetime.hash(state);
         mutable.hash(state);
         ty.hash(state);
+        rc_tokens.as_ref().map(|(rc, ..)| rc).hash(state);
     }
 }
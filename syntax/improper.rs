@@ -18,6 +18,10 @@ impl<'a> Types<'a> {
                     Definite(atom == RustString)
                 } else if let Some(strct) = self.structs.get(ident) {
                     Depends(&strct.name.rust) // iterate to fixed-point
+                } else if self.data_enums.contains_key(ident) {
+                    // Pointers to data-carrying enums are always passed as
+                    // `*mut c_void`, regardless of what the variants hold.
+                    Definite(true)
                 } else {
                     Definite(self.rust.contains(ident) || self.aliases.contains_key(ident))
                 }
@@ -33,13 +37,20 @@ impl<'a> Types<'a> {
             | Type::KjOwn(_)
             | Type::KjRc(_)
             | Type::KjArc(_)
+            | Type::KjString(_)
+            | Type::KjStringPtr(_)
+            | Type::KjStream(_)
+            | Type::KjOwnedPromise(_)
+            | Type::KjForkedPromise(_)
+            | Type::KjFunction(_)
             | Type::SharedPtr(_)
             | Type::WeakPtr(_)
             | Type::CxxVector(_) => Definite(false),
             Type::Ref(ty) => self.determine_improper_ctype(&ty.inner),
             Type::Ptr(ty) => self.determine_improper_ctype(&ty.inner),
             Type::Array(ty) => self.determine_improper_ctype(&ty.inner),
-            Type::KjMaybe(ty) => self.determine_improper_ctype(&ty.inner),
+            Type::KjMaybe(ty) | Type::KjArray(ty) => self.determine_improper_ctype(&ty.inner),
+            Type::KjArrayPtr(ty) => self.determine_improper_ctype(&ty.inner),
             Type::Future(_) => {
                 todo!("file a workerd-cxx ticket")
             }
@@ -6,7 +6,7 @@ use syn::Token;
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ImplKey<'a> {
     RustBox(NamedImplKey<'a>),
-    RustVec(NamedImplKey<'a>),
+    RustVec(NamedImplKey<'a>, VecElement),
     UniquePtr(NamedImplKey<'a>),
     Own(NamedImplKey<'a>),
     Maybe(NamedImplKey<'a>),
@@ -17,6 +17,15 @@ pub enum ImplKey<'a> {
     CxxVector(NamedImplKey<'a>),
 }
 
+// The element of a `Vec` instantiation: the named type itself, or an owning
+// C++ pointer to it, whose elements are destroyed by C++.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VecElement {
+    Value,
+    KjOwn,
+    UniquePtr,
+}
+
 #[derive(Copy, Clone)]
 pub struct NamedImplKey<'a> {
     #[allow(dead_code)] // only used by cxxbridge-macro, not cxx-build
@@ -37,8 +46,13 @@ impl Type {
                 return Some(ImplKey::RustBox(NamedImplKey::new(ty, ident)));
             }
         } else if let Type::RustVec(ty) = self {
-            if let Type::Ident(ident) = &ty.inner {
-                return Some(ImplKey::RustVec(NamedImplKey::new(ty, ident)));
+            let (inner, element) = match &ty.inner {
+                Type::KjOwn(ptr) => (&ptr.inner, VecElement::KjOwn),
+                Type::UniquePtr(ptr) => (&ptr.inner, VecElement::UniquePtr),
+                inner => (inner, VecElement::Value),
+            };
+            if let Type::Ident(ident) = inner {
+                return Some(ImplKey::RustVec(NamedImplKey::new(ty, ident), element));
             }
         } else if let Type::UniquePtr(ty) = self {
             if let Type::Ident(ident) = &ty.inner {
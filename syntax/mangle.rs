@@ -50,9 +50,10 @@
 //             - second segment from end is `operator` (not possible in type or namespace names)
 //
 //   (g) Closure trampoline.
-//          pattern:  {NAMESPACE...} $ {CXXBRIDGE} $ {TYPE?} $ {NAME} $ {ARGUMENT} $ {DIRECTION}
+//          pattern:  {NAMESPACE...} $ {CXXBRIDGE} $ {TYPE?} $ {NAME} $ {ARGUMENT or return} $ {DIRECTION}
 //          examples:
 //             - org$rust$cxxbridge1$Struct$invoke$f$0
+//             - org$rust$cxxbridge1$make_callback$return$1
 //          defining characteristics:
 //             - last symbol is `0` (C half) or `1` (Rust half) which are not legal identifiers on their own
 //
@@ -118,3 +119,14 @@ pub fn c_trampoline(efn: &ExternFn, var: &Pair, types: &Types) -> Symbol {
 pub fn r_trampoline(efn: &ExternFn, var: &Pair, types: &Types) -> Symbol {
     join!(extern_fn(efn, types), var.rust, 1)
 }
+
+// The C half of the trampoline of a returned closure. `return` is a keyword, so it can't collide
+// with the name of an argument.
+pub fn c_return_trampoline(efn: &ExternFn, types: &Types) -> Symbol {
+    join!(extern_fn(efn, types), "return", 0)
+}
+
+// The Rust half of the trampoline of a returned closure.
+pub fn r_return_trampoline(efn: &ExternFn, types: &Types) -> Symbol {
+    join!(extern_fn(efn, types), "return", 1)
+}
@@ -79,12 +79,14 @@ pub enum Api {
     Include(Include),
     Struct(Struct),
     Enum(Enum),
+    DataEnum(DataEnum),
     CxxType(ExternType),
     CxxFunction(ExternFn),
     RustType(ExternType),
     RustFunction(ExternFn),
     TypeAlias(TypeAlias),
     Impl(Impl),
+    Trait(ExternTrait),
 }
 
 pub struct Include {
@@ -164,6 +166,36 @@ pub struct Enum {
     pub explicit_repr: bool,
 }
 
+/// A shared enum with data-carrying variants.
+pub struct DataEnum {
+    #[allow(dead_code)] // only used by cxx-build, not cxxbridge-macro
+    pub cfg: CfgExpr,
+    pub doc: Doc,
+    pub derives: Vec<Derive>,
+    #[allow(dead_code)] // only used by cxxbridge-macro, not cxx-build
+    pub attrs: OtherAttrs,
+    #[allow(dead_code)] // only used by cxxbridge-macro, not cxx-build
+    pub visibility: Token![pub],
+    pub enum_token: Token![enum],
+    pub name: Pair,
+    pub generics: Lifetimes,
+    pub brace_token: Brace,
+    pub variants: Vec<DataVariant>,
+    /// `#[cxx::one_of]`: the C++ side is a `kj::OneOf` of the variant types,
+    /// rather than a generated tagged struct.
+    pub one_of: bool,
+}
+
+pub struct DataVariant {
+    pub doc: Doc,
+    #[allow(dead_code)] // only used by cxxbridge-macro, not cxx-build
+    pub attrs: OtherAttrs,
+    pub name: Pair,
+    pub discriminant: Discriminant,
+    /// None for unit variants, which only tagged structs support.
+    pub ty: Option<Type>,
+}
+
 pub enum EnumRepr {
     Native {
         atom: Atom,
@@ -212,6 +244,23 @@ pub struct TypeAlias {
     pub semi_token: Token![;],
 }
 
+/// A trait with methods implemented in C++. C++ implements it by deriving from an abstract class of
+/// the same name, and its implementations are the opaque C++ type `impl_type`.
+pub struct ExternTrait {
+    #[allow(dead_code)] // only used by cxx-build, not cxxbridge-macro
+    pub cfg: CfgExpr,
+    pub doc: Doc,
+    #[allow(dead_code)] // only used by cxxbridge-macro, not cxx-build
+    pub attrs: OtherAttrs,
+    #[allow(dead_code)] // only used by cxxbridge-macro, not cxx-build
+    pub visibility: Token![pub],
+    pub trait_token: Token![trait],
+    pub name: Pair,
+    pub impl_type: ExternType,
+    #[allow(dead_code)] // only used by cxxbridge-macro, not cxx-build
+    pub brace_token: Brace,
+}
+
 pub struct Impl {
     #[allow(dead_code)] // only used by cxx-build, not cxxbridge-macro
     pub cfg: CfgExpr,
@@ -244,6 +293,9 @@ pub struct Signature {
     pub throws: bool,
     pub paren_token: Paren,
     pub throws_tokens: Option<(kw::Result, Token![<], Token![>])>,
+    /// `E` of a `Result<T, E>` return type, for functions that fail with a
+    /// shared struct or enum instead of an exception.
+    pub error: Option<Type>,
 }
 
 pub struct Var {
@@ -273,6 +325,8 @@ pub struct Receiver {
     #[allow(dead_code)] // only used by cxxbridge-macro, not cxx-build
     pub pin_tokens: Option<(kw::Pin, Token![<], Token![>])>,
     pub mutability: Option<Token![mut]>,
+    // `KjRc<Self>` or `KjArc<Self>`, for a receiver that the method takes ownership of.
+    pub rc_tokens: Option<(Ident, Token![<], Token![>])>,
 }
 
 pub struct Variant {
@@ -302,9 +356,17 @@ pub enum Type {
     Str(Box<Ref>),
     CxxVector(Box<Ty1>),
     Fn(Box<Signature>),
+    KjFunction(Box<Signature>),
     Void(Span),
     KjMaybe(Box<Ty1>),
     KjDate(Span),
+    KjString(Span),
+    KjStringPtr(NamedType),
+    KjArray(Box<Ty1>),
+    KjArrayPtr(Box<ArrayPtr>),
+    KjStream(Box<Ty1>),
+    KjOwnedPromise(Box<Ty1>),
+    KjForkedPromise(Box<Ty1>),
     SliceRef(Box<SliceRef>),
     Array(Box<Array>),
     Future(Box<Future>),
@@ -335,6 +397,14 @@ pub struct Ptr {
     pub constness: Option<Token![const]>,
 }
 
+pub struct ArrayPtr {
+    pub name: Ident,
+    pub langle: Token![<],
+    pub lifetime: Option<Lifetime>,
+    pub inner: Type,
+    pub rangle: Token![>],
+}
+
 pub struct SliceRef {
     pub ampersand: Token![&],
     pub lifetime: Option<Lifetime>,
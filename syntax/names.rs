@@ -57,6 +57,23 @@ impl ForeignName {
             Err(err) => Err(Error::new(span, err)),
         }
     }
+
+    /// `FooBar` -> `foo_bar`, for the accessors of a generated tagged struct.
+    pub fn to_snake_case(&self) -> String {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut snake = String::new();
+        for (i, &ch) in chars.iter().enumerate() {
+            if ch.is_uppercase() && i > 0 {
+                let prev = chars[i - 1];
+                let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+                if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                    snake.push('_');
+                }
+            }
+            snake.extend(ch.to_lowercase());
+        }
+        snake
+    }
 }
 
 impl Display for ForeignName {
@@ -5,21 +5,23 @@ use crate::file::{Item, ItemForeignMod};
 use crate::report::Errors;
 use crate::Atom::*;
 use crate::{
-    attrs, error, Api, Array, Derive, Doc, Enum, EnumRepr, ExternFn, ExternType, ForeignName,
-    Future, Impl, Include, IncludeKind, Lang, Lifetimes, NamedType, Namespace, Pair, Ptr, Receiver,
-    Ref, Signature, SliceRef, Struct, Ty1, Type, TypeAlias, Var, Variant,
+    attrs, error, Api, Array, ArrayPtr, DataEnum, DataVariant, Derive, Doc, Enum, EnumRepr,
+    ExternFn, ExternTrait, ExternType, ForeignName, Future, Impl, Include, IncludeKind, Lang,
+    Lifetimes, NamedType, Namespace, Pair, Ptr, Receiver, Ref, Signature, SliceRef, Struct, Ty1,
+    Type, TypeAlias, Var, Variant,
 };
 use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
 use quote::{format_ident, quote, quote_spanned};
 use std::mem;
 use syn::parse::{ParseStream, Parser};
 use syn::punctuated::Punctuated;
+use syn::token::Paren;
 use syn::{
     Abi, Attribute, Error, Expr, Fields, FnArg, ForeignItem, ForeignItemFn, ForeignItemType,
-    GenericArgument, GenericParam, Generics, Ident, ItemEnum, ItemImpl, ItemStruct, Lit, LitStr,
-    Pat, PathArguments, Result, ReturnType, Signature as RustSignature, Token, TraitBound,
-    TraitBoundModifier, Type as RustType, TypeArray, TypeBareFn, TypeParamBound, TypePath, TypePtr,
-    TypeReference, Variant as RustVariant, Visibility,
+    GenericArgument, GenericParam, Generics, Ident, ItemEnum, ItemImpl, ItemStruct, ItemTrait, Lit,
+    LitStr, Pat, PathArguments, Result, ReturnType, Signature as RustSignature, Token, TraitBound,
+    TraitBoundModifier, TraitItem, Type as RustType, TypeArray, TypeBareFn, TypeParamBound,
+    TypePath, TypePtr, TypeReference, TypeTraitObject, Variant as RustVariant, Visibility,
 };
 
 pub mod kw {
@@ -48,6 +50,7 @@ pub fn parse_items(
                 Ok(imp) => apis.push(imp),
                 Err(err) => cx.push(err),
             },
+            Item::Trait(item) => parse_trait(cx, item, &mut apis, trusted, namespace),
             Item::Use(item) => cx.error(item, error::USE_NOT_ALLOWED),
             Item::Other(item) => cx.error(item, "unsupported item"),
         }
@@ -78,7 +81,13 @@ fn parse_struct(cx: &mut Errors, mut item: ItemStruct, namespace: &Namespace) ->
 
     let named_fields = match item.fields {
         Fields::Named(fields) => fields,
-        Fields::Unit => return Err(Error::new_spanned(item, "unit structs are not supported")),
+        Fields::Unit => {
+            let msg = format!(
+                "unit structs are not supported, use `struct {} {{}}` instead",
+                item.ident,
+            );
+            return Err(Error::new_spanned(item, msg));
+        }
         Fields::Unnamed(_) => {
             return Err(Error::new_spanned(item, "tuple structs are not supported"));
         }
@@ -187,7 +196,7 @@ fn parse_struct(cx: &mut Errors, mut item: ItemStruct, namespace: &Namespace) ->
     }))
 }
 
-fn parse_enum(cx: &mut Errors, item: ItemEnum, namespace: &Namespace) -> Api {
+fn parse_enum(cx: &mut Errors, mut item: ItemEnum, namespace: &Namespace) -> Api {
     let mut cfg = CfgExpr::Unconditional;
     let mut doc = Doc::new();
     let mut derives = Vec::new();
@@ -196,9 +205,10 @@ fn parse_enum(cx: &mut Errors, item: ItemEnum, namespace: &Namespace) -> Api {
     let mut cxx_name = None;
     let mut rust_name = None;
     let mut variants_from_header = None;
+    let mut one_of = None;
     let attrs = attrs::parse(
         cx,
-        item.attrs,
+        mem::take(&mut item.attrs),
         attrs::Parser {
             cfg: Some(&mut cfg),
             doc: Some(&mut doc),
@@ -208,6 +218,7 @@ fn parse_enum(cx: &mut Errors, item: ItemEnum, namespace: &Namespace) -> Api {
             cxx_name: Some(&mut cxx_name),
             rust_name: Some(&mut rust_name),
             variants_from_header: Some(&mut variants_from_header),
+            one_of: Some(&mut one_of),
             ..Default::default()
         },
     );
@@ -223,6 +234,29 @@ fn parse_enum(cx: &mut Errors, item: ItemEnum, namespace: &Namespace) -> Api {
         cx.error(where_clause, "enum with where-clause is not supported");
     }
 
+    let has_data = item
+        .variants
+        .iter()
+        .any(|variant| !matches!(variant.fields, Fields::Unit));
+    if one_of.is_some() || has_data {
+        let one_of = one_of.is_some();
+        if let Some(variants_from_header) = &variants_from_header {
+            let msg = "#[variants_from_header] is not supported on enum with data";
+            cx.error(variants_from_header, msg);
+        }
+        match repr {
+            None => {}
+            Some(U8) if !one_of => {}
+            Some(_) if one_of => {
+                let msg = "#[repr(...)] is not supported on #[cxx::one_of] enum, its layout follows kj::OneOf";
+                cx.error(&item.ident, msg);
+            }
+            Some(_) => cx.error(&item.ident, "enum with data must be #[repr(u8)]"),
+        }
+        let name = pair(namespace, &item.ident, cxx_name, rust_name);
+        return parse_data_enum(cx, item, cfg, doc, derives, attrs, name, one_of);
+    }
+
     let mut variants = Vec::new();
     let mut discriminants = DiscriminantSet::new(repr);
     for variant in item.variants {
@@ -332,6 +366,110 @@ fn parse_variant(
     })
 }
 
+fn parse_data_enum(
+    cx: &mut Errors,
+    item: ItemEnum,
+    cfg: CfgExpr,
+    doc: Doc,
+    derives: Vec<Derive>,
+    attrs: OtherAttrs,
+    name: Pair,
+    one_of: bool,
+) -> Api {
+    let mut variants = Vec::new();
+    let mut discriminants = DiscriminantSet::new(Some(if one_of { U32 } else { U8 }));
+    if one_of {
+        // Tag 0 is the null state of kj::OneOf, its variants count from 1.
+        let _ = discriminants.insert_next();
+    }
+    for variant in item.variants {
+        match parse_data_variant(cx, variant, &mut discriminants, one_of) {
+            Ok(variant) => variants.push(variant),
+            Err(err) => cx.push(err),
+        }
+    }
+
+    let enum_token = item.enum_token;
+    let visibility = visibility_pub(&item.vis, enum_token.span);
+
+    Api::DataEnum(DataEnum {
+        cfg,
+        doc,
+        derives,
+        attrs,
+        visibility,
+        enum_token,
+        name,
+        generics: Lifetimes::default(),
+        brace_token: item.brace_token,
+        variants,
+        one_of,
+    })
+}
+
+fn parse_data_variant(
+    cx: &mut Errors,
+    mut variant: RustVariant,
+    discriminants: &mut DiscriminantSet,
+    one_of: bool,
+) -> Result<DataVariant> {
+    let mut doc = Doc::new();
+    let mut cxx_name = None;
+    let mut rust_name = None;
+    let attrs = attrs::parse(
+        cx,
+        mem::take(&mut variant.attrs),
+        attrs::Parser {
+            doc: Some(&mut doc),
+            cxx_name: Some(&mut cxx_name),
+            rust_name: Some(&mut rust_name),
+            ..Default::default()
+        },
+    );
+
+    let try_discriminant = match &variant.discriminant {
+        Some((_, expr)) if one_of => {
+            let msg = "explicit discriminant is not supported in #[cxx::one_of] enum, variants are numbered in declaration order like in kj::OneOf";
+            return Err(Error::new_spanned(expr, msg));
+        }
+        Some((_, expr)) => discriminants.insert(expr),
+        None => discriminants.insert_next(),
+    };
+    let discriminant = match try_discriminant {
+        Ok(discriminant) => discriminant,
+        Err(err) => return Err(Error::new_spanned(variant, err)),
+    };
+
+    let ty = match variant.fields {
+        Fields::Unit if !one_of => None,
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let field = fields.unnamed.into_iter().next().unwrap();
+            if let Some(attr) = field.attrs.first() {
+                return Err(Error::new_spanned(attr, "unsupported attribute"));
+            }
+            Some(parse_type(&field.ty)?)
+        }
+        _ => {
+            let msg = if one_of {
+                "variant of #[cxx::one_of] enum must hold exactly one unnamed field"
+            } else {
+                "variant of enum with data must either be a unit variant or hold exactly one unnamed field; use a shared struct for more fields"
+            };
+            return Err(Error::new_spanned(variant, msg));
+        }
+    };
+
+    let name = pair(Namespace::ROOT, &variant.ident, cxx_name, rust_name);
+
+    Ok(DataVariant {
+        doc,
+        attrs,
+        name,
+        discriminant,
+        ty,
+    })
+}
+
 fn parse_foreign_mod(
     cx: &mut Errors,
     foreign_mod: ItemForeignMod,
@@ -435,6 +573,115 @@ fn parse_foreign_mod(
     out.extend(items);
 }
 
+fn parse_trait(
+    cx: &mut Errors,
+    item: ItemTrait,
+    out: &mut Vec<Api>,
+    trusted: bool,
+    namespace: &Namespace,
+) {
+    let mut cfg = CfgExpr::Unconditional;
+    let mut doc = Doc::new();
+    let mut namespace = namespace.clone();
+    let mut cxx_name = None;
+    let attrs = attrs::parse(
+        cx,
+        item.attrs,
+        attrs::Parser {
+            cfg: Some(&mut cfg),
+            doc: Some(&mut doc),
+            namespace: Some(&mut namespace),
+            cxx_name: Some(&mut cxx_name),
+            ..Default::default()
+        },
+    );
+
+    if let Some(unsafety) = item.unsafety {
+        cx.error(unsafety, "unsafe trait is not supported");
+    }
+    if let Some(auto_token) = item.auto_token {
+        cx.error(auto_token, "auto trait is not supported");
+    }
+    if !item.generics.params.is_empty() || item.generics.where_clause.is_some() {
+        cx.error(
+            &item.generics,
+            "trait with generic parameters is not supported yet",
+        );
+    }
+    if let Some(colon_token) = item.colon_token {
+        let supertraits = &item.supertraits;
+        let span = quote!(#colon_token #supertraits);
+        cx.error(span, "supertraits are not supported");
+    }
+
+    let visibility = visibility_pub(&item.vis, item.trait_token.span);
+    let name = pair(namespace.clone(), &item.ident, cxx_name.clone(), None);
+
+    // C++ implementations of the trait are exposed to Rust as an opaque C++ type, whose methods
+    // call the virtual methods of the abstract class.
+    let impl_ident = format_ident!("Cxx{}", item.ident);
+    let impl_type = ExternType {
+        cfg: cfg.clone(),
+        lang: Lang::Cxx,
+        doc: Doc::new(),
+        derives: Vec::new(),
+        attrs: attrs.clone(),
+        visibility,
+        type_token: Token![type](item.trait_token.span),
+        name: pair(namespace.clone(), &item.ident, cxx_name, Some(impl_ident)),
+        generics: Lifetimes::default(),
+        colon_token: None,
+        bounds: Vec::new(),
+        semi_token: Token![;](item.brace_token.span.close()),
+        trusted: true,
+    };
+
+    for trait_item in item.items {
+        let TraitItem::Fn(method) = trait_item else {
+            cx.error(trait_item, "only methods are supported in a trait");
+            continue;
+        };
+        if let Some(default) = &method.default {
+            cx.error(
+                default,
+                "methods of a trait implemented in C++ cannot have a body",
+            );
+            continue;
+        }
+        let foreign_fn = ForeignItemFn {
+            attrs: method.attrs,
+            vis: Visibility::Inherited,
+            sig: method.sig,
+            semi_token: method.semi_token.unwrap_or_default(),
+        };
+        match parse_extern_fn(cx, foreign_fn, Lang::Cxx, trusted, &cfg, &namespace, &attrs) {
+            Ok(Api::CxxFunction(mut efn)) => match &mut efn.receiver {
+                Some(receiver) if receiver.ty.rust == "Self" => {
+                    receiver.ty.rust = impl_type.name.rust.clone();
+                    out.push(Api::CxxFunction(efn));
+                }
+                _ => cx.error(
+                    efn,
+                    "methods of a trait must take `&self` or `self: Pin<&mut Self>`",
+                ),
+            },
+            Ok(_) => unreachable!(),
+            Err(err) => cx.push(err),
+        }
+    }
+
+    out.push(Api::Trait(ExternTrait {
+        cfg,
+        doc,
+        attrs,
+        visibility,
+        trait_token: item.trait_token,
+        name,
+        impl_type,
+        brace_token: item.brace_token,
+    }));
+}
+
 fn parse_lang(abi: &Abi) -> Result<Lang> {
     let Some(name) = &abi.name else {
         return Err(Error::new_spanned(
@@ -591,27 +838,56 @@ fn parse_extern_fn(
                         shorthand: true,
                         pin_tokens: None,
                         mutability: arg.mutability,
+                        rc_tokens: None,
                     });
                     continue;
                 }
                 if let Some(colon_token) = arg.colon_token {
                     let ty = parse_type(&arg.ty)?;
-                    if let Type::Ref(reference) = ty {
-                        if let Type::Ident(ident) = reference.inner {
-                            receiver = Some(Receiver {
-                                pinned: reference.pinned,
-                                ampersand: reference.ampersand,
-                                lifetime: reference.lifetime,
-                                mutable: reference.mutable,
-                                var: Token![self](ident.rust.span()),
-                                colon_token,
-                                ty: ident,
-                                shorthand: false,
-                                pin_tokens: reference.pin_tokens,
-                                mutability: reference.mutability,
-                            });
-                            continue;
+                    match ty {
+                        Type::Ref(reference) => {
+                            if let Type::Ident(ident) = reference.inner {
+                                receiver = Some(Receiver {
+                                    pinned: reference.pinned,
+                                    ampersand: reference.ampersand,
+                                    lifetime: reference.lifetime,
+                                    mutable: reference.mutable,
+                                    var: Token![self](ident.rust.span()),
+                                    colon_token,
+                                    ty: ident,
+                                    shorthand: false,
+                                    pin_tokens: reference.pin_tokens,
+                                    mutability: reference.mutability,
+                                    rc_tokens: None,
+                                });
+                                continue;
+                            }
+                        }
+                        Type::KjRc(rc) | Type::KjArc(rc) => {
+                            let Ty1 {
+                                name,
+                                langle,
+                                inner,
+                                rangle,
+                            } = *rc;
+                            if let Type::Ident(ident) = inner {
+                                receiver = Some(Receiver {
+                                    pinned: false,
+                                    ampersand: Token![&](name.span()),
+                                    lifetime: None,
+                                    mutable: false,
+                                    var: Token![self](ident.rust.span()),
+                                    colon_token,
+                                    ty: ident,
+                                    shorthand: false,
+                                    pin_tokens: None,
+                                    mutability: None,
+                                    rc_tokens: Some((name, langle, rangle)),
+                                });
+                                continue;
+                            }
                         }
+                        _ => {}
                     }
                 }
                 return Err(Error::new_spanned(arg, "unsupported method receiver"));
@@ -648,8 +924,15 @@ fn parse_extern_fn(
     }
 
     let mut throws_tokens = None;
-    let ret = parse_return_type(&foreign_fn.sig.output, &mut throws_tokens)?;
+    let mut error = None;
+    let ret = parse_return_type(&foreign_fn.sig.output, &mut throws_tokens, &mut error)?;
     let asyncness = foreign_fn.sig.asyncness;
+    if let (Some(asyncness), Some(_)) = (asyncness, &error) {
+        return Err(Error::new_spanned(
+            asyncness,
+            "async fn returning Result with a typed error is not supported yet",
+        ));
+    }
     let (ret, throws_tokens) = if asyncness.is_some() {
         (
             Some(Type::Future(Box::new(Future {
@@ -692,6 +975,7 @@ fn parse_extern_fn(
             throws,
             paren_token,
             throws_tokens,
+            error,
         },
         semi_token,
         trusted,
@@ -1072,11 +1356,19 @@ fn parse_impl(cx: &mut Errors, imp: ItemImpl) -> Result<Api> {
         | Type::Ptr(_)
         | Type::Str(_)
         | Type::Fn(_)
+        | Type::KjFunction(_)
         | Type::Void(_)
         | Type::KjDate(_)
+        | Type::KjString(_)
+        | Type::KjStringPtr(_)
+        | Type::KjArray(_)
+        | Type::KjArrayPtr(_)
+        | Type::KjStream(_)
+        | Type::KjOwnedPromise(_)
+        | Type::KjForkedPromise(_)
+        | Type::Future(_)
         | Type::SliceRef(_)
         | Type::Array(_) => Lifetimes::default(),
-        Type::Future(_) => todo!("file a workerd-cxx ticket"),
     };
 
     let negative = negative_token.is_some();
@@ -1221,6 +1513,10 @@ fn parse_type_path(ty: &TypePath) -> Result<Type> {
             PathArguments::None => {
                 if ident == "KjDate" {
                     return Ok(Type::KjDate(ident.span()));
+                } else if ident == "KjString" {
+                    return Ok(Type::KjString(ident.span()));
+                } else if ident == "KjStringPtr" {
+                    return Ok(Type::KjStringPtr(NamedType::new(ident)));
                 }
                 return Ok(Type::Ident(NamedType::new(ident)));
             }
@@ -1297,6 +1593,9 @@ fn parse_type_path(ty: &TypePath) -> Result<Type> {
                     }
                 } else if ident == "Box" && generic.args.len() == 1 {
                     if let GenericArgument::Type(arg) = &generic.args[0] {
+                        if let RustType::TraitObject(arg) = arg {
+                            return parse_type_kj_function(arg);
+                        }
                         let inner = parse_type(arg)?;
                         return Ok(Type::RustBox(Box::new(Ty1 {
                             name: ident,
@@ -1315,6 +1614,63 @@ fn parse_type_path(ty: &TypePath) -> Result<Type> {
                             rangle: generic.gt_token,
                         })));
                     }
+                } else if ident == "KjArray" && generic.args.len() == 1 {
+                    if let GenericArgument::Type(arg) = &generic.args[0] {
+                        let inner = parse_type(arg)?;
+                        return Ok(Type::KjArray(Box::new(Ty1 {
+                            name: ident,
+                            langle: generic.lt_token,
+                            inner,
+                            rangle: generic.gt_token,
+                        })));
+                    }
+                } else if ident == "KjOwnedPromise" && generic.args.len() == 1 {
+                    if let GenericArgument::Type(arg) = &generic.args[0] {
+                        let inner = parse_type(arg)?;
+                        return Ok(Type::KjOwnedPromise(Box::new(Ty1 {
+                            name: ident,
+                            langle: generic.lt_token,
+                            inner,
+                            rangle: generic.gt_token,
+                        })));
+                    }
+                } else if ident == "KjForkedPromise" && generic.args.len() == 1 {
+                    if let GenericArgument::Type(arg) = &generic.args[0] {
+                        let inner = parse_type(arg)?;
+                        return Ok(Type::KjForkedPromise(Box::new(Ty1 {
+                            name: ident,
+                            langle: generic.lt_token,
+                            inner,
+                            rangle: generic.gt_token,
+                        })));
+                    }
+                } else if ident == "KjStream" && generic.args.len() == 1 {
+                    if let GenericArgument::Type(arg) = &generic.args[0] {
+                        let inner = parse_type(arg)?;
+                        return Ok(Type::KjStream(Box::new(Ty1 {
+                            name: ident,
+                            langle: generic.lt_token,
+                            inner,
+                            rangle: generic.gt_token,
+                        })));
+                    }
+                } else if ident == "KjArrayPtr" && (1..=2).contains(&generic.args.len()) {
+                    let lifetime = match &generic.args[0] {
+                        GenericArgument::Lifetime(lifetime) => Some(lifetime.clone()),
+                        _ => None,
+                    };
+                    if lifetime.is_some() == (generic.args.len() == 2) {
+                        if let Some(GenericArgument::Type(arg)) = generic.args.last() {
+                            let inner = parse_type(arg)?;
+                            return Ok(Type::KjArrayPtr(Box::new(ArrayPtr {
+                                name: ident,
+                                langle: generic.lt_token,
+                                lifetime,
+                                inner,
+                                rangle: generic.gt_token,
+                            })));
+                        }
+                    }
                 } else if ident == "Vec" && generic.args.len() == 1 {
                     if let GenericArgument::Type(arg) = &generic.args[0] {
                         let inner = parse_type(arg)?;
@@ -1352,14 +1708,18 @@ fn parse_type_path(ty: &TypePath) -> Result<Type> {
                         }
                     }
                     if only_lifetimes {
-                        return Ok(Type::Ident(NamedType {
+                        let named = NamedType {
                             rust: ident,
                             generics: Lifetimes {
                                 lt_token: Some(generic.lt_token),
                                 lifetimes,
                                 gt_token: Some(generic.gt_token),
                             },
-                        }));
+                        };
+                        if named.rust == "KjStringPtr" && named.generics.lifetimes.len() == 1 {
+                            return Ok(Type::KjStringPtr(named));
+                        }
+                        return Ok(Type::Ident(named));
                     }
                 }
             }
@@ -1427,37 +1787,19 @@ fn parse_type_fn(ty: &TypeBareFn) -> Result<Type> {
         .inputs
         .iter()
         .enumerate()
-        .map(|(i, arg)| {
-            let (ident, colon_token) = match &arg.name {
-                Some((ident, colon_token)) => (ident.clone(), *colon_token),
-                None => {
-                    let fn_span = ty.paren_token.span.join();
-                    let ident = format_ident!("arg{}", i, span = fn_span);
-                    let colon_token = Token![:](fn_span);
-                    (ident, colon_token)
-                }
-            };
-            let ty = parse_type(&arg.ty)?;
-            let cfg = CfgExpr::Unconditional;
-            let doc = Doc::new();
-            let attrs = OtherAttrs::none();
-            let visibility = Token![pub](ident.span());
-            let name = pair(Namespace::default(), &ident, None, None);
-            Ok(Var {
-                cfg,
-                doc,
-                attrs,
-                visibility,
-                name,
-                colon_token,
-                ty,
-            })
-        })
+        .map(|(i, arg)| parse_fn_type_arg(i, arg.name.as_ref(), &arg.ty, ty.paren_token))
         .collect::<Result<_>>()?;
 
     let mut throws_tokens = None;
-    let ret = parse_return_type(&ty.output, &mut throws_tokens)?;
+    let mut error = None;
+    let ret = parse_return_type(&ty.output, &mut throws_tokens, &mut error)?;
     let throws = throws_tokens.is_some();
+    if error.is_some() {
+        return Err(Error::new_spanned(
+            &ty.output,
+            "function pointer returning Result with a typed error is not supported",
+        ));
+    }
 
     let asyncness = None;
     let unsafety = ty.unsafety;
@@ -1477,12 +1819,114 @@ fn parse_type_fn(ty: &TypeBareFn) -> Result<Type> {
         throws,
         paren_token,
         throws_tokens,
+        error,
+    })))
+}
+
+fn parse_fn_type_arg(
+    i: usize,
+    name: Option<&(Ident, Token![:])>,
+    ty: &RustType,
+    paren_token: Paren,
+) -> Result<Var> {
+    let (ident, colon_token) = match name {
+        Some((ident, colon_token)) => (ident.clone(), *colon_token),
+        None => {
+            let fn_span = paren_token.span.join();
+            let ident = format_ident!("arg{}", i, span = fn_span);
+            let colon_token = Token![:](fn_span);
+            (ident, colon_token)
+        }
+    };
+    let ty = parse_type(ty)?;
+    let cfg = CfgExpr::Unconditional;
+    let doc = Doc::new();
+    let attrs = OtherAttrs::none();
+    let visibility = Token![pub](ident.span());
+    let name = pair(Namespace::default(), &ident, None, None);
+    Ok(Var {
+        cfg,
+        doc,
+        attrs,
+        visibility,
+        name,
+        colon_token,
+        ty,
+    })
+}
+
+// `Box<dyn FnMut(Args) -> Ret>`, which C++ sees as `kj::Function<Ret(Args)>`.
+fn parse_type_kj_function(ty: &TypeTraitObject) -> Result<Type> {
+    let unsupported = || {
+        Error::new_spanned(
+            ty,
+            "unsupported trait object, only `Box<dyn FnMut(...) -> ...>` is supported",
+        )
+    };
+
+    if ty.dyn_token.is_none() || ty.bounds.len() != 1 {
+        return Err(unsupported());
+    }
+    let TypeParamBound::Trait(bound) = &ty.bounds[0] else {
+        return Err(unsupported());
+    };
+    if bound.paren_token.is_some()
+        || !matches!(bound.modifier, TraitBoundModifier::None)
+        || bound.lifetimes.is_some()
+        || bound.path.leading_colon.is_some()
+        || bound.path.segments.len() != 1
+    {
+        return Err(unsupported());
+    }
+    let segment = &bound.path.segments[0];
+    let PathArguments::Parenthesized(generic) = &segment.arguments else {
+        return Err(unsupported());
+    };
+    if segment.ident != "FnMut" {
+        return Err(Error::new_spanned(
+            &segment.ident,
+            "boxed closures must be `FnMut`",
+        ));
+    }
+
+    let paren_token = generic.paren_token;
+    let args = generic
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| parse_fn_type_arg(i, None, arg, paren_token))
+        .collect::<Result<_>>()?;
+
+    let mut throws_tokens = None;
+    let mut error = None;
+    let ret = parse_return_type(&generic.output, &mut throws_tokens, &mut error)?;
+    let throws = throws_tokens.is_some();
+    if error.is_some() {
+        return Err(Error::new_spanned(
+            &generic.output,
+            "closure returning Result with a typed error is not supported",
+        ));
+    }
+
+    Ok(Type::KjFunction(Box::new(Signature {
+        asyncness: None,
+        unsafety: None,
+        fn_token: Token![fn](segment.ident.span()),
+        generics: Generics::default(),
+        receiver: None,
+        args,
+        ret,
+        throws,
+        paren_token,
+        throws_tokens,
+        error,
     })))
 }
 
 fn parse_return_type(
     ty: &ReturnType,
     throws_tokens: &mut Option<(kw::Result, Token![<], Token![>])>,
+    error: &mut Option<Type>,
 ) -> Result<Option<Type>> {
     let mut ret = match ty {
         ReturnType::Default => return Ok(None),
@@ -1495,8 +1939,11 @@ fn parse_return_type(
             let segment = &path.segments[0];
             let ident = segment.ident.clone();
             if let PathArguments::AngleBracketed(generic) = &segment.arguments {
-                if ident == "Result" && generic.args.len() == 1 {
+                if ident == "Result" && (1..=2).contains(&generic.args.len()) {
                     if let GenericArgument::Type(arg) = &generic.args[0] {
+                        if let Some(GenericArgument::Type(err)) = generic.args.get(1) {
+                            *error = Some(parse_type(err)?);
+                        }
                         ret = arg;
                         *throws_tokens =
                             Some((kw::Result(ident.span()), generic.lt_token, generic.gt_token));
@@ -1521,7 +1968,13 @@ fn parse_return_type(
 }
 fn has_references_without_lifetime(ty: &Type) -> bool {
     match ty {
-        Type::Fn(_) | Type::Ident(_) | Type::Str(_) | Type::Void(_) | Type::KjDate(_) => false,
+        Type::Fn(_)
+        | Type::KjFunction(_)
+        | Type::Ident(_)
+        | Type::Str(_)
+        | Type::Void(_)
+        | Type::KjDate(_)
+        | Type::KjString(_) => false,
         Type::RustBox(t)
         | Type::RustVec(t)
         | Type::UniquePtr(t)
@@ -1531,11 +1984,17 @@ fn has_references_without_lifetime(ty: &Type) -> bool {
         | Type::SharedPtr(t)
         | Type::WeakPtr(t)
         | Type::KjMaybe(t)
+        | Type::KjArray(t)
+        | Type::KjStream(t)
+        | Type::KjOwnedPromise(t)
+        | Type::KjForkedPromise(t)
         | Type::CxxVector(t) => has_references_without_lifetime(&t.inner),
         Type::Ptr(t) => has_references_without_lifetime(&t.inner),
         Type::Array(t) => has_references_without_lifetime(&t.inner),
         Type::SliceRef(t) => t.lifetime.is_none(),
         Type::Ref(t) => t.lifetime.is_none(),
+        Type::KjStringPtr(t) => t.generics.lifetimes.is_empty(),
+        Type::KjArrayPtr(t) => t.lifetime.is_none() || has_references_without_lifetime(&t.inner),
         Type::Future(t) => has_references_without_lifetime(&t.output),
     }
 }
@@ -31,13 +31,21 @@ impl Types<'_> {
             | Type::SharedPtr(_)
             | Type::WeakPtr(_)
             | Type::CxxVector(_)
+            | Type::KjString(_)
+            | Type::KjArray(_)
+            | Type::KjStream(_)
+            | Type::KjOwnedPromise(_)
+            | Type::KjForkedPromise(_)
+            | Type::KjFunction(_)
             | Type::Void(_) => false,
             Type::Ref(_)
             | Type::Str(_)
             | Type::Fn(_)
             | Type::SliceRef(_)
             | Type::Ptr(_)
-            | Type::KjDate(_) => true,
+            | Type::KjDate(_)
+            | Type::KjStringPtr(_)
+            | Type::KjArrayPtr(_) => true,
             // kj::Maybe can't be considered to be a POD:
             // <https://itanium-cxx-abi.github.io/cxx-abi/abi.html#non-trivial>
             Type::KjMaybe(_) => false,
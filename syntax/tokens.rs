@@ -1,10 +1,11 @@
 use crate::atom::Atom::*;
 use crate::{
-    Array, Atom, Derive, Enum, EnumRepr, ExternFn, ExternType, Future, Impl, Lifetimes, NamedType,
-    Ptr, Ref, Signature, SliceRef, Struct, Ty1, Type, TypeAlias, Var,
+    Array, ArrayPtr, Atom, DataEnum, Derive, Enum, EnumRepr, ExternFn, ExternTrait, ExternType,
+    Future, Impl, Lifetimes, NamedType, Ptr, Ref, Signature, SliceRef, Struct, Ty1, Type,
+    TypeAlias, Var,
 };
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::{quote_spanned, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::spanned::Spanned;
 use syn::{token, Token};
 
@@ -33,13 +34,41 @@ impl ToTokens for Type {
             | Type::WeakPtr(ty)
             | Type::CxxVector(ty)
             | Type::KjMaybe(ty)
+            | Type::KjArray(ty)
+            | Type::KjStream(ty)
+            | Type::KjOwnedPromise(ty)
+            | Type::KjForkedPromise(ty)
             | Type::RustVec(ty) => ty.to_tokens(tokens),
             Type::Ref(r) | Type::Str(r) => r.to_tokens(tokens),
             Type::Ptr(p) => p.to_tokens(tokens),
             Type::Array(a) => a.to_tokens(tokens),
             Type::Fn(f) => f.to_tokens(tokens),
+            Type::KjFunction(f) => {
+                let span = f.fn_token.span;
+                let args = f.args.iter().map(|arg| &arg.ty);
+                let ret = match (&f.ret, f.throws) {
+                    (Some(ret), false) => quote!(-> #ret),
+                    (None, false) => quote!(),
+                    (Some(ret), true) => {
+                        quote_spanned!(span=> -> ::cxx::core::result::Result<#ret, ::cxx::KjException>)
+                    }
+                    (None, true) => {
+                        quote_spanned!(span=> -> ::cxx::core::result::Result<(), ::cxx::KjException>)
+                    }
+                };
+                tokens.extend(quote_spanned! {span=>
+                    ::cxx::alloc::boxed::Box<dyn ::cxx::core::ops::FnMut(#(#args),*) #ret>
+                });
+            }
             Type::Void(span) => tokens.extend(quote_spanned!(*span=> ())),
             Type::KjDate(span) => tokens.extend(quote_spanned!(*span=> ::kj_rs::KjDate)),
+            Type::KjString(span) => tokens.extend(quote_spanned!(*span=> ::kj_rs::repr::KjString)),
+            Type::KjStringPtr(ty) => {
+                let span = ty.rust.span();
+                tokens.extend(quote_spanned!(span=> ::kj_rs::repr::));
+                ty.to_tokens(tokens);
+            }
+            Type::KjArrayPtr(p) => p.to_tokens(tokens),
             Type::SliceRef(r) => r.to_tokens(tokens),
             Type::Future(f) => f.to_tokens(tokens),
         }
@@ -88,9 +117,12 @@ impl ToTokens for Ty1 {
             "Box" => {
                 tokens.extend(quote_spanned!(span=> ::cxx::alloc::boxed::));
             }
-            "KjMaybe" => {
+            "KjMaybe" | "KjArray" | "KjStream" => {
                 tokens.extend(quote_spanned!(span=> ::kj_rs::repr::));
             }
+            "KjOwnedPromise" | "KjForkedPromise" => {
+                tokens.extend(quote_spanned!(span=> ::kj_rs::));
+            }
             "Vec" => {
                 tokens.extend(quote_spanned!(span=> ::cxx::alloc::vec::));
             }
@@ -144,6 +176,28 @@ impl ToTokens for Ptr {
     }
 }
 
+impl ToTokens for ArrayPtr {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ArrayPtr {
+            name,
+            langle,
+            lifetime,
+            inner,
+            rangle,
+        } = self;
+        let span = name.span();
+        tokens.extend(quote_spanned!(span=> ::kj_rs::repr::));
+        name.to_tokens(tokens);
+        langle.to_tokens(tokens);
+        if let Some(lifetime) = lifetime {
+            lifetime.to_tokens(tokens);
+            Token![,](lifetime.apostrophe).to_tokens(tokens);
+        }
+        inner.to_tokens(tokens);
+        rangle.to_tokens(tokens);
+    }
+}
+
 impl ToTokens for SliceRef {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let SliceRef {
@@ -201,6 +255,14 @@ impl ToTokens for ExternType {
     }
 }
 
+impl ToTokens for ExternTrait {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        // Notional token range for error reporting purposes.
+        self.trait_token.to_tokens(tokens);
+        self.name.rust.to_tokens(tokens);
+    }
+}
+
 impl ToTokens for TypeAlias {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         // Notional token range for error reporting purposes.
@@ -228,6 +290,14 @@ impl ToTokens for Enum {
     }
 }
 
+impl ToTokens for DataEnum {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        // Notional token range for error reporting purposes.
+        self.enum_token.to_tokens(tokens);
+        self.name.rust.to_tokens(tokens);
+    }
+}
+
 impl ToTokens for ExternFn {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         // Notional token range for error reporting purposes.
@@ -292,6 +362,7 @@ impl ToTokens for Signature {
             throws: _,
             paren_token,
             throws_tokens,
+            error,
         } = self;
         fn_token.to_tokens(tokens);
         paren_token.surround(tokens, |tokens| {
@@ -303,6 +374,10 @@ impl ToTokens for Signature {
                 result.to_tokens(tokens);
                 langle.to_tokens(tokens);
                 ret.to_tokens(tokens);
+                if let Some(error) = error {
+                    Token![,](rangle.span).to_tokens(tokens);
+                    error.to_tokens(tokens);
+                }
                 rangle.to_tokens(tokens);
             } else {
                 ret.to_tokens(tokens);
@@ -312,6 +387,10 @@ impl ToTokens for Signature {
             result.to_tokens(tokens);
             langle.to_tokens(tokens);
             token::Paren(langle.span).surround(tokens, |_| ());
+            if let Some(error) = error {
+                Token![,](rangle.span).to_tokens(tokens);
+                error.to_tokens(tokens);
+            }
             rangle.to_tokens(tokens);
         }
     }
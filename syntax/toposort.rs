@@ -1,6 +1,6 @@
 use crate::map::{Entry, UnorderedMap as Map};
 use crate::report::Errors;
-use crate::{Api, Struct, Type, Types};
+use crate::{Api, DataEnum, Struct, Type, Types};
 
 enum Mark {
     Visiting,
@@ -36,12 +36,15 @@ fn visit<'a>(
     }
     let mut result = Ok(());
     for field in &strct.fields {
-        if let Type::Ident(ident) = &field.ty {
-            if let Some(inner) = types.structs.get(&ident.rust) {
-                if visit(cx, inner, sorted, marks, types).is_err() {
-                    cx.error(field, "unsupported cyclic data structure");
-                    result = Err(());
-                }
+        // An enum with data holds its variants by value, so they need to be
+        // defined first, same as direct struct fields.
+        let mut inner_structs = Vec::new();
+        let mut enums_seen = Vec::new();
+        by_value_structs(&field.ty, types, &mut inner_structs, &mut enums_seen);
+        for inner in inner_structs {
+            if visit(cx, inner, sorted, marks, types).is_err() {
+                cx.error(field, "unsupported cyclic data structure");
+                result = Err(());
             }
         }
     }
@@ -49,3 +52,30 @@ fn visit<'a>(
     sorted.push(strct);
     result
 }
+
+// Collects the shared structs held by value in `ty`, looking through the
+// variants of enums with data.
+pub(crate) fn by_value_structs<'a>(
+    ty: &Type,
+    types: &Types<'a>,
+    structs: &mut Vec<&'a Struct>,
+    enums_seen: &mut Vec<*const DataEnum>,
+) {
+    let Type::Ident(ident) = ty else {
+        return;
+    };
+    if let Some(strct) = types.structs.get(&ident.rust) {
+        structs.push(strct);
+    } else if let Some(enm) = types.data_enums.get(&ident.rust) {
+        let ptr: *const DataEnum = *enm;
+        if enums_seen.contains(&ptr) {
+            return;
+        }
+        enums_seen.push(ptr);
+        for variant in &enm.variants {
+            if let Some(ty) = &variant.ty {
+                by_value_structs(ty, types, structs, enums_seen);
+            }
+        }
+    }
+}
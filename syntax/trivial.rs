@@ -1,12 +1,13 @@
 use crate::map::UnorderedMap;
 use crate::set::{OrderedSet as Set, UnorderedSet};
-use crate::{Api, Enum, ExternFn, NamedType, Pair, Struct, Type};
+use crate::{Api, DataEnum, Enum, ExternFn, NamedType, Pair, Struct, Type};
 use proc_macro2::Ident;
 use std::fmt::{self, Display};
 
 #[derive(Copy, Clone)]
 pub enum TrivialReason<'a> {
     StructField(&'a Struct),
+    EnumVariant(&'a DataEnum),
     FunctionArgument(&'a ExternFn),
     FunctionReturn(&'a ExternFn),
     BoxTarget,
@@ -20,6 +21,7 @@ pub fn required_trivial_reasons<'a>(
     all: &Set<&'a Type>,
     structs: &UnorderedMap<&'a Ident, &'a Struct>,
     enums: &UnorderedMap<&'a Ident, &'a Enum>,
+    data_enums: &UnorderedMap<&'a Ident, &'a DataEnum>,
     cxx: &UnorderedSet<&'a Ident>,
 ) -> UnorderedMap<&'a Ident, Vec<TrivialReason<'a>>> {
     let mut required_trivial = UnorderedMap::new();
@@ -28,6 +30,7 @@ pub fn required_trivial_reasons<'a>(
         if cxx.contains(&ident.rust)
             && !structs.contains_key(&ident.rust)
             && !enums.contains_key(&ident.rust)
+            && !data_enums.contains_key(&ident.rust)
         {
             required_trivial
                 .entry(&ident.rust)
@@ -46,6 +49,14 @@ pub fn required_trivial_reasons<'a>(
                     }
                 }
             }
+            Api::DataEnum(enm) => {
+                for variant in &enm.variants {
+                    if let Some(Type::Ident(ident)) = &variant.ty {
+                        let reason = TrivialReason::EnumVariant(enm);
+                        insist_extern_types_are_trivial(ident, reason);
+                    }
+                }
+            }
             Api::CxxFunction(efn) | Api::RustFunction(efn) => {
                 if let Some(receiver) = &efn.receiver {
                     if receiver.mutable && !receiver.pinned {
@@ -133,6 +144,7 @@ pub fn as_what<'a>(name: &'a Pair, reasons: &'a [TrivialReason]) -> impl Display
     impl Display for Description<'_> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             let mut field_of = Set::new();
+            let mut variant_of = Set::new();
             let mut argument_of = Set::new();
             let mut return_of = Set::new();
             let mut box_target = false;
@@ -146,6 +158,9 @@ pub fn as_what<'a>(name: &'a Pair, reasons: &'a [TrivialReason]) -> impl Display
                     TrivialReason::StructField(strct) => {
                         field_of.insert(&strct.name.rust);
                     }
+                    TrivialReason::EnumVariant(enm) => {
+                        variant_of.insert(&enm.name.rust);
+                    }
                     TrivialReason::FunctionArgument(efn) => {
                         argument_of.insert(&efn.name.rust);
                     }
@@ -175,6 +190,13 @@ pub fn as_what<'a>(name: &'a Pair, reasons: &'a [TrivialReason]) -> impl Display
                     set: &field_of,
                 });
             }
+            if !variant_of.is_empty() {
+                clauses.push(Clause::Set {
+                    article: "a",
+                    desc: "variant of",
+                    set: &variant_of,
+                });
+            }
             if !argument_of.is_empty() {
                 clauses.push(Clause::Set {
                     article: "an",
@@ -7,7 +7,8 @@ use crate::set::{OrderedSet, UnorderedSet};
 use crate::trivial::{self, TrivialReason};
 use crate::visit::{self, Visit};
 use crate::{
-    toposort, Api, Atom, Enum, EnumRepr, ExternType, Impl, Lifetimes, Pair, Struct, Type, TypeAlias,
+    toposort, Api, Atom, DataEnum, Enum, EnumRepr, ExternType, Impl, Lifetimes, Pair, Struct, Type,
+    TypeAlias,
 };
 use proc_macro2::Ident;
 use quote::ToTokens;
@@ -16,6 +17,7 @@ pub struct Types<'a> {
     pub all: OrderedSet<&'a Type>,
     pub structs: UnorderedMap<&'a Ident, &'a Struct>,
     pub enums: UnorderedMap<&'a Ident, &'a Enum>,
+    pub data_enums: UnorderedMap<&'a Ident, &'a DataEnum>,
     pub cxx: UnorderedSet<&'a Ident>,
     pub rust: UnorderedSet<&'a Ident>,
     pub aliases: UnorderedMap<&'a Ident, &'a TypeAlias>,
@@ -32,6 +34,7 @@ impl<'a> Types<'a> {
         let mut all = OrderedSet::new();
         let mut structs = UnorderedMap::new();
         let mut enums = UnorderedMap::new();
+        let mut data_enums = UnorderedMap::new();
         let mut cxx = UnorderedSet::new();
         let mut rust = UnorderedSet::new();
         let mut aliases = UnorderedMap::new();
@@ -62,7 +65,8 @@ impl<'a> Types<'a> {
         let mut function_names = UnorderedSet::new();
         for api in apis {
             // The same identifier is permitted to be declared as both a shared
-            // enum and extern C++ type, or shared struct and extern C++ type.
+            // enum and extern C++ type, shared struct and extern C++ type, or
+            // #[cxx::one_of] enum and extern C++ type.
             // That indicates to not emit the C++ enum/struct definition because
             // it's defined by the included headers already.
             //
@@ -74,7 +78,8 @@ impl<'a> Types<'a> {
                     if !type_names.insert(ident)
                         && (!cxx.contains(ident)
                             || structs.contains_key(ident)
-                            || enums.contains_key(ident))
+                            || enums.contains_key(ident)
+                            || data_enums.contains_key(ident))
                     {
                         // If already declared as a struct or enum, or if
                         // colliding with something other than an extern C++
@@ -99,7 +104,8 @@ impl<'a> Types<'a> {
                     if !type_names.insert(ident)
                         && (!cxx.contains(ident)
                             || structs.contains_key(ident)
-                            || enums.contains_key(ident))
+                            || enums.contains_key(ident)
+                            || data_enums.contains_key(ident))
                     {
                         // If already declared as a struct or enum, or if
                         // colliding with something other than an extern C++
@@ -114,15 +120,38 @@ impl<'a> Types<'a> {
                     }
                     add_resolution(&enm.name, &enm.generics);
                 }
+                Api::DataEnum(enm) => {
+                    let ident = &enm.name.rust;
+                    if !type_names.insert(ident)
+                        && (!cxx.contains(ident)
+                            || structs.contains_key(ident)
+                            || enums.contains_key(ident)
+                            || data_enums.contains_key(ident))
+                    {
+                        // If already declared as a struct or enum, or
+                        // if colliding with something other than an extern C++
+                        // type, then error.
+                        duplicate_name(cx, enm, ident);
+                    }
+                    data_enums.insert(ident, enm);
+                    for variant in &enm.variants {
+                        if let Some(ty) = &variant.ty {
+                            visit(&mut all, ty);
+                        }
+                    }
+                    add_resolution(&enm.name, &enm.generics);
+                }
                 Api::CxxType(ety) => {
                     let ident = &ety.name.rust;
                     if !type_names.insert(ident)
                         && (cxx.contains(ident)
-                            || !structs.contains_key(ident) && !enums.contains_key(ident))
+                            || !structs.contains_key(ident)
+                                && !enums.contains_key(ident)
+                                && !data_enums.contains_key(ident))
                     {
                         // If already declared as an extern C++ type, or if
-                        // colliding with something which is neither struct nor
-                        // enum, then error.
+                        // colliding with something which is neither struct,
+                        // enum nor #[cxx::one_of] enum, then error.
                         duplicate_name(cx, ety, ident);
                     }
                     cxx.insert(ident);
@@ -151,6 +180,9 @@ impl<'a> Types<'a> {
                     if let Some(ret) = &efn.ret {
                         visit(&mut all, ret);
                     }
+                    if let Some(error) = &efn.error {
+                        visit(&mut all, error);
+                    }
                 }
                 Api::TypeAlias(alias) => {
                     let ident = &alias.name.rust;
@@ -161,6 +193,17 @@ impl<'a> Types<'a> {
                     aliases.insert(ident, alias);
                     add_resolution(&alias.name, &alias.generics);
                 }
+                Api::Trait(trt) => {
+                    if !type_names.insert(&trt.name.rust) {
+                        duplicate_name(cx, trt, &trt.name.rust);
+                    }
+                    let ety = &trt.impl_type;
+                    if !type_names.insert(&ety.name.rust) {
+                        duplicate_name(cx, ety, &ety.name.rust);
+                    }
+                    cxx.insert(&ety.name.rust);
+                    add_resolution(&ety.name, &ety.generics);
+                }
                 Api::Impl(imp) => {
                     visit(&mut all, &imp.ty);
                     if let Some(key) = imp.ty.impl_key() {
@@ -176,7 +219,7 @@ impl<'a> Types<'a> {
             };
             let implicit_impl = match impl_key {
                 ImplKey::RustBox(ident)
-                | ImplKey::RustVec(ident)
+                | ImplKey::RustVec(ident, _)
                 | ImplKey::UniquePtr(ident)
                 | ImplKey::Own(ident)
                 | ImplKey::KjRc(ident)
@@ -198,12 +241,13 @@ impl<'a> Types<'a> {
         // the APIs above, in case some function or struct references a type
         // which is declared subsequently.
         let required_trivial =
-            trivial::required_trivial_reasons(apis, &all, &structs, &enums, &cxx);
+            trivial::required_trivial_reasons(apis, &all, &structs, &enums, &data_enums, &cxx);
 
         let mut types = Types {
             all,
             structs,
             enums,
+            data_enums,
             cxx,
             rust,
             aliases,
@@ -249,6 +293,18 @@ impl<'a> Types<'a> {
             Type::RustBox(_) | Type::UniquePtr(_) => false,
             Type::Array(_) => true,
             Type::Future(_) | Type::KjMaybe(_) | Type::KjOwn(_) => true,
+            // Closures travel as a `kj_rs::repr::Closure`, a pair of pointers.
+            Type::KjFunction(_) => false,
+            // C++ doesn't pass an empty class in any register while the Rust side holds a byte,
+            // so by value the arguments after it would be read from the wrong registers.
+            Type::Ident(ident)
+                if self
+                    .structs
+                    .get(&ident.rust)
+                    .is_some_and(|strct| strct.fields.is_empty()) =>
+            {
+                true
+            }
             _ => !self.is_guaranteed_pod(ty),
         }
     }
@@ -272,6 +328,7 @@ impl<'a> Types<'a> {
     pub fn is_maybe_trivial(&self, ty: &Ident) -> bool {
         self.structs.contains_key(ty)
             || self.enums.contains_key(ty)
+            || self.data_enums.contains_key(ty)
             || self.aliases.contains_key(ty)
     }
 }
@@ -11,7 +11,12 @@ where
     V: Visit<'a> + ?Sized,
 {
     match ty {
-        Type::Ident(_) | Type::Str(_) | Type::Void(_) | Type::KjDate(_) => {}
+        Type::Ident(_)
+        | Type::Str(_)
+        | Type::Void(_)
+        | Type::KjDate(_)
+        | Type::KjString(_)
+        | Type::KjStringPtr(_) => {}
         Type::RustBox(ty)
         | Type::UniquePtr(ty)
         | Type::KjOwn(ty)
@@ -21,12 +26,17 @@ where
         | Type::WeakPtr(ty)
         | Type::CxxVector(ty)
         | Type::KjMaybe(ty)
+        | Type::KjArray(ty)
+        | Type::KjStream(ty)
+        | Type::KjOwnedPromise(ty)
+        | Type::KjForkedPromise(ty)
         | Type::RustVec(ty) => visitor.visit_type(&ty.inner),
         Type::Ref(r) => visitor.visit_type(&r.inner),
         Type::Ptr(p) => visitor.visit_type(&p.inner),
         Type::Array(a) => visitor.visit_type(&a.inner),
+        Type::KjArrayPtr(a) => visitor.visit_type(&a.inner),
         Type::SliceRef(s) => visitor.visit_type(&s.inner),
-        Type::Fn(fun) => {
+        Type::Fn(fun) | Type::KjFunction(fun) => {
             if let Some(ret) = &fun.ret {
                 visitor.visit_type(ret);
             }
@@ -87,6 +87,144 @@ const BRIDGE4: &str = r#"
     }
 "#;
 
+const BRIDGE5: &str = r#"
+    #[cxx::bridge]
+    mod ffi {
+        unsafe extern "C++" {
+            type CxxThing;
+            type RefcountedThing;
+            type AtomicRefcountedThing;
+            async fn cxx_method(self: &CxxThing, value: i32) -> Result<i32>;
+            async fn rc_method(self: KjRc<RefcountedThing>, value: i32) -> Result<i32>;
+            async fn arc_method(self: KjArc<AtomicRefcountedThing>);
+        }
+
+        extern "Rust" {
+            type RustThing;
+            async fn static_method(self: &'static RustThing) -> Result<i32>;
+            async unsafe fn borrowing_method<'a>(self: &'a RustThing, value: i32);
+        }
+    }
+"#;
+
+const BRIDGE6: &str = r#"
+    #[cxx::bridge]
+    mod ffi {
+        extern "Rust" {
+            type RustThing;
+            async fn elided_method(&self) -> Result<i32>;
+        }
+    }
+"#;
+
+const BRIDGE7: &str = r#"
+    #[cxx::bridge]
+    mod ffi {
+        struct Size {
+            width: u32,
+            height: u32,
+        }
+
+        #[cxx::one_of]
+        enum Shape {
+            Rectangle(Size),
+            Circle(f64),
+        }
+
+        struct Holder {
+            shape: Shape,
+        }
+
+        extern "Rust" {
+            fn area(shape: &Shape) -> f64;
+        }
+    }
+"#;
+
+const BRIDGE8: &str = r"
+    #[cxx::bridge]
+    mod ffi {
+        #[cxx::one_of]
+        enum Ambiguous {
+            First(i32),
+            Second(i32),
+        }
+    }
+";
+
+const BRIDGE9: &str = r#"
+    #[cxx::bridge]
+    mod ffi {
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        #[derive(Clone)]
+        enum Event {
+            Start,
+            Move(Point),
+            KeyPress(u32),
+            Text(String),
+        }
+
+        struct Log {
+            last: Event,
+        }
+
+        extern "Rust" {
+            fn describe(event: &Event) -> String;
+        }
+    }
+"#;
+
+const BRIDGE10: &str = r"
+    #[cxx::bridge]
+    mod ffi {
+        enum Setting {
+            Default,
+            Custom(u32),
+        }
+    }
+";
+
+const BRIDGE11: &str = r#"
+    #[cxx::bridge]
+    mod ffi {
+        struct ParseError {
+            position: usize,
+        }
+
+        extern "Rust" {
+            fn parse(text: &str) -> Result<u32, ParseError>;
+            fn validate(text: &str) -> Result<(), ParseError>;
+        }
+    }
+"#;
+
+const BRIDGE12: &str = r#"
+    #[cxx::bridge]
+    mod ffi {
+        struct ParseError {
+            position: usize,
+        }
+
+        unsafe extern "C++" {
+            fn parse(text: &str) -> Result<u32, ParseError>;
+        }
+    }
+"#;
+
+const BRIDGE13: &str = r#"
+    #[cxx::bridge]
+    mod ffi {
+        unsafe extern "C++" {
+            type RefcountedThing;
+            fn sync_method(self: KjRc<RefcountedThing>) -> i32;
+        }
+    }
+"#;
+
 #[test]
 fn test_extern_c_function() {
     let opt = Opt::default();
@@ -173,3 +311,142 @@ fn test_kj_arc_in_shared_struct() {
     assert!(!implementation.contains("cxxbridge1$kj_rs$arc$"));
     assert!(implementation.contains("::rust::ManuallyDrop<::Holder> holder$(::std::move(holder));"));
 }
+
+#[test]
+fn test_async_methods() {
+    let opt = Opt::default();
+    let source = BRIDGE5.parse().unwrap();
+    let generated = generate_header_and_cc(source, &opt).unwrap();
+    let header = str::from_utf8(&generated.header).unwrap();
+    let implementation = str::from_utf8(&generated.implementation).unwrap();
+    assert!(header.contains("kj::Promise<::std::int32_t> static_method() const;"));
+    assert!(header.contains("kj::Promise<void> borrowing_method(::std::int32_t value) const;"));
+    assert!(implementation.contains(
+        "kj::Promise<::std::int32_t> (::CxxThing::*cxx_method$)(::std::int32_t) const = &::CxxThing::cxx_method;"
+    ));
+    assert!(implementation.contains("cxxbridge1$RustThing$static_method(*this, &return$.value);"));
+    assert!(implementation.contains("(::kj::Rc<::RefcountedThing> *self, ::std::int32_t value, "));
+    assert!(implementation.contains("::kj::Rc<::RefcountedThing> self$(::std::move(*self));"));
+    assert!(implementation.contains("((*self$).*rc_method$)(value).attach(::std::move(self$))"));
+    assert!(
+        implementation.contains("::kj::Arc<::AtomicRefcountedThing> self$(::std::move(*self));")
+    );
+    assert!(implementation.contains("((*self$).*arc_method$)().attach(::std::move(self$))"));
+}
+
+#[test]
+fn test_async_method_requires_receiver_lifetime() {
+    let opt = Opt::default();
+    let source = BRIDGE6.parse().unwrap();
+    let Err(error) = generate_header_and_cc(source, &opt) else {
+        panic!("async method with an elided receiver lifetime must be rejected");
+    };
+    assert!(error
+        .to_string()
+        .contains("async method must name the lifetime of its receiver"));
+}
+
+#[test]
+fn test_one_of() {
+    let opt = Opt::default();
+    let source = BRIDGE7.parse().unwrap();
+    let generated = generate_header_and_cc(source, &opt).unwrap();
+    let header = str::from_utf8(&generated.header).unwrap();
+    let implementation = str::from_utf8(&generated.implementation).unwrap();
+    assert!(header.contains("using Shape = ::kj::OneOf<::Size, double>;"));
+    assert!(header.contains("::Shape shape;"));
+    assert!(header.contains("kj-rs/kj-rs.h"));
+    assert!(implementation.contains("static_assert(::kj_rs::repr::OneOfLayout<::Shape>::matches"));
+}
+
+#[test]
+fn test_one_of_rejects_duplicate_variant_types() {
+    let opt = Opt::default();
+    let source = BRIDGE8.parse().unwrap();
+    let Err(error) = generate_header_and_cc(source, &opt) else {
+        panic!("kj::OneOf with the same type in two variants must be rejected");
+    };
+    assert!(error
+        .to_string()
+        .contains("kj::OneOf cannot hold the same type in more than one variant"));
+}
+
+#[test]
+fn test_data_enum() {
+    let opt = Opt::default();
+    let source = BRIDGE9.parse().unwrap();
+    let generated = generate_header_and_cc(source, &opt).unwrap();
+    let header = str::from_utf8(&generated.header).unwrap();
+    let implementation = str::from_utf8(&generated.implementation).unwrap();
+    assert!(header.contains("struct Event final {"));
+    assert!(header.contains("enum class Tag : ::std::uint8_t {"));
+    assert!(header.contains("struct Start final {};"));
+    assert!(header.contains("static Event make_move(::Point value) noexcept {"));
+    assert!(header.contains("bool is_key_press() const noexcept {"));
+    assert!(header.contains("const ::rust::String &get_text() const noexcept {"));
+    assert!(header.contains("decltype(auto) visit(Visitor &&visitor) const {"));
+    assert!(header.contains("Event(const Event &other) : tag(other.tag) {"));
+    assert!(!header.contains("kj-rs/kj-rs.h"));
+    // The tagged struct is complete before the struct holding it.
+    let event = header.find("struct Event final {").unwrap();
+    let point = header.find("struct Point final {").unwrap();
+    let log = header.find("struct Log final {").unwrap();
+    assert!(point < event && event < log);
+    assert!(implementation.contains(
+        "static_assert(sizeof(::Event) == sizeof(::rust::repr::Tagged<::Point, ::std::uint32_t, ::rust::String>)"
+    ));
+}
+
+#[test]
+fn test_data_enum_rejects_keyword_member() {
+    let opt = Opt::default();
+    let source = BRIDGE10.parse().unwrap();
+    let Err(error) = generate_header_and_cc(source, &opt) else {
+        panic!("a variant named after a C++ keyword must be rejected");
+    };
+    assert!(error
+        .to_string()
+        .contains("variant `Default` would be named `default` in C++, which is a keyword"));
+}
+
+#[test]
+fn test_typed_error() {
+    let opt = Opt::default();
+    let source = BRIDGE11.parse().unwrap();
+    let generated = generate_header_and_cc(source, &opt).unwrap();
+    let header = str::from_utf8(&generated.header).unwrap();
+    let implementation = str::from_utf8(&generated.implementation).unwrap();
+    assert!(
+        header.contains("::kj_rs::Result<::std::uint32_t, ::ParseError> parse(::rust::Str text);")
+    );
+    assert!(header.contains("::kj_rs::Result<void, ::ParseError> validate(::rust::Str text);"));
+    assert!(header.contains("kj-rs/kj-rs.h"));
+    assert!(implementation.contains("::std::uint32_t *return$, ::ParseError *err$);"));
+    assert!(implementation.contains("if (error$.typed_error()) {"));
+    assert!(implementation
+        .contains("return ::kj_rs::Result<void, ::ParseError>::err(::std::move(err$.value));"));
+}
+
+#[test]
+fn test_typed_error_rejected_for_cxx_function() {
+    let opt = Opt::default();
+    let source = BRIDGE12.parse().unwrap();
+    let Err(error) = generate_header_and_cc(source, &opt) else {
+        panic!("a C++ function returning a typed error must be rejected");
+    };
+    assert!(error
+        .to_string()
+        .contains("Result with a typed error is only supported for extern \"Rust\" functions"));
+}
+
+#[test]
+fn test_rc_receiver_requires_async_method() {
+    let opt = Opt::default();
+    let source = BRIDGE13.parse().unwrap();
+    let Err(error) = generate_header_and_cc(source, &opt) else {
+        panic!("a synchronous method taking `self: KjRc<...>` must be rejected");
+    };
+    assert!(error
+        .to_string()
+        .contains("`self: KjRc<...>` receiver is only supported on async C++ methods"));
+}
@@ -19,7 +19,8 @@ pub mod module;
 
 use core::fmt;
 use cxx::{
-    type_id, CxxString, CxxVector, ExternType, KjError, KjExceptionType, SharedPtr, UniquePtr,
+    type_id, CxxString, CxxVector, ExceptionDetail, ExternType, KjError, KjExceptionType,
+    SharedPtr, UniquePtr,
 };
 // The bridge parser accepts the unqualified smart-pointer name, while expansion
 // fully qualifies the emitted Rust field type.
@@ -133,6 +134,14 @@ pub mod ffi {
         s: &'a str,
     }
 
+    #[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+    struct Empty {}
+
+    struct SharedWithEmpty {
+        marker: Empty,
+        z: usize,
+    }
+
     unsafe extern "C++" {
         include!("tests/ffi/tests.h");
 
@@ -155,6 +164,8 @@ pub mod ffi {
         fn c_return_unique_ptr_vector_string() -> UniquePtr<CxxVector<CxxString>>;
         fn c_return_unique_ptr_vector_shared() -> UniquePtr<CxxVector<Shared>>;
         fn c_return_unique_ptr_vector_opaque() -> UniquePtr<CxxVector<C>>;
+        fn c_return_shared_ptr_vector_u8() -> SharedPtr<CxxVector<u8>>;
+        fn c_return_shared_ptr_vector_shared() -> SharedPtr<CxxVector<Shared>>;
         unsafe fn c_return_ref_vector<'a>(c: &'a C) -> &'a CxxVector<u8>;
         unsafe fn c_return_mut_vector<'a>(c: Pin<&'a mut C>) -> Pin<&'a mut CxxVector<u8>>;
         fn c_return_rust_vec_u8() -> Vec<u8>;
@@ -162,6 +173,7 @@ pub mod ffi {
         unsafe fn c_return_mut_rust_vec<'a>(c: Pin<&'a mut C>) -> &'a mut Vec<u8>;
         fn c_return_rust_vec_string() -> Vec<String>;
         fn c_return_rust_vec_bool() -> Vec<bool>;
+        fn c_return_rust_vec_unique_ptr() -> Vec<UniquePtr<C>>;
         fn c_return_identity(_: usize) -> usize;
         fn c_return_sum(_: usize, _: usize) -> usize;
         fn c_return_enum(n: u16) -> Enum;
@@ -232,6 +244,9 @@ pub mod ffi {
 
         fn c_take_primitive(n: usize);
         fn c_take_shared(shared: Shared);
+        fn c_take_empty(empty: Empty);
+        fn c_take_empty_then_primitive(empty: Empty, n: u32);
+        fn c_return_shared_with_empty() -> SharedWithEmpty;
         fn c_take_box(r: Box<R>);
         fn c_take_ref_r(r: &R);
         fn c_take_ref_c(c: &C);
@@ -258,6 +273,7 @@ pub mod ffi {
         fn c_take_rust_vec_shared_clear(v: Vec<Shared>);
         fn c_take_rust_vec_shared_forward_iterator(v: Vec<Shared>);
         fn c_take_rust_vec_shared_sort(v: Vec<Shared>);
+        fn c_take_rust_vec_unique_ptr(v: Vec<UniquePtr<C>>);
         fn c_take_ref_rust_vec(v: &Vec<u8>);
         fn c_take_ref_rust_vec_string(v: &Vec<String>);
         fn c_take_ref_rust_vec_index(v: &Vec<u8>);
@@ -284,9 +300,11 @@ pub mod ffi {
         fn c_fail_kj_exception_return_primitive() -> Result<usize>;
         fn c_fail_kj_exception_disconnected_return_primitive() -> Result<usize>;
         fn c_fail_kj_exception_with_details_return_primitive() -> Result<usize>;
+        fn c_fail_kj_exception_with_typed_detail_return_primitive() -> Result<usize>;
         fn c_cancel_return_primitive() -> Result<usize>;
         fn c_cancel_via_rust_return_primitive() -> Result<usize>;
         fn c_cancel_roundtrip_return_primitive() -> Result<usize>;
+        fn c_call_r_panic(s: &str) -> Result<()>;
 
         // These signatures are infallible, but the C++ implementations throw.
         // The exception has to become a panic; it must not abort the process.
@@ -323,6 +341,7 @@ pub mod ffi {
         fn c_set_array(self: &mut Array, value: i32);
 
         fn c_get_use_count(weak: &WeakPtr<C>) -> usize;
+        fn c_get_vector_use_count(weak: &WeakPtr<CxxVector<Shared>>) -> usize;
 
         #[rust_name = "i32_overloaded_method"]
         fn cOverloadedMethod(&self, x: i32) -> String;
@@ -416,6 +435,9 @@ pub mod ffi {
 
         fn r_take_primitive(n: usize);
         fn r_take_shared(shared: Shared);
+        fn r_take_shared_with_empty(shared: SharedWithEmpty);
+        fn r_return_empty() -> Empty;
+        fn r_take_empty_then_primitive(empty: Empty, n: u32);
         fn r_take_box(r: Box<R>);
         fn r_take_unique_ptr(c: UniquePtr<C>);
         fn r_take_shared_ptr(c: SharedPtr<C>);
@@ -442,6 +464,7 @@ pub mod ffi {
         fn r_result_kj_exception_fail_return_primitive() -> Result<usize>;
         fn r_result_kj_exception_disconnected_return_primitive() -> Result<usize>;
         fn r_result_kj_exception_with_details_return_primitive() -> Result<usize>;
+        fn r_result_kj_exception_with_typed_detail_return_primitive() -> Result<usize>;
         fn r_cancel_panic_test();
         fn r_call_c_cancel_return_primitive();
         fn r_call_c_infallible_fail_primitive();
@@ -460,6 +483,9 @@ pub mod ffi {
         fn r_aliased_function(x: i32) -> String;
 
         fn r_panic(s: &str);
+        fn r_panic_todo();
+        fn r_panic_kj_error();
+        fn r_resume_unwind();
     }
 
     struct Dag0 {
@@ -537,6 +563,16 @@ mod other {
         type Id = type_id!("tests::E");
         type Kind = Opaque;
     }
+
+    #[repr(C)]
+    pub struct Relocatable {
+        pub value: u64,
+    }
+
+    unsafe impl ExternType for Relocatable {
+        type Id = type_id!("tests::Relocatable");
+        type Kind = Trivial;
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -707,6 +743,18 @@ fn r_take_shared(shared: ffi::Shared) {
     assert_eq!(shared.z, 2020);
 }
 
+fn r_take_shared_with_empty(shared: ffi::SharedWithEmpty) {
+    assert_eq!(shared.z, 2020);
+}
+
+fn r_return_empty() -> ffi::Empty {
+    ffi::Empty::new()
+}
+
+fn r_take_empty_then_primitive(_empty: ffi::Empty, n: u32) {
+    assert_eq!(n, 2020);
+}
+
 fn r_take_box(r: Box<R>) {
     let _ = r;
 }
@@ -825,6 +873,33 @@ fn r_result_kj_exception_with_details_return_primitive() -> Result<usize, KjErro
     .with_details(details))
 }
 
+fn r_result_kj_exception_with_typed_detail_return_primitive() -> Result<usize, KjError> {
+    Err(KjError::new(
+        KjExceptionType::Overloaded,
+        "rust exception with typed detail".to_owned(),
+    )
+    .with_detail(&RetryAfter { millis: 250 }))
+}
+
+/// Exception detail shared with `tests::RetryAfter` in tests.h.
+#[derive(Debug, PartialEq)]
+pub struct RetryAfter {
+    pub millis: u32,
+}
+
+impl ExceptionDetail for RetryAfter {
+    const TYPE_ID: u64 = 0x5245_5452_5941_4654;
+
+    fn encode(&self) -> Vec<u8> {
+        self.millis.to_le_bytes().to_vec()
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        let millis = u32::from_le_bytes(data.try_into().ok()?);
+        Some(RetryAfter { millis })
+    }
+}
+
 // Panic with CanceledException to simulate cancellation
 fn r_cancel_panic_test() {
     cxx::CanceledException::panic()
@@ -876,3 +951,19 @@ fn r_aliased_function(x: i32) -> String {
 fn r_panic(s: &str) {
     panic!("{s}");
 }
+
+fn r_panic_todo() {
+    todo!("r_panic_todo")
+}
+
+fn r_panic_kj_error() {
+    std::panic::panic_any(KjError::new(
+        KjExceptionType::Overloaded,
+        "too many panics".to_owned(),
+    ));
+}
+
+fn r_resume_unwind() {
+    let _ = std::panic::catch_unwind(|| panic!("caught"));
+    std::panic::resume_unwind(Box::new("resumed"));
+}
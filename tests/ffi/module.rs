@@ -33,6 +33,8 @@ pub mod ffi2 {
         #[namespace = "G"]
         type G = crate::other::G;
 
+        type Relocatable = crate::other::Relocatable;
+
         #[namespace = "H"]
         type H;
 
@@ -55,6 +57,8 @@ pub mod ffi2 {
         fn c_take_opaque_ns_ref(e: &F);
         fn c_return_trivial_ptr() -> UniquePtr<D>;
         fn c_return_trivial() -> D;
+        fn c_return_relocatable_vec() -> Vec<Relocatable>;
+        fn c_take_relocatable_vec(v: Vec<Relocatable>);
         fn c_return_trivial_ns_ptr() -> UniquePtr<G>;
         fn c_return_trivial_ns() -> G;
         fn c_return_opaque_ptr() -> UniquePtr<E>;
@@ -83,4 +87,5 @@ pub mod ffi2 {
     impl UniquePtr<E> {}
     impl UniquePtr<F> {}
     impl UniquePtr<G> {}
+    impl Vec<Relocatable> {}
 }
@@ -201,6 +201,18 @@ std::unique_ptr<std::vector<C>> c_return_unique_ptr_vector_opaque() {
   return std::unique_ptr<std::vector<C>>(new std::vector<C>());
 }
 
+std::shared_ptr<std::vector<uint8_t>> c_return_shared_ptr_vector_u8() {
+  return std::make_shared<std::vector<uint8_t>>(
+      std::vector<uint8_t>{86, 75, 30, 9});
+}
+
+std::shared_ptr<std::vector<Shared>> c_return_shared_ptr_vector_shared() {
+  auto vec = std::make_shared<std::vector<Shared>>();
+  vec->push_back(Shared{1010});
+  vec->push_back(Shared{1011});
+  return vec;
+}
+
 const std::vector<uint8_t> &c_return_ref_vector(const C &c) {
   return c.get_v();
 }
@@ -228,6 +240,13 @@ rust::Vec<rust::String> c_return_rust_vec_string() {
 
 rust::Vec<bool> c_return_rust_vec_bool() { return {true, true, false}; }
 
+rust::Vec<std::unique_ptr<C>> c_return_rust_vec_unique_ptr() {
+  rust::Vec<std::unique_ptr<C>> vec;
+  vec.push_back(std::unique_ptr<C>(new C{2020}));
+  vec.emplace_back(new C{2021});
+  return vec;
+}
+
 size_t c_return_identity(size_t n) { return n; }
 
 size_t c_return_sum(size_t n1, size_t n2) { return n1 + n2; }
@@ -463,6 +482,22 @@ void c_take_shared(Shared shared) {
   }
 }
 
+void c_take_empty(Empty empty) {
+  if (empty == Empty{}) {
+    cxx_test_suite_set_correct();
+  }
+}
+
+void c_take_empty_then_primitive(Empty empty, uint32_t n) {
+  if (empty == Empty{} && n == 2020) {
+    cxx_test_suite_set_correct();
+  }
+}
+
+SharedWithEmpty c_return_shared_with_empty() {
+  return SharedWithEmpty{Empty{}, 2020};
+}
+
 void c_take_ns_shared(::A::AShared shared) {
   if (shared.type == 2020) {
     cxx_test_suite_set_correct();
@@ -667,6 +702,15 @@ void c_take_rust_vec_shared_sort(rust::Vec<Shared> v) {
   }
 }
 
+void c_take_rust_vec_unique_ptr(rust::Vec<std::unique_ptr<C>> v) {
+  if (v.size() == 2 && v[0]->get() == 2020 && v[1]->get() == 2021) {
+    v.truncate(1);
+    if (v.size() == 1 && v.back()->get() == 2020) {
+      cxx_test_suite_set_correct();
+    }
+  }
+}
+
 void c_take_rust_vec_shared_index(rust::Vec<Shared> v) {
   if (v[0].z == 1010 && v.at(0).z == 1010 && v.front().z == 1010 &&
       v[1].z == 1011 && v.at(1).z == 1011 && v.back().z == 1011) {
@@ -804,6 +848,12 @@ size_t c_fail_kj_exception_with_details_return_primitive() {
   kj::throwFatalException(kj::mv(ex));
 }
 
+size_t c_fail_kj_exception_with_typed_detail_return_primitive() {
+  auto ex = KJ_EXCEPTION(OVERLOADED, "test exception with typed detail");
+  rust::setDetail(ex, RetryAfter{1500});
+  kj::throwFatalException(kj::mv(ex));
+}
+
 size_t c_cancel_return_primitive() { throw kj::CanceledException{}; }
 
 // Call Rust function that panics with CanceledException
@@ -820,6 +870,8 @@ size_t c_cancel_roundtrip_return_primitive() {
   return 2020; // Should not reach here
 }
 
+void c_call_r_panic(rust::Str s) { r_panic(s); }
+
 // The functions below throw even though their bridge signature is infallible.
 // Rust cannot report the exception to its caller, so it panics instead. None of
 // them may terminate the process.
@@ -894,6 +946,11 @@ size_t c_get_use_count(const std::weak_ptr<C> &weak) noexcept {
   return weak.use_count();
 }
 
+size_t
+c_get_vector_use_count(const std::weak_ptr<std::vector<Shared>> &weak) noexcept {
+  return weak.use_count();
+}
+
 extern "C" C *cxx_test_suite_get_unique_ptr() noexcept {
   return std::unique_ptr<C>(new C{2020}).release();
 }
@@ -1025,6 +1082,30 @@ D c_return_trivial() {
   return d;
 }
 
+static size_t relocatable_destroyed = 0;
+
+Relocatable::~Relocatable() { relocatable_destroyed++; }
+
+rust::Vec<Relocatable> c_return_relocatable_vec() {
+  rust::Vec<Relocatable> vec;
+  vec.push_back(Relocatable{2020});
+  vec.push_back(Relocatable{2021});
+  vec.push_back(Relocatable{2022});
+  return vec;
+}
+
+void c_take_relocatable_vec(rust::Vec<Relocatable> v) {
+  size_t destroyed = relocatable_destroyed;
+  v.truncate(1);
+  if (v.size() == 1 && v[0].value == 2020 &&
+      relocatable_destroyed == destroyed + 2) {
+    v.clear();
+    if (relocatable_destroyed == destroyed + 3) {
+      cxx_test_suite_set_correct();
+    }
+  }
+}
+
 std::unique_ptr<::G::G> c_return_trivial_ns_ptr() {
   auto g = std::unique_ptr<::G::G>(new ::G::G());
   g->g = 30;
@@ -1085,6 +1166,9 @@ extern "C" const char *cxx_run_test() noexcept {
 
   r_take_primitive(2020);
   r_take_shared(Shared{2020});
+  r_take_shared_with_empty(SharedWithEmpty{r_return_empty(), 2020});
+  ASSERT(!(r_return_empty() < Empty{}));
+  r_take_empty_then_primitive(Empty{}, 2020);
   r_take_unique_ptr(std::unique_ptr<C>(new C{2020}));
   r_take_shared_ptr(std::shared_ptr<C>(new C{2020}));
   r_take_ref_c(C{2020});
@@ -1152,6 +1236,16 @@ extern "C" const char *cxx_run_test() noexcept {
     KJ_ASSERT(memcmp(details[1].value.begin(), "rust detail 2", 13) == 0);
   }
 
+  // Test Rust->C++ exception with a typed detail
+  try {
+    r_result_kj_exception_with_typed_detail_return_primitive();
+    KJ_ASSERT(false);
+  } catch (const kj::Exception &e) {
+    KJ_ASSERT(e.getType() == kj::Exception::Type::OVERLOADED);
+    auto &retry = KJ_ASSERT_NONNULL(rust::getDetail<RetryAfter>(e));
+    KJ_ASSERT(retry.millis == 250);
+  }
+
   auto r = r_return_box();
   ASSERT(r->get() == 2020);
   ASSERT(r->set(2021) == 2021);
@@ -1334,6 +1428,35 @@ extern "C" const char *cxx_run_test() noexcept {
   } catch (const kj::Exception &e) {
     ASSERT(std::strcmp(e.getDescription().cStr(),
                        "panic in cxx_test_suite::ffi::r_panic: foobar") == 0);
+    // The exception points at the panic, not at the ffi shim.
+    ASSERT(e.getFile() == "tests/ffi/lib.rs"_kj);
+    ASSERT(e.getLine() > 0);
+  }
+
+  try {
+    r_panic_todo();
+    ASSERT(false);
+  } catch (const kj::Exception &e) {
+    ASSERT(e.getType() == kj::Exception::Type::UNIMPLEMENTED);
+    ASSERT(e.getDescription().endsWith("not yet implemented: r_panic_todo"));
+  }
+
+  try {
+    r_panic_kj_error();
+    ASSERT(false);
+  } catch (const kj::Exception &e) {
+    ASSERT(e.getType() == kj::Exception::Type::OVERLOADED);
+    ASSERT(e.getDescription() == "too many panics"_kj);
+  }
+
+  try {
+    r_resume_unwind();
+    ASSERT(false);
+  } catch (const kj::Exception &e) {
+    ASSERT(e.getDescription().endsWith("resumed"));
+    // `resume_unwind` doesn't run the panic hook, so there is no location to
+    // report; in particular not the one of the panic caught before.
+    ASSERT(e.getFile() != "tests/ffi/lib.rs"_kj);
   }
 
   // Test C++->Rust CanceledException roundtrip
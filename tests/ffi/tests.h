@@ -115,6 +115,13 @@ struct D {
   void c_take_trivial_mut_ref_method();
 };
 
+// Trivially relocatable, but with a destructor which Rust can't run.
+struct Relocatable {
+  uint64_t value;
+  ~Relocatable();
+  using IsRelocatable = std::true_type;
+};
+
 struct E {
   uint64_t e;
   std::string e_str;
@@ -160,6 +167,8 @@ std::unique_ptr<std::vector<double>> c_return_unique_ptr_vector_f64();
 std::unique_ptr<std::vector<std::string>> c_return_unique_ptr_vector_string();
 std::unique_ptr<std::vector<Shared>> c_return_unique_ptr_vector_shared();
 std::unique_ptr<std::vector<C>> c_return_unique_ptr_vector_opaque();
+std::shared_ptr<std::vector<uint8_t>> c_return_shared_ptr_vector_u8();
+std::shared_ptr<std::vector<Shared>> c_return_shared_ptr_vector_shared();
 const std::vector<uint8_t> &c_return_ref_vector(const C &c);
 std::vector<uint8_t> &c_return_mut_vector(C &c);
 rust::Vec<uint8_t> c_return_rust_vec_u8();
@@ -167,6 +176,7 @@ const rust::Vec<uint8_t> &c_return_ref_rust_vec(const C &c);
 rust::Vec<uint8_t> &c_return_mut_rust_vec(C &c);
 rust::Vec<rust::String> c_return_rust_vec_string();
 rust::Vec<bool> c_return_rust_vec_bool();
+rust::Vec<std::unique_ptr<C>> c_return_rust_vec_unique_ptr();
 size_t c_return_identity(size_t n);
 size_t c_return_sum(size_t n1, size_t n2);
 Enum c_return_enum(uint16_t n);
@@ -233,6 +243,9 @@ c_roundtrip_shared_with_multiple_kj_arcs(SharedWithMultipleKjArcs shared);
 
 void c_take_primitive(size_t n);
 void c_take_shared(Shared shared);
+void c_take_empty(Empty empty);
+void c_take_empty_then_primitive(Empty empty, uint32_t n);
+SharedWithEmpty c_return_shared_with_empty();
 void c_take_ns_shared(::A::AShared shared);
 void c_take_nested_ns_shared(::A::B::ABShared shared);
 void c_take_box(rust::Box<R> r);
@@ -266,6 +279,7 @@ void c_take_rust_vec_shared_truncate(rust::Vec<Shared> v);
 void c_take_rust_vec_shared_clear(rust::Vec<Shared> v);
 void c_take_rust_vec_shared_forward_iterator(rust::Vec<Shared> v);
 void c_take_rust_vec_shared_sort(rust::Vec<Shared> v);
+void c_take_rust_vec_unique_ptr(rust::Vec<std::unique_ptr<C>> v);
 void c_take_ref_rust_vec(const rust::Vec<uint8_t> &v);
 void c_take_ref_rust_vec_string(const rust::Vec<rust::String> &v);
 void c_take_ref_rust_vec_index(const rust::Vec<uint8_t> &v);
@@ -286,9 +300,11 @@ size_t c_fail_return_primitive();
 size_t c_fail_kj_exception_return_primitive();
 size_t c_fail_kj_exception_disconnected_return_primitive();
 size_t c_fail_kj_exception_with_details_return_primitive();
+size_t c_fail_kj_exception_with_typed_detail_return_primitive();
 size_t c_cancel_return_primitive();
 size_t c_cancel_via_rust_return_primitive();
 size_t c_cancel_roundtrip_return_primitive();
+void c_call_r_panic(rust::Str s);
 
 // Functions which throw even though their bridge signature is infallible. Their
 // exceptions must turn into Rust panics rather than terminating the process.
@@ -311,6 +327,8 @@ rust::Vec<rust::String> c_try_return_rust_vec_string();
 const rust::Vec<uint8_t> &c_try_return_ref_rust_vec(const C &c);
 
 size_t c_get_use_count(const std::weak_ptr<C> &weak) noexcept;
+size_t
+c_get_vector_use_count(const std::weak_ptr<std::vector<Shared>> &weak) noexcept;
 
 void c_take_trivial_ptr(std::unique_ptr<D> d);
 void c_take_trivial_ref(const D &d);
@@ -328,6 +346,8 @@ void c_take_opaque_ref(const E &e);
 void c_take_opaque_ns_ref(const ::F::F &f);
 std::unique_ptr<D> c_return_trivial_ptr();
 D c_return_trivial();
+rust::Vec<Relocatable> c_return_relocatable_vec();
+void c_take_relocatable_vec(rust::Vec<Relocatable> v);
 std::unique_ptr<::G::G> c_return_trivial_ns_ptr();
 ::G::G c_return_trivial_ns();
 std::unique_ptr<E> c_return_opaque_ptr();
@@ -337,8 +357,37 @@ std::unique_ptr<::F::F> c_return_ns_opaque_ptr();
 rust::String cOverloadedFunction(int32_t x);
 rust::String cOverloadedFunction(rust::Str x);
 
+// Exception detail shared with `RetryAfter` in lib.rs.
+struct RetryAfter {
+  uint32_t millis;
+};
+
 } // namespace tests
 
+template <>
+struct rust::ExceptionDetail<tests::RetryAfter> {
+  static constexpr kj::Exception::DetailTypeId TYPE_ID = 0x5245545259414654;
+
+  static kj::Array<kj::byte> encode(const tests::RetryAfter &value) {
+    auto data = kj::heapArray<kj::byte>(sizeof(value.millis));
+    for (size_t i = 0; i < data.size(); i++) {
+      data[i] = static_cast<kj::byte>(value.millis >> (8 * i));
+    }
+    return data;
+  }
+
+  static kj::Maybe<tests::RetryAfter> decode(kj::ArrayPtr<const kj::byte> data) {
+    if (data.size() != sizeof(uint32_t)) {
+      return kj::none;
+    }
+    uint32_t millis = 0;
+    for (size_t i = 0; i < data.size(); i++) {
+      millis |= static_cast<uint32_t>(data[i]) << (8 * i);
+    }
+    return tests::RetryAfter{millis};
+  }
+};
+
 namespace other {
 void ns_c_take_trivial(::tests::D d);
 ::tests::D ns_c_return_trivial();
@@ -0,0 +1,19 @@
+// Separate from test.rs: `Backtrace::capture` reads `RUST_LIB_BACKTRACE` only once per process,
+// so it has to be set before any other test panics.
+
+use cxx_test_suite::ffi;
+
+#[test]
+fn test_panic_backtrace_detail() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    let err = ffi::c_call_r_panic("with backtrace").unwrap_err();
+    assert_eq!(
+        "panic in cxx_test_suite::ffi::r_panic: with backtrace",
+        err.what(),
+    );
+    let backtrace = err
+        .detail::<cxx::PanicBacktrace>()
+        .expect("panic backtrace detail");
+    assert!(backtrace.0.contains("r_panic"), "{}", backtrace.0);
+}
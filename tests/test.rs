@@ -10,8 +10,9 @@
 
 use cxx::{SharedPtr, UniquePtr};
 use cxx_test_suite::module::ffi2;
-use cxx_test_suite::{cast, ffi, R};
+use cxx_test_suite::{cast, ffi, RetryAfter, R};
 use std::cell::Cell;
+use std::collections::HashSet;
 use std::ffi::CStr;
 use std::mem::{align_of, size_of};
 use std::panic::{self, RefUnwindSafe, UnwindSafe};
@@ -78,6 +79,13 @@ fn test_c_return() {
     );
     assert_eq!(b"\x02\0\x02\0"[..], ffi::c_return_rust_vec_u8());
     assert_eq!([true, true, false][..], ffi::c_return_rust_vec_bool());
+    assert_eq!(
+        [2020, 2021][..],
+        ffi::c_return_rust_vec_unique_ptr()
+            .iter()
+            .map(|c| c.get())
+            .collect::<Vec<_>>(),
+    );
     assert_eq!(2020, ffi::c_return_identity(2020));
     assert_eq!(2021, ffi::c_return_sum(2020, 1));
     match ffi::c_return_enum(0) {
@@ -401,6 +409,9 @@ fn test_c_take() {
         ffi::Shared { z: 4 },
     ];
     check!(ffi::c_take_rust_vec_shared_sort(shared_sort_vec));
+    check!(ffi::c_take_rust_vec_unique_ptr(
+        ffi::c_return_rust_vec_unique_ptr()
+    ));
     check!(ffi::c_take_ref_rust_vec(&test_vec));
     check!(ffi::c_take_ref_rust_vec_index(&test_vec));
     check!(ffi::c_take_ref_rust_vec_copy(&test_vec));
@@ -510,6 +521,27 @@ fn test_shared_ptr_weak_ptr() {
     assert!(weak_ptr.upgrade().is_null());
 }
 
+#[test]
+fn test_shared_ptr_weak_ptr_vector() {
+    let shared_ptr = ffi::c_return_shared_ptr_vector_u8();
+    assert_eq!(4, shared_ptr.len());
+    assert_eq!(200_u8, shared_ptr.iter().sum());
+    assert_eq!(4, shared_ptr.clone().len());
+
+    let shared_ptr = ffi::c_return_shared_ptr_vector_shared();
+    let weak_ptr = SharedPtr::downgrade(&shared_ptr);
+    assert_eq!(1, ffi::c_get_vector_use_count(&weak_ptr));
+
+    let upgraded = weak_ptr.upgrade();
+    assert_eq!(2021_usize, upgraded.iter().map(|o| o.z).sum());
+    assert_eq!(2, ffi::c_get_vector_use_count(&weak_ptr));
+    drop(upgraded);
+
+    drop(shared_ptr);
+    assert_eq!(0, ffi::c_get_vector_use_count(&weak_ptr));
+    assert!(weak_ptr.upgrade().is_null());
+}
+
 #[test]
 fn test_c_ns_method_calls() {
     let unique_ptr = ffi2::ns_c_return_unique_ptr_ns();
@@ -532,6 +564,22 @@ fn test_debug() {
     assert_eq!("Enum(9)", format!("{:?}", ffi::Enum { repr: 9 }));
 }
 
+#[test]
+fn test_empty_struct() {
+    assert_eq!(1, std::mem::size_of::<ffi::Empty>());
+    assert_eq!("Empty", format!("{:?}", ffi::Empty::new()));
+    assert_eq!(ffi::Empty::new(), ffi::Empty::default());
+
+    // The padding byte C++ leaves uninitialized is ignored by the derives.
+    let shared = ffi::c_return_shared_with_empty();
+    assert_eq!(ffi::Empty::new(), shared.marker);
+    assert_eq!(2020, shared.z);
+    let set: HashSet<ffi::Empty> = [shared.marker, ffi::Empty::new()].into();
+    assert_eq!(1, set.len());
+    check!(ffi::c_take_empty(shared.marker));
+    check!(ffi::c_take_empty_then_primitive(shared.marker, 2020));
+}
+
 #[no_mangle]
 extern "C" fn cxx_test_suite_get_box() -> *mut R {
     Box::into_raw(Box::new(R(2020usize)))
@@ -572,6 +620,11 @@ fn test_extern_trivial() {
     let g = ffi2::c_return_trivial_ns_ptr();
     check!(ffi2::c_take_trivial_ns_ptr(g));
     cxx::UniquePtr::new(ffi2::G { g: 42 });
+
+    let vec = ffi2::c_return_relocatable_vec();
+    let values: Vec<u64> = vec.iter().map(|relocatable| relocatable.value).collect();
+    assert_eq!(values, [2020, 2021, 2022]);
+    check!(ffi2::c_take_relocatable_vec(vec));
 }
 
 #[test]
@@ -741,6 +794,16 @@ fn test_kj_exception_with_details() {
     assert_eq!(b"another detail", details[1].1.as_slice());
 }
 
+#[test]
+fn test_kj_exception_with_typed_detail() {
+    let err = ffi::c_fail_kj_exception_with_typed_detail_return_primitive().unwrap_err();
+    assert_eq!(cxx::KjExceptionType::Overloaded, err.r#type());
+    assert_eq!(
+        Some(RetryAfter { millis: 1500 }),
+        err.detail::<RetryAfter>()
+    );
+}
+
 #[test]
 fn test_rust_to_cpp_to_rust_cancellation() {
     // Test Rust->C++->Rust cancellation roundtrip